@@ -0,0 +1,97 @@
+//! Concrete suggestions for making a valid solution faster, based on its [critical path](crate::analysis::critical).
+//!
+//! Each heuristic proposes moving one arm's tape earlier, and the change is simulated before it's suggested, so every
+//! suggestion says how many cycles it really saves. Suggestions are independent: applying one may change what the
+//! others save.
+
+use core::fmt::{self, Display, Formatter};
+use crate::analysis::critical::{critical_path, CriticalEvent};
+use crate::data::{Puzzle, Solution};
+use crate::sim::config::SimConfig;
+use crate::sim::error::SimError;
+use crate::sim::Sim;
+use crate::compat::prelude::*;
+
+/// A change to a solution that makes it complete sooner.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Suggestion{
+    /// The arm's index in the solution.
+    pub part: usize,
+    pub arm: i32,
+    pub reason: Reason,
+    /// How many cycles earlier to move the arm's instructions.
+    pub shift: i32,
+    /// How many cycles the solution saves with the change, as simulated.
+    pub saves: u64,
+    /// The solution with the change made.
+    pub solution: Solution
+}
+
+/// What made a heuristic suggest a change.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Reason{
+    /// The arm does nothing for this many cycles at the start.
+    LateStart{ cycles: u64 },
+    /// The last product's atoms were ready this many cycles before the arm picked them up.
+    Waiting{ cycles: u64 }
+}
+
+impl Display for Suggestion{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result{
+        match self.reason{
+            Reason::LateStart{ cycles } => write!(f, "arm {} waits {cycles} cycles before its first instruction", self.arm)?,
+            Reason::Waiting{ cycles } => write!(f, "arm {} picks up the last product's atoms {cycles} cycles after they're ready", self.arm)?
+        }
+        write!(f, "; shifting its tape left by {} saves {} cycles", self.shift, self.saves)
+    }
+}
+
+/// Suggest changes that make a valid solution complete sooner, most cycles saved first.
+pub fn advise(puzzle: &Puzzle, solution: &Solution) -> Result<Vec<Suggestion>, SimError>{
+    let path = critical_path(puzzle, solution, SimConfig::default())?;
+    let numbers: Vec<(usize, i32)> = path.arms.iter().map(|arm| (arm.part, arm.number)).collect();
+    let mut candidates = Vec::new();
+    for arm in &path.arms{
+        if let Some(first @ 1..) = arm.first_active{
+            candidates.push((arm.part, Reason::LateStart{ cycles: first }, first));
+        }
+    }
+    for pair in path.chain.windows(2){
+        let ready = match pair[0]{
+            CriticalEvent::Carried{ last, .. } => last,
+            event => event.cycle()
+        };
+        if let CriticalEvent::Carried{ first, part, .. } = pair[1]{
+            if first > ready + 1{
+                let cycles = first - ready - 1;
+                candidates.push((part, Reason::Waiting{ cycles }, cycles));
+            }
+        }
+    }
+
+    let mut suggestions: Vec<Suggestion> = Vec::new();
+    for (part, reason, most) in candidates{
+        let Some(arm) = numbers.iter().find(|(p, _)| *p == part).map(|(_, number)| *number) else { continue };
+        // try every shift up to the gap, since moving the whole tape may collide before it saves the whole gap
+        let best = (1..=most.min(i32::MAX as u64) as i32)
+            .filter(|shift| !suggestions.iter().any(|s| s.part == part && s.shift == *shift))
+            .filter_map(|shift| {
+                let mut shifted = solution.clone();
+                shifted.parts[part].instructions.iter_mut().for_each(|(_, idx)| *idx -= shift);
+                let cycles = cycles_within(puzzle, &shifted, path.cycles)?;
+                Some((path.cycles - cycles, shift, shifted))
+            })
+            .max_by_key(|(saves, shift, _)| (*saves, -shift));
+        if let Some((saves, shift, solution)) = best{
+            suggestions.push(Suggestion{ part, arm, reason, shift, saves, solution });
+        }
+    }
+    suggestions.sort_by_key(|s| core::cmp::Reverse(s.saves));
+    Ok(suggestions)
+}
+
+/// The number of cycles a solution takes, if it's valid and takes fewer than `limit`.
+fn cycles_within(puzzle: &Puzzle, solution: &Solution, limit: u64) -> Option<u64>{
+    let config = SimConfig{ max_cycles: limit.saturating_sub(1), ..SimConfig::default() };
+    Sim::create_with_config(puzzle, solution, config).ok()?.run().ok()
+}
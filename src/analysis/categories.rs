@@ -0,0 +1,96 @@
+use alloc::collections::BTreeMap;
+use crate::data::Metrics;
+use crate::verify::Verdict;
+use crate::compat::prelude::*;
+
+/// One of the four basic metrics, by the letter the community uses for it.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Metric{
+    /// g, for gold.
+    Cost,
+    /// c.
+    Cycles,
+    /// a.
+    Area,
+    /// i.
+    Instructions
+}
+
+impl Metric{
+    pub fn of(self, metrics: &Metrics) -> i64{
+        i64::from(match self{
+            Metric::Cost => metrics.cost,
+            Metric::Cycles => metrics.cycles,
+            Metric::Area => metrics.area,
+            Metric::Instructions => metrics.instructions
+        })
+    }
+}
+
+/// A quantity that solutions are ranked by, lower being better.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Term{
+    Metric(Metric),
+    /// The sum of several metrics, e.g. cost+cycles+area.
+    Sum(&'static [Metric]),
+    /// The product of several metrics, e.g. cost·cycles.
+    Product(&'static [Metric])
+}
+
+impl Term{
+    pub fn of(self, metrics: &Metrics) -> i64{
+        match self{
+            Term::Metric(metric) => metric.of(metrics),
+            Term::Sum(terms) => terms.iter().map(|metric| metric.of(metrics)).sum(),
+            Term::Product(terms) => terms.iter().map(|metric| metric.of(metrics)).product()
+        }
+    }
+}
+
+/// A leaderboard category: the terms solutions are ranked by, each breaking ties in the ones before it.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Category{
+    pub name: &'static str,
+    pub terms: &'static [Term]
+}
+
+impl Category{
+    /// The score of a solution with these metrics, to be compared lexicographically with lower being better.
+    pub fn score(&self, metrics: &Metrics) -> Vec<i64>{
+        self.terms.iter().map(|term| term.of(metrics)).collect()
+    }
+}
+
+const G: Term = Term::Metric(Metric::Cost);
+const C: Term = Term::Metric(Metric::Cycles);
+const A: Term = Term::Metric(Metric::Area);
+const I: Term = Term::Metric(Metric::Instructions);
+/// Cost·cycles·area.
+const X: Term = Term::Product(&[Metric::Cost, Metric::Cycles, Metric::Area]);
+const SUM: Term = Term::Sum(&[Metric::Cost, Metric::Cycles, Metric::Area]);
+
+/// The standard community categories, named by their primary and secondary metrics.
+pub const CATEGORIES: &[Category] = &[
+    Category{ name: "gc", terms: &[G, C, A] },
+    Category{ name: "ga", terms: &[G, A, C] },
+    Category{ name: "gx", terms: &[G, X] },
+    Category{ name: "cg", terms: &[C, G, A] },
+    Category{ name: "ca", terms: &[C, A, G] },
+    Category{ name: "cx", terms: &[C, X] },
+    Category{ name: "ag", terms: &[A, G, C] },
+    Category{ name: "ac", terms: &[A, C, G] },
+    Category{ name: "ax", terms: &[A, X] },
+    Category{ name: "ig", terms: &[I, G, C] },
+    Category{ name: "ic", terms: &[I, C, G] },
+    Category{ name: "ia", terms: &[I, A, G] },
+    Category{ name: "g·c", terms: &[Term::Product(&[Metric::Cost, Metric::Cycles]), G, C] },
+    Category{ name: "g+a", terms: &[Term::Sum(&[Metric::Cost, Metric::Area]), G, A] },
+    Category{ name: "sum", terms: &[SUM, G, C] },
+    Category{ name: "x", terms: &[X, G, C] }
+];
+
+/// The solution's score in each of the standard [`CATEGORIES`], by category name, or nothing if it failed.
+pub fn category_scores(verdict: &Verdict) -> BTreeMap<&'static str, Vec<i64>>{
+    let Ok(metrics) = &verdict.result else { return BTreeMap::new() };
+    CATEGORIES.iter().map(|category| (category.name, category.score(metrics))).collect()
+}
@@ -0,0 +1,58 @@
+use crate::compat::HashSet;
+use core::fmt::{Display, Formatter};
+use crate::data::{HexIndex, HexRotation, PartType, Puzzle, Solution};
+use crate::compat::prelude::*;
+
+/// A way that a solution's conduits don't match the conduits its puzzle defines.
+/// Like overlapping parts, these can't be made in the game, but are accepted by the simulation.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ConduitIssue{
+    /// A conduit part refers to a conduit the puzzle doesn't define, or the puzzle isn't a production puzzle.
+    UnknownConduit{ part: usize, conduit_index: i32 },
+    /// A conduit defined by the puzzle doesn't have exactly two ends in the solution.
+    WrongEndCount{ conduit: usize, ends: usize },
+    /// A conduit part's footprint isn't any rotation of its conduit's footprint.
+    FootprintMismatch{ part: usize, conduit: usize }
+}
+
+impl Display for ConduitIssue{
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result{
+        match self{
+            ConduitIssue::UnknownConduit{ part, conduit_index } =>
+                write!(f, "part {part} is an end of conduit {conduit_index}, which the puzzle doesn't define"),
+            ConduitIssue::WrongEndCount{ conduit, ends } =>
+                write!(f, "conduit {conduit} has {ends} ends instead of 2"),
+            ConduitIssue::FootprintMismatch{ part, conduit } =>
+                write!(f, "part {part} doesn't have the footprint of conduit {conduit} in any rotation")
+        }
+    }
+}
+
+/// Check a solution's conduit parts against the conduits defined by its puzzle, returning every issue found.
+/// Each defined conduit must have exactly two ends, and each end must cover the conduit's hexes, rotated as a whole.
+pub fn check_conduits(puzzle: &Puzzle, solution: &Solution) -> Vec<ConduitIssue>{
+    let mut issues = Vec::new();
+    let defined = puzzle.production_info.as_ref().map_or(&[][..], |info| &info.conduits[..]);
+    let mut ends = vec![0; defined.len()];
+    for (idx, part) in solution.parts.iter().enumerate().filter(|(_, part)| part.ty == PartType::Conduit){
+        let Some(conduit) = usize::try_from(part.conduit_index).ok().filter(|&c| c < defined.len()) else {
+            issues.push(ConduitIssue::UnknownConduit{ part: idx, conduit_index: part.conduit_index });
+            continue;
+        };
+        ends[conduit] += 1;
+        let footprint: HashSet<HexIndex> = part.conduit_hexes.iter().copied().collect();
+        let matches = footprint.len() == part.conduit_hexes.len() && HexRotation::ALL.iter().any(|&rotation| {
+            let rotated: HashSet<HexIndex> = defined[conduit].hexes.iter().map(|hex| hex.rotated(HexIndex::default(), rotation)).collect();
+            rotated == footprint
+        });
+        if !matches{
+            issues.push(ConduitIssue::FootprintMismatch{ part: idx, conduit });
+        }
+    }
+    for (conduit, ends) in ends.into_iter().enumerate(){
+        if ends != 2{
+            issues.push(ConduitIssue::WrongEndCount{ conduit, ends });
+        }
+    }
+    issues
+}
@@ -0,0 +1,138 @@
+use alloc::collections::BTreeSet;
+use crate::data::{Instruction, Puzzle, Solution};
+use crate::sim::config::SimConfig;
+use crate::sim::error::SimError;
+use crate::sim::molecules::molecule_at;
+use crate::sim::provenance::Provenance;
+use crate::sim::{Sim, SimPartType};
+use crate::compat::prelude::*;
+
+/// What each arm did during a solution's run, and which events the last product waited on.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CriticalPath{
+    /// The number of cycles the solution took to complete.
+    pub cycles: u64,
+    /// Every arm and Van Berlo's wheel, in the order of the solution's parts.
+    pub arms: Vec<ArmActivity>,
+    /// The events that made the last product, in the order they happened.
+    /// Shortening the time between any of them, e.g. by running an instruction earlier, is the only way to finish sooner.
+    pub chain: Vec<CriticalEvent>
+}
+
+/// How much of a run an arm spent doing nothing.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ArmActivity{
+    /// The arm's index in the solution.
+    pub part: usize,
+    pub number: i32,
+    /// The number of cycles the arm had a blank instruction, out of the whole run.
+    pub idle_cycles: u64,
+    /// The first cycle the arm did anything, if it ever did.
+    pub first_active: Option<u64>
+}
+
+impl ArmActivity{
+    /// The share of the run the arm spent idle, from 0 to 1.
+    pub fn idle_fraction(&self, cycles: u64) -> f64{
+        if cycles == 0{ 0.0 }else{ self.idle_cycles as f64 / cycles as f64 }
+    }
+}
+
+/// Something that had to happen for the last product to be made. Parts are indices into the solution's parts.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CriticalEvent{
+    /// An input spawned some of the product's atoms.
+    Spawned{ cycle: u64, part: usize },
+    /// A glyph made or changed some of the product's atoms.
+    Transmuted{ cycle: u64, part: usize },
+    /// An arm held some of the product's atoms at the end of every cycle in this range, inclusive.
+    Carried{ first: u64, last: u64, part: usize },
+    /// An output consumed the product, completing the solution.
+    Consumed{ cycle: u64, product: usize }
+}
+
+impl CriticalEvent{
+    /// The cycle the event started in, which the chain is sorted by.
+    pub fn cycle(&self) -> u64{
+        match *self{
+            CriticalEvent::Spawned{ cycle, .. } | CriticalEvent::Transmuted{ cycle, .. } | CriticalEvent::Consumed{ cycle, .. } => cycle,
+            CriticalEvent::Carried{ first, .. } => first
+        }
+    }
+}
+
+/// Where an atom first appeared, which stays the same however it moves or changes: the input or glyph that made it, and when.
+fn origin(provenance: &Provenance) -> Option<(usize, u64)>{
+    provenance.spawned.or_else(|| provenance.transmutations.first().map(|t| (t.part, t.cycle)))
+}
+
+/// Simulate a solution to completion and find how busy its arms were and what gated its last product.
+/// The solution is simulated twice: once to find the last product's atoms, and again to see which arms carried them.
+pub fn critical_path(puzzle: &Puzzle, solution: &Solution, config: SimConfig) -> Result<CriticalPath, SimError>{
+    let config = SimConfig{ track_provenance: true, ..config };
+    let start = Sim::create_with_config(puzzle, solution, config).map_err(SimError::Setup)?;
+    let mut sim = start.clone();
+    let cycles = sim.run()?;
+    let last = sim.consumed.last().expect("a completed solution should have consumed a product");
+
+    let mut chain = Vec::new();
+    let mut origins = BTreeSet::new();
+    for (_, _, provenance) in &last.atoms{
+        origins.extend(origin(provenance));
+        let spawned = provenance.spawned.map(|(part, cycle)| CriticalEvent::Spawned{ cycle, part });
+        let transmuted = provenance.transmutations.iter().map(|t| CriticalEvent::Transmuted{ cycle: t.cycle, part: t.part });
+        for event in spawned.into_iter().chain(transmuted){
+            // atoms spawned or changed together would repeat the same event
+            if !chain.contains(&event){
+                chain.push(event);
+            }
+        }
+    }
+
+    let arms = sim.parts.iter().enumerate().filter_map(|(part, p)| match &p.ty{
+        SimPartType::Arm(arm) => {
+            let active = |cycle: &u64| !matches!(arm.instruction_at(*cycle, sim.period), Instruction::Blank | Instruction::PeriodOverride);
+            Some(ArmActivity{
+                part,
+                number: arm.number,
+                idle_cycles: (0..cycles).filter(|c| !active(c)).count() as u64,
+                first_active: (0..cycles).find(active)
+            })
+        }
+        _ => None
+    }).collect();
+
+    // replay to see which arms held the product's atoms, telling atoms apart by where they came from
+    let mut replay = start;
+    let mut carrying: Vec<Option<(u64, u64)>> = vec![None; replay.parts.len()];
+    while replay.cycle < cycles{
+        let cycle = replay.cycle;
+        replay.step()?;
+        for (part, p) in replay.parts.iter().enumerate(){
+            let SimPartType::Arm(arm) = &p.ty else { continue };
+            let carries = arm.grippers(p.pos, p.rotation).iter().zip(&arm.holding).any(|(gripper, holding)| {
+                *holding && molecule_at(&replay.molecules, *gripper).is_some_and(|idx| {
+                    let molecule = &replay.molecules[idx];
+                    molecule.positions().any(|pos| molecule.provenance_at(pos).and_then(origin).is_some_and(|o| origins.contains(&o)))
+                })
+            });
+            match (&mut carrying[part], carries){
+                (Some((_, last)), true) if *last + 1 == cycle => *last = cycle,
+                (span, true) => {
+                    if let Some((first, last)) = span.replace((cycle, cycle)){
+                        chain.push(CriticalEvent::Carried{ first, last, part });
+                    }
+                }
+                (_, false) => {}
+            }
+        }
+    }
+    for (part, span) in carrying.into_iter().enumerate(){
+        if let Some((first, last)) = span{
+            chain.push(CriticalEvent::Carried{ first, last, part });
+        }
+    }
+    chain.push(CriticalEvent::Consumed{ cycle: last.cycle, product: last.product });
+    chain.sort_by_key(CriticalEvent::cycle);
+    Ok(CriticalPath{ cycles, arms, chain })
+}
@@ -0,0 +1,124 @@
+use crate::compat::HashSet;
+use core::fmt::{Display, Formatter};
+use crate::data::{Atom, Bond, BondType, Permissions, Puzzle};
+use crate::compat::prelude::*;
+
+/// A reason that a puzzle can't be solved.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum FeasibilityIssue{
+    /// The puzzle has no products, so can never be completed.
+    NoProducts,
+    /// The puzzle has products but no reagents.
+    NoReagents,
+    /// No arms are enabled, so nothing can be moved.
+    NoArms,
+    /// A product contains an element that can't be obtained from the reagents with the enabled glyphs.
+    UnobtainableElement{ product: usize, atom: Atom },
+    /// A product contains normal bonds, but no bonders are enabled and no reagent provides them.
+    MissingBonder{ product: usize },
+    /// A product contains triplex bonds, but the triplex bonder is not enabled and no reagent provides them.
+    MissingTriplexBonder{ product: usize }
+}
+
+impl Display for FeasibilityIssue{
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result{
+        match self{
+            FeasibilityIssue::NoProducts => write!(f, "puzzle has no products"),
+            FeasibilityIssue::NoReagents => write!(f, "puzzle has products but no reagents"),
+            FeasibilityIssue::NoArms => write!(f, "puzzle has no arms enabled"),
+            FeasibilityIssue::UnobtainableElement{ product, atom } =>
+                write!(f, "product {product} requires {atom:?}, which can't be obtained from the reagents with the enabled glyphs"),
+            FeasibilityIssue::MissingBonder{ product } =>
+                write!(f, "product {product} has bonds, but no bonders are enabled"),
+            FeasibilityIssue::MissingTriplexBonder{ product } =>
+                write!(f, "product {product} has triplex bonds, but the triplex bonder is not enabled")
+        }
+    }
+}
+
+/// Check a puzzle for basic solvability constraints, returning every issue found.
+/// An empty result does not mean that the puzzle is solvable, only that none of these checks ruled it out.
+///
+/// Since reagents are unlimited, this checks which elements are reachable at all rather than exact atom counts.
+pub fn check_feasibility(puzzle: &Puzzle) -> Vec<FeasibilityIssue>{
+    let mut issues = Vec::new();
+    let perms = puzzle.permissions;
+
+    if puzzle.products.is_empty(){
+        issues.push(FeasibilityIssue::NoProducts);
+    }else if puzzle.reagents.is_empty(){
+        issues.push(FeasibilityIssue::NoReagents);
+    }
+    if !perms.intersects(Permissions::SIMPLE_ARM | Permissions::MULTI_ARMS | Permissions::PISTON_ARM){
+        issues.push(FeasibilityIssue::NoArms);
+    }
+
+    let obtainable = obtainable_elements(puzzle);
+    let (reagents_have_normal, reagents_have_triplex) = bond_kinds(puzzle.reagents.iter().flat_map(|r| r.bonds.iter()));
+    for (idx, product) in puzzle.products.iter().enumerate(){
+        for atom in product.composition().into_keys(){
+            if atom != Atom::Repeat && !obtainable.contains(&atom){
+                issues.push(FeasibilityIssue::UnobtainableElement{ product: idx, atom });
+            }
+        }
+
+        let (has_normal, has_triplex) = bond_kinds(product.bonds.iter());
+        if has_normal && !perms.intersects(Permissions::BONDER | Permissions::MULTI_BONDER) && !reagents_have_normal{
+            issues.push(FeasibilityIssue::MissingBonder{ product: idx });
+        }
+        if has_triplex && !perms.contains(Permissions::TRIPLEX_BONDER) && !reagents_have_triplex{
+            issues.push(FeasibilityIssue::MissingTriplexBonder{ product: idx });
+        }
+    }
+    issues
+}
+
+// whether there are any (normal, triplex) bonds
+fn bond_kinds<'a>(bonds: impl Iterator<Item = &'a Bond>) -> (bool, bool){
+    bonds.fold((false, false), |(n, t), b| match b.ty{
+        BondType::Normal => (true, t),
+        BondType::Triplex{ .. } => (n, true)
+    })
+}
+
+/// The set of elements that can be produced from the puzzle's reagents using its enabled glyphs.
+pub fn obtainable_elements(puzzle: &Puzzle) -> HashSet<Atom>{
+    let perms = puzzle.permissions;
+    let mut obtainable: HashSet<Atom> = puzzle.reagents.iter()
+        .flat_map(|r| r.composition().into_keys())
+        .filter(|a| *a != Atom::Repeat)
+        .collect();
+    const CARDINALS: [Atom; 4] = [Atom::Air, Atom::Earth, Atom::Fire, Atom::Water];
+
+    loop{
+        let mut next = obtainable.clone();
+        if perms.contains(Permissions::CALCIFICATION) && obtainable.iter().any(|a| a.is_cardinal()){
+            next.insert(Atom::Salt);
+        }
+        // the wheel carries every cardinal element, which duplication can copy onto salt
+        if perms.contains(Permissions::BERLO | Permissions::DUPLICATION) && obtainable.contains(&Atom::Salt){
+            next.extend(CARDINALS);
+        }
+        if perms.contains(Permissions::ANIMISMUS) && obtainable.contains(&Atom::Salt){
+            next.extend([Atom::Vitae, Atom::Mors]);
+        }
+        for metal in obtainable.iter().filter_map(|a| a.next_metal()){
+            if perms.contains(Permissions::PURIFICATION)
+            || perms.contains(Permissions::PROJECTION) && obtainable.contains(&Atom::Quicksilver){
+                next.insert(metal);
+            }
+        }
+        if perms.contains(Permissions::QUINTESSENCE){
+            if CARDINALS.iter().all(|a| obtainable.contains(a)){
+                next.insert(Atom::Quintessence);
+            }
+            if obtainable.contains(&Atom::Quintessence){
+                next.extend(CARDINALS);
+            }
+        }
+        if next == obtainable{
+            return obtainable;
+        }
+        obtainable = next;
+    }
+}
@@ -0,0 +1,11 @@
+//! Static analyses over puzzles and solutions that don't require running the simulation, and over the results of simulating them.
+
+pub mod categories;
+pub mod conduits;
+pub mod critical;
+pub mod feasibility;
+pub mod overlap;
+pub mod pareto;
+pub mod stats;
+pub mod throughput;
+pub mod tournament;
@@ -0,0 +1,33 @@
+use crate::compat::HashMap;
+use crate::data::{HexIndex, PartType, Puzzle, Solution};
+use crate::sim::SimPart;
+use crate::compat::prelude::*;
+
+/// Find every pair of parts whose footprints share a hex, as indices into the solution's parts, lower index first.
+/// The game doesn't allow placing parts like this, but solutions can be edited to do so ("overlap").
+/// Arms placed on a track don't count, since that's how arms are put on tracks.
+pub fn find_overlaps(puzzle: &Puzzle, solution: &Solution) -> Vec<(usize, usize)>{
+    // solutions that can't be simulated at all are reported by the simulation instead
+    let Ok(solution) = puzzle.clean_solution(solution) else { return Vec::new() };
+    let mut occupants: HashMap<HexIndex, Vec<usize>> = HashMap::default();
+    let mut overlaps = Vec::new();
+    for (idx, part) in solution.parts.iter().enumerate(){
+        let Ok(sim_part) = SimPart::from_solution_part(part, puzzle, &solution) else { continue };
+        let mut footprint = sim_part.footprint();
+        footprint.dedup();
+        for hex in footprint{
+            let others = occupants.entry(hex).or_default();
+            for &other in others.iter(){
+                if !on_track(&part.ty, &solution.parts[other].ty) && !overlaps.contains(&(other, idx)){
+                    overlaps.push((other, idx));
+                }
+            }
+            others.push(idx);
+        }
+    }
+    overlaps
+}
+
+fn on_track(a: &PartType, b: &PartType) -> bool{
+    *a == PartType::Track && b.is_arm() || *b == PartType::Track && a.is_arm()
+}
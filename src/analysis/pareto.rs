@@ -0,0 +1,44 @@
+use crate::data::Metrics;
+use crate::verify::Verdict;
+use crate::compat::prelude::*;
+
+/// Where a solution stands relative to the others in a set of solutions to the same puzzle.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ParetoStatus{
+    /// No other solution is at least as good in every metric.
+    Frontier,
+    /// The solution at index `by` is at least as good in every metric and better in at least one.
+    Dominated{ by: usize },
+    /// The solution at index `of`, which comes earlier, has exactly the same metrics.
+    Duplicate{ of: usize },
+    /// The solution didn't complete, so it isn't ranked.
+    Invalid
+}
+
+/// Whether `a` is at least as good as `b` in cost, cycles, area, and instructions, and better in at least one of them.
+/// Lower is better in every metric.
+pub fn dominates(a: &Metrics, b: &Metrics) -> bool{
+    let (a, b) = ([a.cost, a.cycles, a.area, a.instructions], [b.cost, b.cycles, b.area, b.instructions]);
+    a.iter().zip(&b).all(|(a, b)| a <= b) && a != b
+}
+
+/// Find the Pareto frontier of a set of solutions to one puzzle, given their metrics or `None` for failed solutions.
+/// Of several solutions with the same metrics, only the first is on the frontier.
+pub fn pareto_frontier(metrics: &[Option<Metrics>]) -> Vec<ParetoStatus>{
+    metrics.iter().enumerate().map(|(idx, m)| {
+        let Some(m) = m else { return ParetoStatus::Invalid };
+        if let Some(by) = metrics.iter().position(|other| other.as_ref().is_some_and(|other| dominates(other, m))){
+            ParetoStatus::Dominated{ by }
+        }else if let Some(of) = metrics[..idx].iter().position(|other| other.as_ref() == Some(m)){
+            ParetoStatus::Duplicate{ of }
+        }else{
+            ParetoStatus::Frontier
+        }
+    }).collect()
+}
+
+/// Find the Pareto frontier of a set of verdicts for solutions to one puzzle; see [`pareto_frontier`].
+pub fn pareto_frontier_of(verdicts: &[Verdict]) -> Vec<ParetoStatus>{
+    let metrics: Vec<Option<Metrics>> = verdicts.iter().map(|verdict| verdict.result.as_ref().ok().copied()).collect();
+    pareto_frontier(&metrics)
+}
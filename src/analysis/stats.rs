@@ -0,0 +1,56 @@
+use crate::data::{Instruction, Solution};
+use crate::compat::prelude::*;
+
+/// Loop statistics for the tapes of a solution's arms, after expanding Repeat instructions.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TapeStats{
+    pub arms: Vec<ArmTapeStats>,
+    /// The number of cycles before every arm's tape starts again, which is the length of the longest tape.
+    pub tape_length: usize,
+    /// The number of cycles after which every arm is running the same instructions again.
+    /// This is the least common multiple of each arm's period, and always divides `tape_length`.
+    pub period: usize
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ArmTapeStats{
+    pub arm_number: i32,
+    /// The length of this arm's own tape, from the start of the shared timeline to its last instruction.
+    pub tape_length: usize,
+    /// The shortest number of cycles after which this arm repeats its instructions, within the shared loop.
+    pub period: usize
+}
+
+/// The shortest period of a tape padded with blanks to `length` cycles, which is always a divisor of `length`.
+pub fn effective_period(tape: &[Instruction], length: usize) -> usize{
+    let at = |idx: usize| tape.get(idx).copied().unwrap_or_default();
+    (1..=length)
+        .filter(|p| length.is_multiple_of(*p))
+        .find(|p| (0..length - p).all(|idx| at(idx) == at(idx + p)))
+        .unwrap_or(length)
+}
+
+/// Compute loop statistics for every arm in a solution.
+pub fn tape_stats(solution: &Solution) -> Result<TapeStats, &'static str>{
+    let start = solution.tape_start();
+    let tapes = solution.parts.iter()
+        .filter(|p| p.ty.is_arm())
+        .map(|p| Ok((p.arm_number, Instruction::expand_repeats(&p.tape(start))?)))
+        .collect::<Result<Vec<_>, &'static str>>()?;
+    let tape_length = tapes.iter().map(|(_, tape)| tape.len()).max().unwrap_or(0);
+    let arms: Vec<ArmTapeStats> = tapes.iter().map(|(arm_number, tape)| ArmTapeStats{
+        arm_number: *arm_number,
+        tape_length: tape.len(),
+        period: effective_period(tape, tape_length)
+    }).collect();
+    let period = arms.iter().map(|a| a.period).fold(1, lcm);
+    Ok(TapeStats{ arms, tape_length, period: if tape_length == 0{ 0 } else { period } })
+}
+
+fn gcd(a: usize, b: usize) -> usize{
+    if b == 0{ a } else { gcd(b, a % b) }
+}
+
+fn lcm(a: usize, b: usize) -> usize{
+    a / gcd(a, b) * b
+}
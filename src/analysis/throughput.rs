@@ -0,0 +1,245 @@
+//! Finding the steady state of a solution left running forever, to measure its throughput for rate leaderboards.
+//!
+//! The state of a running solution is its arms and molecules and how far it is through its instruction loop;
+//! counters like the number of products consumed are left out. Once a state repeats, everything after it repeats too,
+//! so the run splits into a transient followed by a period that loops forever.
+//! Slow machines can take millions of cycles to settle, so states are compared with Brent's cycle-finding algorithm,
+//! which only keeps two of them around.
+//!
+//! A solution only counts on rate leaderboards if its area stays bounded, which [`check_area_bound`] proves
+//! by finding a state that repeats exactly, molecules and all.
+
+use crate::data::{HexIndex, HexRotation, Molecule};
+use crate::sim::{Sim, SimMolecule};
+use crate::sim::error::SimError;
+use crate::compat::prelude::*;
+
+/// Everything about a sim that determines what it does next, apart from counters.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MachineState{
+    /// The cycle modulo the instruction loop's period.
+    phase: u64,
+    /// Each arm's position, rotation, length, and grip, in part order.
+    arms: Vec<(HexIndex, HexRotation, i32, bool, Vec<bool>)>,
+    /// Each molecule in board positions and whether it's grabbed, sorted by position so their order in the sim doesn't matter.
+    molecules: Vec<(Molecule, bool)>
+}
+
+impl MachineState{
+    pub fn of(sim: &Sim) -> MachineState{
+        MachineState::with_molecules(sim, |_| true)
+    }
+
+    /// The state of the arms and what they're holding, ignoring loose molecules, which stay put until they're grabbed again.
+    pub fn held(sim: &Sim) -> MachineState{
+        MachineState::with_molecules(sim, |molecule| molecule.grabbed)
+    }
+
+    fn with_molecules(sim: &Sim, include: impl Fn(&SimMolecule) -> bool) -> MachineState{
+        let phase = if sim.period == 0{ 0 }else{ sim.cycle % sim.period };
+        let arms = sim.arms().map(|(part, arm)| (part.pos, part.rotation, arm.length, arm.grabbing, arm.holding.clone())).collect();
+        let mut molecules: Vec<((i32, i32), Molecule, bool)> = sim.molecules.iter().filter(|molecule| include(molecule)).map(|molecule| {
+            // molecules never share a hex, so their lowest atom positions tell them apart
+            let key = molecule.positions().map(|pos| (pos.q, pos.r)).min().unwrap_or((0, 0));
+            (key, molecule.layout.translated(molecule.pos), molecule.grabbed)
+        }).collect();
+        molecules.sort_by_key(|(key, _, _)| *key);
+        MachineState{ phase, arms, molecules: molecules.into_iter().map(|(_, molecule, grabbed)| (molecule, grabbed)).collect() }
+    }
+}
+
+/// The repeating part of a solution left running forever.
+#[derive(Clone, Debug)]
+pub struct SteadyState{
+    /// The number of cycles before the solution starts repeating, counted from the sim the search started from.
+    pub transient: u64,
+    /// The number of cycles in each repetition.
+    pub period: u64,
+    /// The number of each product consumed during each repetition, by product index.
+    pub products: Vec<u64>,
+    /// The area after the first repetition, which the solution never grows past.
+    pub area: usize,
+    /// The sim at the start of the first repetition.
+    pub start: Sim
+}
+
+impl SteadyState{
+    /// The number of cycles per product once the solution has settled, or `None` if it doesn't make this product.
+    pub fn cycles_per_product(&self, product: usize) -> Option<f64>{
+        match self.products.get(product){
+            Some(&count) if count > 0 => Some(self.period as f64 / count as f64),
+            _ => None
+        }
+    }
+}
+
+/// Run a copy of `sim` until its state repeats, and find where the repetition starts and how long it is.
+///
+/// Gives up with [`SimError::CycleLimit`] once the sim's [`max_cycles`](crate::sim::config::SimConfig::max_cycles) is reached,
+/// and fails if the solution fails before repeating.
+pub fn find_steady_state(sim: &Sim) -> Result<SteadyState, SimError>{
+    let period = find_period(sim, MachineState::of, |_| {})?;
+    settle(sim, sim.clone(), period)
+}
+
+/// Whether a solution's area stays bounded when left running forever.
+#[derive(Clone, Debug)]
+pub enum AreaBound{
+    /// The whole board repeats, so the area can't grow past what it reaches in the first repetition.
+    Bounded(Box<SteadyState>),
+    /// The arms repeat but the molecules they leave behind never do, until the cycle limit.
+    Divergent{
+        /// The cycle the last repetition of the arms ended on.
+        cycle: u64,
+        /// A molecule, in board positions, that wasn't on the board one repetition earlier, or else one that's gone since then.
+        molecule: Molecule,
+        /// If the molecule was there one repetition earlier but elsewhere, how far it moved.
+        drift: Option<HexIndex>
+    }
+}
+
+/// Prove that a solution's area stays bounded by finding a state that repeats exactly, or find a molecule that keeps changing.
+///
+/// Loose molecules only move when they're grabbed, so the arms and what they hold are searched for a repetition first;
+/// then each repetition is compared with the one before it until the whole board repeats or the cycle limit is reached.
+/// Fails if the arms don't repeat within the cycle limit, or if the solution fails.
+pub fn check_area_bound(sim: &Sim) -> Result<AreaBound, SimError>{
+    let period = find_period(sim, MachineState::held, |_| {})?;
+    // the arms and what they hold only repeat once they start repeating for good
+    let mut before = sim.clone();
+    let mut after = advanced(&before, period)?;
+    while MachineState::held(&before) != MachineState::held(&after){
+        step(&mut before)?;
+        step(&mut after)?;
+    }
+
+    let mut earlier = None;
+    loop{
+        if MachineState::of(&before) == MachineState::of(&after){
+            // the whole board settled during the last repetition
+            return settle(sim, earlier.unwrap_or(before), period).map(|steady| AreaBound::Bounded(Box::new(steady)));
+        }
+        let next = match advanced(&after, period){
+            Ok(next) => next,
+            Err(SimError::CycleLimit{ .. }) => return Ok(divergence(&before, &after)),
+            Err(err) => return Err(err)
+        };
+        earlier = Some(before);
+        before = after;
+        after = next;
+    }
+}
+
+fn divergence(before: &Sim, after: &Sim) -> AreaBound{
+    let placed = |sim: &Sim| -> Vec<Molecule> {
+        sim.molecules.iter().map(|molecule| molecule.layout.translated(molecule.pos)).collect()
+    };
+    let (before, after_sim, after) = (placed(before), after, placed(after));
+    // the arms and what they hold matched, so the loose molecules didn't
+    let molecule = after.iter().find(|molecule| !before.contains(molecule))
+        .or_else(|| before.iter().find(|molecule| !after.contains(molecule)))
+        .cloned()
+        .expect("boards should differ in their loose molecules");
+    // a molecule of the same shape that's gone from where it was has moved
+    let (origin, shape) = normalized(&molecule);
+    let drift = before.iter()
+        .filter(|earlier| !after.contains(earlier))
+        .map(normalized)
+        .find(|(_, earlier)| *earlier == shape)
+        .map(|(earlier, _)| origin - earlier);
+    AreaBound::Divergent{ cycle: after_sim.cycle, molecule, drift }
+}
+
+/// A molecule moved so its lowest atom is at the origin, and where that atom was.
+fn normalized(molecule: &Molecule) -> (HexIndex, Molecule){
+    let origin = molecule.atoms.keys().min_by_key(|pos| (pos.q, pos.r)).copied().unwrap_or_default();
+    (origin, molecule.translated(HexIndex::new(-origin.q, -origin.r)))
+}
+
+/// Like [`find_steady_state`], but once the period is known, the start of the repetition is searched for on `threads` threads at once,
+/// each resuming from a snapshot taken while the period was found. This is faster for machines with long transients.
+#[cfg(feature = "std")]
+pub fn find_steady_state_parallel(sim: &Sim, threads: usize) -> Result<SteadyState, SimError>{
+    const SNAPSHOT_INTERVAL: u64 = 1024;
+    let mut snapshots = vec![sim.clone()];
+    let period = find_period(sim, MachineState::of, |hare| if (hare.cycle - sim.cycle).is_multiple_of(SNAPSHOT_INTERVAL){
+        snapshots.push(hare.clone());
+    })?;
+
+    // once a snapshot is repeating, every later one is too, so find the first one that is
+    let threads = threads.max(1);
+    let repeating: Vec<Result<bool, SimError>> = std::thread::scope(|scope| {
+        let handles: Vec<_> = snapshots.chunks(snapshots.len().div_ceil(threads)).map(|chunk| scope.spawn(move || {
+            chunk.iter().map(|snapshot| Ok(MachineState::of(snapshot) == MachineState::of(&advanced(snapshot, period)?))).collect::<Vec<_>>()
+        })).collect();
+        handles.into_iter().flat_map(|handle| handle.join().expect("steady state search thread panicked")).collect()
+    });
+    let mut first = snapshots.len();
+    for (idx, result) in repeating.into_iter().enumerate(){
+        if result?{
+            first = idx;
+            break;
+        }
+    }
+
+    // the repetition starts somewhere after the snapshot before that one, or after the last one if none are repeating yet
+    settle(sim, snapshots[first.saturating_sub(1)].clone(), period)
+}
+
+/// The first phase of Brent's algorithm: the length of the loop the sim's states, as told apart by `state`, eventually fall into.
+/// `visit` sees the sim after every cycle.
+fn find_period(sim: &Sim, state: impl Fn(&Sim) -> MachineState, mut visit: impl FnMut(&Sim)) -> Result<u64, SimError>{
+    let mut power = 1;
+    let mut period = 1;
+    let mut tortoise = state(sim);
+    let mut hare = sim.clone();
+    step(&mut hare)?;
+    visit(&hare);
+    loop{
+        let current = state(&hare);
+        if current == tortoise{
+            return Ok(period);
+        }
+        if power == period{
+            tortoise = current;
+            power *= 2;
+            period = 0;
+        }
+        step(&mut hare)?;
+        visit(&hare);
+        period += 1;
+    }
+}
+
+/// The second phase of Brent's algorithm: step forward from `from`, which must be no later than the start of the repetition,
+/// until it's found.
+fn settle(sim: &Sim, from: Sim, period: u64) -> Result<SteadyState, SimError>{
+    let mut start = from;
+    let mut hare = advanced(&start, period)?;
+    while MachineState::of(&start) != MachineState::of(&hare){
+        step(&mut start)?;
+        step(&mut hare)?;
+    }
+    let end = advanced(&start, period)?;
+    let products = end.product_counts().iter().zip(&start.product_counts()).map(|(after, before)| after - before).collect();
+    Ok(SteadyState{ transient: start.cycle - sim.cycle, period, products, area: end.area.len(), start })
+}
+
+fn advanced(sim: &Sim, cycles: u64) -> Result<Sim, SimError>{
+    let mut sim = sim.clone();
+    for _ in 0..cycles{
+        step(&mut sim)?;
+    }
+    Ok(sim)
+}
+
+/// Run a cycle without keeping its events, which would otherwise pile up over millions of cycles.
+fn step(sim: &mut Sim) -> Result<(), SimError>{
+    if sim.cycle >= sim.config.max_cycles{
+        return Err(SimError::CycleLimit{ cycles: sim.cycle });
+    }
+    sim.step()?;
+    sim.events.clear();
+    Ok(())
+}
+
@@ -0,0 +1,102 @@
+//! Ranking many solutions to one puzzle in every category, e.g. for a weekly tournament.
+
+use crate::analysis::categories::CATEGORIES;
+use crate::data::{Puzzle, Solution};
+use crate::sim::config::SimConfig;
+use crate::verify::{json_string, verify_with_config, Verdict};
+use crate::compat::prelude::*;
+
+/// A solution entered into a tournament, and how it did.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Entry{
+    /// The name to show for this entry, e.g. its file name or author.
+    pub name: String,
+    pub verdict: Verdict
+}
+
+/// The valid entries in order of their score in one category.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Ranking{
+    pub category: &'static str,
+    /// Each entry's place and index in [`Tournament::entries`], best first. Tied entries share a place, and the next place is skipped.
+    pub places: Vec<(usize, usize)>
+}
+
+/// Every entry's verdict, and their rankings in each of the standard [`CATEGORIES`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Tournament{
+    pub entries: Vec<Entry>,
+    pub rankings: Vec<Ranking>
+}
+
+impl Tournament{
+    /// Verify each named solution, including its asymptotic metrics, and rank them.
+    pub fn run(puzzle: &Puzzle, solutions: impl IntoIterator<Item = (String, Solution)>) -> Tournament{
+        let config = SimConfig{ asymptotic: true, ..SimConfig::default() };
+        Tournament::new(solutions.into_iter().map(|(name, solution)| Entry{ name, verdict: verify_with_config(puzzle, &solution, config.clone()) }).collect())
+    }
+
+    /// Rank entries that have already been verified.
+    pub fn new(entries: Vec<Entry>) -> Tournament{
+        let rankings = CATEGORIES.iter().map(|category| {
+            let mut scored: Vec<(Vec<i64>, usize)> = entries.iter().enumerate()
+                .filter_map(|(idx, entry)| entry.verdict.result.as_ref().ok().map(|metrics| (category.score(metrics), idx)))
+                .collect();
+            scored.sort();
+            let mut places = Vec::with_capacity(scored.len());
+            for (position, (score, idx)) in scored.iter().enumerate(){
+                let place = match places.last(){
+                    Some(&(place, _)) if scored[position - 1].0 == *score => place,
+                    _ => position + 1
+                };
+                places.push((place, *idx));
+            }
+            Ranking{ category: category.name, places }
+        }).collect();
+        Tournament{ entries, rankings }
+    }
+
+    /// A report with a table of every entry's metrics and the standings in each category.
+    pub fn to_markdown(&self) -> String{
+        let mut report = String::from("## Results\n\n| solution | cost | cycles | area | instructions | cycles@∞ | area@∞ |\n|---|---|---|---|---|---|---|\n");
+        for entry in &self.entries{
+            let name = markdown_escape(&entry.name);
+            match &entry.verdict.result{
+                Ok(metrics) => {
+                    let asymptotic = entry.verdict.extended.as_ref().and_then(|extended| extended.asymptotic);
+                    let rate = asymptotic.and_then(|asymptotic| asymptotic.rate()).map_or_else(|| "-".to_string(), |rate| rate.to_string());
+                    let area = asymptotic.map_or_else(|| "-".to_string(), |asymptotic| asymptotic.area.to_string());
+                    report.push_str(&format!("| {name} | {} | {} | {} | {} | {rate} | {area} |\n", metrics.cost, metrics.cycles, metrics.area, metrics.instructions));
+                }
+                Err(err) => report.push_str(&format!("| {name} | invalid: {} | | | | | |\n", markdown_escape(&err.to_string())))
+            }
+        }
+        report.push_str("\n## Standings\n\n| category | ranking |\n|---|---|\n");
+        for ranking in &self.rankings{
+            let places: Vec<String> = ranking.places.iter()
+                .map(|(place, idx)| format!("{place}. {}", markdown_escape(&self.entries[*idx].name)))
+                .collect();
+            report.push_str(&format!("| {} | {} |\n", ranking.category, places.join(", ")));
+        }
+        report
+    }
+
+    /// The report as a JSON object, with each entry's full [verdict](Verdict::to_json) and the rankings by category name.
+    pub fn to_json(&self) -> String{
+        let entries: Vec<String> = self.entries.iter()
+            .map(|entry| format!("{{\"name\":{},\"verdict\":{}}}", json_string(&entry.name), entry.verdict.to_json()))
+            .collect();
+        let rankings: Vec<String> = self.rankings.iter().map(|ranking| {
+            let places: Vec<String> = ranking.places.iter()
+                .map(|(place, idx)| format!("{{\"place\":{place},\"entry\":{idx}}}"))
+                .collect();
+            format!("{}:[{}]", json_string(ranking.category), places.join(","))
+        }).collect();
+        format!("{{\"entries\":[{}],\"rankings\":{{{}}}}}", entries.join(","), rankings.join(","))
+    }
+}
+
+/// Keep table cells from being split by pipes or lines in names and errors.
+fn markdown_escape(s: &str) -> String{
+    s.replace('|', "\\|").replace('\n', " ")
+}
@@ -0,0 +1,73 @@
+use crate::data::{Molecule, Permissions, ProductionInfo, Puzzle};
+use crate::compat::prelude::*;
+
+/// A builder for puzzles, for creating puzzles in code rather than parsing them from files.
+/// Unlike parsing, building checks that every reagent and product is a valid molecule.
+#[derive(Debug, Clone)]
+pub struct PuzzleBuilder{
+    puzzle: Puzzle,
+    allow_quantum_bonds: bool
+}
+
+impl PuzzleBuilder{
+    /// Start building a puzzle with the given string ID, no molecules, default permissions, and a product multiplier of 1.
+    pub fn new(name: impl Into<String>) -> Self{
+        Self{
+            puzzle: Puzzle{
+                name: name.into(),
+                creator_id: 0,
+                reagents: Vec::new(),
+                products: Vec::new(),
+                product_multiplier: 1,
+                permissions: Permissions::DEFAULT_PERMISSIONS,
+                production_info: None
+            },
+            allow_quantum_bonds: false
+        }
+    }
+
+    pub fn creator_id(mut self, creator_id: u64) -> Self{
+        self.puzzle.creator_id = creator_id;
+        self
+    }
+
+    pub fn reagent(mut self, reagent: Molecule) -> Self{
+        self.puzzle.reagents.push(reagent);
+        self
+    }
+
+    pub fn product(mut self, product: Molecule) -> Self{
+        self.puzzle.products.push(product);
+        self
+    }
+
+    pub fn product_multiplier(mut self, product_multiplier: i32) -> Self{
+        self.puzzle.product_multiplier = product_multiplier;
+        self
+    }
+
+    pub fn permissions(mut self, permissions: Permissions) -> Self{
+        self.puzzle.permissions = permissions;
+        self
+    }
+
+    pub fn production_info(mut self, production_info: ProductionInfo) -> Self{
+        self.puzzle.production_info = Some(production_info);
+        self
+    }
+
+    /// Allow bonds between non-adjacent atoms, which can only appear in modded or hand-edited puzzles.
+    pub fn allow_quantum_bonds(mut self, allow: bool) -> Self{
+        self.allow_quantum_bonds = allow;
+        self
+    }
+
+    pub fn build(self) -> Result<Puzzle, &'static str>{
+        for molecule in self.puzzle.reagents.iter().chain(&self.puzzle.products){
+            if let Some(issue) = molecule.validate(self.allow_quantum_bonds).first(){
+                return Err(issue.description());
+            }
+        }
+        Ok(self.puzzle)
+    }
+}
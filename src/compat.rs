@@ -0,0 +1,88 @@
+//! Shims over the parts of `std` that the core of the library uses, so it can be built without `std`.
+
+/// The parts of the `std` prelude that come from `alloc`.
+pub mod prelude{
+    pub use alloc::boxed::Box;
+    pub use alloc::string::{String, ToString};
+    pub use alloc::vec::Vec;
+    pub use alloc::{format, vec};
+}
+
+// logging, which compiles to nothing without the tracing feature
+
+/// Emit a `tracing` event at the given level, like `event!(debug, "message")`.
+#[cfg(feature = "tracing")]
+macro_rules! event{
+    ($level:ident, $($arg:tt)*) => { tracing::$level!($($arg)*) }
+}
+#[cfg(not(feature = "tracing"))]
+macro_rules! event{
+    ($level:ident, $($arg:tt)*) => { () }
+}
+
+/// Enter a `tracing` span, like `span!(debug_span, "cycle", cycle)`, which is exited when the returned guard is dropped.
+#[cfg(feature = "tracing")]
+macro_rules! span{
+    ($level:ident, $($arg:tt)*) => { tracing::$level!($($arg)*).entered() }
+}
+#[cfg(not(feature = "tracing"))]
+macro_rules! span{
+    ($level:ident, $($arg:tt)*) => { $crate::compat::NoSpan }
+}
+
+/// Stands in for an entered span without the tracing feature.
+#[cfg(not(feature = "tracing"))]
+pub struct NoSpan;
+
+// aliases rather than re-exports in both cases, so the hasher type is always inferred
+#[cfg(feature = "std")]
+pub type HashMap<K, V> = std::collections::HashMap<K, V>;
+#[cfg(feature = "std")]
+pub type HashSet<T> = std::collections::HashSet<T>;
+
+#[cfg(not(feature = "std"))]
+pub type HashMap<K, V> = hashbrown::HashMap<K, V, core::hash::BuildHasherDefault<FxHasher>>;
+#[cfg(not(feature = "std"))]
+pub type HashSet<T> = hashbrown::HashSet<T, core::hash::BuildHasherDefault<FxHasher>>;
+
+/// A small, fast, non-randomized hasher, since there's no source of randomness without `std`.
+#[cfg(not(feature = "std"))]
+#[derive(Copy, Clone, Debug, Default)]
+pub struct FxHasher{
+    hash: u64
+}
+
+#[cfg(not(feature = "std"))]
+impl core::hash::Hasher for FxHasher{
+    fn write(&mut self, bytes: &[u8]){
+        for byte in bytes{
+            self.hash = (self.hash.rotate_left(5) ^ *byte as u64).wrapping_mul(0x51_7c_c1_b7_27_22_0a_95);
+        }
+    }
+
+    fn finish(&self) -> u64{
+        self.hash
+    }
+}
+
+// float functions that are only inherent methods with std
+
+#[cfg(feature = "std")]
+pub fn sqrt(x: f32) -> f32{ x.sqrt() }
+#[cfg(not(feature = "std"))]
+pub fn sqrt(x: f32) -> f32{ libm::sqrtf(x) }
+
+#[cfg(feature = "std")]
+pub fn sin(x: f32) -> f32{ x.sin() }
+#[cfg(not(feature = "std"))]
+pub fn sin(x: f32) -> f32{ libm::sinf(x) }
+
+#[cfg(feature = "std")]
+pub fn cos(x: f32) -> f32{ x.cos() }
+#[cfg(not(feature = "std"))]
+pub fn cos(x: f32) -> f32{ libm::cosf(x) }
+
+#[cfg(feature = "std")]
+pub fn round(x: f32) -> f32{ x.round() }
+#[cfg(not(feature = "std"))]
+pub fn round(x: f32) -> f32{ libm::roundf(x) }
@@ -0,0 +1,243 @@
+//! Exporting a puzzle and a fixed layout of parts as a state-transition model, for external solvers to plan tapes with.
+//!
+//! The model describes each arm's state (rotation, length, track position and whether it's grabbing) in every cycle up to
+//! a horizon, how each action changes it, and where its grippers are as a result. The inputs, outputs and glyphs are
+//! listed with the hexes and atoms they cover, but molecules aren't modelled cycle by cycle: bonds, glyph effects and
+//! collisions are left to the simulator, so plans should be checked by turning them back into a solution with
+//! [`apply_plan`] and verifying it.
+
+use crate::data::{Atom, HexIndex, HexRotation, Instruction, Molecule, Part, PartType, Permissions, Puzzle, Solution};
+use crate::sim::arm::SimArm;
+use crate::sim::glyphs::glyph_offsets;
+use crate::sim::{Sim, SimPartType};
+use crate::verify::json_string;
+use crate::compat::prelude::*;
+
+/// Every action an arm can take in one cycle. In the SMT-LIB model, actions are numbered by their index here.
+pub const ACTIONS: [Instruction; 11] = [
+    Instruction::Blank, Instruction::Grab, Instruction::Drop,
+    Instruction::RotateClockwise, Instruction::RotateAnticlockwise,
+    Instruction::Extend, Instruction::Retract,
+    Instruction::PivotClockwise, Instruction::PivotAnticlockwise,
+    Instruction::Advance, Instruction::Retreat
+];
+
+/// An arm in a [`Model`], and what it can do.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ArmModel{
+    pub number: i32,
+    pub ty: PartType,
+    /// Where the base starts.
+    pub pos: HexIndex,
+    pub rotation: HexRotation,
+    pub length: i32,
+    /// The direction of each gripper, relative to the arm's rotation.
+    pub grippers: Vec<HexRotation>,
+    /// If the arm is on a track, the hexes it can move along, and whether the track loops.
+    pub track: Option<(Vec<HexIndex>, bool)>,
+    /// The actions this arm can take, always including Blank.
+    pub actions: Vec<Instruction>
+}
+
+/// A puzzle and layout as a state-transition model over `horizon` cycles.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Model{
+    pub puzzle: String,
+    pub horizon: u64,
+    pub arms: Vec<ArmModel>,
+    /// Each input's reagent index and the atoms it spawns, in board positions.
+    pub inputs: Vec<(usize, Vec<(HexIndex, Atom)>)>,
+    /// Each output's product index and the atoms it takes, in board positions.
+    pub outputs: Vec<(usize, Vec<(HexIndex, Atom)>)>,
+    /// Each glyph's name and the hexes it covers.
+    pub glyphs: Vec<(String, Vec<HexIndex>)>,
+    /// The number of each product needed to complete the puzzle.
+    pub required: Vec<u64>
+}
+
+impl Model{
+    /// Model a layout of parts for a puzzle. Any instructions the layout's arms have are ignored.
+    pub fn new(puzzle: &Puzzle, layout: &Solution, horizon: u64) -> Result<Model, &'static str>{
+        let mut layout = layout.clone();
+        layout.parts.iter_mut().for_each(|part| part.instructions.clear());
+        let sim = Sim::create(puzzle, &layout)?;
+        let mut model = Model{
+            puzzle: puzzle.name.clone(), horizon, arms: Vec::new(), inputs: Vec::new(), outputs: Vec::new(), glyphs: Vec::new(),
+            required: sim.required.clone()
+        };
+        let placed = |molecule: &Molecule, part: &Part| {
+            let mut atoms: Vec<(HexIndex, Atom)> = molecule.placed(part.pos, HexRotation::from_signed(part.rotation)).atoms.into_iter().collect();
+            atoms.sort_by_key(|(pos, _)| (pos.q, pos.r));
+            atoms
+        };
+        // setup keeps the layout's parts in order
+        for (part, sim_part) in layout.parts.iter().zip(&sim.parts){
+            match &sim_part.ty{
+                SimPartType::Arm(arm) => model.arms.push(ArmModel{
+                    number: arm.number, ty: arm.ty.clone(), pos: sim_part.pos, rotation: sim_part.rotation, length: arm.length,
+                    grippers: SimArm::gripper_offsets(&arm.ty).to_vec(),
+                    track: arm.track.as_ref().map(|track| (track.hexes.clone(), track.looping)),
+                    actions: actions(arm, puzzle.permissions)
+                }),
+                SimPartType::Input(input) => model.inputs.push((part.index as usize, placed(&input.spawned_molecule(), part))),
+                SimPartType::Output(output) => model.outputs.push((output.index, placed(&output.product, part))),
+                ty if glyph_offsets(ty).is_some() => model.glyphs.push((part.ty.to_name().to_string(), sim_part.footprint())),
+                _ => {}
+            }
+        }
+        Ok(model)
+    }
+
+    pub fn to_json(&self) -> String{
+        let hex = |pos: &HexIndex| format!("[{},{}]", pos.q, pos.r);
+        let id = |instr: &Instruction| json_string(&(instr.to_id() as char).to_string());
+        let atoms = |list: &[(usize, Vec<(HexIndex, Atom)>)]| list.iter().map(|(index, atoms)| {
+            let atoms: Vec<String> = atoms.iter().map(|(pos, atom)| format!("[{},{},{}]", pos.q, pos.r, json_string(atom.symbol()))).collect();
+            format!("{{\"index\":{index},\"atoms\":[{}]}}", atoms.join(","))
+        }).collect::<Vec<_>>().join(",");
+        let arms: Vec<String> = self.arms.iter().map(|arm| {
+            let grippers: Vec<String> = arm.grippers.iter().map(|gripper| gripper.turns().to_string()).collect();
+            let track = arm.track.as_ref().map_or_else(|| "null".to_string(), |(hexes, looping)| {
+                format!("{{\"hexes\":[{}],\"looping\":{looping}}}", hexes.iter().map(hex).collect::<Vec<_>>().join(","))
+            });
+            format!("{{\"number\":{},\"type\":{},\"pos\":{},\"rotation\":{},\"length\":{},\"grippers\":[{}],\"track\":{track},\"actions\":[{}]}}",
+                arm.number, json_string(arm.ty.to_name()), hex(&arm.pos), arm.rotation.turns(), arm.length, grippers.join(","),
+                arm.actions.iter().map(id).collect::<Vec<_>>().join(","))
+        }).collect();
+        let glyphs: Vec<String> = self.glyphs.iter()
+            .map(|(name, hexes)| format!("{{\"type\":{},\"hexes\":[{}]}}", json_string(name), hexes.iter().map(hex).collect::<Vec<_>>().join(",")))
+            .collect();
+        let required: Vec<String> = self.required.iter().map(u64::to_string).collect();
+        format!("{{\"puzzle\":{},\"horizon\":{},\"actions\":[{}],\"arms\":[{}],\"inputs\":[{}],\"outputs\":[{}],\"glyphs\":[{}],\"required\":[{}]}}",
+            json_string(&self.puzzle), self.horizon, ACTIONS.iter().map(id).collect::<Vec<_>>().join(","), arms.join(","),
+            atoms(&self.inputs), atoms(&self.outputs), glyphs.join(","), required.join(","))
+    }
+
+    /// The arms' state and transitions as SMT-LIB declarations and assertions, for a solver to add goals to.
+    ///
+    /// For arm `n` in cycle `t`: `armn_rot_t` (turns anticlockwise, 0 to 5), `armn_len_t`, `armn_track_t` (index into the
+    /// track, if there is one), `armn_grab_t`, and the gripper positions `armn_gK_q_t` and `armn_gK_r_t`.
+    /// `armn_act_t` is the index in [`ACTIONS`] of the action taken during cycle `t`.
+    pub fn to_smtlib(&self) -> String{
+        let mut out = format!("; puzzle {}, {} cycles\n; actions:", self.puzzle, self.horizon);
+        for (idx, instr) in ACTIONS.iter().enumerate(){
+            out.push_str(&format!(" {idx}={:?}", instr));
+        }
+        out.push_str("\n(set-logic QF_LIA)\n");
+        let unit = |f: fn(HexIndex) -> i32| (0..6).rev().fold(String::new(), |rest, turns| {
+            let value = f(HexRotation::from_unsigned(turns as u8).to_unit_offset());
+            if rest.is_empty(){ int(value) }else{ format!("(ite (= d {turns}) {} {rest})", int(value)) }
+        });
+        out.push_str(&format!("(define-fun dq ((d Int)) Int {})\n", unit(|offset| offset.q)));
+        out.push_str(&format!("(define-fun dr ((d Int)) Int {})\n", unit(|offset| offset.r)));
+        for (list, kind) in [(&self.inputs, "input"), (&self.outputs, "output")]{
+            for (index, atoms) in list{
+                let atoms: Vec<String> = atoms.iter().map(|(pos, atom)| format!("{} at ({}, {})", atom.symbol(), pos.q, pos.r)).collect();
+                out.push_str(&format!("; {kind} {index}: {}\n", atoms.join(", ")));
+            }
+        }
+        for (name, hexes) in &self.glyphs{
+            let hexes: Vec<String> = hexes.iter().map(|pos| format!("({}, {})", pos.q, pos.r)).collect();
+            out.push_str(&format!("; {name}: {}\n", hexes.join(", ")));
+        }
+        for arm in &self.arms{
+            arm.write_smtlib(&mut out, self.horizon);
+        }
+        out
+    }
+}
+
+impl ArmModel{
+    fn write_smtlib(&self, out: &mut String, horizon: u64){
+        let name = format!("arm{}", self.number);
+        let act = |instr: Instruction| ACTIONS.iter().position(|action| *action == instr).expect("every action is listed");
+        for t in 0..=horizon{
+            out.push_str(&format!("(declare-const {name}_rot_{t} Int)\n(assert (and (<= 0 {name}_rot_{t}) (< {name}_rot_{t} 6)))\n"));
+            out.push_str(&format!("(declare-const {name}_len_{t} Int)\n(declare-const {name}_grab_{t} Bool)\n"));
+            if self.track.is_some(){
+                out.push_str(&format!("(declare-const {name}_track_{t} Int)\n"));
+            }
+            if t < horizon{
+                let allowed: Vec<String> = self.actions.iter().map(|instr| format!("(= {name}_act_{t} {})", act(*instr))).collect();
+                out.push_str(&format!("(declare-const {name}_act_{t} Int)\n(assert (or {}))\n", allowed.join(" ")));
+            }
+        }
+        out.push_str(&format!("(assert (= {name}_rot_0 {}))\n(assert (= {name}_len_0 {}))\n(assert (not {name}_grab_0))\n", self.rotation.turns(), self.length));
+        if let Some((hexes, _)) = &self.track{
+            let start = hexes.iter().position(|hex| *hex == self.pos).unwrap_or(0);
+            out.push_str(&format!("(assert (= {name}_track_0 {start}))\n"));
+        }
+        for t in 0..horizon{
+            let (a, next) = (format!("{name}_act_{t}"), t + 1);
+            let is = |instr| format!("(= {a} {})", act(instr));
+            out.push_str(&format!("(assert (= {name}_rot_{next} (ite {} (mod (+ {name}_rot_{t} 5) 6) (ite {} (mod (+ {name}_rot_{t} 1) 6) {name}_rot_{t}))))\n",
+                is(Instruction::RotateClockwise), is(Instruction::RotateAnticlockwise)));
+            out.push_str(&format!("(assert (= {name}_len_{next} (ite (and {} (< {name}_len_{t} 3)) (+ {name}_len_{t} 1) (ite (and {} (> {name}_len_{t} 1)) (- {name}_len_{t} 1) {name}_len_{t}))))\n",
+                is(Instruction::Extend), is(Instruction::Retract)));
+            out.push_str(&format!("(assert (= {name}_grab_{next} (ite {} true (ite {} false {name}_grab_{t}))))\n",
+                is(Instruction::Grab), is(Instruction::Drop)));
+            if let Some((hexes, looping)) = &self.track{
+                let (track, len) = (format!("{name}_track_{t}"), hexes.len());
+                let (forward, back) = if *looping{
+                    (format!("(mod (+ {track} 1) {len})"), format!("(mod (+ {track} {}) {len})", len - 1))
+                }else{
+                    (format!("(ite (< {track} {}) (+ {track} 1) {track})", len - 1), format!("(ite (> {track} 0) (- {track} 1) {track})"))
+                };
+                out.push_str(&format!("(assert (= {name}_track_{next} (ite {} {forward} (ite {} {back} {track}))))\n",
+                    is(Instruction::Advance), is(Instruction::Retreat)));
+            }
+        }
+        // the base is fixed unless it's on a track, and each gripper is `len` steps from it
+        let base = |t: u64, coord: fn(&HexIndex) -> i32| match &self.track{
+            Some((hexes, _)) => hexes.iter().enumerate().rev().fold(String::new(), |rest, (idx, hex)| {
+                if rest.is_empty(){ int(coord(hex)) }else{ format!("(ite (= {name}_track_{t} {idx}) {} {rest})", int(coord(hex))) }
+            }),
+            None => int(coord(&self.pos))
+        };
+        for t in 0..=horizon{
+            for (k, offset) in self.grippers.iter().enumerate(){
+                for (axis, coord, unit) in [("q", (|hex: &HexIndex| hex.q) as fn(&HexIndex) -> i32, "dq"), ("r", |hex: &HexIndex| hex.r, "dr")]{
+                    let dir = format!("({unit} (mod (+ {name}_rot_{t} {}) 6))", offset.turns());
+                    out.push_str(&format!("(define-fun {name}_g{k}_{axis}_{t} () Int (+ {} (ite (= {name}_len_{t} 1) {dir} (ite (= {name}_len_{t} 2) (* 2 {dir}) (* 3 {dir})))))\n",
+                        base(t, coord)));
+                }
+            }
+        }
+    }
+}
+
+/// An integer literal, which SMT-LIB writes without a minus sign.
+fn int(value: i32) -> String{
+    if value < 0{ format!("(- {})", value.unsigned_abs()) }else{ value.to_string() }
+}
+
+/// The actions an arm can take, given what it is and what the puzzle allows.
+fn actions(arm: &SimArm, permissions: Permissions) -> Vec<Instruction>{
+    ACTIONS.iter().copied().filter(|instr| match instr{
+        Instruction::Blank => true,
+        Instruction::Grab | Instruction::Drop if arm.ty == PartType::Berlo => false,
+        Instruction::Grab => permissions.contains(Permissions::GRAB_TURN_INSTRUCTIONS),
+        Instruction::Drop => permissions.contains(Permissions::DROP_INSTRUCTION),
+        Instruction::RotateClockwise | Instruction::RotateAnticlockwise => permissions.contains(Permissions::GRAB_TURN_INSTRUCTIONS),
+        Instruction::Extend | Instruction::Retract => arm.ty == PartType::PistonArm,
+        Instruction::PivotClockwise | Instruction::PivotAnticlockwise => arm.ty != PartType::Berlo && permissions.contains(Permissions::PIVOT_INSTRUCTIONS),
+        Instruction::Advance | Instruction::Retreat => arm.track.is_some(),
+        _ => false
+    }).collect()
+}
+
+/// The layout with each arm's instructions replaced by a plan, given as the action it takes in each cycle from the first,
+/// by arm number. Blank actions are left out, and arms without a plan keep no instructions.
+pub fn apply_plan(layout: &Solution, plan: &[(i32, Vec<Instruction>)]) -> Solution{
+    let mut solution = layout.clone();
+    for part in &mut solution.parts{
+        if !part.ty.is_arm() && part.ty != PartType::Berlo{
+            continue;
+        }
+        part.instructions = plan.iter().find(|(number, _)| *number == part.arm_number).map_or_else(Vec::new, |(_, actions)| {
+            actions.iter().enumerate().filter(|(_, instr)| **instr != Instruction::Blank).map(|(idx, instr)| (*instr, idx as i32)).collect()
+        });
+    }
+    solution.metrics = None;
+    solution
+}
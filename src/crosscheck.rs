@@ -0,0 +1,114 @@
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+use std::process::Command;
+use crate::data::Metrics;
+use crate::parse::{parse_puzzle, parse_solution};
+use crate::verify::{verify, Verdict};
+
+// cross-checking against the reference C omsim (https://github.com/ianh/omsim), run as a subprocess
+
+/// The metrics the reference omsim is asked to report, in the order they appear in [`Metrics`].
+const METRIC_NAMES: [&str; 4] = ["cycles", "cost", "area", "instructions"];
+
+/// The verdicts from both simulators for the same puzzle and solution.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CrossCheck{
+    pub ours: Verdict,
+    /// The reference metrics, or the reference's error message.
+    pub reference: Result<Metrics, String>
+}
+
+impl CrossCheck{
+    /// Whether both simulators agree on validity and, for valid solutions, on every metric.
+    /// Failure reasons aren't compared, since the two simulators word them differently.
+    pub fn agrees(&self) -> bool{
+        match (&self.ours.result, &self.reference){
+            (Ok(ours), Ok(reference)) => ours == reference,
+            (Err(_), Err(_)) => true,
+            _ => false
+        }
+    }
+
+    /// A description of each way the two verdicts differ.
+    pub fn differences(&self) -> Vec<String>{
+        match (&self.ours.result, &self.reference){
+            (Ok(ours), Ok(reference)) => {
+                let ours = metric_values(ours);
+                let reference = metric_values(reference);
+                METRIC_NAMES.iter().zip(ours.iter().zip(reference))
+                    .filter(|(_, (o, r))| *o != r)
+                    .map(|(name, (o, r))| format!("{name}: {o} here, {r} in reference"))
+                    .collect()
+            }
+            (Err(_), Err(_)) => Vec::new(),
+            (Ok(_), Err(reason)) => vec![format!("valid here, but invalid in reference: {reason}")],
+            (Err(err), Ok(_)) => vec![format!("invalid here ({err}), but valid in reference")]
+        }
+    }
+}
+
+impl Display for CrossCheck{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result{
+        if self.agrees(){
+            return write!(f, "agree: {}", self.ours);
+        }
+        write!(f, "disagree: {}", self.differences().join("; "))
+    }
+}
+
+fn metric_values(m: &Metrics) -> [i32; 4]{
+    [m.cycles, m.cost, m.area, m.instructions]
+}
+
+/// The command that runs the reference omsim binary on a puzzle and solution file:
+/// `omsim -p <puzzle> -m cycles -m cost -m area -m instructions <solution>`.
+pub fn reference_command(omsim: &Path, puzzle: &Path, solution: &Path) -> Command{
+    let mut command = Command::new(omsim);
+    command.arg("-p").arg(puzzle);
+    for name in METRIC_NAMES{
+        command.arg("-m").arg(name);
+    }
+    command.arg(solution);
+    command
+}
+
+/// Run the reference omsim binary on a puzzle and solution file, see [`reference_command`].
+///
+/// The binary is expected to print a `<metric> is <value>` line for each metric on success, or exit unsuccessfully
+/// with an error message. Returns `Err` only if the binary couldn't be run or its output couldn't be understood.
+pub fn run_reference(omsim: &Path, puzzle: &Path, solution: &Path) -> Result<Result<Metrics, String>, &'static str>{
+    let output = reference_command(omsim, puzzle, solution).output().map_err(|_| "couldn't run reference omsim")?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    if !output.status.success(){
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let reason = if stderr.trim().is_empty(){ stdout.trim() } else { stderr.trim() };
+        return Ok(Err(reason.to_owned()));
+    }
+    parse_reference_output(&stdout).map(Ok)
+}
+
+/// The metrics in the reference omsim's output, from its `<metric> is <value>` lines. Other lines are ignored.
+pub fn parse_reference_output(stdout: &str) -> Result<Metrics, &'static str>{
+    let mut values = [None; 4];
+    for line in stdout.lines(){
+        let Some((name, value)) = line.split_once(" is ") else { continue };
+        if let Some(idx) = METRIC_NAMES.iter().position(|m| *m == name.trim()){
+            // omsim may print metrics as floating point
+            let value: f64 = value.trim().parse().map_err(|_| "reference omsim printed an invalid metric")?;
+            values[idx] = Some(value as i32);
+        }
+    }
+    let [Some(cycles), Some(cost), Some(area), Some(instructions)] = values else {
+        return Err("reference omsim didn't print every metric");
+    };
+    Ok(Metrics{ cycles, cost, area, instructions })
+}
+
+/// Verify a puzzle and solution file with both this simulator and the reference omsim binary.
+pub fn crosscheck(omsim: &Path, puzzle_path: &Path, solution_path: &Path) -> Result<CrossCheck, &'static str>{
+    let puzzle = parse_puzzle(&std::fs::read(puzzle_path).map_err(|_| "couldn't read puzzle")?)?;
+    let solution = parse_solution(&std::fs::read(solution_path).map_err(|_| "couldn't read solution")?)?;
+    let ours = verify(&puzzle, &solution);
+    let reference = run_reference(omsim, puzzle_path, solution_path)?;
+    Ok(CrossCheck{ ours, reference })
+}
@@ -1,15 +1,16 @@
-use std::collections::{HashMap, HashSet};
-use std::convert::Into;
-use std::fmt::Debug;
-use std::ops::{Add, AddAssign, Sub, SubAssign};
-use bitflags::bitflags;
+use alloc::collections::{BTreeMap, VecDeque};
+use crate::compat::{round, HashMap, HashSet};
+use core::fmt::{Debug, Display, Formatter};
+use core::ops::{Add, AddAssign, Neg, Sub, SubAssign};
+use bitflags::{bitflags, Flags};
 use enum_iterator::Sequence;
+use crate::compat::prelude::*;
 
 // Puzzle and solution files
 
 /// A puzzle, as parsed from a puzzle file.
 /// No attempt is made to check for invalid puzzles. In particular, they may have no inputs or outputs, no enabled parts, or be unsolveable.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Puzzle{
     /// String ID.
     pub name: String,
@@ -43,8 +44,28 @@ pub struct Solution{
     pub parts: Vec<Part>
 }
 
+impl Solution{
+    /// The index of the first cycle on the shared instruction timeline.
+    ///
+    /// Like the game, every arm runs on the same timeline, which starts at index 0 unless some instruction was
+    /// placed before it, in which case it starts at the earliest instruction of any arm.
+    /// Leading blanks are kept, so an arm whose first instruction is at index 2 waits two cycles before starting.
+    pub fn tape_start(&self) -> i32{
+        self.parts.iter()
+            .flat_map(|p| p.instructions.iter().map(|(_, idx)| *idx))
+            .min()
+            .map_or(0, |min| min.min(0))
+    }
+
+    /// Loop statistics for this solution's arms; see [`tape_stats`](crate::analysis::stats::tape_stats).
+    pub fn tape_stats(&self) -> Result<crate::analysis::stats::TapeStats, &'static str>{
+        crate::analysis::stats::tape_stats(self)
+    }
+}
+
 /// Metrics that a solved solution may have.
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Metrics{
     pub cycles: i32,
     pub cost: i32,
@@ -101,8 +122,29 @@ bitflags! {
     }
 }
 
+impl Permissions{
+    /// Split permission bits as stored in a puzzle file into the flags this library knows and the bits it doesn't,
+    /// which newer game versions or mods may have added.
+    pub fn from_bits_report(bits: u64) -> (Permissions, u64){
+        let known = Permissions::FLAGS.iter()
+            .filter(|flag| flag.is_named())
+            .fold(0, |known, flag| known | flag.value().bits());
+        (Permissions::from_bits_retain(bits & known), bits & !known)
+    }
+}
+
 impl Puzzle{
 
+    /// Check this puzzle for basic solvability constraints; see [`check_feasibility`](crate::analysis::feasibility::check_feasibility).
+    pub fn feasibility_issues(&self) -> Vec<crate::analysis::feasibility::FeasibilityIssue>{
+        crate::analysis::feasibility::check_feasibility(self)
+    }
+
+    /// Check a solution's conduits against the ones this puzzle defines; see [`check_conduits`](crate::analysis::conduits::check_conduits).
+    pub fn conduit_issues(&self, solution: &Solution) -> Vec<crate::analysis::conduits::ConduitIssue>{
+        crate::analysis::conduits::check_conduits(self, solution)
+    }
+
     pub fn clean_solution(&self, solution: &Solution) -> Result<Solution, &'static str>{
         // check puzzle name // don't actually, it's implicit in filenames. check filenames?
         // if self.name != solution.puzzle_name{
@@ -111,10 +153,11 @@ impl Puzzle{
 
         // check that there are no IOOB inputs/outputs
         for part in &solution.parts{
-            if part.ty == PartType::Input || part.ty == PartType::Output || part.ty == PartType::PolymerOutput{
-                if part.index < 0 {
-                    return Err("solution contains input/output with negative index");
-                }
+            if part.ty == PartType::Input && self.reagents.is_empty(){
+                return Err("solution contains an input, but the puzzle has no reagents");
+            }
+            if (part.ty == PartType::Input || part.ty == PartType::Output || part.ty == PartType::PolymerOutput) && part.index < 0{
+                return Err("solution contains input/output with negative index");
             }
             if part.ty == PartType::Input && (part.index as usize) >= self.reagents.len(){
                 return Err("solution contains input with out-of-bounds index");
@@ -123,6 +166,19 @@ impl Puzzle{
                 return Err("solution contains output with out-of-bounds index");
             }
         }
+        // check parts that need a permission the puzzle doesn't grant
+        for part in &solution.parts{
+            let (permission, error) = match part.ty{
+                PartType::Unification => (Permissions::QUINTESSENCE, "solution contains a glyph of unification, but the puzzle lacks the QUINTESSENCE permission"),
+                PartType::Dispersion => (Permissions::QUINTESSENCE, "solution contains a glyph of dispersion, but the puzzle lacks the QUINTESSENCE permission"),
+                PartType::Berlo => (Permissions::BERLO, "solution contains Van Berlo's wheel, but the puzzle lacks the BERLO permission"),
+                PartType::Disposal => (Permissions::DISPOSAL, "solution contains a glyph of disposal, but the puzzle lacks the DISPOSAL permission"),
+                _ => continue
+            };
+            if !self.permissions.contains(permission){
+                return Err(error);
+            }
+        }
         // remove forbidden parts
         let cleaned = solution.clone();
         // TODO
@@ -133,17 +189,35 @@ impl Puzzle{
 // Production info
 
 /// Information relevant only to production puzzles.
-/// Purely visual information, like vial placement, is not stored.
-#[derive(Clone, Debug, PartialEq, Eq)]
+/// Purely visual information, like vial placement, doesn't affect solutions, but is kept so puzzles are written back as they were read.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub struct ProductionInfo{
     /// Whether the inputs and outputs must be placed in different chambers.
     pub isolation: bool,
     /// The chambers/cabinets that parts may be placed in.
+    /// Atoms may only leave them through conduits.
     pub chambers: Vec<Chamber>,
     /// The conduits defined by the puzzle.
     /// Note that these are only used when creating a new solution to a puzzle; solutions may have any number and layout of conduits.
-    /// These are considered illegal in the same sense as overlap.
-    pub conduits: Vec<Conduit>
+    /// These are considered illegal in the same sense as overlap; see [`Puzzle::conduit_issues`].
+    pub conduits: Vec<Conduit>,
+    /// Whether the game draws the board narrower on the left, which is purely visual.
+    pub shrink_left: bool,
+    /// Whether the game draws the board narrower on the right, which is purely visual.
+    pub shrink_right: bool,
+    /// The vials drawn around the board, which are purely visual.
+    pub vials: Vec<Vial>
+}
+
+/// A vial drawn beside the board of a production puzzle.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Vial{
+    /// Position on the board, as an offset from the origin (within u8,u8 range).
+    pub pos: HexIndex,
+    /// Whether the vial is drawn at the top.
+    pub top: bool,
+    /// The vial's count, as stored in the file.
+    pub count: i32
 }
 
 /// A chamber/cabinet that parts may be placed within in production puzzles.
@@ -155,6 +229,17 @@ pub struct Chamber{
     pub ty: ChamberType
 }
 
+impl Chamber{
+    /// Whether a position on the board is inside this chamber.
+    pub fn contains(&self, pos: HexIndex) -> bool{
+        let (width, height) = self.ty.size();
+        let offset = pos - self.pos;
+        // rows are shifted back every other step up, so the chamber is a rectangle on screen
+        let column = offset.q + offset.r.div_euclid(2);
+        (0..height).contains(&offset.r) && (0..width).contains(&column)
+    }
+}
+
 /// A conduit defined by a puzzle.
 /// Note that these are only used when creating a new solution to a puzzle; solutions may have any number and layout of conduits.
 /// Since the game does not allow moving conduits between chambers, conduits store only starting positions and not chamber indices.
@@ -189,6 +274,29 @@ impl ChamberType{
             _ => return None
         })
     }
+
+    /// The number of hexes in each of this chamber's rows and the number of rows, as `(width, height)`.
+    pub fn size(&self) -> (i32, i32){
+        match self{
+            ChamberType::Small => (4, 3),
+            ChamberType::SmallWide => (5, 3),
+            ChamberType::SmallWider => (6, 3),
+            ChamberType::Medium => (4, 4),
+            ChamberType::MediumWide => (5, 4),
+            ChamberType::Large => (6, 5)
+        }
+    }
+
+    pub fn to_name(&self) -> &'static str{
+        match self{
+            ChamberType::Small => "Small",
+            ChamberType::SmallWide => "SmallWide",
+            ChamberType::SmallWider => "SmallWider",
+            ChamberType::Medium => "Medium",
+            ChamberType::MediumWide => "MediumWide",
+            ChamberType::Large => "Large"
+        }
+    }
 }
 
 // Atoms and molecules
@@ -205,8 +313,8 @@ pub struct Molecule{
 impl Molecule{
     pub fn mapped_positions(&self, f: impl Fn(HexIndex) -> HexIndex) -> Molecule{
         // it's just easier to copy it
-        let mut next_atoms = HashMap::with_capacity(self.atoms.len());
-        let mut next_bonds = HashSet::with_capacity(self.bonds.len());
+        let mut next_atoms = HashMap::with_capacity_and_hasher(self.atoms.len(), Default::default());
+        let mut next_bonds = HashSet::with_capacity_and_hasher(self.bonds.len(), Default::default());
         for (pos, atom) in &self.atoms{
             next_atoms.insert(f(*pos), *atom);
         }
@@ -224,9 +332,169 @@ impl Molecule{
         self.mapped_positions(|pos| pos.rotated(around, by))
     }
 
+    /// This molecule as placed on the board by a part at `pos` with `rotation`, like an input or output.
+    pub fn placed(&self, pos: HexIndex, rotation: HexRotation) -> Molecule{
+        self.mapped_positions(|p| rotation.rotate_offset(p) + pos)
+    }
+
     pub fn contains_pos(&self, pos: HexIndex) -> bool{
         self.atoms.contains_key(&pos)
     }
+
+    /// If this molecule contains a repeat marker, the repeating unit it describes.
+    pub fn as_repeating(&self) -> Option<RepeatingMolecule>{
+        let (offset, _) = self.atoms.iter().find(|(_, atom)| **atom == Atom::Repeat)?;
+        let mut unit = self.clone();
+        unit.atoms.remove(offset);
+        Some(RepeatingMolecule{ unit, offset: *offset })
+    }
+
+    /// Whether this molecule has any bonds between non-adjacent atoms.
+    pub fn has_quantum_bonds(&self) -> bool{
+        self.bonds.iter().any(|b| b.is_quantum())
+    }
+
+    /// The number of atoms of each element in this molecule.
+    pub fn composition(&self) -> BTreeMap<Atom, usize>{
+        let mut result = BTreeMap::new();
+        for atom in self.atoms.values(){
+            *result.entry(*atom).or_insert(0) += 1;
+        }
+        result
+    }
+
+    pub fn atom_count(&self) -> usize{
+        self.atoms.len()
+    }
+
+    pub fn bond_count(&self) -> usize{
+        self.bonds.len()
+    }
+
+    pub fn is_single_atom(&self) -> bool{
+        self.atoms.len() == 1
+    }
+
+    /// Whether every atom in this molecule can be reached from every other atom by following bonds.
+    /// Empty and single-atom molecules are considered connected.
+    pub fn is_connected(&self) -> bool{
+        let Some(start) = self.atoms.keys().next() else { return true };
+        let mut seen = HashSet::from_iter([*start]);
+        let mut queue = VecDeque::from([*start]);
+        while let Some(pos) = queue.pop_front(){
+            for bond in &self.bonds{
+                let other = if bond.start == pos{ bond.end } else if bond.end == pos{ bond.start } else { continue };
+                if self.atoms.contains_key(&other) && seen.insert(other){
+                    queue.push_back(other);
+                }
+            }
+        }
+        seen.len() == self.atoms.len()
+    }
+
+    /// Check this molecule for structural problems that the game would not allow, returning every issue found.
+    /// Bonds between non-adjacent atoms are only allowed if `allow_quantum_bonds` is set.
+    pub fn validate(&self, allow_quantum_bonds: bool) -> Vec<MoleculeIssue>{
+        let mut issues = Vec::new();
+        if self.atoms.is_empty(){
+            issues.push(MoleculeIssue::Empty);
+        }
+        let mut seen_pairs = HashSet::with_capacity_and_hasher(self.bonds.len(), Default::default());
+        for bond in &self.bonds{
+            if !self.atoms.contains_key(&bond.start) || !self.atoms.contains_key(&bond.end){
+                issues.push(MoleculeIssue::MissingAtom(*bond));
+            }
+            if !allow_quantum_bonds && bond.is_quantum(){
+                issues.push(MoleculeIssue::NonAdjacentBond(*bond));
+            }
+            let pair = if (bond.start.r, bond.start.q) <= (bond.end.r, bond.end.q){ (bond.start, bond.end) } else { (bond.end, bond.start) };
+            if !seen_pairs.insert(pair){
+                issues.push(MoleculeIssue::DuplicateBond(pair.0, pair.1));
+            }
+        }
+        if !self.is_connected(){
+            issues.push(MoleculeIssue::Disconnected);
+        }
+        issues
+    }
+}
+
+/// A molecule that repeats infinitely, as described by a molecule with a repeat marker.
+///
+/// The marker's position gives the offset between consecutive copies, and bonds to the marker
+/// connect each copy to the first atom of the next one.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct RepeatingMolecule{
+    /// A single copy of the repeated section, without the repeat marker.
+    /// Bonds to the marker's position are kept, and dangle unless another copy follows.
+    pub unit: Molecule,
+    /// The offset between consecutive copies.
+    pub offset: HexIndex
+}
+
+impl RepeatingMolecule{
+    /// The molecule formed by `copies` consecutive copies of the unit, with dangling bonds removed.
+    pub fn expand(&self, copies: usize) -> Molecule{
+        let mut result = Molecule{ atoms: HashMap::default(), bonds: HashSet::default() };
+        let mut shift = HexIndex::default();
+        for _ in 0..copies{
+            let copy = self.unit.translated(shift);
+            result.atoms.extend(copy.atoms);
+            result.bonds.extend(copy.bonds);
+            shift += self.offset;
+        }
+        let atoms = &result.atoms;
+        result.bonds.retain(|b| atoms.contains_key(&b.start) && atoms.contains_key(&b.end));
+        result
+    }
+}
+
+/// A structural problem with a molecule, as found by [`Molecule::validate`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum MoleculeIssue{
+    /// The molecule has no atoms.
+    Empty,
+    /// The molecule's atoms are not all connected by bonds.
+    Disconnected,
+    /// A bond has an end with no atom.
+    MissingAtom(Bond),
+    /// A bond connects two non-adjacent atoms.
+    NonAdjacentBond(Bond),
+    /// More than one bond connects the same pair of atoms.
+    DuplicateBond(HexIndex, HexIndex)
+}
+
+impl MoleculeIssue{
+    pub fn description(&self) -> &'static str{
+        match self{
+            MoleculeIssue::Empty => "molecule has no atoms",
+            MoleculeIssue::Disconnected => "molecule is not connected",
+            MoleculeIssue::MissingAtom(_) => "molecule has a bond to a missing atom",
+            MoleculeIssue::NonAdjacentBond(_) => "molecule has a bond between non-adjacent atoms",
+            MoleculeIssue::DuplicateBond(_, _) => "molecule has duplicate bonds"
+        }
+    }
+}
+
+/// Renders as atoms with positions followed by bonds, in row-major order, like `Sa(0,0) Fi(1,0) | (0,0)-(1,0)`.
+/// Triplex bonds are rendered with `~` instead of `-`.
+impl Display for Molecule{
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result{
+        let mut atoms: Vec<_> = self.atoms.iter().collect();
+        atoms.sort_by_key(|(pos, _)| (pos.r, pos.q));
+        let atoms: Vec<String> = atoms.into_iter().map(|(pos, atom)| format!("{}({},{})", atom.symbol(), pos.q, pos.r)).collect();
+        write!(f, "{}", atoms.join(" "))?;
+        if !self.bonds.is_empty(){
+            let mut bonds: Vec<_> = self.bonds.iter().collect();
+            bonds.sort_by_key(|b| (b.start.r, b.start.q, b.end.r, b.end.q));
+            let bonds: Vec<String> = bonds.into_iter().map(|b| {
+                let sep = if b.ty == BondType::Normal{ '-' } else { '~' };
+                format!("({},{}){}({},{})", b.start.q, b.start.r, sep, b.end.q, b.end.r)
+            }).collect();
+            write!(f, " | {}", bonds.join(" "))?;
+        }
+        Ok(())
+    }
 }
 
 /// A bond between atoms.
@@ -241,8 +509,29 @@ pub struct Bond{
     pub ty: BondType
 }
 
+impl Bond{
+    /// Whether this bond connects non-adjacent atoms, which only happens in modded or hand-edited files.
+    pub fn is_quantum(&self) -> bool{
+        !self.start.is_adjacent(self.end)
+    }
+
+    /// Whether this bond connects these two positions, in either order.
+    pub fn connects(&self, a: HexIndex, b: HexIndex) -> bool{
+        (self.start == a && self.end == b) || (self.start == b && self.end == a)
+    }
+
+    /// The same bond with its ends in a fixed order, so bonds written either way round compare equal.
+    pub fn normalized(&self) -> Bond{
+        if (self.end.q, self.end.r) < (self.start.q, self.start.r){
+            Bond{ start: self.end, end: self.start, ty: self.ty }
+        }else{
+            *self
+        }
+    }
+}
+
 /// An atom type, or element.
-#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum Atom{
     #[default] Salt, Air, Earth, Fire, Water,
     Quicksilver, Vitae, Mors,
@@ -274,6 +563,72 @@ impl Atom{
             _ => return None
         })
     }
+
+    /// Get the byte ID of this atom type, as used in puzzle files.
+    pub fn to_id(self) -> u8{
+        match self{
+            Atom::Salt => 1,
+            Atom::Air => 2,
+            Atom::Earth => 3,
+            Atom::Fire => 4,
+            Atom::Water => 5,
+            Atom::Quicksilver => 6,
+            Atom::Gold => 7,
+            Atom::Silver => 8,
+            Atom::Copper => 9,
+            Atom::Iron => 10,
+            Atom::Tin => 11,
+            Atom::Lead => 12,
+            Atom::Vitae => 13,
+            Atom::Mors => 14,
+            Atom::Repeat => 15,
+            Atom::Quintessence => 16
+        }
+    }
+
+    /// A short two-letter symbol for this element, used when displaying molecules.
+    pub fn symbol(self) -> &'static str{
+        match self{
+            Atom::Salt => "Sa",
+            Atom::Air => "Ai",
+            Atom::Earth => "Ea",
+            Atom::Fire => "Fi",
+            Atom::Water => "Wa",
+            Atom::Quicksilver => "Qs",
+            Atom::Vitae => "Vi",
+            Atom::Mors => "Mo",
+            Atom::Lead => "Pb",
+            Atom::Tin => "Sn",
+            Atom::Iron => "Fe",
+            Atom::Copper => "Cu",
+            Atom::Silver => "Ag",
+            Atom::Gold => "Au",
+            Atom::Quintessence => "Qt",
+            Atom::Repeat => "Re"
+        }
+    }
+
+    /// Whether this is one of the four cardinal elements (air, earth, fire, and water).
+    pub fn is_cardinal(self) -> bool{
+        matches!(self, Atom::Air | Atom::Earth | Atom::Fire | Atom::Water)
+    }
+
+    /// Whether this is one of the six planetary metals.
+    pub fn is_metal(self) -> bool{
+        matches!(self, Atom::Lead | Atom::Tin | Atom::Iron | Atom::Copper | Atom::Silver | Atom::Gold)
+    }
+
+    /// The metal that this metal is promoted to by projection or purification, or `None` if this is gold or not a metal.
+    pub fn next_metal(self) -> Option<Atom>{
+        Some(match self{
+            Atom::Lead => Atom::Tin,
+            Atom::Tin => Atom::Iron,
+            Atom::Iron => Atom::Copper,
+            Atom::Copper => Atom::Silver,
+            Atom::Silver => Atom::Gold,
+            _ => return None
+        })
+    }
 }
 
 /// A bond type (normal or triplex).
@@ -297,7 +652,7 @@ pub struct Part{
     /// If this is an input or output, index of which input/output this is for.
     pub index: i32,
     /// If this is a conduit, index of which conduit this is an end of.
-    /// This is *not* validated against the puzzle's defined conduits.
+    /// This is *not* validated against the puzzle's defined conduits; see [`Puzzle::conduit_issues`].
     pub conduit_index: i32,
     /// If this is a track, the hexes this track covers in placement order.
     pub track_hexes: Vec<HexIndex>,
@@ -308,8 +663,44 @@ pub struct Part{
     pub instructions: Vec<(Instruction, i32)>
 }
 
+impl Part{
+    /// This part's contribution to the instructions metric, counted the way the game does.
+    /// Only arms and Van Berlo's wheel have instructions. Each Repeat and Reset counts once as written,
+    /// blanks and period overrides don't count, and if several instructions share an index only the last one counts.
+    pub fn instruction_count(&self) -> i32{
+        if !self.ty.is_arm() && self.ty != PartType::Berlo{
+            return 0;
+        }
+        let tape: BTreeMap<i32, Instruction> = self.instructions.iter().map(|(instr, idx)| (*idx, *instr)).collect();
+        tape.into_values().filter(|instr| !matches!(instr, Instruction::Blank | Instruction::PeriodOverride)).count() as i32
+    }
+
+    /// The in-game cost of this part, counting each hex of a track.
+    pub fn cost(&self) -> i32{
+        match &self.ty{
+            PartType::Track => self.ty.cost() * self.track_hexes.len() as i32,
+            ty => ty.cost()
+        }
+    }
+
+    /// This part's instructions as one instruction per cycle, starting from index `start` of the shared timeline
+    /// (see [`Solution::tape_start`]), with gaps filled by blanks.
+    /// Instructions before `start` are dropped, and if several instructions share an index, the last one wins.
+    pub fn tape(&self, start: i32) -> Vec<Instruction>{
+        let mut tape = Vec::new();
+        for (instr, idx) in &self.instructions{
+            let Ok(idx) = usize::try_from(*idx - start) else { continue };
+            if tape.len() <= idx{
+                tape.resize(idx + 1, Instruction::Blank);
+            }
+            tape[idx] = *instr;
+        }
+        tape
+    }
+}
+
 /// A part type, or kind of mechanism or glyph.
-#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub enum PartType{
     // IO
     Input, Output, PolymerOutput,
@@ -324,7 +715,10 @@ pub enum PartType{
     TriplexBonding,
     Disposal,
     // Misc
-    Conduit
+    Conduit,
+    /// A part this library doesn't know, e.g. from a mod, by its name in the solution file.
+    /// It's kept so the solution can be written back unchanged, but it can't be simulated.
+    Unknown(String)
 }
 
 impl PartType {
@@ -358,7 +752,7 @@ impl PartType {
         })
     }
 
-    pub fn to_name(&self) -> &'static str{
+    pub fn to_name(&self) -> &str{
         match self{
             Self::Input => "input",
             Self::Output => "out-std",
@@ -384,10 +778,54 @@ impl PartType {
             Self::TriplexBonding => "bonder-prisma",
             Self::Disposal => "glyph-disposal",
             Self::Conduit => "pipe",
+            Self::Unknown(name) => name
+        }
+    }
+
+    /// Whether this part is an arm, and so can have instructions.
+    pub fn is_arm(&self) -> bool{
+        matches!(self, Self::Arm | Self::BiArm | Self::TriArm | Self::HexArm | Self::PistonArm)
+    }
+
+    /// Whether this part is a glyph, including the equilibrium glyph and disposal.
+    pub fn is_glyph(&self) -> bool{
+        matches!(self,
+            Self::Equilibrium | Self::Bonding | Self::MultiBonding | Self::Unbonding | Self::Calcification
+            | Self::Projection | Self::Purification | Self::Duplication | Self::Animismus
+            | Self::Unification | Self::Dispersion | Self::TriplexBonding | Self::Disposal
+        )
+    }
+
+    /// The in-game cost of this part, or for tracks, of each hex of track.
+    pub fn cost(&self) -> i32{
+        match self{
+            Self::Arm => 20,
+            Self::BiArm | Self::TriArm | Self::HexArm => 30,
+            Self::PistonArm => 40,
+            Self::Track => 5,
+            Self::Berlo => 30,
+            Self::Bonding | Self::Unbonding | Self::Calcification => 10,
+            Self::MultiBonding => 30,
+            Self::Projection | Self::Purification | Self::Duplication | Self::Animismus
+            | Self::Unification | Self::Dispersion | Self::TriplexBonding => 20,
+            Self::Input | Self::Output | Self::PolymerOutput | Self::Equilibrium | Self::Disposal | Self::Conduit | Self::Unknown(_) => 0
         }
     }
 }
 
+/// How an arm has changed from its starting state, as needed to expand a Reset instruction.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct ArmState{
+    /// Whether the arm's grippers are closed.
+    pub grabbing: bool,
+    /// Net number of turns the arm has rotated, with anticlockwise turns positive.
+    pub rotation: i32,
+    /// Net change in piston length.
+    pub extension: i32,
+    /// Net number of steps moved along a track, with advancing positive.
+    pub track_steps: i32
+}
+
 /// A type of instruction.
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Default)]
 pub enum Instruction{
@@ -422,6 +860,79 @@ impl Instruction {
         })
     }
 
+    /// Expand each Repeat instruction in a tape into a copy of the instructions it repeats, leaving Resets as they are.
+    /// See [`Instruction::expand_tape`].
+    pub fn expand_repeats(tape: &[Instruction]) -> Result<Vec<Instruction>, &'static str>{
+        Self::expand_tape(tape, |_| vec![Instruction::Reset])
+    }
+
+    /// Expand each Repeat and Reset instruction in a tape into the instructions it stands for.
+    ///
+    /// Like the game, a Repeat repeats every instruction since the arm's first instruction, or since the last Reset,
+    /// including any earlier expansions. Resets are expanded by `reset`, which is given every instruction before the
+    /// Reset, already expanded. Each expansion starts at the position of its instruction, so later instructions must not
+    /// overlap it.
+    pub fn expand_tape(tape: &[Instruction], mut reset: impl FnMut(&[Instruction]) -> Vec<Instruction>) -> Result<Vec<Instruction>, &'static str>{
+        let mut expanded = Vec::with_capacity(tape.len());
+        // index in `expanded` of the first instruction a Repeat would copy
+        let mut segment_start = None;
+        for (idx, instr) in tape.iter().enumerate(){
+            if expanded.len() > idx{
+                // covered by an earlier expansion
+                if *instr != Instruction::Blank{
+                    return Err("instruction overlaps an expanded repeat or reset");
+                }
+                continue;
+            }
+            match instr{
+                Instruction::Repeat => {
+                    let start = segment_start.unwrap_or(expanded.len());
+                    if start == expanded.len(){
+                        // nothing to repeat
+                        expanded.push(Instruction::Blank);
+                    }else{
+                        expanded.extend_from_within(start..);
+                    }
+                }
+                Instruction::Reset => {
+                    let expansion = reset(&expanded);
+                    expanded.extend(expansion);
+                    segment_start = None;
+                }
+                Instruction::Blank => expanded.push(Instruction::Blank),
+                _ => {
+                    segment_start.get_or_insert(expanded.len());
+                    expanded.push(*instr);
+                }
+            }
+        }
+        Ok(expanded)
+    }
+
+    /// The instructions a Reset expands to, for an arm that has changed from its starting state by `arm_state`.
+    ///
+    /// Like the game, the arm first drops anything it's holding, then undoes its piston extension, then unwinds its
+    /// rotation, then moves back along its track. Rotation and track movement are undone by their net amount rather than
+    /// the shortest way, so an arm that rotated all the way around rotates all the way back, and an arm that went around a
+    /// looping track goes all the way back around it. A Reset with nothing to undo still takes a cycle.
+    pub fn expand_reset(arm_state: ArmState) -> Vec<Instruction>{
+        let ArmState{ grabbing, rotation, extension, track_steps } = arm_state;
+        let mut expansion = Vec::new();
+        if grabbing{
+            expansion.push(Instruction::Drop);
+        }
+        let undo = |amount: i32, forward: Instruction, backward: Instruction| {
+            core::iter::repeat_n(if amount > 0{ backward } else { forward }, amount.unsigned_abs() as usize)
+        };
+        expansion.extend(undo(extension, Instruction::Extend, Instruction::Retract));
+        expansion.extend(undo(rotation, Instruction::RotateAnticlockwise, Instruction::RotateClockwise));
+        expansion.extend(undo(track_steps, Instruction::Advance, Instruction::Retreat));
+        if expansion.is_empty(){
+            expansion.push(Instruction::Blank);
+        }
+        expansion
+    }
+
     pub const fn to_id(&self) -> u8{
         match self{
             Instruction::Blank => b' ',
@@ -444,7 +955,13 @@ impl Instruction {
 
 // Misc
 
-/// A position or offset on a hex grid.
+/// Horizontal distance between the centres of adjacent hexes, in pixels.
+pub const HEX_WIDTH: f32 = 82.0;
+/// Vertical distance between the centres of hexes in adjacent rows, in pixels.
+pub const HEX_HEIGHT: f32 = 71.0;
+
+/// A position or offset on a hex grid, in axial coordinates.
+/// The third cube coordinate, S, is implicit; see [`HexIndex::s`].
 #[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
 pub struct HexIndex{
     /// Position along the horizontal Q axis (also called X).
@@ -454,11 +971,65 @@ pub struct HexIndex{
 }
 
 impl HexIndex{
+    pub const fn new(q: i32, r: i32) -> HexIndex{
+        HexIndex{ q, r }
+    }
+
+    /// Create a position from cube coordinates, or `None` if they don't sum to zero.
+    pub const fn from_cube(q: i32, r: i32, s: i32) -> Option<HexIndex>{
+        if q + r + s == 0{ Some(HexIndex{ q, r }) } else { None }
+    }
+
+    /// This position in cube coordinates, as `(q, r, s)`.
+    pub const fn to_cube(self) -> (i32, i32, i32){
+        (self.q, self.r, self.s())
+    }
+
+    /// Create a position from coordinates in the order they're stored in puzzle and solution files.
+    pub const fn from_file_order(first: i32, second: i32) -> HexIndex{
+        HexIndex{ q: first, r: second }
+    }
+
+    /// This position's coordinates in the order they're stored in puzzle and solution files.
+    pub const fn to_file_order(self) -> (i32, i32){
+        (self.q, self.r)
+    }
+
+    /// The centre of this hex in pixels, with Y pointing up, relative to the centre of the origin hex.
+    pub fn to_pixel(self) -> (f32, f32){
+        ((self.q as f32) * HEX_WIDTH + 0.5 * (self.r as f32) * HEX_WIDTH, (self.r as f32) * HEX_HEIGHT)
+    }
+
+    /// The hex containing this pixel position, with Y pointing up, relative to the centre of the origin hex.
+    pub fn from_pixel(x: f32, y: f32) -> HexIndex{
+        let r = y / HEX_HEIGHT;
+        let q = x / HEX_WIDTH - 0.5 * r;
+        let s = -q - r;
+        // round in cube coordinates, then fix whichever coordinate was rounded the most
+        let (mut rq, mut rr, rs) = (round(q), round(r), round(s));
+        let (dq, dr, ds) = ((rq - q).abs(), (rr - r).abs(), (rs - s).abs());
+        if dq > dr && dq > ds{
+            rq = -rr - rs;
+        }else if dr > ds{
+            rr = -rq - rs;
+        }
+        HexIndex{ q: rq as i32, r: rr as i32 }
+    }
+
     /// Implicit S coordinate of this coordinate.
     pub const fn s(self) -> i32{
         -self.q - self.r
     }
 
+    /// Number of steps between this position and another.
+    pub const fn distance(self, other: HexIndex) -> i32{
+        ((self.q - other.q).abs() + (self.r - other.r).abs() + (self.s() - other.s()).abs()) / 2
+    }
+
+    pub const fn is_adjacent(self, other: HexIndex) -> bool{
+        self.distance(other) == 1
+    }
+
     pub const fn rotated_cw(self) -> HexIndex{
         HexIndex{ q: -self.r, r: -self.s() }
     }
@@ -468,11 +1039,22 @@ impl HexIndex{
     }
 
     pub fn rotated(self, around: HexIndex, by: HexRotation) -> HexIndex{
-        let mut offset = self - around;
-        for _ in 0..by.turns(){
-            offset = offset.rotated_cw();
-        }
-        offset + around
+        by.rotate_offset(self - around) + around
+    }
+
+    /// This position reflected across the horizontal line through the origin.
+    pub const fn mirrored(self) -> HexIndex{
+        HexIndex{ q: self.q + self.r, r: -self.r }
+    }
+
+    /// The direction of an adjacent position from this one, or `None` if it isn't adjacent.
+    pub fn direction_to(self, neighbor: HexIndex) -> Option<HexRotation>{
+        HexRotation::from_unit_offset(neighbor - self)
+    }
+
+    /// This offset scaled by a whole number of steps.
+    pub const fn scaled(self, by: i32) -> HexIndex{
+        HexIndex{ q: self.q * by, r: self.r * by }
     }
 }
 
@@ -530,16 +1112,60 @@ impl HexRotation{
         self.turns
     }
 
+    /// The number of turns, taking the short way around, between -2 and 3.
+    pub fn signed_turns(self) -> i8{
+        if self.turns > 3{ self.turns as i8 - 6 } else { self.turns as i8 }
+    }
+
     pub fn to_radians(self) -> f32{
-        return (self.turns as f32 * 60.0) * (std::f32::consts::PI / 180.0);
+        (self.turns as f32 * 60.0) * (core::f32::consts::PI / 180.0)
+    }
+
+    /// Every rotation, in order of increasing turns.
+    pub const ALL: [HexRotation; 6] = [Self::R0, Self::R60, Self::R120, Self::R180, Self::R240, Self::R300];
+
+    /// The offset of the adjacent hex in this direction, where `R0` faces along the positive Q axis.
+    pub const fn to_unit_offset(self) -> HexIndex{
+        UNIT_OFFSETS[self.turns as usize]
+    }
+
+    /// The direction of an offset to an adjacent hex, or `None` if the offset isn't to an adjacent hex.
+    pub fn from_unit_offset(offset: HexIndex) -> Option<HexRotation>{
+        UNIT_OFFSETS.iter().position(|o| *o == offset).map(|turns| HexRotation{ turns: turns as u8 })
+    }
+
+    /// Rotate an offset around the origin by this rotation.
+    pub const fn rotate_offset(self, offset: HexIndex) -> HexIndex{
+        let mut offset = offset;
+        let mut turns = self.turns;
+        while turns > 0{
+            offset = offset.rotated_cw();
+            turns -= 1;
+        }
+        offset
+    }
+
+    /// The rotation that undoes this one.
+    pub const fn inverse(self) -> HexRotation{
+        HexRotation{ turns: (6 - self.turns) % 6 }
+    }
+
+    /// The rotation from this direction to another.
+    pub const fn to(self, other: HexRotation) -> HexRotation{
+        HexRotation{ turns: (other.turns + 6 - self.turns) % 6 }
     }
 }
 
-// basically any number can be a hex rotation
-// we can't have both Into<usize> and Into<isize>, and we mostly work with u8s and i32s here, so isize wins
-impl<T: Into<i64>> From<T> for HexRotation{
-    fn from(turns: T) -> HexRotation{
-        Self::from_signed(turns.into())
+/// The offsets to each adjacent hex, in order of increasing turns.
+const UNIT_OFFSETS: [HexIndex; 6] = [
+    HexIndex{ q: 1, r: 0 }, HexIndex{ q: 0, r: 1 }, HexIndex{ q: -1, r: 1 },
+    HexIndex{ q: -1, r: 0 }, HexIndex{ q: 0, r: -1 }, HexIndex{ q: 1, r: -1 }
+];
+
+// a blanket impl over Into<i64> makes untyped integer literals ambiguous, so only convert from the i32s used in solution files
+impl From<i32> for HexRotation{
+    fn from(turns: i32) -> HexRotation{
+        Self::from_signed(turns)
     }
 }
 
@@ -561,7 +1187,15 @@ impl Sub for HexRotation{
     type Output = HexRotation;
 
     fn sub(self, rhs: HexRotation) -> HexRotation{
-        HexRotation{ turns: ((((self.turns as i8) - (rhs.turns as i8) % 6) + 6) % 6) as u8 }
+        self + (-rhs)
+    }
+}
+
+impl Neg for HexRotation{
+    type Output = HexRotation;
+
+    fn neg(self) -> HexRotation{
+        self.inverse()
     }
 }
 
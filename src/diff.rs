@@ -0,0 +1,147 @@
+use alloc::collections::BTreeMap;
+use core::fmt::{Display, Formatter};
+use crate::data::{Instruction, Metrics, Part, PartType, Solution};
+use crate::compat::prelude::*;
+
+/// A structured difference between two solutions, from `before` to `after`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SolutionDiff{
+    /// Parts that only exist in the `after` solution.
+    pub added: Vec<Part>,
+    /// Parts that only exist in the `before` solution.
+    pub removed: Vec<Part>,
+    /// Parts that exist in both solutions, but were placed differently.
+    pub moved: Vec<PartMove>,
+    /// Instruction changes for arms that exist in both solutions.
+    pub tapes: Vec<TapeDiff>,
+    /// If the recorded metrics differ, the `before` and `after` metrics.
+    pub metrics: Option<(Option<Metrics>, Option<Metrics>)>
+}
+
+/// A part that exists in both solutions, but with a different position, rotation, or shape.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PartMove{
+    pub before: Part,
+    pub after: Part
+}
+
+/// Instruction changes on a single arm, identified by arm number.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TapeDiff{
+    pub arm_number: i32,
+    /// Changed instructions as `(index, before, after)`, in index order.
+    /// Missing instructions are reported as `Instruction::Blank`.
+    pub changes: Vec<(i32, Instruction, Instruction)>
+}
+
+impl SolutionDiff{
+    /// Whether the two solutions have the same parts, instructions, and recorded metrics.
+    /// Solution names are not compared.
+    pub fn is_empty(&self) -> bool{
+        self.added.is_empty() && self.removed.is_empty() && self.moved.is_empty() && self.tapes.is_empty() && self.metrics.is_none()
+    }
+}
+
+/// Compute the difference between two solutions.
+///
+/// Parts are paired up by placement first, then by identity (arm number for arms, index for inputs and outputs,
+/// and conduit index for conduits), then by type in file order; any remaining parts are added or removed.
+pub fn diff_solutions(before: &Solution, after: &Solution) -> SolutionDiff{
+    let mut remaining: Vec<Option<&Part>> = after.parts.iter().map(Some).collect();
+    let mut pairs: Vec<(&Part, &Part)> = Vec::new();
+    let mut unpaired: Vec<&Part> = Vec::new();
+
+    for part in &before.parts{
+        match take_match(&mut remaining, |other| same_placement(part, other)){
+            Some(other) => pairs.push((part, other)),
+            None => unpaired.push(part)
+        }
+    }
+    let mut removed = Vec::new();
+    let mut moved = Vec::new();
+    for part in unpaired{
+        match take_match(&mut remaining, |other| part.ty == other.ty && identity(part) == identity(other)){
+            Some(other) => {
+                moved.push(PartMove{ before: part.clone(), after: other.clone() });
+                pairs.push((part, other));
+            }
+            None => removed.push(part.clone())
+        }
+    }
+    let added = remaining.into_iter().flatten().cloned().collect();
+
+    let mut tapes: Vec<TapeDiff> = pairs.iter()
+        .filter(|(part, _)| part.ty.is_arm())
+        .filter_map(|(b, a)| diff_tapes(b, a))
+        .collect();
+    tapes.sort_by_key(|t| t.arm_number);
+
+    let metrics = if before.metrics != after.metrics{ Some((before.metrics, after.metrics)) } else { None };
+
+    SolutionDiff{ added, removed, moved, tapes, metrics }
+}
+
+fn take_match<'a>(parts: &mut [Option<&'a Part>], f: impl Fn(&Part) -> bool) -> Option<&'a Part>{
+    parts.iter_mut().find(|p| p.is_some_and(&f)).and_then(Option::take)
+}
+
+fn same_placement(a: &Part, b: &Part) -> bool{
+    a.ty == b.ty && a.pos == b.pos && a.rotation == b.rotation && a.arm_length == b.arm_length
+        && identity(a) == identity(b)
+        && a.track_hexes == b.track_hexes && a.conduit_hexes == b.conduit_hexes
+}
+
+fn identity(part: &Part) -> Option<i32>{
+    match part.ty{
+        _ if part.ty.is_arm() => Some(part.arm_number),
+        PartType::Input | PartType::Output | PartType::PolymerOutput => Some(part.index),
+        PartType::Conduit => Some(part.conduit_index),
+        _ => None
+    }
+}
+
+fn diff_tapes(before: &Part, after: &Part) -> Option<TapeDiff>{
+    let before_tape: BTreeMap<i32, Instruction> = before.instructions.iter().map(|(instr, idx)| (*idx, *instr)).collect();
+    let after_tape: BTreeMap<i32, Instruction> = after.instructions.iter().map(|(instr, idx)| (*idx, *instr)).collect();
+    let mut indices: Vec<i32> = before_tape.keys().chain(after_tape.keys()).copied().collect();
+    indices.sort();
+    indices.dedup();
+    let changes: Vec<_> = indices.into_iter().filter_map(|idx| {
+        let b = before_tape.get(&idx).copied().unwrap_or_default();
+        let a = after_tape.get(&idx).copied().unwrap_or_default();
+        if a != b{ Some((idx, b, a)) } else { None }
+    }).collect();
+    if changes.is_empty(){
+        None
+    }else{
+        Some(TapeDiff{ arm_number: after.arm_number, changes })
+    }
+}
+
+impl Display for SolutionDiff{
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result{
+        if self.is_empty(){
+            return writeln!(f, "no differences");
+        }
+        for part in &self.removed{
+            writeln!(f, "- {} at ({}, {})", part.ty.to_name(), part.pos.q, part.pos.r)?;
+        }
+        for part in &self.added{
+            writeln!(f, "+ {} at ({}, {})", part.ty.to_name(), part.pos.q, part.pos.r)?;
+        }
+        for PartMove{ before, after } in &self.moved{
+            writeln!(f, "~ {} moved from ({}, {}) rotation {} to ({}, {}) rotation {}",
+                before.ty.to_name(), before.pos.q, before.pos.r, before.rotation, after.pos.q, after.pos.r, after.rotation)?;
+        }
+        for tape in &self.tapes{
+            writeln!(f, "arm {}:", tape.arm_number)?;
+            for (idx, b, a) in &tape.changes{
+                writeln!(f, "  {}: '{}' -> '{}'", idx, b.to_id() as char, a.to_id() as char)?;
+            }
+        }
+        if let Some((b, a)) = &self.metrics{
+            writeln!(f, "metrics: {b:?} -> {a:?}")?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,50 @@
+//! Hashes that are stable across platforms, compiler versions, and runs, for identifying puzzles and solutions in
+//! databases. `core::hash` makes no such promises, so these hash bytes in a fixed format with 64-bit FNV-1a.
+//!
+//! The format is this crate's own, so the hashes only identify puzzles and solutions among tools that use this crate.
+//! They aren't the IDs that existing community tools and leaderboard databases use, and can't be looked up there.
+
+use crate::data::{Bond, Molecule, ProductionInfo, Puzzle};
+use crate::compat::prelude::*;
+
+/// The 64-bit FNV-1a hash of these bytes.
+pub fn stable_hash(bytes: &[u8]) -> u64{
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in bytes{
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    hash
+}
+
+impl Puzzle{
+    /// A hash of everything about this puzzle that affects its solutions: reagents, products, product multiplier,
+    /// permissions, and production info, but not its name or creator, or how its board is drawn.
+    ///
+    /// The algorithm is fixed, so hashes can be stored and compared across versions: it's the [`stable_hash`] of the
+    /// puzzle's [file contents](Puzzle::unparse), written with an empty name and a creator ID of 0.
+    /// Molecules are written with atoms and bonds in a fixed order, and each bond with its ends in a fixed order,
+    /// so it doesn't depend on how the file lists them.
+    /// Since it hashes this crate's [`unparse`](Puzzle::unparse) output, it isn't compatible with puzzle IDs from other tools.
+    pub fn content_hash(&self) -> u64{
+        let normalized = |molecules: &[Molecule]| molecules.iter().map(|molecule| Molecule{
+            atoms: molecule.atoms.clone(),
+            bonds: molecule.bonds.iter().map(Bond::normalized).collect()
+        }).collect();
+        let anonymous = Puzzle{
+            name: String::new(),
+            creator_id: 0,
+            reagents: normalized(&self.reagents),
+            products: normalized(&self.products),
+            // how the board is drawn doesn't matter to solutions
+            production_info: self.production_info.as_ref().map(|info| ProductionInfo{
+                shrink_left: false,
+                shrink_right: false,
+                vials: Vec::new(),
+                ..info.clone()
+            }),
+            ..self.clone()
+        };
+        stable_hash(&anonymous.unparse())
+    }
+}
@@ -1,3 +1,46 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+#[macro_use]
+mod compat;
 pub mod parse;
 pub mod data;
 pub mod sim;
+pub mod diff;
+pub mod analysis;
+pub mod builder;
+pub mod scenario;
+pub mod verify;
+pub mod minify;
+pub mod optimize;
+pub mod advise;
+pub mod solver;
+pub mod constraints;
+pub mod render;
+pub mod transform;
+pub mod hash;
+pub mod selftest;
+pub mod trace;
+#[cfg(feature = "std")]
+pub mod crosscheck;
+#[cfg(feature = "std")]
+pub mod load;
+
+// sims and their results are moved between threads by the parallel analyses and batch verification, and into WASM
+// workers, so anything added to them, like custom parts or callbacks, must be Send and Sync too
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync>(){}
+    assert_send_sync::<data::Puzzle>();
+    assert_send_sync::<data::Solution>();
+    assert_send_sync::<sim::Sim>();
+    assert_send_sync::<sim::config::SimConfig>();
+    assert_send_sync::<sim::error::SimError>();
+    assert_send_sync::<analysis::throughput::SteadyState>();
+    assert_send_sync::<verify::Verdict>();
+    assert_send_sync::<trace::Trace>();
+    #[cfg(feature = "batch")]
+    assert_send_sync::<load::batch::SolutionBatch>();
+    #[cfg(feature = "batch")]
+    assert_send_sync::<load::batch::SolutionEntry>();
+};
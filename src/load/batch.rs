@@ -0,0 +1,196 @@
+//! Scanning directories and zip archives of many solution files, e.g. to re-verify a whole leaderboard.
+//!
+//! Files are memory-mapped rather than read, and only parsed when asked: an entry's names and recorded metrics
+//! are read from its header alone, and the parts list is only parsed by [`SolutionEntry::solution`].
+
+use std::fs::File;
+use std::io::{Cursor, Read};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use memmap2::Mmap;
+use zip::ZipArchive;
+use crate::data::{Metrics, Puzzle, Solution};
+use crate::load::LoadError;
+use crate::load::filename::sanitize_path;
+use crate::parse::{parse_solution, parse_solution_header, parse_solution_names, SolutionHeader};
+use crate::sim::config::{CancelToken, SimConfig};
+use crate::sim::error::SimError;
+use crate::verify::{verify_with_config, Verdict};
+
+/// A memory-mapped archive, shared by every clone of its `ZipArchive`.
+#[derive(Clone, Debug)]
+struct Mapped(Arc<Mmap>);
+
+impl AsRef<[u8]> for Mapped{
+    fn as_ref(&self) -> &[u8]{
+        &self.0
+    }
+}
+
+#[derive(Debug)]
+enum Source{
+    Directory(Vec<PathBuf>),
+    /// The archive, and the indices of its `.solution` entries.
+    Zip(ZipArchive<Cursor<Mapped>>, Vec<usize>)
+}
+
+/// The solution files in a directory or zip archive.
+#[derive(Debug)]
+pub struct SolutionBatch{
+    source: Source
+}
+
+impl SolutionBatch{
+    /// Find the `.solution` files in a directory and its subdirectories, or in a zip archive, in order of their paths.
+    ///
+    /// # Safety
+    ///
+    /// The archive, or the solution files in the directory, are memory-mapped when the batch is opened or an entry is
+    /// loaded. No file may be modified or truncated, by this process or any other, while the batch or any
+    /// [`SolutionEntry`] loaded from it exists: a mapped file changing underneath it is undefined behaviour.
+    pub unsafe fn open(path: impl AsRef<Path>) -> Result<SolutionBatch, LoadError>{
+        let path = path.as_ref();
+        if path.is_dir(){
+            let mut paths = Vec::new();
+            find_solutions(path, &mut paths)?;
+            paths.sort();
+            return Ok(SolutionBatch{ source: Source::Directory(paths) });
+        }
+        // safety: the caller promises the archive isn't modified while the batch exists
+        let map = unsafe{ map_file(path)? };
+        let archive = ZipArchive::new(Cursor::new(Mapped(Arc::new(map))))?;
+        let mut indices: Vec<usize> = (0..archive.len())
+            .filter(|&idx| archive.name_for_index(idx).is_some_and(|name| name.ends_with(".solution")))
+            .collect();
+        indices.sort_by_key(|&idx| archive.name_for_index(idx));
+        Ok(SolutionBatch{ source: Source::Zip(archive, indices) })
+    }
+
+    pub fn len(&self) -> usize{
+        match &self.source{
+            Source::Directory(paths) => paths.len(),
+            Source::Zip(_, indices) => indices.len()
+        }
+    }
+
+    pub fn is_empty(&self) -> bool{
+        self.len() == 0
+    }
+
+    /// Load the `idx`th solution file, without parsing it.
+    pub fn get(&self, idx: usize) -> Result<SolutionEntry, LoadError>{
+        match &self.source{
+            Source::Directory(paths) => {
+                let path = &paths[idx];
+                // safety: the caller of `SolutionBatch::open` promised the files aren't modified while its entries exist
+                Ok(SolutionEntry{ path: path.clone(), data: Data::Mapped(unsafe{ map_file(path)? }) })
+            }
+            Source::Zip(archive, indices) => {
+                // cloning the archive only clones a reference to the map and its central directory
+                let mut archive = archive.clone();
+                let mut file = archive.by_index(indices[idx])?;
+                let mut data = Vec::with_capacity(file.size() as usize);
+                file.read_to_end(&mut data)?;
+                // names in archives can be anything, like `../x.solution`, so make sure they're safe to extract to
+                Ok(SolutionEntry{ path: sanitize_path(file.name()), data: Data::Owned(data) })
+            }
+        }
+    }
+
+    /// Load each solution file in turn, without parsing them.
+    pub fn iter(&self) -> impl Iterator<Item = Result<SolutionEntry, LoadError>> + '_{
+        (0..self.len()).map(|idx| self.get(idx))
+    }
+}
+
+/// Verify each solution in a batch against `puzzle`, in the batch's order.
+///
+/// If `config` has a [`CancelToken`](crate::sim::config::CancelToken) and it's cancelled, the solution being verified
+/// fails with [`SimError::Cancelled`] and the rest are skipped, so there are fewer results than solutions.
+pub fn verify_batch(batch: &SolutionBatch, puzzle: &Puzzle, config: &SimConfig) -> Vec<Result<Verdict, LoadError>>{
+    let mut results = Vec::with_capacity(batch.len());
+    for entry in batch.iter(){
+        if config.cancel.as_ref().is_some_and(CancelToken::is_cancelled){
+            break;
+        }
+        let verdict = entry.and_then(|entry| entry.solution().map_err(LoadError::Parse))
+            .map(|solution| verify_with_config(puzzle, &solution, config.clone()));
+        let cancelled = matches!(&verdict, Ok(Verdict{ result: Err(SimError::Cancelled{ .. }), .. }));
+        results.push(verdict);
+        if cancelled{
+            break;
+        }
+    }
+    results
+}
+
+#[derive(Debug)]
+enum Data{
+    Mapped(Mmap),
+    Owned(Vec<u8>)
+}
+
+/// A solution file from a [`SolutionBatch`], parsed as far as needed.
+#[derive(Debug)]
+pub struct SolutionEntry{
+    /// The file's path, or its name within the zip archive, sanitized so it's a relative path with nothing like `..` in it.
+    pub path: PathBuf,
+    data: Data
+}
+
+impl SolutionEntry{
+    /// The raw contents of the file.
+    pub fn bytes(&self) -> &[u8]{
+        match &self.data{
+            Data::Mapped(map) => map,
+            Data::Owned(data) => data
+        }
+    }
+
+    /// The name of the puzzle this solution is for, read from the header only.
+    pub fn puzzle_name(&self) -> Result<&str, &'static str>{
+        parse_solution_names(self.bytes()).map(|(puzzle_name, _, _)| puzzle_name)
+    }
+
+    /// The solution's name, read from the header only.
+    pub fn name(&self) -> Result<&str, &'static str>{
+        parse_solution_names(self.bytes()).map(|(_, name, _)| name)
+    }
+
+    /// The metrics recorded in the file, read from the header only.
+    pub fn metrics(&self) -> Result<Option<Metrics>, &'static str>{
+        parse_solution_names(self.bytes()).map(|(_, _, metrics)| metrics)
+    }
+
+    /// The solution's names and recorded metrics, without parsing its parts.
+    pub fn header(&self) -> Result<SolutionHeader, &'static str>{
+        parse_solution_header(self.bytes())
+    }
+
+    /// Parse the whole solution.
+    pub fn solution(&self) -> Result<Solution, &'static str>{
+        parse_solution(self.bytes())
+    }
+}
+
+fn find_solutions(dir: &Path, paths: &mut Vec<PathBuf>) -> std::io::Result<()>{
+    for entry in std::fs::read_dir(dir)?{
+        let entry = entry?;
+        let path = entry.path();
+        if entry.file_type()?.is_dir(){
+            find_solutions(&path, paths)?;
+        }else if path.extension().is_some_and(|ext| ext == "solution"){
+            paths.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// # Safety
+///
+/// The file must not be modified while the map exists, see [`SolutionBatch::open`].
+unsafe fn map_file(path: &Path) -> std::io::Result<Mmap>{
+    let file = File::open(path)?;
+    // safety: passed on to the caller
+    unsafe{ Mmap::map(&file) }
+}
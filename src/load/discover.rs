@@ -0,0 +1,65 @@
+//! Finding the game's own solution files, so players can check what they just saved without hunting for it.
+//!
+//! The game keeps a save directory per Steam account, named by account ID, inside a per-platform base directory:
+//! `Documents/My Games/Opus Magnum` on Windows, `Library/Application Support/Opus Magnum` on macOS,
+//! and `$XDG_DATA_HOME/Opus Magnum` (usually `~/.local/share/Opus Magnum`) on Linux.
+
+use std::env;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use crate::load::LoadError;
+use crate::parse::parse_solution_header;
+
+/// The directory containing the game's per-account save directories on this platform, if the home directory is known.
+/// It isn't checked to exist.
+pub fn base_dir() -> Option<PathBuf>{
+    if cfg!(windows){
+        env::var_os("USERPROFILE").map(|home| Path::new(&home).join("Documents").join("My Games").join("Opus Magnum"))
+    }else if cfg!(target_os = "macos"){
+        env::var_os("HOME").map(|home| Path::new(&home).join("Library").join("Application Support").join("Opus Magnum"))
+    }else{
+        env::var_os("XDG_DATA_HOME").filter(|dir| !dir.is_empty()).map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".local").join("share")))
+            .map(|data| data.join("Opus Magnum"))
+    }
+}
+
+/// Every account's save directory on this computer, in order of their paths.
+pub fn save_dirs() -> Vec<PathBuf>{
+    let Some(Ok(entries)) = base_dir().map(std::fs::read_dir) else { return Vec::new() };
+    let mut dirs: Vec<PathBuf> = entries.filter_map(Result::ok).map(|entry| entry.path()).filter(|path| path.is_dir()).collect();
+    dirs.sort();
+    dirs
+}
+
+/// The solution files directly inside `dir` for the puzzle with this string ID, in order of their paths.
+/// Files that can't be read or aren't solutions are skipped.
+pub fn find_solutions_in(dir: impl AsRef<Path>, puzzle_name: &str) -> Result<Vec<PathBuf>, LoadError>{
+    let mut paths = Vec::new();
+    for entry in std::fs::read_dir(dir)?{
+        let path = entry?.path();
+        if path.extension().is_none_or(|ext| ext != "solution"){
+            continue;
+        }
+        let Ok(data) = std::fs::read(&path) else { continue };
+        if parse_solution_header(&data).is_ok_and(|header| header.puzzle_name == puzzle_name){
+            paths.push(path);
+        }
+    }
+    paths.sort();
+    Ok(paths)
+}
+
+/// The solution files for the puzzle with this string ID in every account's save directory.
+pub fn find_solutions(puzzle_name: &str) -> Result<Vec<PathBuf>, LoadError>{
+    let mut paths = Vec::new();
+    for dir in save_dirs(){
+        paths.extend(find_solutions_in(dir, puzzle_name)?);
+    }
+    Ok(paths)
+}
+
+/// Of these files, the one modified most recently, e.g. the one the game just saved.
+pub fn latest(paths: &[PathBuf]) -> Option<&PathBuf>{
+    paths.iter().max_by_key(|path| std::fs::metadata(path).and_then(|meta| meta.modified()).unwrap_or(SystemTime::UNIX_EPOCH))
+}
@@ -0,0 +1,69 @@
+//! The file names the game gives solutions, and making arbitrary names safe to use as file names.
+//!
+//! The game saves each solution as `<puzzle id>-<slot>.solution` in an account's save directory (see
+//! [`discover`](super::discover)), with slots counting from 1.
+
+use std::path::{Path, PathBuf};
+
+/// Names Windows won't create files with, whatever the extension.
+const RESERVED: &[&str] = &[
+    "CON", "PRN", "AUX", "NUL",
+    "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8", "COM9",
+    "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9"
+];
+
+/// The longest sanitized name, in bytes, leaving room for a slot and extension within common file name limits.
+pub const MAX_NAME_LEN: usize = 200;
+
+/// A version of `name` that's safe as a single file name on every platform: characters that aren't allowed in file
+/// names on some platform, like path separators, become underscores, as do names that only mean something to the file
+/// system, like `..` or `CON`. Names are also cut short at [`MAX_NAME_LEN`] bytes.
+pub fn sanitize(name: &str) -> String{
+    let mut sanitized: String = name.chars()
+        .map(|c| if c.is_control() || matches!(c, '/' | '\\' | '<' | '>' | ':' | '"' | '|' | '?' | '*'){ '_' }else{ c })
+        .collect();
+    if sanitized.len() > MAX_NAME_LEN{
+        let mut end = MAX_NAME_LEN;
+        while !sanitized.is_char_boundary(end){
+            end -= 1;
+        }
+        sanitized.truncate(end);
+    }
+    // Windows drops trailing dots and spaces, so names that differ only in them would clash
+    let trimmed = sanitized.trim_end_matches(['.', ' ']).len();
+    if trimmed < sanitized.len(){
+        sanitized.truncate(trimmed);
+        sanitized.push('_');
+    }
+    let stem = sanitized.split('.').next().unwrap_or_default();
+    if sanitized.is_empty() || RESERVED.iter().any(|reserved| stem.eq_ignore_ascii_case(reserved)){
+        sanitized.insert(0, '_');
+    }
+    sanitized
+}
+
+/// The file name the game gives the solution to this puzzle in this slot, e.g. `P007-1.solution`.
+pub fn solution_file_name(puzzle_name: &str, slot: u32) -> String{
+    format!("{}-{slot}.solution", sanitize(puzzle_name))
+}
+
+/// The puzzle id and slot of a solution file name the game gave, like `P007-1.solution`, if it is one.
+pub fn parse_solution_file_name(file_name: &str) -> Option<(&str, u32)>{
+    let (puzzle, slot) = file_name.strip_suffix(".solution")?.rsplit_once('-')?;
+    if puzzle.is_empty() || slot.is_empty() || !slot.bytes().all(|b| b.is_ascii_digit()){
+        return None;
+    }
+    Some((puzzle, slot.parse().ok().filter(|slot| *slot >= 1)?))
+}
+
+/// The path to save a new solution to this puzzle in `dir` without overwriting one, using the lowest free slot.
+pub fn free_solution_path(dir: impl AsRef<Path>, puzzle_name: &str) -> PathBuf{
+    let dir = dir.as_ref();
+    (1..).map(|slot| dir.join(solution_file_name(puzzle_name, slot))).find(|path| !path.exists()).expect("some slot should be free")
+}
+
+/// A relative path with every component sanitized, e.g. for an entry in an archive, so it can't point outside the
+/// directory it's joined onto.
+pub fn sanitize_path(path: &str) -> PathBuf{
+    path.split(['/', '\\']).filter(|component| !component.is_empty()).map(sanitize).collect()
+}
@@ -0,0 +1,100 @@
+//! Loading puzzles and solutions from files and readers, rather than from bytes already in memory.
+
+use std::fmt::{Display, Formatter};
+use std::io::Read;
+use std::path::Path;
+use crate::data::{Puzzle, Solution};
+use crate::parse::{parse_puzzle, parse_solution};
+
+#[cfg(feature = "batch")]
+pub mod batch;
+pub mod discover;
+pub mod filename;
+
+/// A reason that a puzzle or solution couldn't be loaded.
+#[derive(Debug)]
+pub enum LoadError{
+    /// The data couldn't be read.
+    Io(std::io::Error),
+    /// The data was read, but isn't a valid puzzle or solution.
+    Parse(&'static str),
+    /// A solution archive isn't a valid zip file.
+    #[cfg(feature = "batch")]
+    Archive(zip::result::ZipError)
+}
+
+impl Display for LoadError{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result{
+        match self{
+            LoadError::Io(err) => write!(f, "couldn't read file: {err}"),
+            LoadError::Parse(reason) => write!(f, "{reason}"),
+            #[cfg(feature = "batch")]
+            LoadError::Archive(err) => write!(f, "couldn't read archive: {err}")
+        }
+    }
+}
+
+impl std::error::Error for LoadError{
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)>{
+        match self{
+            LoadError::Io(err) => Some(err),
+            LoadError::Parse(_) => None,
+            #[cfg(feature = "batch")]
+            LoadError::Archive(err) => Some(err)
+        }
+    }
+}
+
+impl From<std::io::Error> for LoadError{
+    fn from(err: std::io::Error) -> LoadError{
+        LoadError::Io(err)
+    }
+}
+
+#[cfg(feature = "batch")]
+impl From<zip::result::ZipError> for LoadError{
+    fn from(err: zip::result::ZipError) -> LoadError{
+        match err{
+            zip::result::ZipError::Io(err) => LoadError::Io(err),
+            err => LoadError::Archive(err)
+        }
+    }
+}
+
+pub fn parse_puzzle_from(mut reader: impl Read) -> Result<Puzzle, LoadError>{
+    let mut data = Vec::new();
+    reader.read_to_end(&mut data)?;
+    parse_puzzle(&data).map_err(LoadError::Parse)
+}
+
+pub fn parse_solution_from(mut reader: impl Read) -> Result<Solution, LoadError>{
+    let mut data = Vec::new();
+    reader.read_to_end(&mut data)?;
+    parse_solution(&data).map_err(LoadError::Parse)
+}
+
+pub fn parse_puzzle_file(path: impl AsRef<Path>) -> Result<Puzzle, LoadError>{
+    parse_puzzle(&std::fs::read(path)?).map_err(LoadError::Parse)
+}
+
+pub fn parse_solution_file(path: impl AsRef<Path>) -> Result<Solution, LoadError>{
+    parse_solution(&std::fs::read(path)?).map_err(LoadError::Parse)
+}
+
+/// Read a puzzle from an async reader, like a network stream.
+#[cfg(feature = "async")]
+pub async fn parse_puzzle_async(mut reader: impl futures_lite::AsyncRead + Unpin) -> Result<Puzzle, LoadError>{
+    use futures_lite::AsyncReadExt;
+    let mut data = Vec::new();
+    reader.read_to_end(&mut data).await?;
+    parse_puzzle(&data).map_err(LoadError::Parse)
+}
+
+/// Read a solution from an async reader, like a network stream.
+#[cfg(feature = "async")]
+pub async fn parse_solution_async(mut reader: impl futures_lite::AsyncRead + Unpin) -> Result<Solution, LoadError>{
+    use futures_lite::AsyncReadExt;
+    let mut data = Vec::new();
+    reader.read_to_end(&mut data).await?;
+    parse_solution(&data).map_err(LoadError::Parse)
+}
@@ -1,24 +1,591 @@
 use std::env;
-use std::fs::File;
-use std::io::Read;
-use omsim_rs::parse::{parse_puzzle, parse_solution};
-use omsim_rs::sim::Sim;
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+use omsim_rs::advise::advise;
+use omsim_rs::analysis::tournament::Tournament;
+use omsim_rs::crosscheck::crosscheck;
+use omsim_rs::data::HexIndex;
+use omsim_rs::diff::diff_solutions;
+use omsim_rs::minify::minify;
+use omsim_rs::optimize::{optimize_parallel, Goal};
+use omsim_rs::render::{render_blueprint, render_svg, Card, RenderOptions};
+use omsim_rs::render::theme::RenderTheme;
+use omsim_rs::load::{parse_puzzle_file, parse_solution_file, LoadError};
+use omsim_rs::load::discover::{find_solutions, latest};
+use omsim_rs::selftest::{run_case, CORPUS};
+use omsim_rs::sim::{Sim, Stop};
+use omsim_rs::sim::config::{Progress, ProgressCallback, SimConfig};
+use omsim_rs::sim::error::SimError;
+use omsim_rs::trace::Trace;
+use omsim_rs::verify::{verify, verify_with_config, Verdict};
+
+// exit codes of the commands that verify solutions
+const EXIT_INVALID: i32 = 1;
+/// The puzzle or solution couldn't be found, read, or parsed, or an argument couldn't be parsed.
+const EXIT_PARSE_ERROR: i32 = 2;
+/// A bug in the simulator, i.e. a panic or a broken invariant.
+const EXIT_INTERNAL_ERROR: i32 = 3;
+/// The solution didn't complete within the cycle limit, or took too much memory.
+const EXIT_LIMIT_EXCEEDED: i32 = 4;
 
 fn main() {
+    #[cfg(feature = "tracing-subscriber")]
+    tracing_subscriber::fmt()
+        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
+        .with_writer(std::io::stderr)
+        .init();
+
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        default_hook(info);
+        std::process::exit(EXIT_INTERNAL_ERROR);
+    }));
+
     let args: Vec<String> = env::args().collect();
 
-    let mut buffer: Vec<u8> = Vec::new();
-    let _ = File::open(&args[1]).unwrap().read_to_end(&mut buffer).unwrap();
-    let puzzle = parse_puzzle(buffer.as_slice()).unwrap();
+    match args.get(1).map(String::as_str){
+        Some("diff") => diff(&args[2..]),
+        Some("check") => check(&args[2..]),
+        Some("selftest") => selftest(),
+        Some("crosscheck") => cross(&args[2..]),
+        Some("minify") => minify_solution(&args[2..]),
+        Some("optimize") => optimize_solution(&args[2..]),
+        Some("advise") => advise_solution(&args[2..]),
+        Some("render") => render(&args[2..]),
+        Some("trace") => record_trace(&args[2..]),
+        Some("tracediff") => trace_diff(&args[2..]),
+        Some("debug") => debug(&args[2..]),
+        Some("tournament") => tournament(&args[2..]),
+        Some("watch") => watch(&args[2..]),
+        Some("verify") => verify_solution(&args[2..]),
+        _ => inspect(&args[1..])
+    }
+}
+
+/// The loaded file, or exit with [`EXIT_PARSE_ERROR`].
+fn loaded<T>(result: Result<T, LoadError>) -> T{
+    result.unwrap_or_else(|err| {
+        eprintln!("{err}");
+        std::process::exit(EXIT_PARSE_ERROR);
+    })
+}
+
+/// The parsed argument, or exit with [`EXIT_PARSE_ERROR`] saying what it should look like.
+fn argument<T>(parsed: Option<T>, expected: &str) -> T{
+    parsed.unwrap_or_else(|| {
+        eprintln!("{expected}");
+        std::process::exit(EXIT_PARSE_ERROR);
+    })
+}
+
+/// The sim, or whatever needs one, or exit with [`EXIT_INVALID`] if the solution can't be set up on its puzzle.
+fn created<T>(result: Result<T, &'static str>) -> T{
+    result.unwrap_or_else(|err| {
+        println!("invalid: {err}");
+        std::process::exit(EXIT_INVALID);
+    })
+}
+
+fn verdict_exit_code(verdict: &Verdict) -> i32{
+    match &verdict.result{
+        Ok(_) => 0,
+        Err(SimError::CycleLimit{ .. } | SimError::MemoryLimit{ .. }) => EXIT_LIMIT_EXCEEDED,
+        Err(SimError::Invariant{ .. }) => EXIT_INTERNAL_ERROR,
+        Err(_) => EXIT_INVALID
+    }
+}
+
+/// The verdict as one line of tab-separated fields: `valid` and the cycles, cost, area and instructions,
+/// or `invalid` or `limit` and the reason.
+fn porcelain(verdict: &Verdict) -> String{
+    match &verdict.result{
+        Ok(metrics) => format!("valid\t{}\t{}\t{}\t{}", metrics.cycles, metrics.cost, metrics.area, metrics.instructions),
+        Err(err @ (SimError::CycleLimit{ .. } | SimError::MemoryLimit{ .. })) => format!("limit\t{err}"),
+        Err(err) => format!("invalid\t{err}")
+    }
+}
+
+fn inspect(args: &[String]){
+    let skip_collision = args.iter().any(|arg| arg == "--skip-collision");
+    let json = args.iter().any(|arg| arg == "--json");
+    // `--quota=R:N` allows at most N copies of reagent R
+    let input_quotas = args.iter().filter_map(|arg| arg.strip_prefix("--quota=")).map(|quota| {
+        let parsed = quota.split_once(':').and_then(|(reagent, count)| Some((reagent.parse().ok()?, count.parse().ok()?)));
+        argument(parsed, "--quota should be reagent:count, where both are numbers")
+    }).collect();
+    let args: Vec<&String> = args.iter().filter(|arg| !arg.starts_with("--")).collect();
+    let usage = "usage: omsim-rs [--skip-collision] [--json] [--quota=reagent:count] <puzzle> <solution>";
+    let puzzle = loaded(parse_puzzle_file(argument(args.first(), usage)));
+    let solution_path = argument(args.get(1), usage);
+    let config = SimConfig{ skip_collision, input_quotas, asymptotic: json, ..SimConfig::default() };
+    if json{
+        let sol = loaded(parse_solution_file(solution_path));
+        let verdict = verify_with_config(&puzzle, &sol, config);
+        println!("{}", verdict.to_json());
+        std::process::exit(verdict_exit_code(&verdict));
+    }
     println!("it's like {puzzle:?}\n");
+    for (idx, reagent) in puzzle.reagents.iter().enumerate(){
+        println!("reagent {idx}: {reagent}");
+    }
+    for (idx, product) in puzzle.products.iter().enumerate(){
+        println!("product {idx}: {product}");
+    }
 
     println!("and then!!");
 
-    let mut buffer2: Vec<u8> = Vec::new();
-    let _ = File::open(&args[2]).unwrap().read_to_end(&mut buffer2).unwrap();
-    let sol = parse_solution(buffer2.as_slice()).unwrap();
+    let sol = loaded(parse_solution_file(solution_path));
     println!("they're like {sol:?}\n");
 
-    let sim: Sim = Sim::create(&puzzle, &sol).unwrap();
-    println!("and now we set the empty stage of {sim:?}");
+    let verdict = verify_with_config(&puzzle, &sol, config);
+    println!("{verdict}");
+    if let Some(explanation) = verdict.explanation(){
+        println!("{explanation}");
+    }
+    if !verdict.wasted.is_empty(){
+        let wasted: Vec<String> = verdict.wasted.iter().map(|(atom, count)| format!("{count} {}", atom.symbol())).collect();
+        println!("wasted: {}", wasted.join(", "));
+    }
+    for issue in puzzle.conduit_issues(&sol){
+        println!("illegal conduit: {issue}");
+    }
+    std::process::exit(verdict_exit_code(&verdict));
+}
+
+fn verify_solution(args: &[String]){
+    let latest_saved = args.iter().any(|arg| arg == "--latest");
+    let quiet = args.iter().any(|arg| arg == "--quiet");
+    let porcelain_output = args.iter().any(|arg| arg == "--porcelain");
+    let show_progress = args.iter().any(|arg| arg == "--progress");
+    let args: Vec<&String> = args.iter().filter(|arg| !arg.starts_with("--")).collect();
+    let usage = "usage: omsim-rs verify [--quiet] [--porcelain] [--progress] <puzzle> (<solution> | --latest)";
+    let puzzle = loaded(parse_puzzle_file(argument(args.first(), usage)));
+    let path = if latest_saved{
+        let found = loaded(find_solutions(&puzzle.name));
+        let Some(path) = latest(&found) else {
+            eprintln!("no saved solutions to {} found", puzzle.name);
+            std::process::exit(EXIT_PARSE_ERROR);
+        };
+        if !quiet && !porcelain_output{
+            println!("{}", path.display());
+        }
+        path.clone()
+    }else{
+        argument(args.get(1), usage).into()
+    };
+    let sol = loaded(parse_solution_file(path));
+    let config = SimConfig::default();
+    let config = SimConfig{ progress: show_progress.then(|| spinner(config.max_cycles)), ..config };
+    let verdict = verify_with_config(&puzzle, &sol, config);
+    if show_progress{
+        // clear the spinner's line
+        eprint!("\r\x1b[K");
+    }
+    if porcelain_output{
+        println!("{}", porcelain(&verdict));
+    }else if !quiet{
+        println!("{verdict}");
+    }
+    std::process::exit(verdict_exit_code(&verdict));
+}
+
+/// A spinner on stderr with how far verification has got, and how long it could take at most, assuming it runs to the cycle limit.
+fn spinner(max_cycles: u64) -> ProgressCallback{
+    const INTERVAL: u64 = 1000;
+    let start = std::time::Instant::now();
+    ProgressCallback::new(INTERVAL, move |progress: &Progress| {
+        let frame = ['|', '/', '-', '\\'][(progress.cycle / INTERVAL) as usize % 4];
+        let per_cycle = start.elapsed().as_secs_f64() / progress.cycle as f64;
+        let left = max_cycles.saturating_sub(progress.cycle) as f64 * per_cycle;
+        let products: u64 = progress.products.iter().sum();
+        eprint!("\r{frame} cycle {}, {products} products, area {}, at most {left:.0}s left\x1b[K", progress.cycle, progress.area);
+    })
+}
+
+fn diff(args: &[String]){
+    let usage = "usage: omsim-rs diff <solution> <solution>";
+    let before = loaded(parse_solution_file(argument(args.first(), usage)));
+    let after = loaded(parse_solution_file(argument(args.get(1), usage)));
+    print!("{}", diff_solutions(&before, &after));
+}
+
+fn check(args: &[String]){
+    let puzzle = loaded(parse_puzzle_file(argument(args.first(), "usage: omsim-rs check <puzzle>")));
+    let issues = puzzle.feasibility_issues();
+    if issues.is_empty(){
+        println!("no feasibility issues found");
+    }
+    for issue in issues{
+        println!("{issue}");
+    }
+}
+
+fn selftest(){
+    let mut failed = 0;
+    for case in CORPUS{
+        match run_case(case){
+            None => println!("ok   {}", case.name),
+            Some(divergence) => {
+                failed += 1;
+                println!("FAIL {divergence}");
+            }
+        }
+    }
+    println!("{} passed, {failed} failed", CORPUS.len() - failed);
+    if failed > 0{
+        std::process::exit(1);
+    }
+}
+
+fn minify_solution(args: &[String]){
+    let usage = "usage: omsim-rs minify <puzzle> <solution> <output solution>";
+    let puzzle = loaded(parse_puzzle_file(argument(args.first(), usage)));
+    let sol = loaded(parse_solution_file(argument(args.get(1), usage)));
+    let output = argument(args.get(2), usage);
+    match minify(&puzzle, &sol){
+        Ok(minified) => {
+            std::fs::write(output, minified.solution.unparse()).unwrap();
+            println!("removed {} parts and {} blank instructions, trimmed {} track hexes",
+                minified.removed_parts, minified.removed_blanks, minified.trimmed_track_hexes);
+            let m = minified.metrics;
+            println!("now {} cycles, {} cost, {} area, {} instructions", m.cycles, m.cost, m.area, m.instructions);
+        }
+        Err(err) => {
+            println!("can't minify an invalid solution: {err}");
+            std::process::exit(1);
+        }
+    }
+}
+
+fn render(args: &[String]){
+    let blueprint = args.iter().any(|arg| arg == "--blueprint");
+    let coordinates = args.iter().any(|arg| arg == "--coordinates");
+    let counter = args.iter().any(|arg| arg == "--counter");
+    let steady_state = args.iter().any(|arg| arg == "--steady-state");
+    // `--frame-step=N` draws every Nth cycle, for previews
+    let frame_step = args.iter().find_map(|arg| arg.strip_prefix("--frame-step=")).map_or(1, |step| argument(step.parse().ok(), "--frame-step should be a number"));
+    let threads = std::thread::available_parallelism().map_or(1, |threads| threads.get());
+    let theme = if args.iter().any(|arg| arg == "--colorblind"){ RenderTheme::colorblind() }else{ RenderTheme::classic() };
+    let args: Vec<&String> = args.iter().filter(|arg| !arg.starts_with("--")).collect();
+    let usage = "usage: omsim-rs render [--blueprint] [--coordinates] [--counter] [--steady-state] [--colorblind] [--frame-step=n] <puzzle> <solution> <output>.svg|.gif";
+    let puzzle = loaded(parse_puzzle_file(argument(args.first(), usage)));
+    let sol = loaded(parse_solution_file(argument(args.get(1), usage)));
+    let output = argument(args.get(2), usage);
+    // blueprints are for previews, so they show the recorded metrics instead of simulating
+    let metrics = if blueprint{ sol.metrics }else{ verify(&puzzle, &sol).result.ok() };
+    let card = Card{ puzzle: puzzle.name.clone(), solution: sol.name.clone(), metrics };
+    let options = RenderOptions{ card: Some(card), coordinates, counter, steady_state, frame_step, threads, theme, ..RenderOptions::default() };
+    if output.ends_with(".gif"){
+        #[cfg(feature = "gif")]
+        omsim_rs::render::gif::render_gif(&created(Sim::create(&puzzle, &sol)), &options, std::io::BufWriter::new(std::fs::File::create(output).unwrap())).unwrap();
+        #[cfg(not(feature = "gif"))]
+        {
+            println!("rendering GIFs needs the gif feature");
+            std::process::exit(1);
+        }
+    }else if blueprint{
+        // only sets up the parts, without running the solution
+        std::fs::write(output, created(render_blueprint(&puzzle, &sol, &options))).unwrap();
+    }else{
+        std::fs::write(output, render_svg(&created(Sim::create(&puzzle, &sol)), &options)).unwrap();
+    }
+}
+
+fn optimize_solution(args: &[String]){
+    let goal = if args.iter().any(|arg| arg == "--instructions"){ Goal::Instructions }else{ Goal::Cycles };
+    let args: Vec<&String> = args.iter().filter(|arg| !arg.starts_with("--")).collect();
+    let usage = "usage: omsim-rs optimize [--instructions] <puzzle> <solution> <output solution>";
+    let puzzle = loaded(parse_puzzle_file(argument(args.first(), usage)));
+    let sol = loaded(parse_solution_file(argument(args.get(1), usage)));
+    let output = argument(args.get(2), usage);
+    let threads = std::thread::available_parallelism().map_or(1, |threads| threads.get());
+    match optimize_parallel(&puzzle, &sol, goal, threads){
+        Ok(optimized) => {
+            std::fs::write(output, optimized.solution.unparse()).unwrap();
+            println!("removed {} instructions, shifted arms {} times, added {} repeats",
+                optimized.removed_instructions, optimized.shifts, optimized.repeats);
+            let m = optimized.metrics;
+            println!("now {} cycles, {} cost, {} area, {} instructions", m.cycles, m.cost, m.area, m.instructions);
+        }
+        Err(err) => {
+            println!("can't optimize an invalid solution: {err}");
+            std::process::exit(1);
+        }
+    }
+}
+
+fn advise_solution(args: &[String]){
+    let usage = "usage: omsim-rs advise <puzzle> <solution>";
+    let puzzle = loaded(parse_puzzle_file(argument(args.first(), usage)));
+    let sol = loaded(parse_solution_file(argument(args.get(1), usage)));
+    match advise(&puzzle, &sol){
+        Ok(suggestions) if suggestions.is_empty() => println!("no suggestions"),
+        Ok(suggestions) => for suggestion in suggestions{
+            println!("{suggestion}");
+        }
+        Err(err) => {
+            println!("can't advise on an invalid solution: {err}");
+            std::process::exit(EXIT_INVALID);
+        }
+    }
+}
+
+fn tournament(args: &[String]){
+    let json = args.iter().any(|arg| arg == "--json");
+    let args: Vec<&String> = args.iter().filter(|arg| !arg.starts_with("--")).collect();
+    let puzzle = loaded(parse_puzzle_file(argument(args.first(), "usage: omsim-rs tournament [--json] <puzzle> <solution>...")));
+    let solutions = args[1..].iter().map(|path| (path.to_string(), loaded(parse_solution_file(path))));
+    let tournament = Tournament::run(&puzzle, solutions);
+    if json{
+        println!("{}", tournament.to_json());
+    }else{
+        print!("{}", tournament.to_markdown());
+    }
+}
+
+#[cfg(feature = "watch")]
+fn watch(args: &[String]){
+    use std::sync::mpsc;
+    use std::time::Duration;
+    use notify::{EventKind, RecursiveMode, Watcher};
+    use omsim_rs::verify::verify;
+
+    let usage = "usage: omsim-rs watch <puzzle> <solution>";
+    let puzzle = loaded(parse_puzzle_file(argument(args.first(), usage)));
+    let path = Path::new(argument(args.get(1), usage));
+    let report = || match parse_solution_file(path){
+        Ok(sol) => println!("{}", verify(&puzzle, &sol)),
+        Err(err) => println!("{err}")
+    };
+    report();
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx).unwrap();
+    // saving may replace the file rather than write to it, so watch its directory
+    let dir = path.parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    watcher.watch(dir, RecursiveMode::NonRecursive).unwrap();
+    for event in &rx{
+        let event = event.unwrap();
+        if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) && event.paths.iter().any(|changed| changed.file_name() == path.file_name()){
+            // a single save can arrive as several events, so wait for them to stop before reading the file
+            while rx.recv_timeout(Duration::from_millis(100)).is_ok(){}
+            report();
+        }
+    }
+}
+
+#[cfg(not(feature = "watch"))]
+fn watch(_: &[String]){
+    println!("omsim-rs was built without the watch feature");
+    std::process::exit(1);
+}
+
+fn record_trace(args: &[String]){
+    let usage = "usage: omsim-rs trace <puzzle> <solution> <output trace>";
+    let puzzle = loaded(parse_puzzle_file(argument(args.first(), usage)));
+    let sol = loaded(parse_solution_file(argument(args.get(1), usage)));
+    let output = argument(args.get(2), usage);
+    let trace = Trace::record(&mut created(Sim::create(&puzzle, &sol)));
+    std::fs::write(output, trace.unparse()).unwrap();
+    match &trace.error{
+        Some(error) => println!("recorded {} frames, then: {error}", trace.frames.len()),
+        None => println!("recorded {} frames", trace.frames.len())
+    }
+}
+
+fn trace_diff(args: &[String]){
+    // either two recorded traces, or a puzzle and two solutions to record
+    let usage = "usage: omsim-rs tracediff (<trace> <trace> | <puzzle> <solution> <solution>)";
+    let (left, right) = if args.len() == 2{
+        let load = |path: &String| loaded(std::fs::read(path).map_err(LoadError::Io)
+            .and_then(|data| omsim_rs::parse::parse_trace(&data).map_err(LoadError::Parse)));
+        (load(&args[0]), load(&args[1]))
+    }else{
+        let puzzle = loaded(parse_puzzle_file(argument(args.first(), usage)));
+        let record = |path: &String| Trace::record(&mut created(Sim::create(&puzzle, &loaded(parse_solution_file(path)))));
+        (record(argument(args.get(1), usage)), record(argument(args.get(2), usage)))
+    };
+    let Some(divergence) = left.divergence(&right) else {
+        println!("the traces agree for all {} frames", left.frames.len());
+        return;
+    };
+    println!("the traces diverge after {} cycles: {}", divergence.cycle, divergence.reason);
+    for (name, trace, frame) in [("first", &left, &divergence.left), ("second", &right, &divergence.right)]{
+        match frame{
+            Some(frame) => println!("\n{name}:\n{}", frame.to_text()),
+            None => println!("\n{name}: stopped after {} cycles{}", trace.frames.len().saturating_sub(1),
+                trace.error.as_ref().map(|error| format!(": {error}")).unwrap_or_default())
+        }
+    }
+    std::process::exit(1);
+}
+
+const DEBUG_HELP: &str = "\
+step [n]       run one or n cycles
+back [n]       undo one or n cycles
+break <cycle>  stop continuing when this cycle is reached
+continue       run until a breakpoint, completion, or an error such as a collision
+arm <number>   show an arm's position, rotation, grip, and next instruction
+at <q> <r>     show the molecule at a position
+watch          toggle showing output counters after every step
+board          draw the board
+quit";
+
+/// How many cycles apart the debugger keeps boards to replay from when stepping back.
+const DEBUG_SNAPSHOT_INTERVAL: u64 = 64;
+
+/// The boards of a debugging session: the current one, and one every [`DEBUG_SNAPSHOT_INTERVAL`] cycles up to it.
+/// Stepping back replays from the nearest snapshot instead of keeping every board.
+struct DebugHistory{
+    snapshots: Vec<Sim>,
+    current: Sim
+}
+
+impl DebugHistory{
+    fn new(sim: Sim) -> DebugHistory{
+        DebugHistory{ snapshots: vec![sim.clone()], current: sim }
+    }
+
+    /// Go back to the board at the end of `cycle`, or the first board if that's earlier.
+    fn rewind(&mut self, cycle: u64){
+        while self.snapshots.len() > 1 && self.snapshots.last().unwrap().cycle > cycle{
+            self.snapshots.pop();
+        }
+        let mut sim = self.snapshots.last().unwrap().clone();
+        while sim.cycle < cycle{
+            // these cycles already ran once without an error
+            sim.step().expect("replaying a debugging session should repeat it");
+        }
+        self.current = sim;
+    }
+}
+
+fn debug(args: &[String]){
+    let usage = "usage: omsim-rs debug <puzzle> <solution>";
+    let puzzle = loaded(parse_puzzle_file(argument(args.first(), usage)));
+    let sol = loaded(parse_solution_file(argument(args.get(1), usage)));
+    let mut history = DebugHistory::new(created(Sim::create(&puzzle, &sol)));
+    let mut breakpoints: Vec<u64> = Vec::new();
+    let mut watch = false;
+    println!("{}", history.current.to_text());
+    loop{
+        print!("(cycle {}) ", history.current.cycle);
+        io::stdout().flush().unwrap();
+        let mut line = String::new();
+        if io::stdin().lock().read_line(&mut line).unwrap() == 0{
+            break;
+        }
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let count = |idx: usize| words.get(idx).and_then(|word| word.parse().ok()).unwrap_or(1);
+        match words.first().copied(){
+            None => {}
+            Some("step" | "s") => {
+                let mut remaining = count(1).max(1);
+                debug_run(&mut history, watch, |_| {
+                    remaining -= 1;
+                    remaining == 0
+                });
+                println!("{}", history.current.to_text());
+            }
+            Some("back" | "b") => {
+                history.rewind(history.current.cycle.saturating_sub(count(1)));
+                println!("{}", history.current.to_text());
+            }
+            Some("break") => match words.get(1).and_then(|word| word.parse().ok()){
+                Some(cycle) => {
+                    breakpoints.push(cycle);
+                    println!("breaking at cycle {cycle}");
+                }
+                None => println!("usage: break <cycle>")
+            }
+            Some("continue" | "c") => {
+                debug_run(&mut history, watch, |sim| breakpoints.contains(&sim.cycle));
+                println!("{}", history.current.to_text());
+            }
+            Some("arm") => {
+                let sim = &history.current;
+                let number = words.get(1).and_then(|word| word.parse().ok());
+                match number.and_then(|number| sim.arm(number)){
+                    Some(arm) => println!("at {:?} facing {:?}, length {}, {}, holding {:?}, next {:?}",
+                        arm.pos, arm.rotation, arm.length, if arm.grabbing{ "grabbing" }else{ "open" }, arm.held, arm.next),
+                    None => println!("no such arm")
+                }
+            }
+            Some("at") => {
+                let sim = &history.current;
+                let coord = |idx: usize| words.get(idx).and_then(|word| word.parse().ok());
+                match (coord(1), coord(2)){
+                    (Some(q), Some(r)) => match sim.molecules.iter().find(|molecule| molecule.contains_pos(HexIndex::new(q, r))){
+                        Some(molecule) => println!("{}", molecule.layout.translated(molecule.pos)),
+                        None => println!("no molecule there")
+                    }
+                    _ => println!("usage: at <q> <r>")
+                }
+            }
+            Some("watch") => {
+                watch = !watch;
+                println!("watching output counters: {watch}");
+            }
+            Some("board") => println!("{}", history.current.to_text()),
+            Some("quit" | "q") => break,
+            Some(_) => println!("{DEBUG_HELP}")
+        }
+    }
+}
+
+/// Continue a debugging session from its current board until `stop` returns `true` at the end of a cycle,
+/// taking snapshots along the way. On an error, the current board is the one before it.
+fn debug_run(history: &mut DebugHistory, watch: bool, mut stop: impl FnMut(&Sim) -> bool){
+    let mut sim = history.current.clone();
+    let mut last_cycle = sim.cycle;
+    let result = sim.run_until_break(|sim, event| match event{
+        Some(event) => {
+            println!("{event:?}");
+            false
+        }
+        None => {
+            if watch{
+                let progress: Vec<String> = sim.progress().iter().map(|product| format!("{}/{}", product.consumed, product.required)).collect();
+                println!("outputs: {}", progress.join(", "));
+            }
+            if sim.cycle % DEBUG_SNAPSHOT_INTERVAL == 0{
+                history.snapshots.push(sim.clone());
+            }
+            last_cycle = sim.cycle;
+            stop(sim)
+        }
+    });
+    match result{
+        Ok(stop) => {
+            history.current = sim;
+            if let Stop::Completed(cycles) = stop{
+                println!("completed in {cycles} cycles");
+            }
+        }
+        Err(err) => {
+            println!("{err}");
+            history.rewind(last_cycle);
+        }
+    }
+}
+
+fn cross(args: &[String]){
+    let usage = "usage: omsim-rs crosscheck <reference omsim> <puzzle> <solution>...";
+    let omsim = Path::new(argument(args.first(), usage));
+    let puzzle = Path::new(argument(args.get(1), usage));
+    let mut disagreed = false;
+    for solution in &args[2..]{
+        match crosscheck(omsim, puzzle, Path::new(solution)){
+            Ok(check) => {
+                disagreed |= !check.agrees();
+                println!("{solution}: {check}");
+            }
+            Err(err) => {
+                disagreed = true;
+                println!("{solution}: error: {err}");
+            }
+        }
+    }
+    if disagreed{
+        std::process::exit(1);
+    }
 }
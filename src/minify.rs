@@ -0,0 +1,121 @@
+//! Shrinking solutions without changing what they do, e.g. as a last step of an optimizer pipeline.
+//!
+//! Every change is checked by simulating the result again, and kept only if the solution still completes in the same
+//! number of cycles without using more area.
+
+use crate::data::{Instruction, Metrics, PartType, Puzzle, Solution};
+use crate::sim::error::SimError;
+use crate::sim::SimEvent;
+use crate::verify::simulate;
+use crate::compat::prelude::*;
+
+/// A minified solution, and what was removed to get there.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Minified{
+    pub solution: Solution,
+    pub metrics: Metrics,
+    /// Explicit blank instructions removed from arms.
+    pub removed_blanks: usize,
+    /// Hexes trimmed off the ends of tracks.
+    pub trimmed_track_hexes: usize,
+    /// Tracks that no arm needed, and glyphs that never did anything.
+    pub removed_parts: usize
+}
+
+/// Minify a solution, which must be valid. If it has recorded metrics, they're updated to the new metrics.
+pub fn minify(puzzle: &Puzzle, solution: &Solution) -> Result<Minified, SimError>{
+    let (metrics, sim) = simulate(puzzle, solution)?;
+    let mut minified = Minified{ solution: solution.clone(), metrics, removed_blanks: 0, trimmed_track_hexes: 0, removed_parts: 0 };
+
+    // glyphs that never acted are unused, and tracks may be too if their arms never move along them,
+    // so try removing them all first
+    let used: Vec<bool> = (0..solution.parts.len()).map(|idx| {
+        sim.events.iter().any(|event| matches!(event, SimEvent::GlyphApplied{ part, .. } if *part == idx))
+    }).collect();
+    for idx in (0..solution.parts.len()).rev(){
+        let part = &minified.solution.parts[idx];
+        let unused = match &part.ty{
+            PartType::Track => true,
+            ty => is_glyph(ty) && !used[idx]
+        };
+        if unused{
+            let mut candidate = minified.solution.clone();
+            candidate.parts.remove(idx);
+            if minified.accept(puzzle, candidate){
+                minified.removed_parts += 1;
+            }
+        }
+    }
+
+    for idx in 0..minified.solution.parts.len(){
+        let part = &minified.solution.parts[idx];
+        let is_track = part.ty == PartType::Track;
+        let blanks = part.instructions.iter().filter(|(instr, _)| *instr == Instruction::Blank).count();
+        if blanks > 0{
+            let mut candidate = minified.solution.clone();
+            candidate.parts[idx].instructions.retain(|(instr, _)| *instr != Instruction::Blank);
+            if minified.accept(puzzle, candidate){
+                minified.removed_blanks += blanks;
+            }
+        }
+        if is_track{
+            minified.trimmed_track_hexes += minified.trim_track(puzzle, idx);
+        }
+    }
+
+    if minified.solution.metrics.is_some(){
+        minified.solution.metrics = Some(minified.metrics);
+    }
+    Ok(minified)
+}
+
+impl Minified{
+    /// Replace the solution with `candidate` if it's equivalent, returning whether it was.
+    fn accept(&mut self, puzzle: &Puzzle, candidate: Solution) -> bool{
+        match simulate(puzzle, &candidate){
+            Ok((metrics, _)) if metrics.cycles == self.metrics.cycles && metrics.area <= self.metrics.area => {
+                self.solution = candidate;
+                self.metrics = metrics;
+                true
+            }
+            _ => false
+        }
+    }
+
+    /// Trim hexes off either end of the track at `idx` for as long as the solution stays equivalent,
+    /// returning how many were trimmed.
+    fn trim_track(&mut self, puzzle: &Puzzle, idx: usize) -> usize{
+        let mut trimmed = 0;
+        loop{
+            let track = &self.solution.parts[idx];
+            if track.track_hexes.len() <= 1{
+                return trimmed;
+            }
+            let mut last = self.solution.clone();
+            last.parts[idx].track_hexes.pop();
+            if self.accept(puzzle, last){
+                trimmed += 1;
+                continue;
+            }
+            // the first hex is where the track is placed, so move the track along with it
+            let mut first = self.solution.clone();
+            let part = &mut first.parts[idx];
+            part.track_hexes.remove(0);
+            let next = part.track_hexes[0];
+            part.pos += next;
+            part.track_hexes.iter_mut().for_each(|hex| *hex -= next);
+            if self.accept(puzzle, first){
+                trimmed += 1;
+                continue;
+            }
+            return trimmed;
+        }
+    }
+}
+
+/// Glyphs that only act on atoms, which can be removed if they never do.
+fn is_glyph(ty: &PartType) -> bool{
+    matches!(ty, PartType::Bonding | PartType::MultiBonding | PartType::TriplexBonding | PartType::Unbonding
+        | PartType::Calcification | PartType::Duplication | PartType::Projection | PartType::Purification
+        | PartType::Animismus | PartType::Unification | PartType::Dispersion | PartType::Disposal | PartType::Equilibrium)
+}
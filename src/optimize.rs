@@ -0,0 +1,213 @@
+//! Searching for faster or shorter tapes for a valid solution.
+//!
+//! The search is greedy: each round tries removing every instruction and shifting every arm a cycle earlier or later,
+//! and keeps whichever change improves the solution most, until none does. Removals are checked by patching a
+//! [`Rerun`](crate::sim::patch::Rerun) of the current solution, so each only simulates the cycles after it.
+//! Repeated runs of instructions at the start of a tape are also merged into Repeat instructions.
+//! Area isn't taken into account, and parts are never changed.
+
+use crate::data::{Instruction, Metrics, Part, Puzzle, Solution};
+use crate::sim::error::SimError;
+use crate::sim::patch::Rerun;
+use crate::sim::Sim;
+use crate::verify::simulate;
+use crate::compat::prelude::*;
+
+/// What an optimization is trying to reduce. Changes that make the other one worse are kept only if this one improves.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Goal{
+    Cycles,
+    Instructions
+}
+
+impl Goal{
+    /// Lower is better.
+    fn score(self, metrics: &Metrics) -> (i32, i32){
+        match self{
+            Goal::Cycles => (metrics.cycles, metrics.instructions),
+            Goal::Instructions => (metrics.instructions, metrics.cycles)
+        }
+    }
+}
+
+/// An optimized solution, and what changed to get there.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Optimized{
+    pub solution: Solution,
+    pub metrics: Metrics,
+    /// Instructions removed from arms, not counting those merged into repeats.
+    pub removed_instructions: usize,
+    /// Times an arm's instructions were moved a cycle earlier or later.
+    pub shifts: usize,
+    /// Repeat instructions added in place of the instructions they repeat.
+    pub repeats: usize
+}
+
+/// One change to try in a round of the search.
+#[derive(Copy, Clone, Debug)]
+enum Change{
+    /// Remove the instruction at this position in the part's instruction list.
+    Remove{ part: usize, instr: usize },
+    /// Move the part's instructions this many cycles later.
+    Shift{ part: usize, by: i32 }
+}
+
+/// Optimize a solution, which must be valid. If it has recorded metrics, they're updated to the new metrics.
+pub fn optimize(puzzle: &Puzzle, solution: &Solution, goal: Goal) -> Result<Optimized, SimError>{
+    optimize_with_threads(puzzle, solution, goal, 1)
+}
+
+/// Like [`optimize`], but trying each round's changes on up to `threads` threads.
+#[cfg(feature = "std")]
+pub fn optimize_parallel(puzzle: &Puzzle, solution: &Solution, goal: Goal, threads: usize) -> Result<Optimized, SimError>{
+    optimize_with_threads(puzzle, solution, goal, threads)
+}
+
+fn optimize_with_threads(puzzle: &Puzzle, solution: &Solution, goal: Goal, threads: usize) -> Result<Optimized, SimError>{
+    let (metrics, _) = simulate(puzzle, solution)?;
+    let mut optimized = Optimized{ solution: solution.clone(), metrics, removed_instructions: 0, shifts: 0, repeats: 0 };
+    if goal == Goal::Instructions{
+        optimized.merge_repeats(puzzle, goal);
+    }
+
+    loop{
+        let base = optimized.rerun(puzzle, goal)?;
+        let changes = optimized.changes();
+        let tried = evaluate(&changes, threads, |change| optimized.try_change(puzzle, &base, *change));
+        let best = changes.iter().zip(tried)
+            .filter_map(|(change, metrics)| Some((goal.score(&metrics?), *change)))
+            .min_by_key(|(score, _)| *score);
+        let Some((score, change)) = best else { break };
+        if score >= goal.score(&optimized.metrics){
+            break;
+        }
+        // the change was tried on a rerun, so simulate it properly before keeping it
+        let candidate = optimized.changed(change);
+        let Ok((metrics, _)) = simulate(puzzle, &candidate) else { break };
+        if goal.score(&metrics) >= goal.score(&optimized.metrics){
+            break;
+        }
+        optimized.solution = candidate;
+        optimized.metrics = metrics;
+        match change{
+            Change::Remove{ .. } => optimized.removed_instructions += 1,
+            Change::Shift{ .. } => optimized.shifts += 1
+        }
+    }
+
+    // instructions before a repeat can't be removed, so when going for speed, merge them last
+    optimized.merge_repeats(puzzle, goal);
+    if optimized.solution.metrics.is_some(){
+        optimized.solution.metrics = Some(optimized.metrics);
+    }
+    Ok(optimized)
+}
+
+impl Optimized{
+    /// Merge each arm's repeated instructions into a Repeat where that's an improvement.
+    fn merge_repeats(&mut self, puzzle: &Puzzle, goal: Goal){
+        for idx in 0..self.solution.parts.len(){
+            let Some(instructions) = merge_repeat(&self.solution.parts[idx], self.solution.tape_start()) else { continue };
+            let mut candidate = self.solution.clone();
+            candidate.parts[idx].instructions = instructions;
+            if let Ok((metrics, _)) = simulate(puzzle, &candidate){
+                if goal.score(&metrics) < goal.score(&self.metrics){
+                    self.solution = candidate;
+                    self.metrics = metrics;
+                    self.repeats += 1;
+                }
+            }
+        }
+    }
+
+    /// A run of the current solution with snapshots to patch, stopping once it's too slow to be an improvement.
+    fn rerun(&self, puzzle: &Puzzle, goal: Goal) -> Result<Rerun, SimError>{
+        let mut sim = Sim::create(puzzle, &self.solution).map_err(SimError::Setup)?;
+        let cycles = self.metrics.cycles as u64;
+        sim.config.max_cycles = match goal{
+            Goal::Cycles => cycles,
+            Goal::Instructions => sim.config.max_cycles.min(cycles * 2 + 100)
+        };
+        let mut rerun = Rerun::new(sim, (cycles / 32).max(1));
+        rerun.run()?;
+        Ok(rerun)
+    }
+
+    /// Every change worth trying this round.
+    fn changes(&self) -> Vec<Change>{
+        let mut changes = Vec::new();
+        for (part, p) in self.solution.parts.iter().enumerate(){
+            if p.instructions.is_empty(){
+                continue;
+            }
+            // repeats and resets are expanded from the instructions before them, so those can't be patched one by one
+            let after = p.instructions.iter().filter(|(instr, _)| matches!(instr, Instruction::Repeat | Instruction::Reset)).map(|(_, idx)| *idx).max();
+            changes.extend(p.instructions.iter().enumerate()
+                .filter(|(_, (instr, idx))| after.is_none_or(|after| *idx > after)
+                    && !matches!(instr, Instruction::Blank | Instruction::PeriodOverride | Instruction::Repeat | Instruction::Reset))
+                .map(|(instr, _)| Change::Remove{ part, instr }));
+            changes.push(Change::Shift{ part, by: -1 });
+            changes.push(Change::Shift{ part, by: 1 });
+        }
+        changes
+    }
+
+    /// The metrics the solution would have with this change, if it's still valid.
+    fn try_change(&self, puzzle: &Puzzle, base: &Rerun, change: Change) -> Option<Metrics>{
+        match change{
+            Change::Remove{ part, instr } => {
+                let p = &self.solution.parts[part];
+                let index = usize::try_from(p.instructions[instr].1 - self.solution.tape_start()).ok()?;
+                let mut rerun = base.clone();
+                rerun.patch_instruction(p.arm_number, index, Instruction::Blank).ok()?;
+                let cycles = rerun.run().ok()?;
+                Some(Metrics{ cycles: cycles as i32, area: rerun.sim.area.len() as i32, instructions: self.metrics.instructions - 1, ..self.metrics })
+            }
+            Change::Shift{ .. } => simulate(puzzle, &self.changed(change)).ok().map(|(metrics, _)| metrics)
+        }
+    }
+
+    /// The solution with this change made.
+    fn changed(&self, change: Change) -> Solution{
+        let mut solution = self.solution.clone();
+        match change{
+            Change::Remove{ part, instr } => {
+                solution.parts[part].instructions.remove(instr);
+            }
+            Change::Shift{ part, by } => solution.parts[part].instructions.iter_mut().for_each(|(_, idx)| *idx += by)
+        }
+        solution
+    }
+}
+
+/// If an arm's instructions start with the same run twice in a row, its instructions with the second run replaced by a
+/// Repeat. Takes the longest such run.
+fn merge_repeat(part: &Part, start: i32) -> Option<Vec<(Instruction, i32)>>{
+    if part.instructions.iter().any(|(instr, _)| matches!(instr, Instruction::Repeat | Instruction::Reset)){
+        return None;
+    }
+    let tape = part.tape(start);
+    let first = tape.iter().position(|instr| *instr != Instruction::Blank)?;
+    let len = (1..=(tape.len() - first) / 2).rev().find(|len| tape[first..first + len] == tape[first + len..first + 2 * len])?;
+    let copy = (first + len) as i32 + start..(first + 2 * len) as i32 + start;
+    let mut instructions: Vec<(Instruction, i32)> = part.instructions.iter().filter(|(_, idx)| !copy.contains(idx)).copied().collect();
+    instructions.push((Instruction::Repeat, copy.start));
+    instructions.sort_by_key(|(_, idx)| *idx);
+    Some(instructions)
+}
+
+/// Apply `f` to each item, on up to `threads` threads if there are threads.
+fn evaluate<T: Sync, R: Send>(items: &[T], threads: usize, f: impl Fn(&T) -> R + Sync) -> Vec<R>{
+    #[cfg(feature = "std")]
+    if threads > 1 && items.len() > 1{
+        let f = &f;
+        return std::thread::scope(|scope| {
+            let handles: Vec<_> = items.chunks(items.len().div_ceil(threads)).map(|chunk| scope.spawn(move || {
+                chunk.iter().map(f).collect::<Vec<_>>()
+            })).collect();
+            handles.into_iter().flat_map(|handle| handle.join().expect("optimizer thread panicked")).collect()
+        });
+    }
+    let _ = threads;
+    items.iter().map(f).collect()
+}
@@ -1,14 +1,167 @@
-use std::backtrace::Backtrace;
-use std::collections::{HashMap, HashSet};
+use crate::compat::{HashMap, HashSet};
+use alloc::borrow::Cow;
 use arrayref::array_ref;
 use super::data::*;
+use crate::sim::SimEvent;
+use crate::trace::{Frame, TracedArm, Trace};
+use crate::compat::prelude::*;
 
 pub fn parse_puzzle(data: &[u8]) -> Result<Puzzle, &'static str>{
+    parse_puzzle_from(&mut BaseParser::new(data)).map(PuzzleRef::into_owned)
+}
+
+/// How to handle the anomalies real files sometimes have.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct ParseOptions{
+    /// Reject any [`Anomaly`] with an error describing it, instead of tolerating it like the game does.
+    /// For puzzles, this also rejects invalid molecules, like [`parse_puzzle_strict`].
+    pub strict: bool
+}
+
+/// Something unusual about a file that the game tolerates, see [`ParseOptions`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Anomaly{
+    /// The puzzle's name, or the name of the puzzle a solution is for, is empty.
+    EmptyPuzzleName,
+    /// The solution's own name is empty.
+    EmptySolutionName,
+    /// A part's instructions aren't in increasing order of their indices. The game sorts them, and later instructions
+    /// at the same index replace earlier ones. Gives the index of the part.
+    InstructionsOutOfOrder(usize),
+    /// The solution's name or puzzle name isn't valid UTF-8, as in some very old or modded files.
+    /// Invalid sequences were replaced with U+FFFD.
+    InvalidUtf8Name,
+    /// This many bytes are left over after the parts list of a solution, which usually means the file is corrupted,
+    /// or was written by something that doesn't quite follow the format.
+    TrailingBytes(usize),
+    /// The puzzle's permissions have bits set that aren't any known [`Permissions`] flag, as from a newer game version
+    /// or a mod. They're kept in [`Puzzle::permissions`] so the puzzle is written back unchanged. Gives those bits.
+    UnknownPermissions(u64)
+}
+
+impl Anomaly{
+    /// The error for this anomaly when parsing strictly.
+    pub fn description(&self) -> &'static str{
+        match self{
+            Anomaly::EmptyPuzzleName => "puzzle name is empty",
+            Anomaly::EmptySolutionName => "solution name is empty",
+            Anomaly::InstructionsOutOfOrder(_) => "instructions are out of order",
+            Anomaly::InvalidUtf8Name => "invalid utf8",
+            Anomaly::TrailingBytes(_) => "trailing data after solution",
+            Anomaly::UnknownPermissions(_) => "puzzle has unknown permission bits"
+        }
+    }
+}
+
+/// A parsed file, with the anomalies that were tolerated in it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Parsed<T>{
+    pub value: T,
+    /// Empty when parsing strictly, since any anomaly is an error then.
+    pub anomalies: Vec<Anomaly>
+}
+
+/// Collects anomalies, or fails on the first one when parsing strictly.
+struct Anomalies{
+    options: ParseOptions,
+    found: Vec<Anomaly>
+}
+
+impl Anomalies{
+    fn new(options: ParseOptions) -> Anomalies{
+        Anomalies{ options, found: Vec::new() }
+    }
+
+    fn found(&mut self, anomaly: Anomaly) -> Result<(), &'static str>{
+        if self.options.strict{
+            return Err(anomaly.description());
+        }
+        self.found.push(anomaly);
+        Ok(())
+    }
+
+    fn finish<T>(self, value: T) -> Parsed<T>{
+        Parsed{ value, anomalies: self.found }
+    }
+}
+
+/// Parse a puzzle, handling anomalies as `options` says.
+pub fn parse_puzzle_with_options(data: &[u8], options: ParseOptions) -> Result<Parsed<Puzzle>, &'static str>{
+    let mut anomalies = Anomalies::new(options);
+    let puzzle = parse_puzzle_from(&mut BaseParser{ data, strict: options.strict })?.into_owned();
+    if puzzle.name.is_empty(){
+        anomalies.found(Anomaly::EmptyPuzzleName)?;
+    }
+    let (_, unknown) = Permissions::from_bits_report(puzzle.permissions.bits());
+    if unknown != 0{
+        anomalies.found(Anomaly::UnknownPermissions(unknown))?;
+    }
+    Ok(anomalies.finish(puzzle))
+}
+
+/// Parse a solution, handling anomalies as `options` says.
+pub fn parse_solution_with_options(data: &[u8], options: ParseOptions) -> Result<Parsed<Solution>, &'static str>{
+    let mut anomalies = Anomalies::new(options);
     let mut parser = BaseParser::new(data);
+    let mut replaced = false;
+    let (puzzle_name, name, metrics) = parse_solution_head(&mut parser, |p| {
+        if options.strict{
+            return p.parse_str().map(str::to_string);
+        }
+        let name = String::from_utf8_lossy(p.parse_str_bytes()?);
+        replaced |= matches!(name, Cow::Owned(_));
+        Ok(name.into_owned())
+    })?;
+    let solution = Solution{ name, puzzle_name, metrics, parts: parse_parts(&mut parser)? };
+    if replaced{
+        anomalies.found(Anomaly::InvalidUtf8Name)?;
+    }
+    if !parser.data.is_empty(){
+        anomalies.found(Anomaly::TrailingBytes(parser.data.len()))?;
+    }
+    if solution.puzzle_name.is_empty(){
+        anomalies.found(Anomaly::EmptyPuzzleName)?;
+    }
+    if solution.name.is_empty(){
+        anomalies.found(Anomaly::EmptySolutionName)?;
+    }
+    for (idx, part) in solution.parts.iter().enumerate(){
+        if part.instructions.windows(2).any(|pair| pair[0].1 >= pair[1].1){
+            anomalies.found(Anomaly::InstructionsOutOfOrder(idx))?;
+        }
+    }
+    Ok(anomalies.finish(solution))
+}
+
+/// Parse a puzzle, additionally rejecting reagents and products that are not valid molecules (see [`Molecule::validate`]).
+pub fn parse_puzzle_strict(data: &[u8]) -> Result<Puzzle, &'static str>{
+    parse_puzzle_from(&mut BaseParser{ data, strict: true }).map(PuzzleRef::into_owned)
+}
+
+/// Parse a puzzle without copying its name out of `data`.
+pub fn parse_puzzle_ref(data: &[u8]) -> Result<PuzzleRef<'_>, &'static str>{
+    parse_puzzle_from(&mut BaseParser::new(data))
+}
+
+/// A puzzle whose name borrows from the data it was parsed from, to avoid copying when loading many files at once.
+#[derive(Debug, Clone)]
+pub struct PuzzleRef<'a>{
+    pub name: &'a str,
+    /// Everything else about the puzzle, with an empty name.
+    pub puzzle: Puzzle
+}
+
+impl PuzzleRef<'_>{
+    pub fn into_owned(self) -> Puzzle{
+        Puzzle{ name: self.name.to_string(), ..self.puzzle }
+    }
+}
+
+fn parse_puzzle_from<'a>(parser: &mut BaseParser<'a>) -> Result<PuzzleRef<'a>, &'static str>{
     if parser.parse_int()? != 3{
         return Err("not an opus magnum puzzle");
     }
-    let name = parser.parse_string()?;
+    let name = parser.parse_str()?;
     let creator_id = parser.parse_ulong()?;
     let permissions = Permissions::from_bits_retain(parser.parse_ulong()?);
     let reagents = parser.parse_list(|s| s.parse_molecule())?;
@@ -16,36 +169,83 @@ pub fn parse_puzzle(data: &[u8]) -> Result<Puzzle, &'static str>{
     let product_multiplier = parser.parse_int()?;
 
     let production_info = if parser.parse_bool()?{
-        let _shrink_left = parser.parse_bool()?; // visual, don't care
-        let _shrink_right = parser.parse_bool()?;
+        let shrink_left = parser.parse_bool()?;
+        let shrink_right = parser.parse_bool()?;
         let isolation = parser.parse_bool()?;
         let chambers = parser.parse_list(|p| Ok(Chamber{
             pos: p.parse_b_hex_index()?,
-            ty: ChamberType::from_name(&p.parse_string()?).ok_or("invalid chamber type")?
+            ty: ChamberType::from_name(p.parse_str()?).ok_or("invalid chamber type")?
         }))?;
         let conduits = parser.parse_list(|p| Ok(Conduit{
             pos_a: p.parse_b_hex_index()?,
             pos_b: p.parse_b_hex_index()?,
             hexes: p.parse_list(|p| p.parse_b_hex_index())?
         }))?;
-        // vial visuals also ignored
+        let vials = parser.parse_list(|p| Ok(Vial{
+            pos: p.parse_b_hex_index()?,
+            top: p.parse_bool()?,
+            count: p.parse_int()?
+        }))?;
         Some(ProductionInfo{
             isolation,
             chambers,
-            conduits
+            conduits,
+            shrink_left,
+            shrink_right,
+            vials
         })
     } else { None };
 
-    Ok(Puzzle{ name, creator_id, reagents, products, permissions, product_multiplier, production_info })
+    let puzzle = Puzzle{ name: String::new(), creator_id, reagents, products, permissions, product_multiplier, production_info };
+    Ok(PuzzleRef{ name, puzzle })
 }
 
 pub fn parse_solution(data: &[u8]) -> Result<Solution, &'static str>{
-    let mut parser = BaseParser::new(data);
+    parse_solution_ref(data).map(SolutionRef::into_owned)
+}
+
+/// A solution whose names borrow from the data it was parsed from, to avoid copying when loading many files at once.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SolutionRef<'a>{
+    pub name: &'a str,
+    pub puzzle_name: &'a str,
+    pub metrics: Option<Metrics>,
+    pub parts: Vec<Part>
+}
+
+impl SolutionRef<'_>{
+    pub fn into_owned(self) -> Solution{
+        Solution{ name: self.name.to_string(), puzzle_name: self.puzzle_name.to_string(), metrics: self.metrics, parts: self.parts }
+    }
+}
+
+/// The part of a solution file before its parts list.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SolutionHeader{
+    pub puzzle_name: String,
+    pub name: String,
+    pub metrics: Option<Metrics>
+}
+
+/// Parse only a solution's header, stopping before the parts list, e.g. for indexing many files.
+pub fn parse_solution_header(data: &[u8]) -> Result<SolutionHeader, &'static str>{
+    let (puzzle_name, name, metrics) = parse_solution_head(&mut BaseParser::new(data), BaseParser::parse_str)?;
+    Ok(SolutionHeader{ puzzle_name: puzzle_name.to_string(), name: name.to_string(), metrics })
+}
+
+/// Parse only a solution's puzzle name, name, and recorded metrics, for loaders that index many files.
+#[cfg(feature = "batch")]
+pub(crate) fn parse_solution_names(data: &[u8]) -> Result<(&str, &str, Option<Metrics>), &'static str>{
+    parse_solution_head(&mut BaseParser::new(data), BaseParser::parse_str)
+}
+
+/// Parse the header, reading each name with `parse_name`.
+fn parse_solution_head<'a, S>(parser: &mut BaseParser<'a>, mut parse_name: impl FnMut(&mut BaseParser<'a>) -> Result<S, &'static str>) -> Result<(S, S, Option<Metrics>), &'static str>{
     if parser.parse_int()? != 7 {
         return Err("not an opus magnum solution");
     }
-    let puzzle_name = parser.parse_string()?;
-    let name = parser.parse_string()?;
+    let puzzle_name = parse_name(parser)?;
+    let name = parse_name(parser)?;
     let metrics = match parser.parse_int()? {
         0 => None,
         4 => {
@@ -61,8 +261,23 @@ pub fn parse_solution(data: &[u8]) -> Result<Solution, &'static str>{
         },
         _ => return Err("invalid number of metrics")
     };
-    let parts: Vec<Part> = parser.parse_list(|p| {
-        let part_name = p.parse_string()?;
+    Ok((puzzle_name, name, metrics))
+}
+
+/// Parse a solution without copying its names out of `data`.
+pub fn parse_solution_ref(data: &[u8]) -> Result<SolutionRef<'_>, &'static str>{
+    parse_solution_from(&mut BaseParser::new(data))
+}
+
+fn parse_solution_from<'a>(parser: &mut BaseParser<'a>) -> Result<SolutionRef<'a>, &'static str>{
+    let (puzzle_name, name, metrics) = parse_solution_head(parser, BaseParser::parse_str)?;
+    let parts = parse_parts(parser)?;
+    Ok(SolutionRef{ name, puzzle_name, metrics, parts })
+}
+
+fn parse_parts(parser: &mut BaseParser) -> Result<Vec<Part>, &'static str>{
+    parser.parse_list(|p| {
+        let part_name = p.parse_str()?;
         if p.parse_byte()? != 1 { return Err("invalid solution part (1 != 1)") }
         let pos = p.parse_i_hex_index()?;
         let arm_length = p.parse_int()?;
@@ -85,7 +300,7 @@ pub fn parse_solution(data: &[u8]) -> Result<Solution, &'static str>{
         }else{ (0, Vec::new()) };
 
         Ok(Part{
-            ty: PartType::from_name(&part_name).ok_or("invalid part type")?,
+            ty: PartType::from_name(part_name).unwrap_or_else(|| PartType::Unknown(part_name.to_string())),
             pos,
             rotation,
             arm_number,
@@ -96,11 +311,46 @@ pub fn parse_solution(data: &[u8]) -> Result<Solution, &'static str>{
             conduit_hexes,
             instructions
         })
-    })?;
-    Ok(Solution{ name, puzzle_name, metrics, parts })
+    })
 }
 
-//TODO function to unparse a puzzle
+impl Puzzle{
+    /// Write this puzzle in the game's puzzle format.
+    pub fn unparse(&self) -> Vec<u8>{
+        let mut unparser = BaseUnparser::new();
+        unparser.unparse_int(3);
+        unparser.unparse_string(&self.name);
+        unparser.unparse_ulong(self.creator_id);
+        unparser.unparse_ulong(self.permissions.bits());
+        unparser.unparse_list(&self.reagents, |u, m| u.unparse_molecule(m));
+        unparser.unparse_list(&self.products, |u, m| u.unparse_molecule(m));
+        unparser.unparse_int(self.product_multiplier);
+        match &self.production_info{
+            None => unparser.unparse_bool(false),
+            Some(info) => {
+                unparser.unparse_bool(true);
+                unparser.unparse_bool(info.shrink_left);
+                unparser.unparse_bool(info.shrink_right);
+                unparser.unparse_bool(info.isolation);
+                unparser.unparse_list(&info.chambers, |u, chamber| {
+                    u.unparse_b_hex_index(chamber.pos);
+                    u.unparse_string(chamber.ty.to_name());
+                });
+                unparser.unparse_list(&info.conduits, |u, conduit| {
+                    u.unparse_b_hex_index(conduit.pos_a);
+                    u.unparse_b_hex_index(conduit.pos_b);
+                    u.unparse_list(&conduit.hexes, |u, hex| u.unparse_b_hex_index(*hex));
+                });
+                unparser.unparse_list(&info.vials, |u, vial| {
+                    u.unparse_b_hex_index(vial.pos);
+                    u.unparse_bool(vial.top);
+                    u.unparse_int(vial.count);
+                });
+            }
+        }
+        unparser.finish()
+    }
+}
 
 impl Solution{
     pub fn unparse(&self) -> Vec<u8>{
@@ -150,20 +400,139 @@ impl Solution{
     }
 }
 
+// traces
+
+const TRACE_MAGIC: &[u8; 4] = b"OMTR";
+const TRACE_VERSION: u8 = 1;
+
+/// Parse a trace written by [`Trace::unparse`].
+pub fn parse_trace(data: &[u8]) -> Result<Trace, &'static str>{
+    let mut parser = BaseParser::new(data);
+    for expected in TRACE_MAGIC{
+        if parser.parse_byte()? != *expected{
+            return Err("not an omsim-rs trace");
+        }
+    }
+    if parser.parse_byte()? != TRACE_VERSION{
+        return Err("unsupported trace version");
+    }
+    let completed = if parser.parse_bool()?{ Some(parser.parse_var_int()? as u64) }else{ None };
+    let error = if parser.parse_bool()?{ Some(parser.parse_str()?.to_string()) }else{ None };
+    let frames = parser.parse_var_list(|p| {
+        let cycle = p.parse_var_int()? as u64;
+        let arms = p.parse_var_list(|p| Ok(TracedArm{
+            part: p.parse_var_int()?,
+            pos: p.parse_z_hex_index()?,
+            rotation: HexRotation::from_signed(p.parse_byte()?),
+            length: p.parse_zigzag()?,
+            grabbing: p.parse_bool()?
+        }))?;
+        let molecules = p.parse_var_list(|p| {
+            let atoms = p.parse_var_list(|p| {
+                let atom = p.parse_atom()?;
+                Ok((p.parse_z_hex_index()?, atom))
+            })?;
+            let bonds = p.parse_var_list(|p| Ok(Bond{ ty: p.parse_bond_type()?, start: p.parse_z_hex_index()?, end: p.parse_z_hex_index()? }))?;
+            Ok(Molecule{ atoms: HashMap::from_iter(atoms), bonds: HashSet::from_iter(bonds) })
+        })?;
+        let events = p.parse_var_list(|p| {
+            let tag = p.parse_byte()?;
+            let cycle = p.parse_var_int()? as u64;
+            let value = p.parse_var_int()?;
+            Ok(match tag{
+                0 => SimEvent::ProductConsumed{ cycle, product: value },
+                1 => SimEvent::AreaGrew{ cycle, area: value },
+                2 => SimEvent::GlyphApplied{ cycle, part: value },
+                3 => SimEvent::AtomSpawned{ cycle, part: value, pos: p.parse_z_hex_index()? },
+                4 => SimEvent::AtomTransmuted{ cycle, part: value, pos: p.parse_z_hex_index()? },
+                _ => return Err("invalid trace event")
+            })
+        })?;
+        Ok(Frame{ cycle, arms, molecules, events })
+    })?;
+    if !parser.data.is_empty(){
+        return Err("trailing data after trace");
+    }
+    Ok(Trace{ frames, completed, error })
+}
+
+impl Trace{
+    /// Write this trace in a compact binary format, which can be read back with [`parse_trace`].
+    /// Coordinates and counts are variable-length, so a frame takes a few bytes per atom and arm.
+    pub fn unparse(&self) -> Vec<u8>{
+        let mut unparser = BaseUnparser::new();
+        unparser.data.extend_from_slice(TRACE_MAGIC);
+        unparser.unparse_byte(TRACE_VERSION);
+        unparser.unparse_bool(self.completed.is_some());
+        if let Some(cycles) = self.completed{
+            unparser.unparse_var_int(cycles as usize);
+        }
+        unparser.unparse_bool(self.error.is_some());
+        if let Some(error) = &self.error{
+            unparser.unparse_string(error);
+        }
+        unparser.unparse_var_list(&self.frames, |u, frame| {
+            u.unparse_var_int(frame.cycle as usize);
+            u.unparse_var_list(&frame.arms, |u, arm| {
+                u.unparse_var_int(arm.part);
+                u.unparse_z_hex_index(arm.pos);
+                u.unparse_byte(arm.rotation.turns());
+                u.unparse_zigzag(arm.length);
+                u.unparse_bool(arm.grabbing);
+            });
+            u.unparse_var_list(&frame.molecules, |u, molecule| {
+                // sort for deterministic output
+                let mut atoms: Vec<_> = molecule.atoms.iter().collect();
+                atoms.sort_by_key(|(pos, _)| (pos.q, pos.r));
+                u.unparse_var_list(&atoms, |u, (pos, atom)| {
+                    u.unparse_byte(atom.to_id());
+                    u.unparse_z_hex_index(**pos);
+                });
+                let mut bonds: Vec<_> = molecule.bonds.iter().collect();
+                bonds.sort_by_key(|b| (b.start.q, b.start.r, b.end.q, b.end.r));
+                u.unparse_var_list(&bonds, |u, bond| {
+                    u.unparse_bond_type(bond.ty);
+                    u.unparse_z_hex_index(bond.start);
+                    u.unparse_z_hex_index(bond.end);
+                });
+            });
+            u.unparse_var_list(&frame.events, |u, event| {
+                // provenance events also have a position
+                let (tag, cycle, value, pos) = match *event{
+                    SimEvent::ProductConsumed{ cycle, product } => (0, cycle, product, None),
+                    SimEvent::AreaGrew{ cycle, area } => (1, cycle, area, None),
+                    SimEvent::GlyphApplied{ cycle, part } => (2, cycle, part, None),
+                    SimEvent::AtomSpawned{ cycle, part, pos } => (3, cycle, part, Some(pos)),
+                    SimEvent::AtomTransmuted{ cycle, part, pos } => (4, cycle, part, Some(pos))
+                };
+                u.unparse_byte(tag);
+                u.unparse_var_int(cycle as usize);
+                u.unparse_var_int(value);
+                if let Some(pos) = pos{
+                    u.unparse_z_hex_index(pos);
+                }
+            });
+        });
+        unparser.finish()
+    }
+}
+
 // byte parsing
 
 struct BaseParser<'a>{
-    data: &'a [u8]
+    data: &'a [u8],
+    /// Whether to reject structurally invalid data that would otherwise be accepted.
+    strict: bool
 }
 
 impl<'a> BaseParser<'a>{
 
     fn new(data: &'a [u8]) -> Self{
-        Self{ data }
+        Self{ data, strict: false }
     }
 
     fn parse_byte(&mut self) -> Result<u8, &'static str>{
-        if self.data.len() == 0{
+        if self.data.is_empty(){
             Err("not enough bytes")
         }else{
             let result = self.data[0];
@@ -173,7 +542,7 @@ impl<'a> BaseParser<'a>{
     }
 
     fn parse_sbyte(&mut self) -> Result<i8, &'static str>{
-        if self.data.len() == 0{
+        if self.data.is_empty(){
             Err("not enough bytes")
         }else{
             let result = i8::from_be_bytes([self.data[0]]);
@@ -188,18 +557,19 @@ impl<'a> BaseParser<'a>{
 
     fn parse_int(&mut self) -> Result<i32, &'static str>{
         if self.data.len() >= 4{
-            let result = i32::from_le_bytes(array_ref![self.data, 0, 4].clone());
+            let result = i32::from_le_bytes(*array_ref![self.data, 0, 4]);
             self.data = &self.data[4..];
             Ok(result)
         }else{
-            println!("a {}", Backtrace::capture());
+            event!(debug, remaining = self.data.len(), "not enough bytes to read int");
             Err("not enough bytes to read int")
         }
     }
 
+    #[allow(dead_code)]
     fn parse_long(&mut self) -> Result<i64, &'static str>{
         if self.data.len() >= 8{
-            let result = i64::from_le_bytes(array_ref![self.data, 0, 8].clone());
+            let result = i64::from_le_bytes(*array_ref![self.data, 0, 8]);
             self.data = &self.data[8..];
             Ok(result)
         }else{
@@ -209,7 +579,7 @@ impl<'a> BaseParser<'a>{
 
     fn parse_ulong(&mut self) -> Result<u64, &'static str>{
         if self.data.len() >= 8{
-            let result = u64::from_le_bytes(array_ref![self.data, 0, 8].clone());
+            let result = u64::from_le_bytes(*array_ref![self.data, 0, 8]);
             self.data = &self.data[8..];
             Ok(result)
         }else{
@@ -219,7 +589,11 @@ impl<'a> BaseParser<'a>{
 
     fn parse_list<T>(&mut self, f: fn(&mut Self) -> Result<T, &'static str>) -> Result<Vec<T>, &'static str>{
         let amount = self.parse_int()?;
-        let mut result = Vec::with_capacity(amount as usize);
+        if amount < 0{
+            return Err("negative list length");
+        }
+        // every element takes at least a byte, so don't trust the count beyond the data
+        let mut result = Vec::with_capacity((amount as usize).min(self.data.len()));
         for _ in 0..amount{
             result.push(f(self)?)
         }
@@ -228,12 +602,15 @@ impl<'a> BaseParser<'a>{
 
     fn parse_var_int(&mut self) -> Result<usize, &'static str>{
         let mut value: usize = 0;
-        let mut shift: i32 = 0;
-        while self.data.len() > 0{
+        let mut shift: u32 = 0;
+        while !self.data.is_empty(){
+            if shift >= usize::BITS{
+                return Err("invalid varint");
+            }
             let next = self.parse_byte()?;
             value |= ((next & 0x7F) as usize) << shift;
             shift += 7;
-            if (next & 0x80) != 1{
+            if (next & 0x80) == 0{
                 break
             }
         }
@@ -241,32 +618,61 @@ impl<'a> BaseParser<'a>{
         Ok(value)
     }
 
-    fn parse_string(&mut self) -> Result<String, &'static str>{
-        let length = self.parse_var_int()?;
-        let result = String::from_utf8(Vec::from(&self.data[..length])).map_err(|_| "invalid utf8")?;
-        self.data = &self.data[length..];
+    fn parse_var_list<T>(&mut self, f: fn(&mut Self) -> Result<T, &'static str>) -> Result<Vec<T>, &'static str>{
+        let amount = self.parse_var_int()?;
+        // every element takes at least a byte, so don't trust the count beyond the data
+        let mut result = Vec::with_capacity(amount.min(self.data.len()));
+        for _ in 0..amount{
+            result.push(f(self)?)
+        }
         Ok(result)
     }
 
+    /// Parse a signed number stored as a zigzag-encoded variable-length integer, used in traces.
+    fn parse_zigzag(&mut self) -> Result<i32, &'static str>{
+        let n = self.parse_var_int()? as u32;
+        Ok((n >> 1) as i32 ^ -((n & 1) as i32))
+    }
+
+    /// Parse a hex index represented with zigzag-encoded variable-length offsets, used in traces.
+    fn parse_z_hex_index(&mut self) -> Result<HexIndex, &'static str>{
+        Ok(HexIndex::from_file_order(self.parse_zigzag()?, self.parse_zigzag()?))
+    }
+
+    fn parse_str(&mut self) -> Result<&'a str, &'static str>{
+        core::str::from_utf8(self.parse_str_bytes()?).map_err(|_| "invalid utf8")
+    }
+
+    /// A string's bytes, without checking they're UTF-8.
+    fn parse_str_bytes(&mut self) -> Result<&'a [u8], &'static str>{
+        let length = self.parse_var_int()?;
+        if self.data.len() < length{
+            return Err("not enough bytes to read string");
+        }
+        let (bytes, rest) = self.data.split_at(length);
+        self.data = rest;
+        Ok(bytes)
+    }
+
     /// Parse a hex index represented with signed byte offsets, used in puzzles.
     fn parse_b_hex_index(&mut self) -> Result<HexIndex, &'static str>{
-        Ok(HexIndex{ q: self.parse_sbyte()? as i32, r: self.parse_sbyte()? as i32 })
+        Ok(HexIndex::from_file_order(self.parse_sbyte()? as i32, self.parse_sbyte()? as i32))
     }
 
     /// Parse a hex index represented with signed 32-bit integer offsets, used in solutions.
     fn parse_i_hex_index(&mut self) -> Result<HexIndex, &'static str>{
-        Ok(HexIndex{ q: self.parse_int()?, r: self.parse_int()? })
+        Ok(HexIndex::from_file_order(self.parse_int()?, self.parse_int()?))
     }
 
     fn parse_atom(&mut self) -> Result<Atom, &'static str>{
-        Ok(Atom::from_id(self.parse_byte()?).ok_or("invalid atom type")?)
+        Atom::from_id(self.parse_byte()?).ok_or("invalid atom type")
     }
 
     fn parse_bond_type(&mut self) -> Result<BondType, &'static str>{
         let ty = self.parse_byte()?;
         if ty == 1 {
             Ok(BondType::Normal)
-        }else if (ty & 0b1111_000_1) != 0{
+        }else if (ty & 0b1111_0001) != 0{
             Err("invalid bond type")
         }else{
             Ok(BondType::Triplex{ red: (ty & 0b10) != 0, black: (ty & 0b100) != 0, yellow: (ty & 0b1000) != 0 })
@@ -278,16 +684,29 @@ impl<'a> BaseParser<'a>{
     }
 
     fn parse_molecule(&mut self) -> Result<Molecule, &'static str>{
-        Ok(Molecule{
-            atoms: HashMap::from_iter(self.parse_list(
-                |s| {
-                    let atom = s.parse_atom()?;
-                    let index = s.parse_b_hex_index()?;
-                    Ok((index, atom))
-                }
-            )?),
-            bonds: HashSet::from_iter(self.parse_list(|s| s.parse_bond())?.iter().cloned())
-        })
+        let atoms = self.parse_list(|s| {
+            let atom = s.parse_atom()?;
+            let index = s.parse_b_hex_index()?;
+            Ok((index, atom))
+        })?;
+        let bonds = self.parse_list(|s| s.parse_bond())?;
+        let molecule = Molecule{
+            atoms: HashMap::from_iter(atoms.iter().cloned()),
+            bonds: HashSet::from_iter(bonds.iter().cloned())
+        };
+        if self.strict{
+            // these are lost when collecting into maps, so validate can't see them
+            if molecule.atoms.len() != atoms.len(){
+                return Err("molecule has multiple atoms in the same position");
+            }
+            if molecule.bonds.len() != bonds.len(){
+                return Err("molecule has duplicate bonds");
+            }
+            if let Some(issue) = molecule.validate(false).first(){
+                return Err(issue.description());
+            }
+        }
+        Ok(molecule)
     }
 }
 
@@ -305,11 +724,23 @@ impl BaseUnparser{
         self.data.push(b);
     }
 
+    fn unparse_sbyte(&mut self, b: i8){
+        self.data.extend_from_slice(&b.to_le_bytes());
+    }
+
+    fn unparse_bool(&mut self, b: bool){
+        self.unparse_byte(b as u8);
+    }
+
     fn unparse_int(&mut self, n: i32){
         self.data.extend_from_slice(&n.to_le_bytes());
     }
 
-    fn unparse_list<T>(&mut self, l: &[T], f: fn(&mut Self, &T) -> ()){
+    fn unparse_ulong(&mut self, n: u64){
+        self.data.extend_from_slice(&n.to_le_bytes());
+    }
+
+    fn unparse_list<T>(&mut self, l: &[T], f: fn(&mut Self, &T)){
         self.unparse_int(l.len() as i32);
         for element in l{
             f(self, element);
@@ -328,14 +759,62 @@ impl BaseUnparser{
         }
     }
 
+    fn unparse_var_list<T>(&mut self, l: &[T], f: fn(&mut Self, &T)){
+        self.unparse_var_int(l.len());
+        for element in l{
+            f(self, element);
+        }
+    }
+
+    fn unparse_zigzag(&mut self, n: i32){
+        self.unparse_var_int(((n << 1) ^ (n >> 31)) as u32 as usize);
+    }
+
+    fn unparse_z_hex_index(&mut self, hex: HexIndex){
+        let (first, second) = hex.to_file_order();
+        self.unparse_zigzag(first);
+        self.unparse_zigzag(second);
+    }
+
     fn unparse_string(&mut self, s: &str){
         self.unparse_var_int(s.len());
         self.data.extend_from_slice(s.as_bytes());
     }
 
-    fn unparse_i_hex_index(&mut self, HexIndex{ q, r }: HexIndex){
-        self.unparse_int(q);
-        self.unparse_int(r);
+    fn unparse_i_hex_index(&mut self, hex: HexIndex){
+        let (first, second) = hex.to_file_order();
+        self.unparse_int(first);
+        self.unparse_int(second);
+    }
+
+    fn unparse_b_hex_index(&mut self, hex: HexIndex){
+        let (first, second) = hex.to_file_order();
+        self.unparse_sbyte(first as i8);
+        self.unparse_sbyte(second as i8);
+    }
+
+    fn unparse_bond_type(&mut self, ty: BondType){
+        match ty{
+            BondType::Normal => self.unparse_byte(1),
+            BondType::Triplex{ red, black, yellow } => self.unparse_byte(((red as u8) << 1) | ((black as u8) << 2) | ((yellow as u8) << 3))
+        }
+    }
+
+    fn unparse_molecule(&mut self, molecule: &Molecule){
+        // sort for deterministic output
+        let mut atoms: Vec<_> = molecule.atoms.iter().collect();
+        atoms.sort_by_key(|(pos, _)| (pos.q, pos.r));
+        self.unparse_list(&atoms, |u, (pos, atom)| {
+            u.unparse_byte(atom.to_id());
+            u.unparse_b_hex_index(**pos);
+        });
+        let mut bonds: Vec<_> = molecule.bonds.iter().collect();
+        bonds.sort_by_key(|b| (b.start.q, b.start.r, b.end.q, b.end.r));
+        self.unparse_list(&bonds, |u, bond| {
+            u.unparse_bond_type(bond.ty);
+            u.unparse_b_hex_index(bond.start);
+            u.unparse_b_hex_index(bond.end);
+        });
     }
 
     fn finish(self) -> Vec<u8>{
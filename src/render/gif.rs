@@ -0,0 +1,115 @@
+//! Animated GIFs of a solution running, one frame per cycle.
+
+use std::io::Write;
+use gif::{Encoder, EncodingError, Frame, Repeat};
+use super::{Bounds, Scene, RenderOptions};
+use super::raster::Canvas;
+use crate::analysis::throughput::find_steady_state;
+use crate::compat::HashMap;
+use crate::sim::Sim;
+use crate::sim::error::SimError;
+
+/// The scene at each cycle, from the current one until the solution completes, fails, or runs for
+/// [`max_cycles`](RenderOptions::max_cycles). `sim` itself isn't changed.
+///
+/// With [`steady_state`](RenderOptions::steady_state), these are the [`steady_frames`] instead, unless the solution never repeats.
+pub fn frames(sim: &Sim, options: &RenderOptions) -> Vec<Scene>{
+    if options.steady_state{
+        if let Ok(scenes) = steady_frames(sim, options){
+            return scenes;
+        }
+    }
+    let mut sim = sim.clone();
+    let mut scenes = vec![Scene::of_sim(&sim, &options.theme)];
+    for cycle in 1..=options.max_cycles{
+        if sim.completed.is_some() || sim.step().is_err(){
+            break;
+        }
+        if cycle.is_multiple_of(options.frame_step.max(1)){
+            scenes.push(Scene::of_sim(&sim, &options.theme));
+        }
+    }
+    scenes
+}
+
+/// The scene at each cycle of one repetition of the solution once it has settled (see [`find_steady_state`]), so that
+/// an animation of them loops without a jump. Fails if the solution fails or doesn't repeat within its sim's cycle limit.
+pub fn steady_frames(sim: &Sim, options: &RenderOptions) -> Result<Vec<Scene>, SimError>{
+    let steady = find_steady_state(sim)?;
+    let mut sim = steady.start;
+    let mut scenes = Vec::new();
+    for cycle in 0..steady.period{
+        if cycle.is_multiple_of(options.frame_step.max(1)){
+            scenes.push(Scene::of_sim(&sim, &options.theme));
+        }
+        sim.step()?;
+    }
+    Ok(scenes)
+}
+
+/// The number of frames each thread draws before they're written, which bounds how many are in memory at once.
+const FRAMES_PER_THREAD: usize = 4;
+
+/// Write an animation of the solution running to `out`, looping forever.
+///
+/// Frames are drawn on [`threads`](RenderOptions::threads) threads a few at a time and written as soon as they're
+/// drawn, so long animations don't need every frame in memory.
+pub fn render_gif(sim: &Sim, options: &RenderOptions, out: impl Write) -> Result<(), EncodingError>{
+    let scenes = frames(sim, options);
+    // every frame covers the area of all of them, so nothing jumps around
+    let bounds = scenes.iter().filter_map(Scene::bounds).reduce(Bounds::union).unwrap_or(Bounds{ min_x: 0.0, min_y: 0.0, max_x: 0.0, max_y: 0.0 });
+    let (_, area) = Scene::default().framed(bounds, options);
+    let size = |length: f32| u16::try_from(libm::ceilf(length * options.scale) as usize).unwrap_or(u16::MAX);
+    let (width, height) = (size(area.width()), size(area.height()));
+    let mut encoder = Encoder::new(out, width, height, &[])?;
+    encoder.set_repeat(Repeat::Infinite)?;
+    let delay = u16::try_from(u64::from(options.frame_delay) * options.frame_step.max(1)).unwrap_or(u16::MAX);
+    let threads = options.threads.max(1);
+    for chunk in scenes.chunks(threads * FRAMES_PER_THREAD){
+        let frames = in_parallel(chunk, threads, |scene| {
+            let (framed, area) = scene.framed(bounds, options);
+            let canvas = Canvas::draw(&framed, area, options.scale);
+            let mut frame = palette_frame(&canvas, width, height).unwrap_or_else(|| Frame::from_rgb_speed(width, height, &canvas.pixels, 10));
+            frame.delay = delay;
+            frame
+        });
+        for frame in &frames{
+            encoder.write_frame(frame)?;
+        }
+    }
+    Ok(())
+}
+
+/// Apply `f` to each item on up to `threads` threads, keeping their order.
+fn in_parallel<T: Sync, R: Send>(items: &[T], threads: usize, f: impl Fn(&T) -> R + Sync) -> Vec<R>{
+    if threads <= 1 || items.len() <= 1{
+        return items.iter().map(f).collect();
+    }
+    let f = &f;
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = items.chunks(items.len().div_ceil(threads)).map(|chunk| scope.spawn(move || {
+            chunk.iter().map(f).collect::<Vec<_>>()
+        })).collect();
+        handles.into_iter().flat_map(|handle| handle.join().expect("render thread panicked")).collect()
+    })
+}
+
+/// The canvas as a frame with an exact palette, if it has few enough colours for one.
+fn palette_frame(canvas: &Canvas, width: u16, height: u16) -> Option<Frame<'static>>{
+    let mut palette = Vec::new();
+    let mut indices = HashMap::new();
+    let mut pixels = Vec::with_capacity(canvas.width * canvas.height);
+    for rgb in canvas.pixels.chunks_exact(3){
+        let idx = match indices.get(rgb){
+            Some(idx) => *idx,
+            None => {
+                let idx = u8::try_from(indices.len()).ok()?;
+                indices.insert(rgb, idx);
+                palette.extend_from_slice(rgb);
+                idx
+            }
+        };
+        pixels.push(idx);
+    }
+    Some(Frame::from_palette_pixels(width, height, pixels, palette, None))
+}
@@ -0,0 +1,391 @@
+//! Drawing boards as pictures: SVG, rasterized images, and with the `gif` feature, animated GIFs like the game's own.
+//!
+//! Drawing happens in two steps. A board becomes a [`Scene`] of simple shapes in board pixels, with the Y axis pointing
+//! down, which is then written out as SVG or [rasterized](raster::Canvas).
+
+pub mod raster;
+pub mod theme;
+#[cfg(feature = "gif")]
+pub mod gif;
+
+use crate::data::{Atom, BondType, HexIndex, Metrics, Molecule, Puzzle, Solution, HEX_HEIGHT, HEX_WIDTH};
+use crate::sim::{Sim, SimPartType};
+use crate::sim::glyphs::glyph_offsets;
+use crate::compat::prelude::*;
+use theme::RenderTheme;
+
+/// Radius of an atom, in board pixels.
+const ATOM_RADIUS: f32 = 29.0;
+/// Height of the metadata card below the board, in board pixels.
+const CARD_HEIGHT: f32 = 110.0;
+/// Height of the text on the metadata card, in board pixels.
+const CARD_TEXT_SIZE: f32 = 28.0;
+
+/// An RGB colour.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Color(pub u8, pub u8, pub u8);
+
+impl Color{
+    /// This colour as an SVG hex code like `#28241f`.
+    pub fn to_hex(self) -> String{
+        format!("#{:02x}{:02x}{:02x}", self.0, self.1, self.2)
+    }
+
+    /// Black or white, whichever reads better on this colour.
+    pub fn contrasting(self) -> Color{
+        let luma = 299 * self.0 as u32 + 587 * self.1 as u32 + 114 * self.2 as u32;
+        if luma > 128_000{ Color(0, 0, 0) }else{ Color(255, 255, 255) }
+    }
+}
+
+/// A shape in a [`Scene`], in board pixels.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Shape{
+    Circle{ center: (f32, f32), radius: f32, fill: Color },
+    Line{ from: (f32, f32), to: (f32, f32), width: f32, color: Color },
+    Polygon{ points: Vec<(f32, f32)>, fill: Color },
+    /// Text centred on `pos`, `size` pixels high.
+    Text{ pos: (f32, f32), size: f32, color: Color, text: String }
+}
+
+/// The area a scene covers, in board pixels.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Bounds{
+    pub min_x: f32,
+    pub min_y: f32,
+    pub max_x: f32,
+    pub max_y: f32
+}
+
+impl Bounds{
+    pub fn width(&self) -> f32{
+        self.max_x - self.min_x
+    }
+
+    pub fn height(&self) -> f32{
+        self.max_y - self.min_y
+    }
+
+    /// The smallest bounds covering both.
+    pub fn union(self, other: Bounds) -> Bounds{
+        Bounds{ min_x: self.min_x.min(other.min_x), min_y: self.min_y.min(other.min_y), max_x: self.max_x.max(other.max_x), max_y: self.max_y.max(other.max_y) }
+    }
+}
+
+/// Shapes to draw, back to front.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Scene{
+    pub shapes: Vec<Shape>,
+    /// The cycle the board is at, for the [counter](RenderOptions::counter).
+    pub cycle: Option<u64>
+}
+
+/// The puzzle, solution and metrics shown below an animation, like the game shows in its GIFs.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Card{
+    pub puzzle: String,
+    pub solution: String,
+    pub metrics: Option<Metrics>
+}
+
+/// How to draw boards.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RenderOptions{
+    /// Output pixels per board pixel.
+    pub scale: f32,
+    /// Space to leave around the board, in board pixels.
+    pub margin: f32,
+    /// If set, a card with these details is drawn below the board.
+    pub card: Option<Card>,
+    /// The most cycles an animation shows, if the solution doesn't complete sooner.
+    pub max_cycles: u64,
+    /// How long each frame of an animation is shown, in hundredths of a second.
+    pub frame_delay: u16,
+    /// Whether to label every hex with its axial coordinates, as `q,r`.
+    pub coordinates: bool,
+    /// Whether to show the cycle each board is at in the top left corner. Boards are only drawn between cycles, never
+    /// halfway through one.
+    pub counter: bool,
+    /// Whether animations skip the start of the solution and show exactly one repetition once it has settled, so they
+    /// loop seamlessly. Animations show the start as usual if the solution never repeats.
+    pub steady_state: bool,
+    /// Animations show every this many cycles, with each frame shown that many times as long, for quicker previews.
+    pub frame_step: u64,
+    /// How many threads to draw animation frames on.
+    pub threads: usize,
+    pub theme: RenderTheme
+}
+
+impl Default for RenderOptions{
+    fn default() -> Self{
+        RenderOptions{ scale: 0.5, margin: 40.0, card: None, max_cycles: 1000, frame_delay: 10, coordinates: false, counter: false, steady_state: false, frame_step: 1, threads: 1, theme: RenderTheme::default() }
+    }
+}
+
+/// The centre of a hex, in board pixels with the Y axis pointing down.
+pub fn hex_center(pos: HexIndex) -> (f32, f32){
+    let (x, y) = pos.to_pixel();
+    (x, -y)
+}
+
+/// Every hex overlapping `area`, and a few around it.
+fn hexes_in(area: Bounds) -> Vec<HexIndex>{
+    // rows are HEX_HEIGHT apart with the board's Y axis flipped, and each row up shifts half a hex right
+    let rows = libm::floorf(-area.max_y / HEX_HEIGHT) as i32 - 1..=libm::ceilf(-area.min_y / HEX_HEIGHT) as i32 + 1;
+    rows.flat_map(|r| {
+        let shift = r as f32 / 2.0;
+        (libm::floorf(area.min_x / HEX_WIDTH - shift) as i32 - 1..=libm::ceilf(area.max_x / HEX_WIDTH - shift) as i32 + 1).map(move |q| HexIndex{ q, r })
+    }).collect()
+}
+
+/// The corners of a hex, in board pixels.
+fn hex_corners(pos: HexIndex) -> Vec<(f32, f32)>{
+    let (x, y) = hex_center(pos);
+    // hexes are pointy-topped, and just touch their neighbours
+    let (half_width, radius) = (HEX_WIDTH / 2.0, HEX_HEIGHT * 2.0 / 3.0);
+    vec![(x, y - radius), (x + half_width, y - radius / 2.0), (x + half_width, y + radius / 2.0),
+        (x, y + radius), (x - half_width, y + radius / 2.0), (x - half_width, y - radius / 2.0)]
+}
+
+impl Scene{
+    /// The board as it is now: parts, then molecules, then arms on top.
+    pub fn of_sim(sim: &Sim, theme: &RenderTheme) -> Scene{
+        let mut scene = Scene::default();
+        scene.parts(sim, theme);
+        let mut molecules: Vec<Molecule> = sim.molecules.iter().map(|molecule| molecule.layout.translated(molecule.pos)).collect();
+        molecules.sort_by_key(|molecule| molecule.atoms.keys().map(|pos| (pos.q, pos.r)).min());
+        for molecule in &molecules{
+            scene.molecule(molecule, theme, false);
+        }
+        scene.arms(sim, theme);
+        scene.cycle = Some(sim.cycle);
+        scene
+    }
+
+    /// Just the parts, with ghosts of the molecules inputs and outputs take, and no molecules on the board:
+    /// a blueprint of the solution's layout.
+    pub fn blueprint(sim: &Sim, theme: &RenderTheme) -> Scene{
+        let mut scene = Scene::default();
+        scene.parts(sim, theme);
+        scene.arms(sim, theme);
+        scene
+    }
+
+    /// A single molecule, e.g. a reagent or product, at its own positions.
+    pub fn of_molecule(molecule: &Molecule, theme: &RenderTheme) -> Scene{
+        let mut scene = Scene::default();
+        scene.molecule(molecule, theme, false);
+        scene
+    }
+
+    /// Everything but arms, which go over molecules.
+    fn parts(&mut self, sim: &Sim, theme: &RenderTheme){
+        for part in &sim.parts{
+            match &part.ty{
+                SimPartType::Input(input) => {
+                    self.hexes(&part.footprint(), theme.input);
+                    self.molecule(&input.spawned_molecule().placed(part.pos, part.rotation), theme, true);
+                }
+                SimPartType::Output(output) => {
+                    self.hexes(&part.footprint(), theme.output);
+                    self.molecule(&output.product.placed(part.pos, part.rotation), theme, true);
+                }
+                SimPartType::Track(track) => {
+                    for pair in track.hexes.windows(2){
+                        self.shapes.push(Shape::Line{ from: hex_center(pair[0]), to: hex_center(pair[1]), width: 14.0, color: theme.track });
+                    }
+                    if track.looping{
+                        self.shapes.push(Shape::Line{ from: hex_center(track.hexes[track.hexes.len() - 1]), to: hex_center(track.hexes[0]), width: 14.0, color: theme.track });
+                    }
+                }
+                SimPartType::Conduit(conduit) => self.hexes(&conduit.hexes, theme.conduit),
+                ty if glyph_offsets(ty).is_some() || matches!(ty, SimPartType::Custom(_)) => self.hexes(&part.footprint(), theme.glyph),
+                _ => {}
+            }
+        }
+    }
+
+    fn arms(&mut self, sim: &Sim, theme: &RenderTheme){
+        for (part, arm) in sim.arms(){
+            let base = hex_center(part.pos);
+            let grippers = arm.grippers(part.pos, part.rotation);
+            for gripper in &grippers{
+                self.shapes.push(Shape::Line{ from: base, to: hex_center(*gripper), width: 10.0, color: theme.arm });
+            }
+            self.shapes.push(Shape::Circle{ center: base, radius: 20.0, fill: theme.arm });
+            for (gripper, holding) in grippers.iter().zip(&arm.holding){
+                let fill = if arm.grabbing && *holding{ theme.gripper_closed }else{ theme.gripper_open };
+                self.shapes.push(Shape::Circle{ center: hex_center(*gripper), radius: 10.0, fill });
+            }
+        }
+    }
+
+    fn hexes(&mut self, hexes: &[HexIndex], fill: Color){
+        for hex in hexes{
+            self.shapes.push(Shape::Polygon{ points: hex_corners(*hex), fill });
+        }
+    }
+
+    /// A molecule in board positions: its bonds, then its atoms, labelled if the theme says so.
+    fn molecule(&mut self, molecule: &Molecule, theme: &RenderTheme, ghost: bool){
+        let shade = |color| if ghost{ theme.faded(color) }else{ color };
+        let mut bonds: Vec<_> = molecule.bonds.iter().collect();
+        bonds.sort_by_key(|bond| (bond.start.q, bond.start.r, bond.end.q, bond.end.r));
+        for bond in bonds{
+            let (from, to) = (hex_center(bond.start), hex_center(bond.end));
+            match bond.ty{
+                BondType::Normal => self.shapes.push(Shape::Line{ from, to, width: theme.bonds.width, color: shade(theme.bonds.color) }),
+                BondType::Triplex{ red, black, yellow } => {
+                    // three thin lines side by side, one for each colour the bond has
+                    let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+                    let length = libm::sqrtf(dx * dx + dy * dy).max(1.0);
+                    let spacing = theme.bonds.width * 0.8;
+                    let (nx, ny) = (-dy / length * spacing, dx / length * spacing);
+                    let [red_color, black_color, yellow_color] = theme.bonds.triplex;
+                    for (present, color, side) in [(red, red_color, -1.0), (black, black_color, 0.0), (yellow, yellow_color, 1.0)]{
+                        if present{
+                            let offset = (nx * side, ny * side);
+                            self.shapes.push(Shape::Line{ from: (from.0 + offset.0, from.1 + offset.1), to: (to.0 + offset.0, to.1 + offset.1), width: theme.bonds.width / 2.0, color: shade(color) });
+                        }
+                    }
+                }
+            }
+        }
+        let mut atoms: Vec<(&HexIndex, &Atom)> = molecule.atoms.iter().collect();
+        atoms.sort_by_key(|(pos, _)| (pos.q, pos.r));
+        for (pos, atom) in atoms{
+            let fill = shade(theme.atom_color(*atom));
+            self.shapes.push(Shape::Circle{ center: hex_center(*pos), radius: ATOM_RADIUS, fill });
+            if theme.labels{
+                self.shapes.push(Shape::Text{ pos: hex_center(*pos), size: 22.0, color: fill.contrasting(), text: atom.symbol().to_string() });
+            }
+        }
+    }
+
+    /// The area the shapes cover, or `None` if there aren't any.
+    pub fn bounds(&self) -> Option<Bounds>{
+        let mut points = self.shapes.iter().flat_map(|shape| match shape{
+            Shape::Circle{ center: (x, y), radius, .. } => vec![(x - radius, y - radius), (x + radius, y + radius)],
+            Shape::Line{ from, to, width, .. } => vec![(from.0 - width, from.1 - width), (to.0 + width, to.1 + width), (from.0 + width, from.1 + width), (to.0 - width, to.1 - width)],
+            Shape::Polygon{ points, .. } => points.clone(),
+            Shape::Text{ pos, size, .. } => vec![(pos.0 - size, pos.1 - size), (pos.0 + size, pos.1 + size)]
+        });
+        let (x, y) = points.next()?;
+        Some(points.fold(Bounds{ min_x: x, min_y: y, max_x: x, max_y: y }, |bounds, (x, y)| bounds.union(Bounds{ min_x: x, min_y: y, max_x: x, max_y: y })))
+    }
+
+    /// This scene framed for output: with a margin around `bounds`, a background, and the card if there is one.
+    /// Returns the framed scene and the area to draw.
+    pub fn framed(&self, bounds: Bounds, options: &RenderOptions) -> (Scene, Bounds){
+        let mut area = Bounds{ min_x: bounds.min_x - options.margin, min_y: bounds.min_y - options.margin, max_x: bounds.max_x + options.margin, max_y: bounds.max_y + options.margin };
+        let lines: Vec<String> = options.card.iter().flat_map(|card| {
+            let metrics = card.metrics.map(|metrics| format!("{} cycles, {} cost, {} area, {} instructions", metrics.cycles, metrics.cost, metrics.area, metrics.instructions));
+            [Some(format!("{} - {}", card.puzzle, card.solution)), metrics].into_iter().flatten()
+        }).collect();
+        if options.card.is_some(){
+            area.max_y += CARD_HEIGHT;
+            // widen small boards so the card's text fits
+            let text_width = lines.iter().map(|line| text_width(line, CARD_TEXT_SIZE)).fold(0.0, f32::max) + 2.0 * options.margin;
+            let extra = (text_width - area.width()).max(0.0) / 2.0;
+            area.min_x -= extra;
+            area.max_x += extra;
+        }
+        let board = Bounds{ max_y: area.max_y - if options.card.is_some(){ CARD_HEIGHT }else{ 0.0 }, ..area };
+        let rect = |area: Bounds| vec![(area.min_x, area.min_y), (area.max_x, area.min_y), (area.max_x, area.max_y), (area.min_x, area.max_y)];
+        let theme = &options.theme;
+        let mut framed = Scene{ shapes: vec![Shape::Polygon{ points: rect(area), fill: theme.background }], cycle: self.cycle };
+        if let Some(color) = theme.grid{
+            framed.grid(board, color);
+        }
+        framed.shapes.extend(self.shapes.iter().cloned());
+        if options.coordinates{
+            // below where atoms go, so they stay readable on a busy board
+            for hex in hexes_in(board){
+                let (x, y) = hex_center(hex);
+                framed.shapes.push(Shape::Text{ pos: (x, y + HEX_HEIGHT / 2.0), size: 12.0, color: theme.card_text, text: format!("{},{}", hex.q, hex.r) });
+            }
+        }
+        if let Some(cycle) = self.cycle.filter(|_| options.counter){
+            let text = format!("cycle {cycle}");
+            let size = CARD_TEXT_SIZE;
+            let backing = Bounds{ min_x: board.min_x, min_y: board.min_y, max_x: board.min_x + text_width(&text, size) + size, max_y: board.min_y + size * 2.0 };
+            framed.shapes.push(Shape::Polygon{ points: rect(backing), fill: theme.card });
+            framed.shapes.push(Shape::Text{ pos: (backing.min_x + backing.width() / 2.0, backing.min_y + size), size, color: theme.card_text, text });
+        }
+        if options.card.is_some(){
+            let strip = Bounds{ min_y: board.max_y, ..area };
+            framed.shapes.push(Shape::Polygon{ points: rect(strip), fill: theme.card });
+            let step = CARD_HEIGHT / (lines.len() + 1) as f32;
+            for (idx, line) in lines.into_iter().enumerate(){
+                framed.shapes.push(Shape::Text{ pos: (area.min_x + area.width() / 2.0, strip.min_y + step * (idx + 1) as f32), size: CARD_TEXT_SIZE, color: theme.card_text, text: line });
+            }
+        }
+        (framed, area)
+    }
+
+    /// Outlines of every hex overlapping `area`.
+    fn grid(&mut self, area: Bounds, color: Color){
+        for hex in hexes_in(area){
+            let corners = hex_corners(hex);
+            for (idx, corner) in corners.iter().enumerate(){
+                self.shapes.push(Shape::Line{ from: *corner, to: corners[(idx + 1) % 6], width: 2.0, color });
+            }
+        }
+    }
+
+    /// This scene as an SVG document showing `area`, scaled by `scale`.
+    pub fn to_svg(&self, area: Bounds, scale: f32) -> String{
+        let mut svg = format!("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"{} {} {} {}\">\n",
+            libm::ceilf(area.width() * scale), libm::ceilf(area.height() * scale), area.min_x, area.min_y, area.width(), area.height());
+        for shape in &self.shapes{
+            match shape{
+                Shape::Circle{ center: (x, y), radius, fill } => svg.push_str(&format!("<circle cx=\"{x}\" cy=\"{y}\" r=\"{radius}\" fill=\"{}\"/>\n", fill.to_hex())),
+                Shape::Line{ from, to, width, color } => svg.push_str(&format!("<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"{}\" stroke-width=\"{width}\" stroke-linecap=\"round\"/>\n",
+                    from.0, from.1, to.0, to.1, color.to_hex())),
+                Shape::Polygon{ points, fill } => {
+                    let points: Vec<String> = points.iter().map(|(x, y)| format!("{x},{y}")).collect();
+                    svg.push_str(&format!("<polygon points=\"{}\" fill=\"{}\"/>\n", points.join(" "), fill.to_hex()));
+                }
+                Shape::Text{ pos: (x, y), size, color, text } => svg.push_str(&format!(
+                    "<text x=\"{x}\" y=\"{y}\" font-size=\"{size}\" font-family=\"sans-serif\" text-anchor=\"middle\" dominant-baseline=\"central\" fill=\"{}\">{}</text>\n",
+                    color.to_hex(), xml_escape(text)))
+            }
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+}
+
+/// The board as it is now, as an SVG document.
+pub fn render_svg(sim: &Sim, options: &RenderOptions) -> String{
+    let scene = Scene::of_sim(sim, &options.theme);
+    let bounds = scene.bounds().unwrap_or(Bounds{ min_x: 0.0, min_y: 0.0, max_x: 0.0, max_y: 0.0 });
+    let (framed, area) = scene.framed(bounds, options);
+    framed.to_svg(area, options.scale)
+}
+
+/// A blueprint of a solution's layout as an SVG document, without simulating it, e.g. for thumbnails in a gallery.
+pub fn render_blueprint(puzzle: &Puzzle, solution: &Solution, options: &RenderOptions) -> Result<String, &'static str>{
+    let sim = Sim::create(puzzle, solution)?;
+    let scene = Scene::blueprint(&sim, &options.theme);
+    let bounds = scene.bounds().unwrap_or(Bounds{ min_x: 0.0, min_y: 0.0, max_x: 0.0, max_y: 0.0 });
+    let (framed, area) = scene.framed(bounds, options);
+    Ok(framed.to_svg(area, options.scale))
+}
+
+/// A molecule as an SVG document, for showing what a puzzle takes and makes, e.g. on a puzzle browser.
+pub fn render_molecule(molecule: &Molecule, options: &RenderOptions) -> String{
+    let scene = Scene::of_molecule(molecule, &options.theme);
+    let bounds = scene.bounds().unwrap_or(Bounds{ min_x: 0.0, min_y: 0.0, max_x: 0.0, max_y: 0.0 });
+    let (framed, area) = scene.framed(bounds, options);
+    framed.to_svg(area, options.scale)
+}
+
+/// How wide text is drawn, in the same units as its size. Characters are 5 pixels wide with a gap, out of 8 high.
+pub fn text_width(text: &str, size: f32) -> f32{
+    text.chars().count() as f32 * size * 6.0 / 8.0
+}
+
+fn xml_escape(s: &str) -> String{
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
@@ -0,0 +1,164 @@
+//! Drawing scenes into pixels, with no dependencies.
+
+use super::{Bounds, Color, Scene, Shape};
+use crate::compat::prelude::*;
+
+/// An RGB image.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Canvas{
+    pub width: usize,
+    pub height: usize,
+    /// Red, green and blue bytes for each pixel, row by row from the top left.
+    pub pixels: Vec<u8>
+}
+
+impl Canvas{
+    /// A canvas filled with one colour.
+    pub fn new(width: usize, height: usize, background: Color) -> Canvas{
+        Canvas{ width, height, pixels: [background.0, background.1, background.2].repeat(width * height) }
+    }
+
+    /// The colour of a pixel, or `None` outside the canvas.
+    pub fn get(&self, x: usize, y: usize) -> Option<Color>{
+        if x >= self.width || y >= self.height{
+            return None;
+        }
+        let idx = (y * self.width + x) * 3;
+        Some(Color(self.pixels[idx], self.pixels[idx + 1], self.pixels[idx + 2]))
+    }
+
+    fn set(&mut self, x: i64, y: i64, color: Color){
+        if x < 0 || y < 0 || x as usize >= self.width || y as usize >= self.height{
+            return;
+        }
+        let idx = (y as usize * self.width + x as usize) * 3;
+        self.pixels[idx..idx + 3].copy_from_slice(&[color.0, color.1, color.2]);
+    }
+
+    /// Draw `scene`'s `area` onto a new canvas, `scale` pixels per board pixel.
+    pub fn draw(scene: &Scene, area: Bounds, scale: f32) -> Canvas{
+        let mut canvas = Canvas::new(libm::ceilf(area.width() * scale) as usize, libm::ceilf(area.height() * scale) as usize, Color(0, 0, 0));
+        let to_canvas = |(x, y): (f32, f32)| ((x - area.min_x) * scale, (y - area.min_y) * scale);
+        for shape in &scene.shapes{
+            match shape{
+                Shape::Circle{ center, radius, fill } => canvas.fill_circle(to_canvas(*center), radius * scale, *fill),
+                Shape::Line{ from, to, width, color } => canvas.line(to_canvas(*from), to_canvas(*to), width * scale, *color),
+                Shape::Polygon{ points, fill } => canvas.fill_polygon(&points.iter().copied().map(to_canvas).collect::<Vec<_>>(), *fill),
+                Shape::Text{ pos, size, color, text } => canvas.text(to_canvas(*pos), size * scale, *color, text)
+            }
+        }
+        canvas
+    }
+
+    /// Every pixel whose centre is within this box, clipped to the canvas.
+    fn pixels_in(&self, min: (f32, f32), max: (f32, f32)) -> impl Iterator<Item = (i64, i64)>{
+        let clamp = |v: f32, limit: usize| (v.max(0.0) as i64).min(limit as i64);
+        let (x0, x1) = (clamp(libm::floorf(min.0), self.width), clamp(libm::ceilf(max.0), self.width));
+        let (y0, y1) = (clamp(libm::floorf(min.1), self.height), clamp(libm::ceilf(max.1), self.height));
+        (y0..y1).flat_map(move |y| (x0..x1).map(move |x| (x, y)))
+    }
+
+    fn fill_circle(&mut self, (cx, cy): (f32, f32), radius: f32, color: Color){
+        for (x, y) in self.pixels_in((cx - radius, cy - radius), (cx + radius, cy + radius)).collect::<Vec<_>>(){
+            let (dx, dy) = (x as f32 + 0.5 - cx, y as f32 + 0.5 - cy);
+            if dx * dx + dy * dy <= radius * radius{
+                self.set(x, y, color);
+            }
+        }
+    }
+
+    /// A line with rounded ends.
+    fn line(&mut self, from: (f32, f32), to: (f32, f32), width: f32, color: Color){
+        let half = (width / 2.0).max(0.5);
+        let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+        let length_squared = (dx * dx + dy * dy).max(f32::EPSILON);
+        let min = (from.0.min(to.0) - half, from.1.min(to.1) - half);
+        let max = (from.0.max(to.0) + half, from.1.max(to.1) + half);
+        for (x, y) in self.pixels_in(min, max).collect::<Vec<_>>(){
+            let (px, py) = (x as f32 + 0.5 - from.0, y as f32 + 0.5 - from.1);
+            let t = ((px * dx + py * dy) / length_squared).clamp(0.0, 1.0);
+            let (ex, ey) = (px - t * dx, py - t * dy);
+            if ex * ex + ey * ey <= half * half{
+                self.set(x, y, color);
+            }
+        }
+    }
+
+    fn fill_polygon(&mut self, points: &[(f32, f32)], color: Color){
+        let Some(first) = points.first() else { return };
+        let min = points.iter().fold(*first, |(x, y), p| (x.min(p.0), y.min(p.1)));
+        let max = points.iter().fold(*first, |(x, y), p| (x.max(p.0), y.max(p.1)));
+        for (x, y) in self.pixels_in(min, max).collect::<Vec<_>>(){
+            let (px, py) = (x as f32 + 0.5, y as f32 + 0.5);
+            // even-odd rule, counting edges crossed by a ray to the right
+            let mut inside = false;
+            for (idx, a) in points.iter().enumerate(){
+                let b = points[(idx + 1) % points.len()];
+                if (a.1 > py) != (b.1 > py) && px < a.0 + (py - a.1) / (b.1 - a.1) * (b.0 - a.0){
+                    inside = !inside;
+                }
+            }
+            if inside{
+                self.set(x, y, color);
+            }
+        }
+    }
+
+    /// Text centred on `pos`, in a blocky 5x8 font scaled to `size` pixels high.
+    fn text(&mut self, pos: (f32, f32), size: f32, color: Color, text: &str){
+        let dot = (size / 8.0).max(1.0);
+        let chars: Vec<char> = text.chars().collect();
+        let left = pos.0 - chars.len() as f32 * 6.0 * dot / 2.0;
+        let top = pos.1 - 4.0 * dot;
+        for (idx, c) in chars.iter().enumerate(){
+            let glyph = glyph(*c);
+            for (column, bits) in glyph.iter().enumerate(){
+                for row in 0..8{
+                    if bits >> row & 1 == 1{
+                        let x = left + (idx * 6 + column) as f32 * dot;
+                        let y = top + row as f32 * dot;
+                        for (px, py) in self.pixels_in((x, y), (x + dot, y + dot)).collect::<Vec<_>>(){
+                            self.set(px, py, color);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// The columns of a character in the font, lowest bit at the top. Characters outside printable ASCII are drawn as `?`.
+fn glyph(c: char) -> [u8; 5]{
+    let idx = if (' '..='~').contains(&c){ c as usize - ' ' as usize }else{ '?' as usize - ' ' as usize };
+    let mut glyph = [0; 5];
+    glyph.copy_from_slice(&FONT[idx * 5..idx * 5 + 5]);
+    glyph
+}
+
+/// A 5x8 font for printable ASCII, five columns per character.
+const FONT: [u8; 95 * 5] = [
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x5F, 0x00, 0x00, 0x00, 0x07, 0x00, 0x07, 0x00, 0x14, 0x7F, 0x14, 0x7F, 0x14,
+    0x24, 0x2A, 0x7F, 0x2A, 0x12, 0x23, 0x13, 0x08, 0x64, 0x62, 0x36, 0x49, 0x56, 0x20, 0x50, 0x00, 0x08, 0x07, 0x03, 0x00,
+    0x00, 0x1C, 0x22, 0x41, 0x00, 0x00, 0x41, 0x22, 0x1C, 0x00, 0x2A, 0x1C, 0x7F, 0x1C, 0x2A, 0x08, 0x08, 0x3E, 0x08, 0x08,
+    0x00, 0x80, 0x70, 0x30, 0x00, 0x08, 0x08, 0x08, 0x08, 0x08, 0x00, 0x00, 0x60, 0x60, 0x00, 0x20, 0x10, 0x08, 0x04, 0x02,
+    0x3E, 0x51, 0x49, 0x45, 0x3E, 0x00, 0x42, 0x7F, 0x40, 0x00, 0x72, 0x49, 0x49, 0x49, 0x46, 0x21, 0x41, 0x49, 0x4D, 0x33,
+    0x18, 0x14, 0x12, 0x7F, 0x10, 0x27, 0x45, 0x45, 0x45, 0x39, 0x3C, 0x4A, 0x49, 0x49, 0x31, 0x41, 0x21, 0x11, 0x09, 0x07,
+    0x36, 0x49, 0x49, 0x49, 0x36, 0x46, 0x49, 0x49, 0x29, 0x1E, 0x00, 0x00, 0x14, 0x00, 0x00, 0x00, 0x40, 0x34, 0x00, 0x00,
+    0x00, 0x08, 0x14, 0x22, 0x41, 0x14, 0x14, 0x14, 0x14, 0x14, 0x00, 0x41, 0x22, 0x14, 0x08, 0x02, 0x01, 0x59, 0x09, 0x06,
+    0x3E, 0x41, 0x5D, 0x59, 0x4E, 0x7C, 0x12, 0x11, 0x12, 0x7C, 0x7F, 0x49, 0x49, 0x49, 0x36, 0x3E, 0x41, 0x41, 0x41, 0x22,
+    0x7F, 0x41, 0x41, 0x41, 0x3E, 0x7F, 0x49, 0x49, 0x49, 0x41, 0x7F, 0x09, 0x09, 0x09, 0x01, 0x3E, 0x41, 0x41, 0x51, 0x73,
+    0x7F, 0x08, 0x08, 0x08, 0x7F, 0x00, 0x41, 0x7F, 0x41, 0x00, 0x20, 0x40, 0x41, 0x3F, 0x01, 0x7F, 0x08, 0x14, 0x22, 0x41,
+    0x7F, 0x40, 0x40, 0x40, 0x40, 0x7F, 0x02, 0x1C, 0x02, 0x7F, 0x7F, 0x04, 0x08, 0x10, 0x7F, 0x3E, 0x41, 0x41, 0x41, 0x3E,
+    0x7F, 0x09, 0x09, 0x09, 0x06, 0x3E, 0x41, 0x51, 0x21, 0x5E, 0x7F, 0x09, 0x19, 0x29, 0x46, 0x26, 0x49, 0x49, 0x49, 0x32,
+    0x03, 0x01, 0x7F, 0x01, 0x03, 0x3F, 0x40, 0x40, 0x40, 0x3F, 0x1F, 0x20, 0x40, 0x20, 0x1F, 0x3F, 0x40, 0x38, 0x40, 0x3F,
+    0x63, 0x14, 0x08, 0x14, 0x63, 0x03, 0x04, 0x78, 0x04, 0x03, 0x61, 0x59, 0x49, 0x4D, 0x43, 0x00, 0x7F, 0x41, 0x41, 0x41,
+    0x02, 0x04, 0x08, 0x10, 0x20, 0x00, 0x41, 0x41, 0x41, 0x7F, 0x04, 0x02, 0x01, 0x02, 0x04, 0x40, 0x40, 0x40, 0x40, 0x40,
+    0x00, 0x03, 0x07, 0x08, 0x00, 0x20, 0x54, 0x54, 0x78, 0x40, 0x7F, 0x28, 0x44, 0x44, 0x38, 0x38, 0x44, 0x44, 0x44, 0x28,
+    0x38, 0x44, 0x44, 0x28, 0x7F, 0x38, 0x54, 0x54, 0x54, 0x18, 0x00, 0x08, 0x7E, 0x09, 0x02, 0x18, 0xA4, 0xA4, 0x9C, 0x78,
+    0x7F, 0x08, 0x04, 0x04, 0x78, 0x00, 0x44, 0x7D, 0x40, 0x00, 0x20, 0x40, 0x40, 0x3D, 0x00, 0x7F, 0x10, 0x28, 0x44, 0x00,
+    0x00, 0x41, 0x7F, 0x40, 0x00, 0x7C, 0x04, 0x78, 0x04, 0x78, 0x7C, 0x08, 0x04, 0x04, 0x78, 0x38, 0x44, 0x44, 0x44, 0x38,
+    0xFC, 0x18, 0x24, 0x24, 0x18, 0x18, 0x24, 0x24, 0x18, 0xFC, 0x7C, 0x08, 0x04, 0x04, 0x08, 0x48, 0x54, 0x54, 0x54, 0x24,
+    0x04, 0x04, 0x3F, 0x44, 0x24, 0x3C, 0x40, 0x40, 0x20, 0x7C, 0x1C, 0x20, 0x40, 0x20, 0x1C, 0x3C, 0x40, 0x30, 0x40, 0x3C,
+    0x44, 0x28, 0x10, 0x28, 0x44, 0x4C, 0x90, 0x90, 0x90, 0x7C, 0x44, 0x64, 0x54, 0x4C, 0x44, 0x00, 0x08, 0x36, 0x41, 0x00,
+    0x00, 0x00, 0x77, 0x00, 0x00, 0x00, 0x41, 0x36, 0x08, 0x00, 0x02, 0x01, 0x02, 0x04, 0x02
+];
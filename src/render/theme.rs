@@ -0,0 +1,123 @@
+//! Colours and styles for rendering, so pictures can match a community's conventions or be easier to tell apart.
+
+use crate::data::Atom;
+use crate::compat::HashMap;
+use super::Color;
+
+/// How bonds are drawn.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BondStyle{
+    pub color: Color,
+    /// Width of a normal bond, in board pixels. Each line of a triplex bond is half as wide.
+    pub width: f32,
+    /// Colours of the red, black and yellow lines of triplex bonds.
+    pub triplex: [Color; 3]
+}
+
+/// Everything about how a board looks that isn't its layout.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RenderTheme{
+    /// The colour of each element. Elements missing from the map are drawn grey.
+    pub atoms: HashMap<Atom, Color>,
+    pub bonds: BondStyle,
+    pub background: Color,
+    /// If set, hex outlines are drawn in this colour across the whole picture.
+    pub grid: Option<Color>,
+    /// Whether atoms are labelled with their element's symbol.
+    pub labels: bool,
+    pub glyph: Color,
+    pub input: Color,
+    pub output: Color,
+    pub conduit: Color,
+    pub track: Color,
+    pub arm: Color,
+    pub gripper_open: Color,
+    /// Grippers holding an atom.
+    pub gripper_closed: Color,
+    pub card: Color,
+    pub card_text: Color
+}
+
+impl RenderTheme{
+    /// Colours close to the game's own.
+    pub fn classic() -> RenderTheme{
+        RenderTheme{
+            atoms: HashMap::from_iter([
+                (Atom::Salt, Color(232, 226, 214)),
+                (Atom::Air, Color(150, 205, 230)),
+                (Atom::Earth, Color(110, 165, 80)),
+                (Atom::Fire, Color(225, 90, 50)),
+                (Atom::Water, Color(60, 115, 215)),
+                (Atom::Quicksilver, Color(195, 198, 210)),
+                (Atom::Vitae, Color(235, 120, 120)),
+                (Atom::Mors, Color(75, 65, 85)),
+                (Atom::Lead, Color(90, 92, 100)),
+                (Atom::Tin, Color(165, 165, 155)),
+                (Atom::Iron, Color(135, 90, 70)),
+                (Atom::Copper, Color(200, 120, 60)),
+                (Atom::Silver, Color(215, 215, 228)),
+                (Atom::Gold, Color(230, 190, 60)),
+                (Atom::Quintessence, Color(215, 175, 230))
+            ]),
+            bonds: BondStyle{ color: Color(20, 18, 16), width: 9.0, triplex: [Color(200, 50, 40), Color(15, 15, 15), Color(230, 200, 60)] },
+            background: Color(40, 36, 31),
+            grid: None,
+            labels: true,
+            glyph: Color(105, 95, 80),
+            input: Color(65, 100, 125),
+            output: Color(125, 100, 65),
+            conduit: Color(80, 110, 80),
+            track: Color(150, 140, 120),
+            arm: Color(185, 165, 125),
+            gripper_open: Color(120, 105, 80),
+            gripper_closed: Color(245, 225, 165),
+            card: Color(24, 21, 18),
+            card_text: Color(235, 225, 205)
+        }
+    }
+
+    /// Atom and triplex colours from the Okabe-Ito palette, which stay distinct with the common kinds of colour
+    /// blindness, with metals told apart by brightness instead, and the grid on.
+    pub fn colorblind() -> RenderTheme{
+        RenderTheme{
+            atoms: HashMap::from_iter([
+                (Atom::Salt, Color(255, 255, 255)),
+                (Atom::Air, Color(86, 180, 233)),
+                (Atom::Earth, Color(0, 158, 115)),
+                (Atom::Fire, Color(213, 94, 0)),
+                (Atom::Water, Color(0, 114, 178)),
+                (Atom::Quicksilver, Color(190, 190, 190)),
+                (Atom::Vitae, Color(204, 121, 167)),
+                (Atom::Mors, Color(0, 0, 0)),
+                (Atom::Lead, Color(70, 70, 70)),
+                (Atom::Tin, Color(110, 110, 110)),
+                (Atom::Iron, Color(150, 150, 150)),
+                (Atom::Copper, Color(230, 159, 0)),
+                (Atom::Silver, Color(220, 220, 220)),
+                (Atom::Gold, Color(240, 228, 66)),
+                (Atom::Quintessence, Color(120, 80, 160))
+            ]),
+            bonds: BondStyle{ color: Color(0, 0, 0), width: 9.0, triplex: [Color(213, 94, 0), Color(0, 0, 0), Color(240, 228, 66)] },
+            grid: Some(Color(60, 60, 60)),
+            labels: true,
+            ..RenderTheme::classic()
+        }
+    }
+
+    /// The colour of an atom of this element.
+    pub fn atom_color(&self, atom: Atom) -> Color{
+        self.atoms.get(&atom).copied().unwrap_or(Color(128, 128, 128))
+    }
+
+    /// A muted version of a colour, for ghosts of molecules that aren't there yet.
+    pub fn faded(&self, color: Color) -> Color{
+        let mix = |channel: u8, background: u8| ((channel as u16 + 2 * background as u16) / 3) as u8;
+        Color(mix(color.0, self.background.0), mix(color.1, self.background.1), mix(color.2, self.background.2))
+    }
+}
+
+impl Default for RenderTheme{
+    fn default() -> Self{
+        RenderTheme::classic()
+    }
+}
@@ -0,0 +1,292 @@
+//! Small scripted boards for testing one behaviour at a time: place some atoms and parts, run a few cycles, and check
+//! what's on the board.
+//!
+//! Scenarios run in a [sandbox](Sim::sandbox), so they need no puzzle. Build one in code:
+//!
+//! ```
+//! # use omsim_rs::data::{Atom, HexIndex, PartType};
+//! # use omsim_rs::scenario::Scenario;
+//! Scenario::new()
+//!     .atom(Atom::Water, HexIndex::new(0, 0))
+//!     .atom(Atom::Fire, HexIndex::new(1, 0))
+//!     .glyph(PartType::Bonding, HexIndex::new(0, 0), 0)
+//!     .expect_bond(1, HexIndex::new(0, 0), HexIndex::new(1, 0))
+//!     .run()
+//!     .unwrap();
+//! ```
+//!
+//! or write it as text, one command per line, with `#` starting a comment:
+//!
+//! ```text
+//! atom Wa 2,0
+//! part arm1 1,0 tape=Gr.g
+//! expect 4 atom Wa 1,1
+//! expect 4 empty 2,0
+//! ```
+//!
+//! The commands are:
+//! - `atom <symbol> q,r` places an atom, by its symbol like `Sa` or `Qs`.
+//! - `bond q,r q,r` bonds two atoms placed earlier.
+//! - `part <name> q,r` places a part, by its name in solution files like `bonder` or `arm1`, with optional
+//!   `rotation=N`, `length=N` and `tape=...` (instructions as in solution files, with `.` for a blank).
+//! - `expect <cycle> atom <symbol> q,r`, `expect <cycle> empty q,r`, `expect <cycle> bond q,r q,r` and
+//!   `expect <cycle> molecules N` check the board after that many cycles, and `expect <cycle> fails` checks that the
+//!   simulation has failed by then, e.g. with a collision.
+
+use core::fmt::{self, Display, Formatter};
+use crate::compat::{HashMap, HashSet};
+use crate::data::{Atom, Bond, BondType, HexIndex, Instruction, Molecule, Part, PartType};
+use crate::sim::config::SimConfig;
+use crate::sim::error::SimError;
+use crate::sim::{Sim, SimMolecule};
+use crate::compat::prelude::*;
+
+/// A board to set up, and what to expect of it as it runs.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Scenario{
+    pub atoms: Vec<(HexIndex, Atom)>,
+    /// Bonds between atoms, in board positions.
+    pub bonds: Vec<(HexIndex, HexIndex)>,
+    pub parts: Vec<Part>,
+    pub config: SimConfig,
+    /// What to check, and after how many cycles.
+    pub expectations: Vec<(u64, Expectation)>
+}
+
+/// Something to check about the board.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Expectation{
+    /// This atom is at this position.
+    Atom(HexIndex, Atom),
+    /// No atom is at this position.
+    Empty(HexIndex),
+    /// The atoms at these positions are bonded.
+    Bonded(HexIndex, HexIndex),
+    /// There are this many molecules on the board.
+    Molecules(usize),
+    /// The simulation has failed, e.g. with a collision.
+    Fails
+}
+
+/// Why a scenario didn't run as expected.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ScenarioError{
+    /// A line of a scenario's text couldn't be parsed. Lines count from 1.
+    Parse{ line: usize, reason: &'static str },
+    /// The board couldn't be set up, see [`Sim::sandbox`].
+    Setup(&'static str),
+    /// The simulation failed before an expectation that needed it to keep going.
+    Sim(SimError),
+    /// An expectation wasn't met, with the board at the time drawn as [text](Sim::to_text).
+    Unmet{ cycle: u64, expectation: Expectation, board: String }
+}
+
+impl Display for Expectation{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result{
+        match self{
+            Expectation::Atom(pos, atom) => write!(f, "{} at ({}, {})", atom.symbol(), pos.q, pos.r),
+            Expectation::Empty(pos) => write!(f, "nothing at ({}, {})", pos.q, pos.r),
+            Expectation::Bonded(a, b) => write!(f, "a bond between ({}, {}) and ({}, {})", a.q, a.r, b.q, b.r),
+            Expectation::Molecules(count) => write!(f, "{count} molecules"),
+            Expectation::Fails => write!(f, "the simulation to fail")
+        }
+    }
+}
+
+impl Display for ScenarioError{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result{
+        match self{
+            ScenarioError::Parse{ line, reason } => write!(f, "line {line}: {reason}"),
+            ScenarioError::Setup(reason) => write!(f, "{reason}"),
+            ScenarioError::Sim(err) => write!(f, "{err}"),
+            ScenarioError::Unmet{ cycle, expectation, board } => write!(f, "expected {expectation} after {cycle} cycles, but the board was:\n{board}")
+        }
+    }
+}
+
+impl core::error::Error for ScenarioError{}
+
+impl Scenario{
+    /// An empty board with the default config.
+    pub fn new() -> Scenario{
+        Scenario::default()
+    }
+
+    pub fn atom(mut self, atom: Atom, pos: HexIndex) -> Self{
+        self.atoms.push((pos, atom));
+        self
+    }
+
+    pub fn bond(mut self, a: HexIndex, b: HexIndex) -> Self{
+        self.bonds.push((a, b));
+        self
+    }
+
+    pub fn part(mut self, part: Part) -> Self{
+        self.parts.push(part);
+        self
+    }
+
+    pub fn glyph(self, ty: PartType, pos: HexIndex, rotation: i32) -> Self{
+        self.part(Part{ rotation, ..blank_part(ty, pos) })
+    }
+
+    /// Place an arm with its instructions starting from the first cycle. Arms are numbered in the order they're placed.
+    pub fn arm(self, ty: PartType, pos: HexIndex, rotation: i32, length: i32, tape: &[Instruction]) -> Self{
+        let arm_number = self.parts.iter().filter(|part| part.ty.is_arm()).count() as i32;
+        let instructions = tape.iter().enumerate().map(|(idx, instr)| (*instr, idx as i32)).collect();
+        self.part(Part{ rotation, arm_number, arm_length: length, instructions, ..blank_part(ty, pos) })
+    }
+
+    pub fn config(mut self, config: SimConfig) -> Self{
+        self.config = config;
+        self
+    }
+
+    pub fn expect(mut self, cycle: u64, expectation: Expectation) -> Self{
+        self.expectations.push((cycle, expectation));
+        self
+    }
+
+    pub fn expect_atom(self, cycle: u64, pos: HexIndex, atom: Atom) -> Self{
+        self.expect(cycle, Expectation::Atom(pos, atom))
+    }
+
+    pub fn expect_empty(self, cycle: u64, pos: HexIndex) -> Self{
+        self.expect(cycle, Expectation::Empty(pos))
+    }
+
+    pub fn expect_bond(self, cycle: u64, a: HexIndex, b: HexIndex) -> Self{
+        self.expect(cycle, Expectation::Bonded(a, b))
+    }
+
+    pub fn expect_molecules(self, cycle: u64, count: usize) -> Self{
+        self.expect(cycle, Expectation::Molecules(count))
+    }
+
+    pub fn expect_failure(self, cycle: u64) -> Self{
+        self.expect(cycle, Expectation::Fails)
+    }
+
+    /// Parse a scenario from text, see the [module docs](self) for the commands.
+    pub fn parse(text: &str) -> Result<Scenario, ScenarioError>{
+        let mut scenario = Scenario::new();
+        for (idx, line) in text.lines().enumerate(){
+            let line = line.split('#').next().unwrap_or_default();
+            let words: Vec<&str> = line.split_whitespace().collect();
+            if words.is_empty(){
+                continue;
+            }
+            scenario = scenario.command(&words).map_err(|reason| ScenarioError::Parse{ line: idx + 1, reason })?;
+        }
+        Ok(scenario)
+    }
+
+    fn command(self, words: &[&str]) -> Result<Scenario, &'static str>{
+        Ok(match *words{
+            ["atom", atom, pos] => self.atom(parse_atom(atom)?, parse_hex(pos)?),
+            ["bond", a, b] => self.bond(parse_hex(a)?, parse_hex(b)?),
+            ["part", name, pos, ref options @ ..] => {
+                let ty = PartType::from_name(name).ok_or("unknown part name")?;
+                let (mut rotation, mut length, mut tape) = (0, 1, Vec::new());
+                for option in options{
+                    match option.split_once('=').ok_or("part options should be name=value")?{
+                        ("rotation", value) => rotation = value.parse().map_err(|_| "rotation should be a number")?,
+                        ("length", value) => length = value.parse().map_err(|_| "length should be a number")?,
+                        ("tape", value) => tape = parse_tape(value)?,
+                        _ => return Err("unknown part option")
+                    }
+                }
+                if ty.is_arm() || ty == PartType::Berlo{
+                    self.arm(ty, parse_hex(pos)?, rotation, length, &tape)
+                }else if !tape.is_empty(){
+                    return Err("only arms have tapes");
+                }else{
+                    self.glyph(ty, parse_hex(pos)?, rotation)
+                }
+            }
+            ["expect", cycle, ref expectation @ ..] => {
+                let cycle = cycle.parse().map_err(|_| "cycle should be a number")?;
+                let expectation = match *expectation{
+                    ["atom", atom, pos] => Expectation::Atom(parse_hex(pos)?, parse_atom(atom)?),
+                    ["empty", pos] => Expectation::Empty(parse_hex(pos)?),
+                    ["bond", a, b] => Expectation::Bonded(parse_hex(a)?, parse_hex(b)?),
+                    ["molecules", count] => Expectation::Molecules(count.parse().map_err(|_| "molecule count should be a number")?),
+                    ["fails"] => Expectation::Fails,
+                    _ => return Err("unknown expectation")
+                };
+                self.expect(cycle, expectation)
+            }
+            _ => return Err("unknown command")
+        })
+    }
+
+    /// The board before any cycles have run.
+    pub fn board(&self) -> Result<Sim, ScenarioError>{
+        let placed = Molecule{
+            atoms: self.atoms.iter().map(|(pos, atom)| (*pos, *atom)).collect::<HashMap<_, _>>(),
+            bonds: self.bonds.iter().map(|(start, end)| Bond{ start: *start, end: *end, ty: BondType::Normal }).collect::<HashSet<_>>()
+        };
+        if placed.atoms.len() < self.atoms.len(){
+            return Err(ScenarioError::Setup("two atoms are placed on the same hex"));
+        }
+        if self.bonds.iter().any(|(a, b)| !placed.atoms.contains_key(a) || !placed.atoms.contains_key(b)){
+            return Err(ScenarioError::Setup("bond between hexes without atoms"));
+        }
+        let molecules: Vec<Molecule> = if placed.atoms.is_empty(){
+            Vec::new()
+        }else{
+            SimMolecule::new(placed, HexIndex::default()).split().into_iter().map(|m| m.layout.translated(m.pos)).collect()
+        };
+        Sim::sandbox(&self.parts, &molecules, self.config.clone()).map_err(ScenarioError::Setup)
+    }
+
+    /// Run the scenario, checking every expectation in order of their cycles, and return the board after the last one.
+    /// Once the simulation fails, only [`Expectation::Fails`] can be met.
+    pub fn run(&self) -> Result<Sim, ScenarioError>{
+        let mut sim = self.board()?;
+        let mut expectations = self.expectations.clone();
+        expectations.sort_by_key(|(cycle, _)| *cycle);
+        let mut failed = None;
+        for (cycle, expectation) in expectations{
+            if failed.is_none(){
+                failed = sim.run_until(cycle).err();
+            }
+            let met = match (expectation, &failed){
+                (Expectation::Fails, failed) => failed.is_some(),
+                (_, Some(err)) => return Err(ScenarioError::Sim(err.clone())),
+                (Expectation::Atom(pos, atom), None) => sim.lookup_atom(pos, |r| r.atom_ty) == Some(atom),
+                (Expectation::Empty(pos), None) => sim.lookup_atom(pos, |_| ()).is_none(),
+                (Expectation::Bonded(a, b), None) => sim.molecules.iter().any(|m| m.contains_pos(a) && m.bond_at(a, b).is_some()),
+                (Expectation::Molecules(count), None) => sim.molecules.len() == count
+            };
+            if !met{
+                return Err(ScenarioError::Unmet{ cycle, expectation, board: sim.to_text() });
+            }
+        }
+        Ok(sim)
+    }
+}
+
+fn blank_part(ty: PartType, pos: HexIndex) -> Part{
+    Part{
+        ty, pos, rotation: 0, arm_number: 0, arm_length: 1, index: 0, conduit_index: 0,
+        track_hexes: Vec::new(), conduit_hexes: Vec::new(), instructions: Vec::new()
+    }
+}
+
+fn parse_hex(text: &str) -> Result<HexIndex, &'static str>{
+    let (q, r) = text.split_once(',').ok_or("positions should be q,r")?;
+    Ok(HexIndex::new(q.parse().map_err(|_| "q should be a number")?, r.parse().map_err(|_| "r should be a number")?))
+}
+
+fn parse_atom(text: &str) -> Result<Atom, &'static str>{
+    (1..=16).filter_map(Atom::from_id).find(|atom| atom.symbol().eq_ignore_ascii_case(text)).ok_or("unknown atom symbol")
+}
+
+fn parse_tape(text: &str) -> Result<Vec<Instruction>, &'static str>{
+    text.bytes()
+        .map(|b| if b == b'.'{ Some(Instruction::Blank) }else{ Instruction::from_id(b) })
+        .collect::<Option<_>>()
+        .ok_or("unknown instruction in tape")
+}
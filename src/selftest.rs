@@ -0,0 +1,133 @@
+use core::fmt::{Display, Formatter};
+use crate::data::Metrics;
+use crate::parse::{parse_puzzle, parse_solution};
+use crate::verify::verify;
+use crate::compat::prelude::*;
+
+/// A puzzle and solution from the bundled regression corpus, with the expected verdict.
+#[derive(Copy, Clone, Debug)]
+pub struct CorpusCase{
+    pub name: &'static str,
+    pub puzzle: &'static [u8],
+    pub solution: &'static [u8],
+    pub expected: Expected
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Expected{
+    /// The solution completes with exactly these metrics.
+    /// These are snapshots of this sim's own output, so they only catch changes in behaviour, not wrong rules.
+    Valid(Metrics),
+    /// The solution completes with exactly the metrics recorded in the solution file by the game or the reference
+    /// omsim. Unlike [`Valid`](Expected::Valid), this checks the sim against an independent implementation.
+    Recorded,
+    /// The solution fails, with an error message containing this text.
+    Invalid(&'static str)
+}
+
+macro_rules! corpus_file{
+    ($name:literal) => { include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/corpus/", $name)) }
+}
+
+const fn metrics(cycles: i32, cost: i32, area: i32, instructions: i32) -> Metrics{
+    Metrics{ cycles, cost, area, instructions }
+}
+
+/// The bundled regression corpus.
+/// Any change in how these are simulated needs to be deliberate, so update the expectations here along with the sim.
+///
+/// Every case so far is a small synthetic puzzle written for this corpus, and every [`Valid`](Expected::Valid)
+/// expectation is a snapshot of this sim's output, so the corpus guards against regressions but can't show that a rule
+/// matches the game. Solutions saved by the game, with their recorded metrics, belong here as
+/// [`Recorded`](Expected::Recorded) cases.
+pub const CORPUS: &[CorpusCase] = &[
+    CorpusCase{
+        name: "transfer",
+        puzzle: corpus_file!("transfer.puzzle"),
+        solution: corpus_file!("transfer.solution"),
+        expected: Expected::Valid(metrics(45, 20, 7, 8))
+    },
+    CorpusCase{
+        name: "calcify",
+        puzzle: corpus_file!("calcify.puzzle"),
+        solution: corpus_file!("calcify.solution"),
+        expected: Expected::Valid(metrics(45, 30, 7, 8))
+    },
+    CorpusCase{
+        name: "bond",
+        puzzle: corpus_file!("bond.puzzle"),
+        solution: corpus_file!("bond.solution"),
+        // area counts every hex an atom passes through, not just those it rests on. the bonded pair hangs off an arm at
+        // (-1, 0), so its far atom swings at distance 2 and rests on (1, 0), (1, -2), (-1, -2) and (-3, 0) in turn; each
+        // 60° swing between those crosses exactly one hex no atom or arm rests on, (1, -1), (0, -2) and (-2, -1), for 14
+        // hexes in total. see `bond_area_counts_the_hexes_the_far_atom_swings_through` in tests/collision.rs
+        expected: Expected::Valid(metrics(45, 30, 14, 8))
+    },
+    CorpusCase{
+        name: "collision",
+        puzzle: corpus_file!("transfer.puzzle"),
+        solution: corpus_file!("collision.solution"),
+        expected: Expected::Invalid("collision at (0, 0) in cycle 1")
+    },
+    CorpusCase{
+        name: "idle",
+        puzzle: corpus_file!("transfer.puzzle"),
+        solution: corpus_file!("idle.solution"),
+        expected: Expected::Invalid("did not complete")
+    },
+    CorpusCase{
+        name: "missing-product",
+        puzzle: corpus_file!("transfer.puzzle"),
+        solution: corpus_file!("missing-product.solution"),
+        expected: Expected::Invalid("out-of-bounds index")
+    }
+];
+
+/// A corpus case whose verdict didn't match its expectation.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Divergence{
+    pub case: &'static str,
+    pub expected: Expected,
+    /// The actual outcome, as displayed by [`Verdict`](crate::verify::Verdict).
+    pub actual: String
+}
+
+impl Display for Divergence{
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result{
+        match self.expected{
+            Expected::Valid(m) => write!(f, "{}: expected valid: {} cycles, {} cost, {} area, {} instructions, got {}",
+                self.case, m.cycles, m.cost, m.area, m.instructions, self.actual),
+            Expected::Recorded => write!(f, "{}: expected the recorded metrics, got {}", self.case, self.actual),
+            Expected::Invalid(reason) => write!(f, "{}: expected failure containing {reason:?}, got {}", self.case, self.actual)
+        }
+    }
+}
+
+/// Run a single corpus case, returning how it diverged from its expectation, if it did.
+pub fn run_case(case: &CorpusCase) -> Option<Divergence>{
+    let diverge = |actual: String| Some(Divergence{ case: case.name, expected: case.expected, actual });
+    let puzzle = match parse_puzzle(case.puzzle){
+        Ok(puzzle) => puzzle,
+        Err(err) => return diverge(format!("unparseable puzzle: {err}"))
+    };
+    let solution = match parse_solution(case.solution){
+        Ok(solution) => solution,
+        Err(err) => return diverge(format!("unparseable solution: {err}"))
+    };
+    if case.expected == Expected::Recorded && solution.metrics.is_none(){
+        return diverge("no metrics recorded in the solution file".to_string());
+    }
+    let verdict = verify(&puzzle, &solution);
+    let ok = match (&verdict.result, case.expected){
+        (Ok(actual), Expected::Valid(expected)) => *actual == expected,
+        (Ok(actual), Expected::Recorded) => solution.metrics == Some(*actual),
+        (Err(err), Expected::Invalid(reason)) => err.to_string().contains(reason),
+        _ => false
+    };
+    if ok{ None } else { diverge(verdict.to_string()) }
+}
+
+/// Run every case in the bundled corpus, returning any divergences.
+pub fn run_corpus() -> Vec<Divergence>{
+    CORPUS.iter().filter_map(run_case).collect()
+}
@@ -0,0 +1,211 @@
+use crate::data::{ArmState, HexIndex, HexRotation, Instruction, Part, PartType};
+use crate::compat::prelude::*;
+
+/// The state of an arm or Van Berlo's wheel.
+/// The base position and rotation are stored on the containing [`SimPart`](super::SimPart).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SimArm{
+    pub ty: PartType,
+    /// Arm number, as displayed in the game.
+    pub number: i32,
+    /// Distance from the base to each gripper.
+    pub length: i32,
+    /// The furthest the grippers have been from the base so far, which only changes for pistons.
+    pub max_length: i32,
+    /// The furthest a piston can extend its grippers: 3 as in the game, or further if a modded solution starts it longer.
+    pub extension_limit: i32,
+    /// Whether the grippers are closed.
+    pub grabbing: bool,
+    /// For each gripper, whether it closed on an atom and is still holding it.
+    pub holding: Vec<bool>,
+    /// The instruction for each cycle of the loop, starting from the first cycle.
+    pub tape: Vec<Instruction>,
+    /// Where the expansion of the tape's last Repeat or Reset ends, or 0 if it has none. Instructions before this were
+    /// either copied by an expansion or are part of one, so changing them alone can't give a tape the game would run.
+    pub expansions_end: usize,
+    /// If this arm is on a track, the track it moves along.
+    pub track: Option<SimTrack>
+}
+
+/// A snapshot of one arm's state during a simulation, as returned by [`Sim::arm`](super::Sim::arm).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ArmView{
+    /// The arm's index in [`Sim::parts`](super::Sim::parts).
+    pub part: usize,
+    pub number: i32,
+    /// The position of the base.
+    pub pos: HexIndex,
+    pub rotation: HexRotation,
+    /// How far the grippers are extended from the base.
+    pub length: i32,
+    /// If this arm is on a track, the index of its base in the track's hexes.
+    pub track_index: Option<usize>,
+    /// Whether the grippers are closed.
+    pub grabbing: bool,
+    /// The position of each gripper.
+    pub grippers: Vec<HexIndex>,
+    /// For each gripper, the index in [`Sim::molecules`](super::Sim::molecules) of the molecule it's holding.
+    pub held: Vec<Option<usize>>,
+    /// The instruction the arm runs in the next cycle.
+    pub next: Instruction
+}
+
+/// A track that an arm is placed on, in board positions.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SimTrack{
+    /// The hexes of the track, in placement order.
+    pub hexes: Vec<HexIndex>,
+    /// Whether the last hex connects back to the first.
+    pub looping: bool
+}
+
+impl SimTrack{
+    pub fn new(hexes: Vec<HexIndex>) -> SimTrack{
+        let looping = hexes.len() > 2 && hexes[0].is_adjacent(hexes[hexes.len() - 1]);
+        SimTrack{ hexes, looping }
+    }
+
+    /// The hex `steps` along the track from `pos`, if the track continues that far.
+    pub fn step(&self, pos: HexIndex, steps: i32) -> Option<HexIndex>{
+        let idx = self.hexes.iter().position(|h| *h == pos)? as i32 + steps;
+        let len = self.hexes.len() as i32;
+        if self.looping{
+            Some(self.hexes[idx.rem_euclid(len) as usize])
+        }else if (0..len).contains(&idx){
+            Some(self.hexes[idx as usize])
+        }else{
+            None
+        }
+    }
+}
+
+/// A movement an arm makes during a cycle.
+/// Rotations follow the game's convention, where positive turns are anticlockwise.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ArmMotion{
+    /// Rotate the arm and everything it holds around its base.
+    Rotate(HexRotation),
+    /// Change the arm's length by this amount, moving everything it holds with it.
+    Extend(i32),
+    /// Rotate everything the arm holds around each gripper.
+    Pivot(HexRotation),
+    /// Move the base along a track to this position, moving everything it holds with it.
+    Move(HexIndex)
+}
+
+impl SimArm{
+    pub fn new(ty: PartType, number: i32, length: i32, tape: Vec<Instruction>) -> SimArm{
+        let grippers = Self::gripper_offsets(&ty).len();
+        SimArm{ ty, number, length, max_length: length, extension_limit: length.max(3), grabbing: false, holding: vec![false; grippers], tape, expansions_end: 0, track: None }
+    }
+
+    pub fn from_solution_part(part: &Part, tape: Vec<Instruction>) -> SimArm{
+        SimArm::new(part.ty.clone(), part.arm_number, part.arm_length, tape)
+    }
+
+    /// The directions of each gripper, relative to the arm's rotation.
+    pub fn gripper_offsets(ty: &PartType) -> &'static [HexRotation]{
+        match ty{
+            PartType::Arm | PartType::PistonArm => &[HexRotation::R0],
+            PartType::BiArm => &[HexRotation::R0, HexRotation::R180],
+            PartType::TriArm => &[HexRotation::R0, HexRotation::R120, HexRotation::R240],
+            PartType::HexArm => &[HexRotation::R0, HexRotation::R60, HexRotation::R120, HexRotation::R180, HexRotation::R240, HexRotation::R300],
+            _ => &[]
+        }
+    }
+
+    /// The positions of each gripper, for an arm with its base at `pos` and the given rotation.
+    pub fn grippers(&self, pos: HexIndex, rotation: HexRotation) -> Vec<HexIndex>{
+        Self::gripper_offsets(&self.ty).iter()
+            .map(|offset| pos + (rotation + *offset).to_unit_offset().scaled(self.length))
+            .collect()
+    }
+
+    /// The hexes a piston's shaft covers between its base and each gripper, which count towards area.
+    /// Other arms are drawn above the board, so only their base and grippers do.
+    pub fn shaft(&self, pos: HexIndex, rotation: HexRotation) -> Vec<HexIndex>{
+        if self.ty != PartType::PistonArm{
+            return Vec::new();
+        }
+        Self::gripper_offsets(&self.ty).iter()
+            .flat_map(|offset| (1..self.length).map(move |dist| pos + (rotation + *offset).to_unit_offset().scaled(dist)))
+            .collect()
+    }
+
+    /// Extend the grippers by `by` hexes, or retract them if negative.
+    pub fn extend(&mut self, by: i32){
+        self.length += by;
+        self.max_length = self.max_length.max(self.length);
+    }
+
+    /// The instruction this arm runs in the given cycle, with the loop repeating every `period` cycles.
+    pub fn instruction_at(&self, cycle: u64, period: u64) -> Instruction{
+        if period == 0{
+            return Instruction::Blank;
+        }
+        self.tape.get((cycle % period) as usize).copied().unwrap_or_default()
+    }
+
+    /// The movement an instruction causes, for an arm with its base at `pos`, or `None` if it doesn't move.
+    pub fn motion(&self, instr: Instruction, pos: HexIndex) -> Option<ArmMotion>{
+        match instr{
+            Instruction::RotateClockwise => Some(ArmMotion::Rotate(HexRotation::R300)),
+            Instruction::RotateAnticlockwise => Some(ArmMotion::Rotate(HexRotation::R60)),
+            Instruction::Extend if self.ty == PartType::PistonArm && self.length < self.extension_limit => Some(ArmMotion::Extend(1)),
+            Instruction::Retract if self.ty == PartType::PistonArm && self.length > 1 => Some(ArmMotion::Extend(-1)),
+            Instruction::PivotClockwise if self.ty != PartType::Berlo => Some(ArmMotion::Pivot(HexRotation::R300)),
+            Instruction::PivotAnticlockwise if self.ty != PartType::Berlo => Some(ArmMotion::Pivot(HexRotation::R60)),
+            Instruction::Advance => self.track.as_ref()?.step(pos, 1).map(ArmMotion::Move),
+            Instruction::Retreat => self.track.as_ref()?.step(pos, -1).map(ArmMotion::Move),
+            _ => None
+        }
+    }
+
+    /// How this arm, starting with its base at `pos`, changes after running these instructions, ignoring everything else
+    /// on the board.
+    pub fn state_after(&self, pos: HexIndex, instructions: &[Instruction]) -> ArmState{
+        let mut arm = self.clone();
+        let mut pos = pos;
+        let mut state = ArmState{ grabbing: arm.grabbing, ..ArmState::default() };
+        for instr in instructions{
+            match instr{
+                Instruction::Grab => state.grabbing = true,
+                Instruction::Drop => state.grabbing = false,
+                // a reset returns to the starting state
+                Instruction::Reset => state = ArmState::default(),
+                _ => match arm.motion(*instr, pos){
+                    Some(ArmMotion::Rotate(by)) => state.rotation += by.signed_turns() as i32,
+                    Some(ArmMotion::Extend(by)) => {
+                        arm.extend(by);
+                        state.extension += by;
+                    }
+                    Some(ArmMotion::Move(to)) => {
+                        pos = to;
+                        state.track_steps += if *instr == Instruction::Advance{ 1 } else { -1 };
+                    }
+                    Some(ArmMotion::Pivot(_)) | None => {}
+                }
+            }
+        }
+        state
+    }
+
+    /// This arm's tape with every Repeat and Reset expanded, for an arm with its base starting at `pos`.
+    pub fn expanded_tape(&self, pos: HexIndex) -> Result<Vec<Instruction>, &'static str>{
+        self.expand_tape(&self.tape, pos)
+    }
+
+    /// Replace this arm's tape with its [expansion](SimArm::expanded_tape), noting where the last expansion ends in
+    /// [`expansions_end`](SimArm::expansions_end).
+    pub fn expand(&mut self, pos: HexIndex) -> Result<(), &'static str>{
+        if let Some(last) = self.tape.iter().rposition(|instr| matches!(instr, Instruction::Repeat | Instruction::Reset)){
+            self.expansions_end = self.expand_tape(&self.tape[..=last], pos)?.len();
+        }
+        self.tape = self.expanded_tape(pos)?;
+        Ok(())
+    }
+
+    fn expand_tape(&self, tape: &[Instruction], pos: HexIndex) -> Result<Vec<Instruction>, &'static str>{
+        Instruction::expand_tape(tape, |before| Instruction::expand_reset(self.state_after(pos, before)))
+    }
+}
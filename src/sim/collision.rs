@@ -1,8 +1,9 @@
-use std::ops::{Add, AddAssign, Mul, MulAssign, Sub, SubAssign};
+use core::ops::{Add, AddAssign, Mul, MulAssign, Sub, SubAssign};
+use crate::compat::{cos, sin, sqrt};
 use crate::data::{HexIndex, HexRotation};
+use crate::compat::prelude::*;
 
-pub const HEX_WIDTH: f32 = 82.0;
-pub const HEX_HEIGHT: f32 = 71.0;
+pub use crate::data::{HEX_HEIGHT, HEX_WIDTH};
 
 /// A 2D vector in ordinary cartesian coordinates.
 #[derive(Copy, Clone, PartialEq, Debug)]
@@ -17,15 +18,16 @@ impl Vector2{
     }
 
     pub fn from_hex_index(h: HexIndex) -> Vector2{
-        Vector2::new((h.q as f32)*HEX_WIDTH + 0.5 * (h.r as f32)*HEX_WIDTH, (h.r as f32)*HEX_HEIGHT)
+        let (x, y) = h.to_pixel();
+        Vector2::new(x, y)
     }
 
     pub fn length2(self) -> f32{
-        self.x * self.x + self.y + self.y
+        self.x * self.x + self.y * self.y
     }
 
     pub fn length(self) -> f32{
-        self.length2().sqrt()
+        sqrt(self.length2())
     }
 
     pub fn dist2(self, other: Vector2) -> f32{
@@ -37,6 +39,13 @@ impl Vector2{
     }
 }
 
+impl Vector2{
+    /// The hex containing this point.
+    pub fn to_hex_index(self) -> HexIndex{
+        HexIndex::from_pixel(self.x, self.y)
+    }
+}
+
 impl From<HexIndex> for Vector2{
     fn from(value: HexIndex) -> Vector2{
         Vector2::from_hex_index(value)
@@ -129,39 +138,221 @@ impl Movement{
                 start + (end - start)*time
             }
             Movement::Rotate{ start, around, rotation } => {
-                let (start, around, r): (Vector2, Vector2, f32) = (start.into(), around.into(), -rotation.to_radians() * time);
+                // rotations always take the short way around, so a rotation of 300 degrees is really -60
+                let r = rotation.signed_turns() as f32 * core::f32::consts::FRAC_PI_3 * time;
+                let (start, around): (Vector2, Vector2) = (start.into(), around.into());
                 let tr = start - around;
-                Vector2::new(f32::cos(r) * tr.x - f32::sin(r) * tr.y, f32::sin(r) * tr.x + f32::cos(r) * tr.y) + around
+                Vector2::new(cos(r) * tr.x - sin(r) * tr.y, sin(r) * tr.x + cos(r) * tr.y) + around
+            }
+        }
+    }
+
+    /// The hex this movement starts from.
+    pub fn start(self) -> HexIndex{
+        match self{
+            Movement::Stay{ at } => at,
+            Movement::Translate{ start, .. } | Movement::Rotate{ start, .. } => start
+        }
+    }
+
+    /// The hex this movement ends on.
+    pub fn end(self) -> HexIndex{
+        match self{
+            Movement::Stay{ at } => at,
+            Movement::Translate{ end, .. } => end,
+            Movement::Rotate{ start, around, rotation } => start.rotated(around, rotation)
+        }
+    }
+
+    /// Every hex that this movement's centre passes through, including where it starts and ends.
+    /// Only rotations can pass through hexes other than their start and end, since translations are between neighbours.
+    pub fn swept_hexes(self) -> Vec<HexIndex>{
+        let Movement::Rotate{ start, around, .. } = self else {
+            return if self.start() == self.end(){ vec![self.start()] }else{ vec![self.start(), self.end()] };
+        };
+        // sample finely enough that no hex the arc crosses is skipped over, even at the arc's far end
+        let samples = 16 * start.distance(around).max(1);
+        let mut hexes: Vec<HexIndex> = Vec::new();
+        for step in 0..=samples{
+            let pos = self.pos_at(step as f32 / samples as f32);
+            let hex = HexIndex::from_pixel(pos.x, pos.y);
+            if !hexes.contains(&hex){
+                hexes.push(hex);
+            }
+        }
+        hexes
+    }
+}
+
+/// The number of fractional bits in fixed-point positions, in pixels.
+pub const FIXED_FRACTION_BITS: u32 = 16;
+/// The number of fractional bits in fixed-point sines and cosines.
+const TRIG_FRACTION_BITS: u32 = 30;
+/// Pi with [`TRIG_FRACTION_BITS`] fractional bits.
+const FIXED_PI: i64 = 3_373_259_426;
+
+/// A 2D vector in fixed-point pixels, with [`FIXED_FRACTION_BITS`] fractional bits.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct FixedVector2{
+    pub x: i64,
+    pub y: i64
+}
+
+impl FixedVector2{
+    /// The exact position of the centre of a hex. Hex positions are whole pixels, so this doesn't round.
+    pub fn from_hex_index(h: HexIndex) -> FixedVector2{
+        let x = (2 * h.q as i64 + h.r as i64) * (HEX_WIDTH as i64 / 2);
+        let y = h.r as i64 * HEX_HEIGHT as i64;
+        FixedVector2{ x: x << FIXED_FRACTION_BITS, y: y << FIXED_FRACTION_BITS }
+    }
+
+    pub fn dist2(self, other: FixedVector2) -> i128{
+        let (dx, dy) = ((self.x - other.x) as i128, (self.y - other.y) as i128);
+        dx * dx + dy * dy
+    }
+}
+
+/// The sine and cosine of an angle in radians, all with [`TRIG_FRACTION_BITS`] fractional bits.
+/// Only integer arithmetic is used, so this gives the same result everywhere.
+pub fn fixed_sin_cos(angle: i64) -> (i64, i64){
+    // bring the angle into [-pi, pi] so the series converges quickly
+    let mut x = angle % (2 * FIXED_PI);
+    if x > FIXED_PI{
+        x -= 2 * FIXED_PI;
+    }else if x < -FIXED_PI{
+        x += 2 * FIXED_PI;
+    }
+    let x = x as i128;
+    let x2 = (x * x) >> TRIG_FRACTION_BITS;
+    // taylor series, adding terms until they vanish
+    let (mut sin, mut sin_term) = (x, x);
+    let (mut cos, mut cos_term) = (1i128 << TRIG_FRACTION_BITS, 1i128 << TRIG_FRACTION_BITS);
+    let mut n = 1;
+    while sin_term != 0 || cos_term != 0{
+        sin_term = -((sin_term * x2) >> TRIG_FRACTION_BITS) / ((2 * n) * (2 * n + 1));
+        cos_term = -((cos_term * x2) >> TRIG_FRACTION_BITS) / ((2 * n - 1) * (2 * n));
+        sin += sin_term;
+        cos += cos_term;
+        n += 1;
+    }
+    (sin as i64, cos as i64)
+}
+
+impl Movement{
+    /// The position `step` out of `steps` of the way through this movement, in fixed-point pixels.
+    /// Unlike [`pos_at`](Movement::pos_at), this is bit-identical across platforms.
+    pub fn fixed_pos_at(self, step: u32, steps: u32) -> FixedVector2{
+        let steps = steps.max(1) as i64;
+        let step = step as i64;
+        match self{
+            Movement::Stay{ at } => FixedVector2::from_hex_index(at),
+            Movement::Translate{ start, end } => {
+                let (start, end) = (FixedVector2::from_hex_index(start), FixedVector2::from_hex_index(end));
+                FixedVector2{
+                    x: start.x + (end.x - start.x) * step / steps,
+                    y: start.y + (end.y - start.y) * step / steps
+                }
+            }
+            Movement::Rotate{ start, around, rotation } => {
+                // a turn is a third of pi
+                let angle = rotation.signed_turns() as i64 * step * FIXED_PI / (3 * steps);
+                let (sin, cos) = fixed_sin_cos(angle);
+                let (start, around) = (FixedVector2::from_hex_index(start), FixedVector2::from_hex_index(around));
+                let (tx, ty) = ((start.x - around.x) as i128, (start.y - around.y) as i128);
+                let (sin, cos) = (sin as i128, cos as i128);
+                FixedVector2{
+                    x: ((cos * tx - sin * ty) >> TRIG_FRACTION_BITS) as i64 + around.x,
+                    y: ((sin * tx + cos * ty) >> TRIG_FRACTION_BITS) as i64 + around.y
+                }
             }
         }
     }
 }
 
+/// How collider positions are computed while checking for collisions.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum CollisionMath{
+    /// Use `f32` and the platform's trigonometry, which is fast but can differ slightly between platforms and compilers.
+    #[default]
+    Float,
+    /// Use fixed-point integer arithmetic, so verdicts are bit-identical on every platform.
+    FixedPoint
+}
+
 /// A collider on the board, with a position and type.
 #[derive(Copy, Clone, PartialEq, Debug)]
 pub struct Collider{
     pub ty: ColliderType,
-    pub movement: Movement
+    pub movement: Movement,
+    /// Colliders that are part of the same rigid body, like atoms of one molecule, never collide with each other.
+    pub body: usize
 }
 
-/// Test whether anything in this list of colliders collides.
-pub fn collides(colliders: &Vec<Collider>, steps: u32) -> bool{
+impl Collider{
+    /// How close these colliders can get without colliding, or `None` if they never collide with each other.
+    ///
+    /// Like the game, this allows colliders that are parts of one rigid body to overlap, like the atoms of a molecule, or
+    /// Van Berlo's wheel and its atoms, as well as grippers and anything but chamber walls, so a gripper can sit over the atom
+    /// it holds and pass over others. Nothing else is exempt: in particular, a held molecule is a different body from the arm
+    /// holding it, so it collides with that arm's base, e.g. when pivoting swings one of its atoms over the base.
+    pub fn radius_with(&self, other: &Collider) -> Option<f32>{
+        if self.body == other.body{
+            return None;
+        }
+        self.ty.radius_with(other.ty)
+    }
+}
+
+/// A collision between two colliders, by index, and the fraction of the movement at which it happened.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Collision{
+    pub a: usize,
+    pub b: usize,
+    pub time: f32
+}
+
+/// Find the first collision between colliders in this list, checking `steps + 1` evenly spaced points in time.
+pub fn find_collision(colliders: &[Collider], steps: u32) -> Option<Collision>{
+    find_first(colliders, steps, |c, i| c.movement.pos_at((i as f32)/(steps as f32)), |l, r, radius| l.dist2(r) < radius*radius)
+}
+
+/// Like [`find_collision`], but computing positions with fixed-point arithmetic, so the result is the same on every platform.
+pub fn find_collision_fixed(colliders: &[Collider], steps: u32) -> Option<Collision>{
+    find_first(colliders, steps, |c, i| c.movement.fixed_pos_at(i, steps), |l, r, radius| {
+        // radii are whole pixels
+        let radius = (radius as i128) << FIXED_FRACTION_BITS;
+        l.dist2(r) < radius*radius
+    })
+}
+
+/// Find a collision using the given [`CollisionMath`].
+pub fn find_collision_with(colliders: &[Collider], steps: u32, math: CollisionMath) -> Option<Collision>{
+    match math{
+        CollisionMath::Float => find_collision(colliders, steps),
+        CollisionMath::FixedPoint => find_collision_fixed(colliders, steps)
+    }
+}
+
+fn find_first<P: Copy>(colliders: &[Collider], steps: u32, pos_at: impl Fn(&Collider, u32) -> P, overlaps: impl Fn(P, P, f32) -> bool) -> Option<Collision>{
     for i in 0..=steps{
         let time: f32 = (i as f32)/(steps as f32);
         // bleh
-        let objs: Vec<(Vector2, ColliderType)> = colliders.iter().map(|c| (c.movement.pos_at(time), c.ty)).collect();
-        for l in 0..objs.len(){
-            for r in 0..objs.len(){
-                if l != r{
-                    let ((lpos, lty), (rpos, rty)) = (objs[l], objs[r]);
-                    if let Some(radius) = lty.radius_with(rty){
-                        if lpos.dist2(rpos) < radius*radius{
-                            return true;
-                        }
+        let positions: Vec<P> = colliders.iter().map(|c| pos_at(c, i)).collect();
+        for l in 0..positions.len(){
+            for r in (l + 1)..positions.len(){
+                let (lpos, rpos) = (positions[l], positions[r]);
+                if let Some(radius) = colliders[l].radius_with(&colliders[r]){
+                    if overlaps(lpos, rpos, radius){
+                        return Some(Collision{ a: l, b: r, time });
                     }
                 }
             }
         }
     }
-    false
+    None
+}
+
+/// Test whether anything in this list of colliders collides.
+pub fn collides(colliders: &[Collider], steps: u32) -> bool{
+    find_collision(colliders, steps).is_some()
 }
\ No newline at end of file
@@ -0,0 +1,96 @@
+//! What it takes for a simulation to be complete.
+//! Normally that's producing every product the puzzle asks for, but challenges can set their own goals.
+
+use core::fmt::Debug;
+use super::Sim;
+use crate::compat::prelude::*;
+
+/// A condition that completes a simulation when met.
+///
+/// It's checked once per cycle, after glyphs and outputs have acted and before anything moves,
+/// so a solution that completes during a cycle still has that cycle's movement checked for collisions.
+pub trait CompletionCriterion: Debug + Send + Sync{
+    /// A short description, like "produce 100 of output 0".
+    fn describe(&self) -> String;
+
+    /// Checked once before simulating: why this can never be met on this board, if it can't, e.g. a puzzle with nothing to produce.
+    fn check_possible(&self, _sim: &Sim) -> Result<(), &'static str>{
+        Ok(())
+    }
+
+    /// Whether the simulation is complete as of its current cycle.
+    fn is_met(&self, sim: &Sim) -> bool;
+}
+
+/// Produce the number of each product that the puzzle requires, like the game.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct StandardProducts;
+
+impl CompletionCriterion for StandardProducts{
+    fn describe(&self) -> String{
+        "produce every required product".to_string()
+    }
+
+    fn check_possible(&self, sim: &Sim) -> Result<(), &'static str>{
+        if sim.required.is_empty(){
+            return Err("puzzle has no products");
+        }
+        Ok(())
+    }
+
+    fn is_met(&self, sim: &Sim) -> bool{
+        !sim.required.is_empty() && sim.progress().iter().all(|p| p.is_satisfied())
+    }
+}
+
+/// Produce at least `count` of a single product, ignoring the others.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ProduceCount{
+    pub product: usize,
+    pub count: u64
+}
+
+impl CompletionCriterion for ProduceCount{
+    fn describe(&self) -> String{
+        format!("produce {} of output {}", self.count, self.product)
+    }
+
+    fn check_possible(&self, sim: &Sim) -> Result<(), &'static str>{
+        if self.product >= sim.required.len(){
+            return Err("puzzle has no such product");
+        }
+        Ok(())
+    }
+
+    fn is_met(&self, sim: &Sim) -> bool{
+        sim.product_counts().get(self.product).is_some_and(|consumed| *consumed >= self.count)
+    }
+}
+
+/// Run this many cycles without failing.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct SurviveCycles(pub u64);
+
+impl CompletionCriterion for SurviveCycles{
+    fn describe(&self) -> String{
+        format!("survive {} cycles", self.0)
+    }
+
+    fn is_met(&self, sim: &Sim) -> bool{
+        sim.cycle + 1 >= self.0
+    }
+}
+
+/// Clear every molecule off the board, checked before inputs spawn new ones.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct EmptyBoard;
+
+impl CompletionCriterion for EmptyBoard{
+    fn describe(&self) -> String{
+        "empty the board".to_string()
+    }
+
+    fn is_met(&self, sim: &Sim) -> bool{
+        sim.molecules.is_empty()
+    }
+}
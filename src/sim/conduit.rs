@@ -0,0 +1,77 @@
+//! Conduits, which carry molecules between the chambers of a production puzzle.
+//!
+//! Each conduit has two ends, placed as separate parts with the same conduit index. Like glyphs, conduits act on atoms
+//! at rest: after the glyphs in each cycle, every molecule that lies entirely on one end's footprint is carried to the
+//! other end, turned by the difference between the two parts' rotations. Molecules held by an arm stay where they are,
+//! as do molecules that only partly cover an end (see [`eligibility`](super::eligibility)). Both ends carry at once, so
+//! molecules on either end swap places.
+//!
+//! A molecule is only carried on the cycle it comes to rest on an end: one that arrived through a conduit stays on the
+//! far end until an arm grabs it again, instead of being carried back on the next cycle.
+
+use crate::compat::HashSet;
+use crate::data::{HexIndex, HexRotation, PartType};
+use super::eligibility;
+use super::error::SimError;
+use super::{Sim, SimPartType};
+use crate::compat::prelude::*;
+
+/// One end of a conduit.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SimConduit{
+    /// Which of the puzzle's conduits this is an end of. The other end has the same index.
+    pub index: i32,
+    /// The position of the part, which the footprint is relative to in the solution.
+    pub pos: HexIndex,
+    /// The rotation of the part. A molecule carried from one end to the other turns by the difference.
+    pub rotation: HexRotation,
+    /// The board positions this end covers.
+    pub hexes: Vec<HexIndex>
+}
+
+impl Sim{
+    /// Carry every molecule that came to rest on one end of a conduit to the other end.
+    /// Fails with a collision if a carried molecule lands on an atom that stays put, or on another carried molecule.
+    pub fn transfer_conduits(&mut self) -> Result<(), SimError>{
+        let ends: Vec<&SimConduit> = self.parts.iter().filter_map(|part| match &part.ty{
+            SimPartType::Conduit(conduit) => Some(conduit),
+            _ => None
+        }).collect();
+        // each carried molecule, with the end it leaves, how it turns, and the end it arrives at
+        let mut carried: Vec<(usize, HexIndex, HexRotation, HexIndex)> = Vec::new();
+        for (i, from) in ends.iter().enumerate(){
+            let others: Vec<_> = ends.iter().enumerate().filter(|(j, end)| *j != i && end.index == from.index).collect();
+            // ends without exactly one partner aren't a conduit the game would allow
+            let [(_, to)] = others[..] else { continue };
+            let rotation = to.rotation - from.rotation;
+            let footprint: HashSet<HexIndex> = from.hexes.iter().copied().collect();
+            for (idx, molecule) in self.molecules.iter().enumerate(){
+                if !molecule.carried && eligibility::acts_on(&PartType::Conduit, molecule) && molecule.positions().all(|pos| footprint.contains(&pos))
+                && !carried.iter().any(|(other, ..)| *other == idx){
+                    carried.push((idx, from.pos, rotation, to.pos));
+                }
+            }
+        }
+        if carried.is_empty(){
+            return Ok(());
+        }
+
+        let mut occupied: HashSet<HexIndex> = self.molecules.iter().enumerate()
+            .filter(|(idx, _)| !carried.iter().any(|(other, ..)| other == idx))
+            .flat_map(|(_, molecule)| molecule.positions())
+            .collect();
+        for (idx, from, rotation, to) in carried{
+            let molecule = &mut self.molecules[idx];
+            molecule.rotate(from, rotation);
+            molecule.translate(to - from);
+            molecule.carried = true;
+            for pos in molecule.positions(){
+                if !occupied.insert(pos){
+                    event!(debug, ?pos, "conduit collision");
+                    return Err(SimError::Collision{ cycle: self.cycle, pos });
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,162 @@
+use alloc::collections::BTreeMap;
+use alloc::sync::Arc;
+use core::fmt::{self, Debug, Formatter};
+use core::sync::atomic::{AtomicBool, Ordering};
+use super::collision::CollisionMath;
+use super::output::OutputMatching;
+use crate::compat::prelude::*;
+
+/// Options controlling how a simulation handles unusual solutions.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SimConfig{
+    /// How to handle bonds between non-adjacent atoms.
+    pub quantum_bonds: QuantumBondPolicy,
+    /// How strictly outputs match molecules.
+    pub output_matching: OutputMatching,
+    /// The number of cycles to simulate before giving up on a solution that hasn't completed.
+    pub max_cycles: u64,
+    /// The number of points in time to check for collisions during each cycle's movement.
+    pub collision_substeps: u32,
+    /// How to compute positions while checking for collisions.
+    /// Use [`CollisionMath::FixedPoint`] when verdicts need to be identical across platforms, e.g. for a leaderboard.
+    pub collision_math: CollisionMath,
+    /// The number of each product needed to complete the puzzle, instead of the puzzle's own target
+    /// (six times the product multiplier, or just the multiplier for polymer outputs).
+    pub output_target: Option<u64>,
+    /// Don't check for collisions at all, for solutions already known to be valid where only metrics matter.
+    /// Collision checking dominates the runtime of long simulations.
+    pub skip_collision: bool,
+    /// Simulate values the game never produces but mods can, like arms longer than 3, instead of rejecting them.
+    /// They're simulated as written: a hex arm of length 5 has six grippers 5 hexes away.
+    pub allow_modded_values: bool,
+    /// Called every so many cycles with how far the simulation has got, so long analyses aren't opaque.
+    pub progress: Option<ProgressCallback>,
+    /// If set, simulations stop with [`SimError::Cancelled`](super::error::SimError::Cancelled) at the start of the first cycle
+    /// after this is cancelled.
+    pub cancel: Option<CancelToken>,
+    /// If set, simulations fail with [`SimError::MemoryLimit`](super::error::SimError::MemoryLimit) once the board takes
+    /// roughly more than this many bytes (see [`Sim::memory_usage`](super::Sim::memory_usage)), e.g. because a hostile
+    /// solution keeps pulling molecules from inputs and leaving them lying around.
+    pub max_memory: Option<usize>,
+    /// Record where every atom came from: which input spawned it and which glyphs made or changed it, and when.
+    /// See [`SimMolecule::provenance_at`](super::SimMolecule::provenance_at), [`Sim::consumed`](super::Sim::consumed),
+    /// and the `AtomSpawned` and `AtomTransmuted` [events](super::SimEvent).
+    pub track_provenance: bool,
+    /// The most molecules each reagent may be spawned, by index in the puzzle's reagents, across all of its inputs, for
+    /// challenges like "use at most 6 copies of reagent 1". Once a reagent's quota is used up its inputs stay empty.
+    /// Reagents not listed are unlimited.
+    pub input_quotas: BTreeMap<usize, u64>,
+    /// After every cycle, check that atoms were only created or destroyed by parts that may do so, that every bond joins
+    /// two atoms, and that no two atoms share a hex, failing with [`SimError::Invariant`](super::error::SimError::Invariant)
+    /// if not. These only fail because of bugs in the sim, so this is for testing it, and costs a pass over the board
+    /// per phase.
+    pub check_invariants: bool,
+    /// After a solution completes, keep running it until it settles into a loop, to find the
+    /// [asymptotic metrics](crate::verify::AsymptoticMetrics) the rate leaderboards rank by. This can take up to
+    /// `max_cycles` more cycles, so it's off unless asked for.
+    pub asymptotic: bool
+}
+
+impl Default for SimConfig{
+    fn default() -> Self{
+        SimConfig{
+            quantum_bonds: QuantumBondPolicy::default(),
+            output_matching: OutputMatching::default(),
+            max_cycles: 100_000,
+            collision_substeps: 8,
+            collision_math: CollisionMath::default(),
+            output_target: None,
+            skip_collision: false,
+            allow_modded_values: false,
+            progress: None,
+            cancel: None,
+            max_memory: None,
+            track_provenance: false,
+            input_quotas: BTreeMap::new(),
+            check_invariants: false,
+            asymptotic: false
+        }
+    }
+}
+
+/// How far a simulation has got, as passed to a [`ProgressCallback`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Progress{
+    /// The number of cycles simulated so far.
+    pub cycle: u64,
+    /// The number of each product consumed so far, by product index.
+    pub products: Vec<u64>,
+    /// The number of hexes in the sim's area, the one table that keeps growing as a solution runs.
+    /// Steady states are found without remembering past states, so there's no table of those.
+    pub area: usize
+}
+
+/// A function called with the [`Progress`] of a simulation at the end of every `interval`th cycle.
+///
+/// Analyses that run copies of a sim, like [`find_steady_state`](crate::analysis::throughput::find_steady_state), keep the
+/// callback in each copy, so the cycles reported may jump back and forth between copies.
+#[derive(Clone)]
+pub struct ProgressCallback{
+    pub interval: u64,
+    pub callback: Arc<dyn Fn(&Progress) + Send + Sync>
+}
+
+impl ProgressCallback{
+    pub fn new(interval: u64, callback: impl Fn(&Progress) + Send + Sync + 'static) -> ProgressCallback{
+        ProgressCallback{ interval, callback: Arc::new(callback) }
+    }
+}
+
+impl Debug for ProgressCallback{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result{
+        f.debug_struct("ProgressCallback").field("interval", &self.interval).finish_non_exhaustive()
+    }
+}
+
+// callbacks can't be compared, so configs only match if they share the same one
+impl PartialEq for ProgressCallback{
+    fn eq(&self, other: &Self) -> bool{
+        self.interval == other.interval && Arc::ptr_eq(&self.callback, &other.callback)
+    }
+}
+
+impl Eq for ProgressCallback{}
+
+/// A flag for stopping simulations from another thread, e.g. when a client disconnects or a deadline passes.
+/// Clones share the flag, so keep one and put another in the [`SimConfig`].
+#[derive(Clone, Debug, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken{
+    pub fn new() -> CancelToken{
+        CancelToken::default()
+    }
+
+    /// Stop every simulation using this token, or a clone of it.
+    pub fn cancel(&self){
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool{
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+// like callbacks, tokens are only equal if they share the same flag
+impl PartialEq for CancelToken{
+    fn eq(&self, other: &Self) -> bool{
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for CancelToken{}
+
+/// What to do with bonds between non-adjacent atoms ("quantum bonds"), which can only appear in modded or hand-edited puzzles.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum QuantumBondPolicy{
+    /// Refuse to simulate puzzles containing quantum bonds.
+    #[default]
+    Reject,
+    /// Treat molecules with quantum bonds as rigid bodies, moving all of their atoms together like any other molecule.
+    Rigid
+}
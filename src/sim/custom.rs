@@ -0,0 +1,73 @@
+//! Modded parts, simulated by behaviours that downstream crates register with [`Sim::register_custom_part`].
+//!
+//! A solution's unknown parts become [`SimPartType::Custom`] parts with no behaviour,
+//! and simulating fails with [`SimError::UnsupportedPart`] until every one of them has one.
+
+use alloc::sync::Arc;
+use core::fmt::Debug;
+use crate::data::{HexIndex, HexRotation};
+use super::{Sim, SimMolecule, SimPart, SimPartType};
+use super::error::SimError;
+use crate::compat::prelude::*;
+
+/// How a modded part behaves. Custom parts act like glyphs: once per cycle, on the atoms at rest, after grabs and drops.
+pub trait PartBehavior: Debug + Send + Sync{
+    /// The hexes the part covers when placed at the origin with no rotation.
+    fn footprint(&self) -> Vec<HexIndex>{
+        vec![HexIndex::default()]
+    }
+
+    /// Act on the molecules on the board, for a part at `pos` with `rotation`, returning whether it did anything.
+    /// The helpers in [`molecules`](super::molecules) add and remove atoms and bonds.
+    fn apply(&self, pos: HexIndex, rotation: HexRotation, molecules: &mut Vec<SimMolecule>) -> bool;
+}
+
+/// A part the simulator doesn't know by itself, and how it behaves if that's been registered.
+#[derive(Clone, Debug)]
+pub struct CustomPart{
+    /// The part's name in the solution file.
+    pub name: String,
+    pub behavior: Option<Arc<dyn PartBehavior>>
+}
+
+impl CustomPart{
+    /// The board positions covered by this part placed at `pos` with `rotation`. Without a behaviour, just `pos`.
+    pub fn footprint(&self, pos: HexIndex, rotation: HexRotation) -> Vec<HexIndex>{
+        match &self.behavior{
+            Some(behavior) => behavior.footprint().into_iter().map(|offset| pos + rotation.rotate_offset(offset)).collect(),
+            None => vec![pos]
+        }
+    }
+}
+
+impl Sim{
+    /// Simulate every part named `name` in the solution with the behaviour `factory` makes for it.
+    /// Registering a name again replaces the behaviour.
+    pub fn register_custom_part(&mut self, name: &str, factory: impl Fn(&SimPart) -> Arc<dyn PartBehavior>){
+        for idx in 0..self.parts.len(){
+            let part = &self.parts[idx];
+            if !matches!(&part.ty, SimPartType::Custom(custom) if custom.name == name){
+                continue;
+            }
+            let behavior = factory(part);
+            let part = &mut self.parts[idx];
+            if let SimPartType::Custom(custom) = &mut part.ty{
+                custom.behavior = Some(behavior);
+            }
+            // the part may cover more than its own hex now
+            let footprint = part.footprint();
+            self.area.extend(footprint);
+        }
+    }
+
+    /// Fail if any custom part has no behaviour registered.
+    pub(crate) fn check_custom_parts(&self) -> Result<(), SimError>{
+        match self.parts.iter().find_map(|part| match &part.ty{
+            SimPartType::Custom(CustomPart{ name, behavior: None }) => Some(name),
+            _ => None
+        }){
+            Some(name) => Err(SimError::UnsupportedPart(name.clone())),
+            None => Ok(())
+        }
+    }
+}
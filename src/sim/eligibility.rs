@@ -0,0 +1,102 @@
+//! Which atoms each glyph and output may act on, in one table rather than scattered through their effects.
+//!
+//! The game doesn't treat held atoms the same everywhere: bonders and calcification act on atoms an arm is holding,
+//! but disposal, outputs and conduits only take molecules that have been dropped. Glyphs that consume atoms, like
+//! projection's quicksilver or animismus's salts, only take free atoms, not ones bonded into a molecule. Each effect in
+//! [`glyphs`](super::glyphs), [`output`](super::output) and [`conduit`](super::conduit) asks this module before touching
+//! a molecule.
+
+use crate::data::PartType;
+use super::{SimMolecule, SimPartType};
+
+/// What a glyph or output needs of the atoms it acts on.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Eligibility{
+    /// Whether it acts on molecules held by an arm. If not, it ignores any molecule with a held atom.
+    pub held: bool,
+    /// Whether it reads the atoms on Van Berlo's wheel, which aren't part of any molecule.
+    pub berlo: bool,
+    /// Whether the atoms it consumes must be free, i.e. not bonded to any other atom.
+    pub free: bool
+}
+
+const ANY: Eligibility = Eligibility{ held: true, berlo: false, free: false };
+const DROPPED: Eligibility = Eligibility{ held: false, berlo: false, free: false };
+const CONSUMES_FREE: Eligibility = Eligibility{ held: true, berlo: false, free: true };
+
+/// The eligibility rule for every part that acts on atoms.
+pub const ELIGIBILITY: [(PartType, Eligibility); 16] = [
+    (PartType::Output, DROPPED),
+    (PartType::PolymerOutput, DROPPED),
+    (PartType::Equilibrium, ANY),
+    (PartType::Bonding, ANY),
+    (PartType::MultiBonding, ANY),
+    (PartType::TriplexBonding, ANY),
+    (PartType::Unbonding, ANY),
+    (PartType::Calcification, ANY),
+    (PartType::Duplication, Eligibility{ held: true, berlo: true, free: false }),
+    (PartType::Projection, CONSUMES_FREE),
+    (PartType::Purification, CONSUMES_FREE),
+    (PartType::Animismus, CONSUMES_FREE),
+    (PartType::Unification, CONSUMES_FREE),
+    (PartType::Dispersion, CONSUMES_FREE),
+    (PartType::Disposal, DROPPED),
+    (PartType::Conduit, DROPPED)
+];
+
+/// The kind of part a sim part was made from, for looking it up in [`ELIGIBILITY`].
+/// Outputs are all [`PartType::Output`], since polymer outputs follow the same rule.
+fn kind(ty: &SimPartType) -> Option<PartType>{
+    Some(match ty{
+        SimPartType::Output(_) => PartType::Output,
+        SimPartType::Equilibrium => PartType::Equilibrium,
+        SimPartType::Bonding => PartType::Bonding,
+        SimPartType::MultiBonding => PartType::MultiBonding,
+        SimPartType::TriplexBonding => PartType::TriplexBonding,
+        SimPartType::Unbonding => PartType::Unbonding,
+        SimPartType::Calcification => PartType::Calcification,
+        SimPartType::Duplication => PartType::Duplication,
+        SimPartType::Projection => PartType::Projection,
+        SimPartType::Purification => PartType::Purification,
+        SimPartType::Animismus => PartType::Animismus,
+        SimPartType::Unification => PartType::Unification,
+        SimPartType::Dispersion => PartType::Dispersion,
+        SimPartType::Disposal => PartType::Disposal,
+        SimPartType::Conduit(_) => PartType::Conduit,
+        _ => return None
+    })
+}
+
+/// The rule for a part type, or `None` if it doesn't act on atoms, or only a custom behaviour knows what it does.
+pub fn eligibility(ty: &PartType) -> Option<Eligibility>{
+    ELIGIBILITY.iter().find(|(t, _)| t == ty).map(|(_, e)| *e)
+}
+
+/// The rule for a sim part, see [`eligibility`].
+pub fn part_eligibility(ty: &SimPartType) -> Option<Eligibility>{
+    kind(ty).as_ref().and_then(eligibility)
+}
+
+/// Whether a part of this type may act on this molecule. Parts without a rule, like custom parts, decide for themselves.
+pub fn acts_on(ty: &PartType, molecule: &SimMolecule) -> bool{
+    allows(eligibility(ty), molecule)
+}
+
+/// Whether a sim part may act on this molecule, see [`acts_on`].
+pub fn can_act_on(ty: &SimPartType, molecule: &SimMolecule) -> bool{
+    allows(part_eligibility(ty), molecule)
+}
+
+/// Whether a sim part may consume this molecule's atoms, see [`Eligibility::free`].
+pub fn can_consume(ty: &SimPartType, molecule: &SimMolecule) -> bool{
+    can_act_on(ty, molecule) && (part_eligibility(ty).is_none_or(|e| !e.free) || molecule.layout.atoms.len() == 1)
+}
+
+fn allows(rule: Option<Eligibility>, molecule: &SimMolecule) -> bool{
+    rule.is_none_or(|e| e.held || !molecule.grabbed)
+}
+
+/// Whether a part reads the atoms on Van Berlo's wheel.
+pub fn reads_berlo(ty: &SimPartType) -> bool{
+    part_eligibility(ty).is_some_and(|e| e.berlo)
+}
@@ -0,0 +1,55 @@
+use core::fmt::{Display, Formatter};
+use crate::data::HexIndex;
+use crate::compat::prelude::*;
+
+/// A reason that a solution failed while being simulated.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SimError{
+    /// The solution couldn't be set up for simulation, e.g. because it uses parts that aren't allowed.
+    Setup(&'static str),
+    /// The solution contains a part this library doesn't know, e.g. from a mod, with this name.
+    UnsupportedPart(String),
+    /// Two atoms, or an atom and an arm, collided.
+    Collision{ cycle: u64, pos: HexIndex },
+    /// A molecule held by two grippers was moved in two different ways at once, like the game forbids: by two arms rotating
+    /// around their own bases, by one arm while another held it still, or by an arm pivoting around two grippers.
+    /// Arms are given by number. If the molecule was `held_still`, the first arm moved it and the second held it.
+    /// Arms that move it the same way, like two grippers of one arm rotating together, don't conflict.
+    ConflictingMovement{ cycle: u64, pos: HexIndex, arms: (i32, i32), held_still: bool },
+    /// In a production puzzle, an atom was moved outside of every chamber and conduit.
+    OutOfBounds{ cycle: u64, pos: HexIndex },
+    /// The solution didn't complete within the configured number of cycles.
+    CycleLimit{ cycles: u64 },
+    /// No solution could ever complete, e.g. because the puzzle has no products.
+    NeverCompletes(&'static str),
+    /// The simulation was stopped through its [`CancelToken`](super::config::CancelToken) before this cycle.
+    Cancelled{ cycle: u64 },
+    /// The board took `bytes` bytes during this cycle, more than the configured `limit`.
+    MemoryLimit{ cycle: u64, bytes: usize, limit: usize },
+    /// With [`SimConfig::check_invariants`](super::config::SimConfig::check_invariants), the board became inconsistent
+    /// during this cycle, which is a bug in the sim.
+    Invariant{ cycle: u64, violation: String }
+}
+
+impl Display for SimError{
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result{
+        match self{
+            SimError::Setup(reason) => write!(f, "{reason}"),
+            SimError::UnsupportedPart(name) => write!(f, "contains unsupported part '{name}'"),
+            SimError::Collision{ cycle, pos } => write!(f, "collision at ({}, {}) in cycle {cycle}", pos.q, pos.r),
+            SimError::ConflictingMovement{ cycle, pos, arms: (first, second), held_still } => match (first == second, held_still){
+                (true, _) => write!(f, "arm {first} pivoted the molecule at ({}, {}) around two grippers at once in cycle {cycle}", pos.q, pos.r),
+                (false, true) => write!(f, "arm {first} moved the molecule at ({}, {}) while arm {second} held it still in cycle {cycle}", pos.q, pos.r),
+                (false, false) => write!(f, "arms {first} and {second} moved the molecule at ({}, {}) in two ways at once in cycle {cycle}", pos.q, pos.r)
+            },
+            SimError::OutOfBounds{ cycle, pos } => write!(f, "atom at ({}, {}) left its chamber in cycle {cycle}", pos.q, pos.r),
+            SimError::CycleLimit{ cycles } => write!(f, "solution did not complete within {cycles} cycles"),
+            SimError::NeverCompletes(reason) => write!(f, "solution can never complete: {reason}"),
+            SimError::Cancelled{ cycle } => write!(f, "simulation was cancelled in cycle {cycle}"),
+            SimError::MemoryLimit{ cycle, bytes, limit } => write!(f, "board took {bytes} bytes in cycle {cycle}, more than the memory limit of {limit} bytes"),
+            SimError::Invariant{ cycle, violation } => write!(f, "internal error in cycle {cycle}: {violation}")
+        }
+    }
+}
+
+impl core::error::Error for SimError{}
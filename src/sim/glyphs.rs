@@ -0,0 +1,209 @@
+use crate::data::{Atom, BondType, HexIndex, HexRotation};
+use super::molecules::{add_atom, add_bond, atom_at, molecule_at, remove_atom, remove_bond};
+use super::{SimMolecule, SimPartType};
+use super::custom::CustomPart;
+use super::eligibility;
+use crate::compat::prelude::*;
+
+/// The elements held by Van Berlo's wheel, anticlockwise from the wheel's rotation.
+pub const BERLO_ATOMS: [Atom; 6] = [Atom::Salt, Atom::Water, Atom::Air, Atom::Salt, Atom::Fire, Atom::Earth];
+
+const fn hex(q: i32, r: i32) -> HexIndex{
+    HexIndex{ q, r }
+}
+
+/// The hexes around the centre of unification and dispersion glyphs, and the cardinal elements dispersion produces on each.
+const QUINTESSENCE_RING: [(HexIndex, Atom); 4] = [(hex(0, 1), Atom::Air), (hex(-1, 1), Atom::Water), (hex(0, -1), Atom::Earth), (hex(1, -1), Atom::Fire)];
+
+/// The hexes covered by a glyph at the origin with no rotation, or `None` if this isn't a glyph.
+pub fn glyph_offsets(ty: &SimPartType) -> Option<&'static [HexIndex]>{
+    const SINGLE: &[HexIndex] = &[hex(0, 0)];
+    const PAIR: &[HexIndex] = &[hex(0, 0), hex(1, 0)];
+    const MULTI: &[HexIndex] = &[hex(0, 0), hex(1, 0), hex(0, -1), hex(-1, 1)];
+    const TRIANGLE: &[HexIndex] = &[hex(0, 0), hex(1, 0), hex(0, 1)];
+    const ANIMISMUS: &[HexIndex] = &[hex(0, 0), hex(1, 0), hex(0, 1), hex(1, -1)];
+    const RING: &[HexIndex] = &[hex(0, 0), hex(0, 1), hex(-1, 1), hex(0, -1), hex(1, -1)];
+    const DISPOSAL: &[HexIndex] = &[hex(0, 0), hex(1, 0), hex(0, 1), hex(-1, 1), hex(-1, 0), hex(0, -1), hex(1, -1)];
+    Some(match ty{
+        SimPartType::Equilibrium | SimPartType::Calcification => SINGLE,
+        SimPartType::Bonding | SimPartType::Unbonding | SimPartType::Duplication | SimPartType::Projection => PAIR,
+        SimPartType::MultiBonding => MULTI,
+        SimPartType::TriplexBonding | SimPartType::Purification => TRIANGLE,
+        SimPartType::Animismus => ANIMISMUS,
+        SimPartType::Unification | SimPartType::Dispersion => RING,
+        SimPartType::Disposal => DISPOSAL,
+        _ => return None
+    })
+}
+
+/// The hex at `offset` from a glyph at `pos` with `rotation`.
+fn at(pos: HexIndex, rotation: HexRotation, offset: HexIndex) -> HexIndex{
+    pos + rotation.rotate_offset(offset)
+}
+
+/// The hex one step from `pos` in the direction of `rotation`.
+pub(super) fn step(pos: HexIndex, rotation: HexRotation) -> HexIndex{
+    pos + rotation.to_unit_offset()
+}
+
+/// The atoms held by a Van Berlo's wheel at `pos` with `rotation`, in board positions.
+pub fn berlo_atoms(pos: HexIndex, rotation: HexRotation) -> [(HexIndex, Atom); 6]{
+    core::array::from_fn(|i| (step(pos, rotation + HexRotation::from_unsigned(i as u8)), BERLO_ATOMS[i]))
+}
+
+fn is_empty(molecules: &[SimMolecule], pos: HexIndex) -> bool{
+    molecule_at(molecules, pos).is_none()
+}
+
+/// Whether a glyph of this type may consume the atom at `pos`, which must be `atom`.
+fn consumable(ty: &SimPartType, molecules: &[SimMolecule], pos: HexIndex, atom: Atom) -> bool{
+    molecule_at(molecules, pos).is_some_and(|idx| molecules[idx].atom_at(pos) == atom && eligibility::can_consume(ty, &molecules[idx]))
+}
+
+/// Apply a glyph's effect to the atoms on it, returning whether it did anything.
+pub fn apply(ty: &SimPartType, pos: HexIndex, rotation: HexRotation, molecules: &mut Vec<SimMolecule>, berlo: &[(HexIndex, Atom)]) -> bool{
+    let h = |offset| at(pos, rotation, offset);
+    let berlo = if eligibility::reads_berlo(ty) { berlo } else { &[] };
+    match ty{
+        SimPartType::Bonding => add_bond(molecules, h(hex(0, 0)), h(hex(1, 0)), BondType::Normal),
+        SimPartType::MultiBonding => {
+            let mut any = false;
+            for offset in [hex(1, 0), hex(0, -1), hex(-1, 1)]{
+                any |= add_bond(molecules, pos, h(offset), BondType::Normal);
+            }
+            any
+        }
+        SimPartType::TriplexBonding => triplex_bond(molecules, h(hex(0, 0)), h(hex(1, 0)), h(hex(0, 1))),
+        SimPartType::Unbonding => remove_bond(molecules, h(hex(0, 0)), h(hex(1, 0))),
+        SimPartType::Calcification => calcify(pos, molecules),
+        SimPartType::Duplication => duplicate(pos, rotation, molecules, berlo),
+        SimPartType::Projection => project(molecules, h(hex(0, 0)), h(hex(1, 0))),
+        SimPartType::Purification => purify(molecules, h(hex(0, 0)), h(hex(1, 0)), h(hex(0, 1))),
+        SimPartType::Animismus => animate(molecules, [h(hex(0, 0)), h(hex(1, 0))], h(hex(0, 1)), h(hex(1, -1))),
+        SimPartType::Unification => unify(molecules, pos, QUINTESSENCE_RING.map(|(offset, atom)| (h(offset), atom))),
+        SimPartType::Dispersion => disperse(molecules, pos, QUINTESSENCE_RING.map(|(offset, atom)| (h(offset), atom))),
+        SimPartType::Disposal => dispose(molecules, pos),
+        SimPartType::Custom(CustomPart{ behavior: Some(behavior), .. }) => behavior.apply(pos, rotation, molecules),
+        _ => false
+    }
+}
+
+/// Calcification: turns a cardinal atom on the glyph into salt, returning whether it did.
+/// Atoms on Van Berlo's wheel are part of the wheel rather than any molecule, so they're never affected.
+pub fn calcify(pos: HexIndex, molecules: &mut [SimMolecule]) -> bool{
+    match molecule_at(molecules, pos){
+        Some(idx) if molecules[idx].atom_at(pos).is_cardinal() => {
+            molecules[idx].set_atom(pos, Atom::Salt);
+            true
+        }
+        _ => false
+    }
+}
+
+/// Duplication: turns a salt atom on the glyph's second hex into a copy of the cardinal atom on its first hex,
+/// returning whether it did.
+/// The source atom may be held by Van Berlo's wheel, but the salt atom must be part of a molecule.
+pub fn duplicate(pos: HexIndex, rotation: HexRotation, molecules: &mut [SimMolecule], berlo: &[(HexIndex, Atom)]) -> bool{
+    let target = step(pos, rotation);
+    let source = berlo.iter()
+        .find(|(p, _)| *p == pos)
+        .map(|(_, atom)| *atom)
+        .or_else(|| atom_at(molecules, pos));
+    let Some(source) = source.filter(|a| a.is_cardinal()) else { return false };
+    match molecule_at(molecules, target){
+        Some(idx) if molecules[idx].atom_at(target) == Atom::Salt => {
+            molecules[idx].set_atom(target, source);
+            true
+        }
+        _ => false
+    }
+}
+
+/// Triplex bonding: adds a different colour of triplex bond between each pair of fire atoms on the glyph.
+fn triplex_bond(molecules: &mut Vec<SimMolecule>, a: HexIndex, b: HexIndex, c: HexIndex) -> bool{
+    let pairs = [
+        (a, b, BondType::Triplex{ red: true, black: false, yellow: false }),
+        (a, c, BondType::Triplex{ red: false, black: true, yellow: false }),
+        (b, c, BondType::Triplex{ red: false, black: false, yellow: true })
+    ];
+    let mut any = false;
+    for (x, y, ty) in pairs{
+        if atom_at(molecules, x) == Some(Atom::Fire) && atom_at(molecules, y) == Some(Atom::Fire){
+            any |= add_bond(molecules, x, y, ty);
+        }
+    }
+    any
+}
+
+/// Projection: consumes free quicksilver on the first hex to promote a metal on the second.
+fn project(molecules: &mut Vec<SimMolecule>, quicksilver: HexIndex, metal: HexIndex) -> bool{
+    let Some(next) = atom_at(molecules, metal).and_then(Atom::next_metal) else { return false };
+    if !consumable(&SimPartType::Projection, molecules, quicksilver, Atom::Quicksilver){
+        return false;
+    }
+    let idx = molecule_at(molecules, metal).expect("metal should exist");
+    molecules[idx].set_atom(metal, next);
+    remove_atom(molecules, quicksilver);
+    true
+}
+
+/// Purification: consumes two identical free metals to produce the next metal on an empty output hex.
+fn purify(molecules: &mut Vec<SimMolecule>, a: HexIndex, b: HexIndex, out: HexIndex) -> bool{
+    let Some(metal) = atom_at(molecules, a) else { return false };
+    let Some(next) = metal.next_metal() else { return false };
+    if [a, b].iter().any(|pos| !consumable(&SimPartType::Purification, molecules, *pos, metal)) || !is_empty(molecules, out){
+        return false;
+    }
+    remove_atom(molecules, a);
+    remove_atom(molecules, b);
+    add_atom(molecules, out, next);
+    true
+}
+
+/// Animismus: consumes two free salts to produce vitae and mors on empty output hexes.
+fn animate(molecules: &mut Vec<SimMolecule>, salts: [HexIndex; 2], vitae: HexIndex, mors: HexIndex) -> bool{
+    if salts.iter().any(|s| !consumable(&SimPartType::Animismus, molecules, *s, Atom::Salt)) || !is_empty(molecules, vitae) || !is_empty(molecules, mors){
+        return false;
+    }
+    for salt in salts{
+        remove_atom(molecules, salt);
+    }
+    add_atom(molecules, vitae, Atom::Vitae);
+    add_atom(molecules, mors, Atom::Mors);
+    true
+}
+
+/// Unification: consumes each free cardinal element from its own hex of the ring to produce quintessence on an empty centre.
+fn unify(molecules: &mut Vec<SimMolecule>, centre: HexIndex, ring: [(HexIndex, Atom); 4]) -> bool{
+    if ring.iter().any(|(p, atom)| !consumable(&SimPartType::Unification, molecules, *p, *atom)) || !is_empty(molecules, centre){
+        return false;
+    }
+    for (pos, _) in ring{
+        remove_atom(molecules, pos);
+    }
+    add_atom(molecules, centre, Atom::Quintessence);
+    true
+}
+
+/// Dispersion: consumes free quintessence on the centre to produce each cardinal element on empty outputs.
+fn disperse(molecules: &mut Vec<SimMolecule>, centre: HexIndex, ring: [(HexIndex, Atom); 4]) -> bool{
+    if !consumable(&SimPartType::Dispersion, molecules, centre, Atom::Quintessence) || ring.iter().any(|(p, _)| !is_empty(molecules, *p)){
+        return false;
+    }
+    remove_atom(molecules, centre);
+    for (pos, atom) in ring{
+        add_atom(molecules, pos, atom);
+    }
+    true
+}
+
+/// Disposal: destroys a dropped molecule with an atom on the centre.
+fn dispose(molecules: &mut Vec<SimMolecule>, centre: HexIndex) -> bool{
+    match molecule_at(molecules, centre){
+        Some(idx) if eligibility::can_act_on(&SimPartType::Disposal, &molecules[idx]) => {
+            molecules.swap_remove(idx);
+            true
+        }
+        _ => false
+    }
+}
@@ -0,0 +1,56 @@
+use crate::data::{HexIndex, HexRotation, Molecule, RepeatingMolecule};
+use super::SimMolecule;
+use crate::compat::prelude::*;
+
+/// The state of an input.
+#[derive(Clone, Debug)]
+pub struct SimInput{
+    /// The reagent's index in the puzzle.
+    pub index: usize,
+    /// The reagent as defined by the puzzle, possibly including a repeat marker.
+    pub reagent: Molecule,
+    /// If the reagent repeats infinitely, its repeating unit.
+    pub repeating: Option<RepeatingMolecule>,
+    /// Number of molecules spawned so far, counting each extension of a repeating chain.
+    pub spawned: u64
+}
+
+impl SimInput{
+    pub fn new(index: usize, reagent: Molecule) -> SimInput{
+        SimInput{ index, repeating: reagent.as_repeating(), reagent, spawned: 0 }
+    }
+
+    /// The molecule this input spawns, not yet placed on the board.
+    /// For repeating reagents, this is a single copy of the unit, which is extended as the chain is pulled away.
+    pub fn spawned_molecule(&self) -> Molecule{
+        match &self.repeating{
+            Some(repeating) => repeating.expand(1),
+            None => self.reagent.clone()
+        }
+    }
+
+    /// Try to spawn this input's molecule, if its footprint is clear of atoms, returning whether it did.
+    ///
+    /// If the reagent repeats and the chain it previously spawned has been pulled exactly one copy away,
+    /// the new copy is bonded onto that chain instead of spawning separately, so the chain behaves as if
+    /// it were infinitely long.
+    pub fn spawn(&mut self, pos: HexIndex, rotation: HexRotation, molecules: &mut Vec<SimMolecule>) -> bool{
+        let placed = self.spawned_molecule().placed(pos, rotation);
+        if molecules.iter().any(|m| placed.atoms.keys().any(|p| m.contains_pos(*p))){
+            return false;
+        }
+        self.spawned += 1;
+        if let Some(repeating) = &self.repeating{
+            let offset = rotation.rotate_offset(repeating.offset);
+            let next = placed.translated(offset);
+            let chain = molecules.iter_mut().find(|m| next.atoms.iter().all(|(p, atom)| m.contains_pos(*p) && m.atom_at(*p) == *atom));
+            if let Some(chain) = chain{
+                // include the bonds that connect to the previous copy
+                chain.merge_placed(&repeating.unit.placed(pos, rotation));
+                return true;
+            }
+        }
+        molecules.push(SimMolecule::new(placed.translated(HexIndex::default() - pos), pos));
+        true
+    }
+}
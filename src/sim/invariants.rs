@@ -0,0 +1,93 @@
+//! Checks that the board is consistent after every cycle, turned on by
+//! [`SimConfig::check_invariants`](super::config::SimConfig::check_invariants).
+//!
+//! None of these should ever fail: a failure is a bug in the sim, reported as [`SimError::Invariant`] so it shows up
+//! where it happened instead of as a wrong verdict many cycles later.
+
+use crate::compat::HashMap;
+use crate::data::HexIndex;
+use super::error::SimError;
+use super::{Sim, SimMolecule, SimPartType};
+use crate::compat::prelude::*;
+
+/// How a phase of a cycle may change the number of atoms on the board.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(super) enum AtomChange{
+    /// Moving and grabbing never create or destroy atoms.
+    Unchanged,
+    /// Inputs only add atoms.
+    Created,
+    /// Outputs only remove them.
+    Destroyed
+}
+
+/// Whether a glyph may change the number of atoms on the board when it acts, like disposal or unification.
+/// Custom parts may do anything.
+pub fn changes_atom_count(ty: &SimPartType) -> bool{
+    matches!(ty,
+        SimPartType::Disposal | SimPartType::Projection | SimPartType::Purification | SimPartType::Unification
+        | SimPartType::Dispersion | SimPartType::Custom(_))
+}
+
+/// The number of atoms in these molecules.
+pub fn atom_count(molecules: &[SimMolecule]) -> usize{
+    molecules.iter().map(|molecule| molecule.layout.atoms.len()).sum()
+}
+
+/// Check that every molecule has atoms, every bond joins two of its atoms, and no two atoms share a hex.
+pub fn check_molecules(molecules: &[SimMolecule]) -> Result<(), String>{
+    let mut occupied: HashMap<HexIndex, usize> = HashMap::default();
+    for (idx, molecule) in molecules.iter().enumerate(){
+        if molecule.layout.atoms.is_empty(){
+            return Err(format!("molecule {idx} has no atoms"));
+        }
+        for bond in &molecule.layout.bonds{
+            if !molecule.layout.atoms.contains_key(&bond.start) || !molecule.layout.atoms.contains_key(&bond.end){
+                let (start, end) = (bond.start + molecule.pos, bond.end + molecule.pos);
+                return Err(format!("bond between ({}, {}) and ({}, {}) is missing an atom", start.q, start.r, end.q, end.r));
+            }
+        }
+        for pos in molecule.positions(){
+            if let Some(other) = occupied.insert(pos, idx){
+                return Err(format!("molecules {other} and {idx} overlap at ({}, {})", pos.q, pos.r));
+            }
+        }
+    }
+    Ok(())
+}
+
+impl Sim{
+    /// The number of atoms on the board, if invariants are being checked.
+    pub(super) fn checked_atom_count(&self) -> Option<usize>{
+        self.config.check_invariants.then(|| atom_count(&self.molecules))
+    }
+
+    /// Check that a phase that started with `before` atoms, as counted by [`checked_atom_count`](Sim::checked_atom_count),
+    /// only changed them as it may.
+    pub(super) fn check_atom_change(&self, before: Option<usize>, phase: &str, allowed: AtomChange) -> Result<(), SimError>{
+        let Some(before) = before else { return Ok(()) };
+        let after = atom_count(&self.molecules);
+        let ok = match allowed{
+            AtomChange::Unchanged => after == before,
+            AtomChange::Created => after >= before,
+            AtomChange::Destroyed => after <= before
+        };
+        if ok{
+            Ok(())
+        }else{
+            Err(self.violation(format!("{phase} changed the number of atoms from {before} to {after}")))
+        }
+    }
+
+    /// Check the molecules on the board, if invariants are being checked.
+    pub(super) fn check_invariants(&self) -> Result<(), SimError>{
+        if self.config.check_invariants{
+            check_molecules(&self.molecules).map_err(|violation| self.violation(violation))?;
+        }
+        Ok(())
+    }
+
+    fn violation(&self, violation: String) -> SimError{
+        SimError::Invariant{ cycle: self.cycle, violation }
+    }
+}
@@ -1,15 +1,124 @@
-mod collision;
+//! Simulating solutions cycle by cycle.
+//!
+//! Parts are a closed enum, [`SimPartType`], and each phase of [`Sim::step`] matches on the kinds it cares about.
+//! Parts this library doesn't know are [`SimPartType::Custom`], which defer to a [`custom::PartBehavior`] trait object.
 
-use std::fmt::Debug;
+pub mod arm;
+pub mod collision;
+pub mod completion;
+pub mod conduit;
+pub mod config;
+pub mod custom;
+pub mod eligibility;
+pub mod error;
+pub mod glyphs;
+pub mod input;
+pub mod invariants;
+pub mod molecules;
+pub mod output;
+pub mod patch;
+pub mod path;
+pub mod provenance;
+pub(crate) mod text;
 
-use crate::data::{Atom, Bond, HexIndex, HexRotation, Molecule, Part, PartType, Puzzle, Solution};
+use alloc::collections::BTreeMap;
+use alloc::sync::Arc;
+use crate::compat::{HashMap, HashSet};
+use core::fmt::Debug;
+
+use crate::data::{Atom, Bond, Chamber, HexIndex, HexRotation, Instruction, Molecule, Part, PartType, Puzzle, Solution};
+use arm::{ArmMotion, ArmView, SimArm, SimTrack};
+use collision::{Collider, ColliderType, Movement};
+use completion::{CompletionCriterion, StandardProducts};
+use conduit::SimConduit;
+use config::{CancelToken, Progress, QuantumBondPolicy, SimConfig};
+use custom::CustomPart;
+use error::SimError;
+use input::SimInput;
+use invariants::AtomChange;
+use output::SimOutput;
+use provenance::{ConsumedProduct, Provenance, Transmutation};
+use crate::compat::prelude::*;
 
 // Data types
 
 #[derive(Clone, Debug)]
 pub struct Sim{
     pub parts: Vec<SimPart>,
-    pub molecules: Vec<SimMolecule>
+    pub molecules: Vec<SimMolecule>,
+    pub config: SimConfig,
+    /// Number of cycles simulated so far.
+    pub cycle: u64,
+    /// Number of cycles before the arms' instructions repeat.
+    pub period: u64,
+    /// Number of each product required to complete the puzzle, by product index.
+    pub required: Vec<u64>,
+    /// If the puzzle has been completed, the number of cycles it took.
+    pub completed: Option<u64>,
+    /// What it takes to complete the puzzle, normally producing every required product.
+    pub criterion: Arc<dyn CompletionCriterion>,
+    /// In a production puzzle, the chambers that atoms must stay in, except while in a conduit.
+    /// Atoms can go anywhere if this is empty.
+    pub chambers: Vec<Chamber>,
+    /// Every hex that any part or atom has occupied so far.
+    pub area: HashSet<HexIndex>,
+    /// Notable things that happened so far, in order.
+    pub events: Vec<SimEvent>,
+    /// The number of atoms of each element destroyed by disposal glyphs so far.
+    pub wasted: BTreeMap<Atom, u64>,
+    /// Every product consumed so far and where its atoms came from. Empty unless [`SimConfig::track_provenance`] is on.
+    pub consumed: Vec<ConsumedProduct>
+}
+
+/// Something notable that happened during a simulation, recorded so results can be explained afterwards.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SimEvent{
+    /// An output consumed a product during this cycle.
+    ProductConsumed{ cycle: u64, product: usize },
+    /// The area grew to this many hexes during this cycle.
+    AreaGrew{ cycle: u64, area: usize },
+    /// The glyph at this index in [`Sim::parts`] acted on atoms during this cycle.
+    GlyphApplied{ cycle: u64, part: usize },
+    /// The input at this index in [`Sim::parts`] spawned an atom at this position during this cycle.
+    /// Only recorded when [`SimConfig::track_provenance`] is on.
+    AtomSpawned{ cycle: u64, part: usize, pos: HexIndex },
+    /// The glyph at this index in [`Sim::parts`] made or changed the atom at this position during this cycle,
+    /// see [`SimMolecule::provenance_at`]. Only recorded when [`SimConfig::track_provenance`] is on.
+    AtomTransmuted{ cycle: u64, part: usize, pos: HexIndex }
+}
+
+/// Why [`Sim::run_until_break`] stopped.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Stop{
+    /// The breakpoint matched this event, or the board at the end of a cycle if `None`.
+    Break(Option<SimEvent>),
+    /// The puzzle was completed in this many cycles.
+    Completed(u64)
+}
+
+/// Progress towards completing the puzzle at the end of a cycle.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CycleReport{
+    /// The cycle that just ran.
+    pub cycle: u64,
+    /// Progress on each product, by product index.
+    pub products: Vec<ProductProgress>,
+    /// If the puzzle has been completed, the number of cycles it took.
+    pub completed: Option<u64>
+}
+
+/// How many of a product have been consumed, out of how many are needed.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ProductProgress{
+    pub product: usize,
+    pub consumed: u64,
+    pub required: u64
+}
+
+impl ProductProgress{
+    pub fn is_satisfied(&self) -> bool{
+        self.consumed >= self.required
+    }
 }
 
 // it's like Molecule but we copy less and offset more
@@ -17,10 +126,18 @@ pub struct Sim{
 pub struct SimMolecule{
     pub layout: Molecule,
     pub pos: HexIndex,
-    pub grabbed: bool
+    pub grabbed: bool,
+    /// Whether the molecule arrived through a conduit and hasn't been grabbed since, so conduits leave it where it is.
+    pub carried: bool,
+    /// Where each atom came from, relative to `pos` like the layout. Empty unless [`SimConfig::track_provenance`] is on.
+    pub provenance: HashMap<HexIndex, Provenance>
 }
 
 impl SimMolecule{
+    pub fn new(layout: Molecule, pos: HexIndex) -> SimMolecule{
+        SimMolecule{ layout, pos, grabbed: false, carried: false, provenance: HashMap::default() }
+    }
+
     pub fn contains_pos(&self, pos: HexIndex) -> bool{
         self.layout.contains_pos(pos - self.pos)
     }
@@ -29,28 +146,68 @@ impl SimMolecule{
         self.layout.atoms[&(pos - self.pos)]
     }
 
+    pub fn set_atom(&mut self, pos: HexIndex, atom: Atom){
+        self.layout.atoms.insert(pos - self.pos, atom);
+    }
+
+    /// The board positions of every atom in this molecule.
+    pub fn positions(&self) -> impl Iterator<Item = HexIndex> + '_{
+        self.layout.atoms.keys().map(|p| *p + self.pos)
+    }
+
+    /// The bond between these two positions, if any, relative to the board.
+    /// The positions need not be adjacent, to support quantum bonds.
     pub fn bond_at(&self, pos_a: HexIndex, pos_b: HexIndex) -> Option<Bond>{
-        todo!()
+        let (a, b) = (pos_a - self.pos, pos_b - self.pos);
+        self.layout.bonds.iter()
+            .find(|bond| bond.connects(a, b))
+            .map(|bond| Bond{ start: bond.start + self.pos, end: bond.end + self.pos, ty: bond.ty })
     }
 
-    // also wrong, should accept other molecule's position and rotation
-    pub fn is(&self, m: &Molecule) -> bool{
-        // if two molecules have the same number of atoms, bond layout, & the first contains all the atoms of the latter, they're the same
-        if self.layout.atoms.len() != m.atoms.len(){
-            return false
-        }
+    /// Add the atoms and bonds of a molecule given in board positions to this molecule.
+    pub fn merge_placed(&mut self, placed: &Molecule){
+        let offset = self.pos;
+        let relative = placed.mapped_positions(|p| p - offset);
+        self.layout.atoms.extend(relative.atoms);
+        self.layout.bonds.extend(relative.bonds);
+    }
+
+    pub fn translate(&mut self, by: HexIndex){
+        self.pos += by;
+    }
 
-        if self.layout.bonds != m.bonds{
-            return false;
+    /// Rotate this molecule around a point on the board.
+    /// Every atom and bond moves together, so bonds between non-adjacent atoms keep their relative layout.
+    pub fn rotate(&mut self, around: HexIndex, by: HexRotation){
+        // rotation is linear, so the offset and the layout can be rotated separately
+        self.pos = self.pos.rotated(around, by);
+        self.layout = self.layout.rotated(HexIndex::default(), by);
+        if !self.provenance.is_empty(){
+            self.provenance = provenance::rotated(core::mem::take(&mut self.provenance), by);
         }
+    }
 
-        for atom in &m.atoms{
-            if !self.contains_pos(*atom.0) || self.atom_at(*atom.0) != *atom.1{
-                return false
-            }
+    fn apply(&mut self, movement: MoleculeMovement){
+        match movement{
+            MoleculeMovement::Rotate{ around, by } => self.rotate(around, by),
+            MoleculeMovement::Translate{ by } => self.translate(by)
         }
+    }
+}
+
+/// A movement of a held molecule during a cycle.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum MoleculeMovement{
+    Rotate{ around: HexIndex, by: HexRotation },
+    Translate{ by: HexIndex }
+}
 
-        return true;
+impl MoleculeMovement{
+    fn of_atom(self, start: HexIndex) -> Movement{
+        match self{
+            MoleculeMovement::Rotate{ around, by } => Movement::Rotate{ start, around, rotation: by },
+            MoleculeMovement::Translate{ by } => Movement::Translate{ start, end: start + by }
+        }
     }
 }
 
@@ -62,20 +219,588 @@ pub struct AtomLookupResult<'a>{
 
 impl Sim{
     pub fn create(puzzle: &Puzzle, solution: &Solution) -> Result<Sim, &'static str>{
+        Self::create_with_config(puzzle, solution, SimConfig::default())
+    }
+
+    pub fn create_with_config(puzzle: &Puzzle, solution: &Solution, config: SimConfig) -> Result<Sim, &'static str>{
+        if config.quantum_bonds == QuantumBondPolicy::Reject
+        && puzzle.reagents.iter().chain(&puzzle.products).any(Molecule::has_quantum_bonds){
+            return Err("puzzle contains bonds between non-adjacent atoms");
+        }
+        check_arm_lengths(&solution.parts, &config)?;
         let sol_clean = puzzle.clean_solution(solution)?;
-        Ok(Sim{
-            parts: sol_clean.parts.iter().map(|p| SimPart::from_solution_part(p, puzzle, solution)).collect::<Result<Vec<_>, _>>()?,
-            molecules: Vec::new()
+        let mut parts = sol_clean.parts.iter().map(|p| SimPart::from_solution_part(p, puzzle, solution)).collect::<Result<Vec<_>, _>>()?;
+        prepare_arms(&mut parts)?;
+
+        // every product needs six times the multiplier, except polymers, which are each worth six
+        let mut required = vec![6 * puzzle.product_multiplier.max(0) as u64; puzzle.products.len()];
+        for part in &sol_clean.parts{
+            if part.ty == PartType::PolymerOutput{
+                required[part.index as usize] = puzzle.product_multiplier.max(0) as u64;
+            }
+        }
+        if let Some(target) = config.output_target{
+            required.iter_mut().for_each(|r| *r = target);
+        }
+
+        let mut sim = Sim::new(parts, Vec::new(), config);
+        sim.required = required;
+        if let Some(info) = &puzzle.production_info{
+            sim.chambers = info.chambers.clone();
+        }
+        sim.spawn_inputs();
+        sim.record_area(Vec::new());
+        Ok(sim)
+    }
+
+    /// Create a board without a puzzle, from solution parts and molecules placed directly on it in board positions,
+    /// e.g. for testing a glyph or building a playground.
+    ///
+    /// Inputs and outputs need a puzzle's molecules, so they aren't allowed, and nothing checks the parts against a
+    /// puzzle's permissions. With no products, a sandbox never completes: drive it with [`step`](Sim::step) or
+    /// [`run_until`](Sim::run_until) rather than [`run`](Sim::run).
+    pub fn sandbox(parts: &[Part], molecules: &[Molecule], config: SimConfig) -> Result<Sim, &'static str>{
+        if parts.iter().any(|part| matches!(part.ty, PartType::Input | PartType::Output | PartType::PolymerOutput)){
+            return Err("sandboxes can't have inputs or outputs, which need a puzzle");
+        }
+        if molecules.iter().any(|molecule| !molecule.validate(config.quantum_bonds == QuantumBondPolicy::Rigid).is_empty()){
+            return Err("sandbox contains an invalid molecule");
+        }
+        let mut positions = HashSet::default();
+        if !molecules.iter().flat_map(|molecule| molecule.atoms.keys()).all(|pos| positions.insert(*pos)){
+            return Err("sandbox contains overlapping molecules");
+        }
+        check_arm_lengths(parts, &config)?;
+        let tape_start = parts.iter().flat_map(|p| p.instructions.iter().map(|(_, idx)| *idx)).min().map_or(0, |min| min.min(0));
+        let mut sim_parts = parts.iter().map(|part| SimPart{
+            pos: part.pos,
+            rotation: HexRotation::from_signed(part.rotation),
+            ty: SimPartType::without_puzzle(part, tape_start).expect("inputs and outputs were rejected")
+        }).collect::<Vec<_>>();
+        prepare_arms(&mut sim_parts)?;
+        let molecules = molecules.iter().map(|molecule| SimMolecule::new(molecule.clone(), HexIndex::default())).collect();
+        let mut sim = Sim::new(sim_parts, molecules, config);
+        sim.record_area(Vec::new());
+        Ok(sim)
+    }
+
+    /// Create a board from already-placed parts and molecules, with no required products.
+    pub fn new(parts: Vec<SimPart>, molecules: Vec<SimMolecule>, config: SimConfig) -> Sim{
+        let period = tape_period(&parts);
+        let area: HashSet<HexIndex> = parts.iter().flat_map(SimPart::footprint).collect();
+        let events = vec![SimEvent::AreaGrew{ cycle: 0, area: area.len() }];
+        Sim{ parts, molecules, config, cycle: 0, period, required: Vec::new(), completed: None, criterion: Arc::new(StandardProducts), chambers: Vec::new(), area, events, wasted: BTreeMap::new(), consumed: Vec::new() }
+    }
+
+    /// Spawn a new molecule from each input whose footprint is clear, unless its reagent's
+    /// [quota](SimConfig::input_quotas) is used up.
+    pub fn spawn_inputs(&mut self){
+        let mut spawned = self.spawned();
+        let Sim{ parts, molecules, config, cycle, events, .. } = self;
+        for (idx, SimPart{ pos, rotation, ty }) in parts.iter_mut().enumerate(){
+            if let SimPartType::Input(input) = ty{
+                let used = spawned.entry(input.index).or_insert(0);
+                if config.input_quotas.get(&input.index).is_some_and(|quota| *used >= *quota){
+                    continue;
+                }
+                if input.spawn(*pos, *rotation, molecules){
+                    *used += 1;
+                    event!(trace, ?pos, "spawned input");
+                    if config.track_provenance{
+                        for atom in input.spawned_molecule().placed(*pos, *rotation).atoms.into_keys(){
+                            let molecule = molecules::molecule_at(molecules, atom).expect("spawned atom should exist");
+                            *molecules[molecule].provenance_mut(atom) = Provenance{ spawned: Some((idx, *cycle)), transmutations: Vec::new() };
+                            events.push(SimEvent::AtomSpawned{ cycle: *cycle, part: idx, pos: atom });
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// The number of molecules spawned from each reagent so far, by index in the puzzle's reagents, across every input of it.
+    /// Each extension of a repeating chain counts as one.
+    pub fn spawned(&self) -> BTreeMap<usize, u64>{
+        let mut spawned = BTreeMap::new();
+        for part in &self.parts{
+            if let SimPartType::Input(input) = &part.ty{
+                *spawned.entry(input.index).or_insert(0) += input.spawned;
+            }
+        }
+        spawned
+    }
+
+    /// The atoms held by every Van Berlo's wheel on the board, in board positions.
+    pub fn berlo_atoms(&self) -> Vec<(HexIndex, Atom)>{
+        self.parts.iter()
+            .filter(|p| matches!(&p.ty, SimPartType::Arm(arm) if arm.ty == PartType::Berlo))
+            .flat_map(|p| glyphs::berlo_atoms(p.pos, p.rotation))
+            .collect()
+    }
+
+    /// Apply the effects of every glyph to the atoms currently on it.
+    /// With [`SimConfig::check_invariants`], fails as soon as a glyph that may not create or destroy atoms does so.
+    pub fn apply_glyphs(&mut self) -> Result<(), SimError>{
+        let berlo = self.berlo_atoms();
+        let Sim{ parts, molecules, config, wasted, cycle, events, .. } = self;
+        for (idx, part) in parts.iter().enumerate(){
+            let _span = span!(trace_span, "part", pos = ?part.pos);
+            // compare the atoms on the glyph before and after, rather than having every effect report what it changed
+            let before: Vec<(HexIndex, Option<Atom>)> = if config.track_provenance && (glyphs::glyph_offsets(&part.ty).is_some() || matches!(part.ty, SimPartType::Custom(_))){
+                part.footprint().into_iter().map(|pos| (pos, molecules::atom_at(molecules, pos))).collect()
+            }else{
+                Vec::new()
+            };
+            // count what a disposal is about to destroy, if it's allowed to
+            let disposed: Vec<Atom> = match (&part.ty, molecules::molecule_at(molecules, part.pos)){
+                (SimPartType::Disposal, Some(idx)) if eligibility::can_act_on(&part.ty, &molecules[idx]) => molecules[idx].layout.atoms.values().copied().collect(),
+                _ => Vec::new()
+            };
+            let atoms = config.check_invariants.then(|| invariants::atom_count(molecules));
+            if glyphs::apply(&part.ty, part.pos, part.rotation, molecules, &berlo){
+                if let Some(before) = atoms{
+                    let after = invariants::atom_count(molecules);
+                    if after != before && !invariants::changes_atom_count(&part.ty){
+                        let violation = format!("{:?} at ({}, {}) changed the number of atoms from {before} to {after}", part.ty, part.pos.q, part.pos.r);
+                        return Err(SimError::Invariant{ cycle: *cycle, violation });
+                    }
+                }
+                for atom in disposed{
+                    *wasted.entry(atom).or_default() += 1;
+                }
+                events.push(SimEvent::GlyphApplied{ cycle: *cycle, part: idx });
+                event!(trace, glyph = ?part.ty, "glyph applied");
+                for (pos, from) in before{
+                    let Some(molecule) = molecules::molecule_at(molecules, pos) else { continue };
+                    let to = molecules[molecule].atom_at(pos);
+                    if from != Some(to){
+                        molecules[molecule].provenance_mut(pos).transmutations.push(Transmutation{ part: idx, cycle: *cycle, from, to });
+                        events.push(SimEvent::AtomTransmuted{ cycle: *cycle, part: idx, pos });
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Consume matching molecules on every output.
+    pub fn consume_outputs(&mut self){
+        let Sim{ parts, molecules, config, cycle, events, consumed, .. } = self;
+        for SimPart{ pos, rotation, ty } in parts.iter_mut(){
+            if let SimPartType::Output(output) = ty{
+                if let Some(molecule) = output.take(*pos, *rotation, molecules, config.output_matching){
+                    if config.track_provenance{
+                        consumed.push(molecule.consumed(*cycle, output.index));
+                    }
+                    events.push(SimEvent::ProductConsumed{ cycle: *cycle, product: output.index });
+                    event!(debug, product = output.index, consumed = output.consumed, "consumed product");
+                }
+            }
+        }
+    }
+
+    /// The number of each product consumed so far, by product index.
+    pub fn product_counts(&self) -> Vec<u64>{
+        let mut counts = vec![0; self.required.len()];
+        for part in &self.parts{
+            if let SimPartType::Output(output) = &part.ty{
+                if let Some(count) = counts.get_mut(output.index){
+                    *count += output.consumed;
+                }
+            }
+        }
+        counts
+    }
+
+    /// Progress on each product, by product index.
+    pub fn progress(&self) -> Vec<ProductProgress>{
+        self.product_counts().into_iter().zip(&self.required).enumerate()
+            .map(|(product, (consumed, required))| ProductProgress{ product, consumed, required: *required })
+            .collect()
+    }
+
+    /// Progress towards completion as of the last cycle that ran.
+    pub fn report(&self) -> CycleReport{
+        CycleReport{ cycle: self.cycle.saturating_sub(1), products: self.progress(), completed: self.completed }
+    }
+
+    /// Whether the completion criterion is met as of the current cycle.
+    pub fn is_complete(&self) -> bool{
+        self.criterion.is_met(self)
+    }
+
+    /// Simulate until the puzzle is completed, returning the number of cycles it took.
+    /// Fails straight away if it can never be completed, e.g. if the puzzle has no products.
+    pub fn run(&mut self) -> Result<u64, SimError>{
+        self.criterion.check_possible(self).map_err(SimError::NeverCompletes)?;
+        loop{
+            if let Some(cycles) = self.completed{
+                return Ok(cycles);
+            }
+            if self.cycle >= self.config.max_cycles{
+                event!(debug, cycles = self.cycle, "hit cycle limit");
+                return Err(SimError::CycleLimit{ cycles: self.cycle });
+            }
+            self.step()?;
+        }
+    }
+
+    /// Simulate until `cycle` cycles have run, whether or not the puzzle completes along the way.
+    /// Does nothing if the simulation is already past that point.
+    ///
+    /// Clone the sim to keep a snapshot, so later seeks can resume from it instead of starting over.
+    pub fn run_until(&mut self, cycle: u64) -> Result<(), SimError>{
+        while self.cycle < cycle{
+            self.step()?;
+        }
+        Ok(())
+    }
+
+    /// Simulate until `breakpoint` returns `true` or the puzzle is completed.
+    ///
+    /// After each cycle, `breakpoint` is called with each event from that cycle, then once with `None` to check the board itself,
+    /// e.g. for a molecule count or an atom appearing at a hex. Running again resumes after the cycle that stopped.
+    pub fn run_until_break(&mut self, mut breakpoint: impl FnMut(&Sim, Option<&SimEvent>) -> bool) -> Result<Stop, SimError>{
+        loop{
+            if let Some(cycles) = self.completed{
+                return Ok(Stop::Completed(cycles));
+            }
+            if self.cycle >= self.config.max_cycles{
+                return Err(SimError::CycleLimit{ cycles: self.cycle });
+            }
+            let seen = self.events.len();
+            self.step()?;
+            for idx in seen..self.events.len(){
+                let event = self.events[idx];
+                if breakpoint(self, Some(&event)){
+                    return Ok(Stop::Break(Some(event)));
+                }
+            }
+            if breakpoint(self, None){
+                return Ok(Stop::Break(None));
+            }
+        }
+    }
+
+    /// Every arm on the board, with the part that places it.
+    pub fn arms(&self) -> impl Iterator<Item = (&SimPart, &SimArm)> + '_{
+        self.parts.iter().filter_map(|part| match &part.ty{
+            SimPartType::Arm(arm) => Some((part, arm)),
+            _ => None
+        })
+    }
+
+    /// The current state of the arm with this number, if there is one.
+    pub fn arm(&self, number: i32) -> Option<ArmView>{
+        let (part, arm) = self.parts.iter().enumerate().find_map(|(idx, part)| match &part.ty{
+            SimPartType::Arm(arm) if arm.number == number => Some((idx, arm)),
+            _ => None
+        })?;
+        let SimPart{ pos, rotation, .. } = self.parts[part];
+        let grippers = arm.grippers(pos, rotation);
+        let held = grippers.iter().zip(&arm.holding)
+            .map(|(gripper, holding)| if *holding{ molecules::molecule_at(&self.molecules, *gripper) }else{ None })
+            .collect();
+        Some(ArmView{
+            part, number, pos, rotation,
+            length: arm.length,
+            track_index: arm.track.as_ref().and_then(|track| track.hexes.iter().position(|hex| *hex == pos)),
+            grabbing: arm.grabbing,
+            grippers, held,
+            next: arm.instruction_at(self.cycle, self.period)
+        })
+    }
+
+    /// The molecules currently on the board.
+    pub fn molecules(&self) -> &[SimMolecule]{
+        &self.molecules
+    }
+
+    /// Every output on the board, with the part that places it, including how many products it has consumed.
+    pub fn outputs(&self) -> impl Iterator<Item = (&SimPart, &SimOutput)> + '_{
+        self.parts.iter().filter_map(|part| match &part.ty{
+            SimPartType::Output(output) => Some((part, output)),
+            _ => None
         })
     }
 
+    /// Simulate a single cycle, returning the progress towards completion at its end.
+    ///
+    /// Grabs and drops happen at the start of the cycle, then glyphs act on the atoms at rest, conduits carry dropped
+    /// molecules to their other ends, and outputs take their products, then everything moves (checking for collisions), and finally inputs spawn into any free space.
+    pub fn step(&mut self) -> Result<CycleReport, SimError>{
+        let _span = span!(debug_span, "cycle", cycle = self.cycle);
+        if self.config.cancel.as_ref().is_some_and(CancelToken::is_cancelled){
+            return Err(SimError::Cancelled{ cycle: self.cycle });
+        }
+        self.check_custom_parts()?;
+        let motions = self.run_instructions();
+        self.update_grabbed();
+
+        self.apply_glyphs()?;
+        let atoms = self.checked_atom_count();
+        self.transfer_conduits()?;
+        self.check_atom_change(atoms, "conduits", AtomChange::Unchanged)?;
+        let atoms = self.checked_atom_count();
+        self.consume_outputs();
+        self.check_atom_change(atoms, "outputs", AtomChange::Destroyed)?;
+        self.release_missing();
+        self.update_grabbed();
+        if self.completed.is_none() && self.is_complete(){
+            self.completed = Some(self.cycle + 1);
+            event!(debug, cycles = self.cycle + 1, "completed");
+        }
+
+        let atoms = self.checked_atom_count();
+        let swept = self.move_all(&motions)?;
+        self.check_atom_change(atoms, "movement", AtomChange::Unchanged)?;
+        self.check_chambers()?;
+        let atoms = self.checked_atom_count();
+        self.spawn_inputs();
+        self.check_atom_change(atoms, "inputs", AtomChange::Created)?;
+        self.check_invariants()?;
+        self.record_area(swept);
+        self.check_memory()?;
+        self.cycle += 1;
+        self.report_progress();
+        Ok(self.report())
+    }
+
+    /// Roughly how many bytes the board takes: its molecules and area.
+    /// The [events](Sim::events) log isn't counted, since it grows with every cycle of any solution, not just ones that
+    /// fill the board.
+    pub fn memory_usage(&self) -> usize{
+        let molecules: usize = self.molecules.iter().map(|molecule| {
+            size_of::<SimMolecule>()
+                + molecule.layout.atoms.len() * size_of::<(HexIndex, Atom)>()
+                + molecule.layout.bonds.len() * size_of::<Bond>()
+        }).sum();
+        molecules + self.area.len() * size_of::<HexIndex>()
+    }
+
+    fn check_memory(&self) -> Result<(), SimError>{
+        let Some(limit) = self.config.max_memory else { return Ok(()) };
+        match self.memory_usage(){
+            bytes if bytes > limit => Err(SimError::MemoryLimit{ cycle: self.cycle, bytes, limit }),
+            _ => Ok(())
+        }
+    }
+
+    fn report_progress(&self){
+        let Some(progress) = &self.config.progress else { return };
+        if self.cycle.is_multiple_of(progress.interval.max(1)){
+            (progress.callback)(&Progress{ cycle: self.cycle, products: self.product_counts(), area: self.area.len() });
+        }
+    }
+
+    /// Run each arm's instruction for this cycle, performing grabs and drops, and returning each part's movement.
+    fn run_instructions(&mut self) -> Vec<Option<ArmMotion>>{
+        let Sim{ parts, molecules, cycle, period, .. } = self;
+        parts.iter_mut().map(|part| {
+            let SimPartType::Arm(arm) = &mut part.ty else { return None };
+            let _span = span!(trace_span, "part", pos = ?part.pos, arm = arm.number);
+            let instr = arm.instruction_at(*cycle, *period);
+            event!(trace, ?instr);
+            match instr{
+                Instruction::Grab => {
+                    if !arm.grabbing{
+                        arm.grabbing = true;
+                        let grippers = arm.grippers(part.pos, part.rotation);
+                        arm.holding = grippers.iter().map(|g| molecules::molecule_at(molecules, *g).is_some()).collect();
+                    }
+                    None
+                }
+                Instruction::Drop => {
+                    arm.grabbing = false;
+                    arm.holding.iter_mut().for_each(|h| *h = false);
+                    None
+                }
+                instr => arm.motion(instr, part.pos)
+            }
+        }).collect()
+    }
+
+    /// Every gripper that is holding an atom, as the index of its part, its position, and its direction.
+    fn holding_grippers(&self) -> Vec<(usize, HexIndex, HexRotation)>{
+        let mut result = Vec::new();
+        for (idx, part) in self.parts.iter().enumerate(){
+            if let SimPartType::Arm(arm) = &part.ty{
+                let grippers = arm.grippers(part.pos, part.rotation);
+                let offsets = SimArm::gripper_offsets(&arm.ty);
+                for ((pos, offset), holding) in grippers.into_iter().zip(offsets).zip(&arm.holding){
+                    if *holding{
+                        result.push((idx, pos, part.rotation + *offset));
+                    }
+                }
+            }
+        }
+        result
+    }
+
+    fn update_grabbed(&mut self){
+        let held: Vec<HexIndex> = self.holding_grippers().into_iter().map(|(_, pos, _)| pos).collect();
+        for molecule in &mut self.molecules{
+            molecule.grabbed = held.iter().any(|p| molecule.contains_pos(*p));
+            molecule.carried &= !molecule.grabbed;
+        }
+    }
+
+    /// Open any grippers whose atom was consumed by a glyph.
+    fn release_missing(&mut self){
+        let Sim{ parts, molecules, .. } = self;
+        for part in parts.iter_mut(){
+            if let SimPartType::Arm(arm) = &mut part.ty{
+                let grippers = arm.grippers(part.pos, part.rotation);
+                for (pos, holding) in grippers.into_iter().zip(arm.holding.iter_mut()){
+                    *holding &= molecules::molecule_at(molecules, pos).is_some();
+                }
+            }
+        }
+    }
+
+    /// Move every arm and held molecule, checking for collisions along the way.
+    /// Returns every hex that the atoms of rotating molecules passed through on their way, which counts towards area.
+    fn move_all(&mut self, motions: &[Option<ArmMotion>]) -> Result<Vec<HexIndex>, SimError>{
+        let cycle = self.cycle;
+        let mut movements: HashMap<usize, Option<MoleculeMovement>> = HashMap::default();
+        // the number of the first arm found holding each molecule, to name in conflicts
+        let mut holders: HashMap<usize, i32> = HashMap::default();
+        for (part_idx, gripper, direction) in self.holding_grippers(){
+            let SimPartType::Arm(arm) = &self.parts[part_idx].ty else { continue };
+            let base = self.parts[part_idx].pos;
+            let movement = motions[part_idx].map(|motion| match motion{
+                ArmMotion::Rotate(by) => MoleculeMovement::Rotate{ around: base, by },
+                ArmMotion::Extend(by) => MoleculeMovement::Translate{ by: direction.to_unit_offset().scaled(by) },
+                ArmMotion::Pivot(by) => MoleculeMovement::Rotate{ around: gripper, by },
+                ArmMotion::Move(to) => MoleculeMovement::Translate{ by: to - base }
+            });
+            let Some(molecule) = molecules::molecule_at(&self.molecules, gripper) else { continue };
+            match movements.get(&molecule){
+                Some(existing) if *existing != movement => {
+                    event!(debug, ?gripper, "conflicting movement");
+                    let holder = holders[&molecule];
+                    let held_still = existing.is_none() || movement.is_none();
+                    // name the arm that moved the molecule first if the other held it still
+                    let arms = if existing.is_none(){ (arm.number, holder) }else{ (holder, arm.number) };
+                    return Err(SimError::ConflictingMovement{ cycle, pos: gripper, arms, held_still });
+                }
+                _ => {
+                    movements.insert(molecule, movement);
+                    holders.entry(molecule).or_insert(arm.number);
+                }
+            }
+        }
+
+        if !self.config.skip_collision{
+            self.check_collisions(motions, &movements)?;
+        }
+
+        let mut swept = Vec::new();
+        for (idx, movement) in movements{
+            if let Some(movement) = movement{
+                if let MoleculeMovement::Rotate{ .. } = movement{
+                    swept.extend(self.molecules[idx].positions().flat_map(|pos| movement.of_atom(pos).swept_hexes()));
+                }
+                self.molecules[idx].apply(movement);
+            }
+        }
+        for (part, motion) in self.parts.iter_mut().zip(motions){
+            let SimPartType::Arm(arm) = &mut part.ty else { continue };
+            match motion{
+                Some(ArmMotion::Rotate(by)) => {
+                    // a piston's shaft sweeps through the hexes between its old and new positions, like atoms do
+                    let around = part.pos;
+                    swept.extend(arm.shaft(part.pos, part.rotation).into_iter()
+                        .flat_map(|start| Movement::Rotate{ start, around, rotation: *by }.swept_hexes()));
+                    part.rotation += *by;
+                }
+                Some(ArmMotion::Extend(by)) => arm.extend(*by),
+                Some(ArmMotion::Move(to)) => part.pos = *to,
+                Some(ArmMotion::Pivot(_)) | None => {}
+            }
+        }
+        Ok(swept)
+    }
+
+    /// Check this cycle's movements of every atom and arm base for collisions.
+    fn check_collisions(&self, motions: &[Option<ArmMotion>], movements: &HashMap<usize, Option<MoleculeMovement>>) -> Result<(), SimError>{
+        let mut colliders = Vec::new();
+        for (idx, molecule) in self.molecules.iter().enumerate(){
+            let movement = movements.get(&idx).copied().flatten();
+            for pos in molecule.positions(){
+                let movement = movement.map_or(Movement::Stay{ at: pos }, |m| m.of_atom(pos));
+                colliders.push(Collider{ ty: ColliderType::Atom, movement, body: idx });
+            }
+        }
+        for (idx, part) in self.parts.iter().enumerate(){
+            let SimPartType::Arm(arm) = &part.ty else { continue };
+            let body = self.molecules.len() + idx;
+            let movement = match motions[idx]{
+                Some(ArmMotion::Move(to)) => Movement::Translate{ start: part.pos, end: to },
+                _ => Movement::Stay{ at: part.pos }
+            };
+            colliders.push(Collider{ ty: ColliderType::ArmBase, movement, body });
+            if arm.ty == PartType::Berlo{
+                for (pos, _) in glyphs::berlo_atoms(part.pos, part.rotation){
+                    let movement = match motions[idx]{
+                        Some(ArmMotion::Rotate(by)) => Movement::Rotate{ start: pos, around: part.pos, rotation: by },
+                        _ => Movement::Stay{ at: pos }
+                    };
+                    colliders.push(Collider{ ty: ColliderType::Atom, movement, body });
+                }
+            }
+        }
+        if let Some(collision) = collision::find_collision_with(&colliders, self.config.collision_substeps, self.config.collision_math){
+            event!(debug, a = ?colliders[collision.a], b = ?colliders[collision.b], time = collision.time, "collision");
+            return Err(SimError::Collision{ cycle: self.cycle, pos: colliders[collision.a].movement.start() });
+        }
+        Ok(())
+    }
+
+    /// In a production puzzle, check that every atom is inside a chamber or conduit.
+    fn check_chambers(&self) -> Result<(), SimError>{
+        if self.chambers.is_empty(){
+            return Ok(());
+        }
+        let conduits: HashSet<HexIndex> = self.parts.iter()
+            .filter(|part| matches!(part.ty, SimPartType::Conduit(_)))
+            .flat_map(SimPart::footprint)
+            .collect();
+        for pos in self.molecules.iter().flat_map(SimMolecule::positions){
+            if !conduits.contains(&pos) && !self.chambers.iter().any(|chamber| chamber.contains(pos)){
+                event!(debug, ?pos, "atom out of bounds");
+                return Err(SimError::OutOfBounds{ cycle: self.cycle, pos });
+            }
+        }
+        Ok(())
+    }
+
+    /// Add the hexes occupied at the end of a cycle to the area, along with any that atoms swept through during it.
+    fn record_area(&mut self, swept: Vec<HexIndex>){
+        let before = self.area.len();
+        self.area.extend(swept);
+        for molecule in &self.molecules{
+            self.area.extend(molecule.positions());
+        }
+        for part in &self.parts{
+            if let SimPartType::Arm(arm) = &part.ty{
+                self.area.insert(part.pos);
+                self.area.extend(arm.grippers(part.pos, part.rotation));
+                self.area.extend(arm.shaft(part.pos, part.rotation));
+            }
+        }
+        if self.area.len() > before{
+            self.events.push(SimEvent::AreaGrew{ cycle: self.cycle, area: self.area.len() });
+        }
+    }
+
     // need a way to remove or modify the molecule (or schedule those)
     pub fn lookup_atom<T>(&self, pos: HexIndex, f: impl for<'a> FnOnce(AtomLookupResult<'a>) -> T) -> Option<T>{
         for molecule in &self.molecules{
             if molecule.contains_pos(pos){
                 return Some(f(AtomLookupResult{
                     atom_ty: molecule.atom_at(pos),
-                    molecule: &molecule
+                    molecule
                 }))
             }
         }
@@ -84,6 +809,47 @@ impl Sim{
     }
 }
 
+/// The number of cycles before the arms' instructions repeat.
+fn tape_period(parts: &[SimPart]) -> u64{
+    parts.iter().filter_map(|p| match &p.ty{
+        SimPartType::Arm(arm) => Some(arm.tape.len() as u64),
+        _ => None
+    }).max().unwrap_or(0)
+}
+
+/// Check that every arm has a length the game allows, unless the config allows modded values.
+fn check_arm_lengths(parts: &[Part], config: &SimConfig) -> Result<(), &'static str>{
+    if !config.allow_modded_values && parts.iter().any(|part| part.ty.is_arm() && !(1..=3).contains(&part.arm_length)){
+        return Err("solution contains an arm with a length other than 1 to 3, which needs allow_modded_values");
+    }
+    Ok(())
+}
+
+/// Put arms on their tracks and expand their tapes.
+fn prepare_arms(parts: &mut [SimPart]) -> Result<(), &'static str>{
+    attach_tracks(parts);
+    for part in parts{
+        if let SimPartType::Arm(arm) = &mut part.ty{
+            // resets depend on how the arm can move, so expand them once it's on its track
+            arm.expand(part.pos)?;
+        }
+    }
+    Ok(())
+}
+
+/// Put arms placed on a track onto it, so they move along it.
+fn attach_tracks(parts: &mut [SimPart]){
+    let tracks: Vec<SimTrack> = parts.iter().filter_map(|p| match &p.ty{
+        SimPartType::Track(track) => Some(track.clone()),
+        _ => None
+    }).collect();
+    for part in parts{
+        if let SimPartType::Arm(arm) = &mut part.ty{
+            arm.track = tracks.iter().find(|t| t.hexes.contains(&part.pos)).cloned();
+        }
+    }
+}
+
 // Parts
 
 #[derive(Clone, Debug)]
@@ -93,16 +859,29 @@ pub struct SimPart{
     pub ty: SimPartType
 }
 
+/// What a part is and its state during a simulation.
+///
+/// Each phase of a cycle dispatches on this: arms run instructions first, then glyphs (including custom parts)
+/// and outputs act on atoms at rest, then arms move, and finally inputs spawn.
+/// To get at one kind of part's state, match on it, e.g. `let SimPartType::Arm(arm) = &part.ty else { .. }`.
 #[derive(Clone, Debug)]
 pub enum SimPartType{
-    Input(Molecule),
-    Output(Molecule, u64),
-    Arms,
-    Track,
-    Bonding, MultiBonding, Unbonding, Calcification,
-    Animismus,
+    Input(SimInput),
+    Output(SimOutput),
+    /// Any kind of arm, or Van Berlo's wheel.
+    Arm(SimArm),
+    Track(SimTrack),
+    Equilibrium,
+    Bonding, MultiBonding, TriplexBonding, Unbonding,
+    Calcification, Duplication,
     Projection, Purification,
-    Conduit,
+    Animismus,
+    Unification, Dispersion,
+    Disposal,
+    /// One end of a conduit.
+    Conduit(SimConduit),
+    /// A part this library doesn't know, e.g. from a mod, simulated by a [registered](Sim::register_custom_part) behaviour.
+    Custom(CustomPart),
 }
 
 impl SimPart{
@@ -114,40 +893,59 @@ impl SimPart{
         })
     }
 
-    pub fn tick(&mut self, sim: &mut Sim){
-        match &mut self.ty{
-            SimPartType::Input(m) => {}
-            SimPartType::Output(m, outputs) => {
-                // we need exactly 1 molecule that touches the output everywhere
-                // so we can just lookup for an arbitrary position (here the centre)
-                sim.lookup_atom(self.pos, |result| {
-                    if result.molecule.is(m){
-                        // wrong
-                        todo!()
-                    }
-                });
-            }
-            _ => panic!("a")
+    /// The hexes this part covers when placed, not counting where arms reach.
+    pub fn footprint(&self) -> Vec<HexIndex>{
+        if let Some(offsets) = glyphs::glyph_offsets(&self.ty){
+            return offsets.iter().map(|o| self.pos + self.rotation.rotate_offset(*o)).collect();
+        }
+        match &self.ty{
+            SimPartType::Input(input) => input.spawned_molecule().placed(self.pos, self.rotation).atoms.into_keys().collect(),
+            SimPartType::Output(output) => output.product.placed(self.pos, self.rotation).atoms.into_keys().collect(),
+            SimPartType::Arm(_) => vec![self.pos],
+            SimPartType::Track(track) => track.hexes.clone(),
+            SimPartType::Conduit(conduit) => conduit.hexes.clone(),
+            SimPartType::Custom(custom) => custom.footprint(self.pos, self.rotation),
+            _ => Vec::new()
         }
     }
 }
 
 impl SimPartType{
     pub fn from_solution_part(part: &Part, puzzle: &Puzzle, solution: &Solution) -> Result<SimPartType, &'static str>{
-        Ok(match part.ty{
-            PartType::Input => SimPartType::Input(puzzle.reagents[part.index as usize].clone()),
-            PartType::Output => SimPartType::Output(puzzle.products[part.index as usize].clone(), 0),
-            PartType::Arm | PartType::BiArm | PartType::TriArm | PartType::HexArm | PartType::PistonArm => SimPartType::Arms,
-            PartType::Track => SimPartType::Track,
+        Ok(match &part.ty{
+            PartType::Input => SimPartType::Input(SimInput::new(part.index as usize, puzzle.reagents[part.index as usize].clone())),
+            PartType::Output | PartType::PolymerOutput => SimPartType::Output(SimOutput::new(&puzzle.products[part.index as usize], part.index as usize)),
+            _ => Self::without_puzzle(part, solution.tape_start()).expect("only inputs and outputs need a puzzle")
+        })
+    }
+
+    /// The sim part for any part but an input or output, whose tape (if it has one) starts at `tape_start`.
+    fn without_puzzle(part: &Part, tape_start: i32) -> Option<SimPartType>{
+        Some(match &part.ty{
+            PartType::Input | PartType::Output | PartType::PolymerOutput => return None,
+            PartType::Arm | PartType::BiArm | PartType::TriArm | PartType::HexArm | PartType::PistonArm | PartType::Berlo =>
+                SimPartType::Arm(SimArm::from_solution_part(part, part.tape(tape_start))),
+            PartType::Track => SimPartType::Track(SimTrack::new(part.track_hexes.iter().map(|h| *h + part.pos).collect())),
+            PartType::Equilibrium => SimPartType::Equilibrium,
             PartType::Bonding => SimPartType::Bonding,
             PartType::MultiBonding => SimPartType::MultiBonding,
+            PartType::TriplexBonding => SimPartType::TriplexBonding,
             PartType::Unbonding => SimPartType::Unbonding,
             PartType::Calcification => SimPartType::Calcification,
-            PartType::Animismus => SimPartType::Animismus,
+            PartType::Duplication => SimPartType::Duplication,
             PartType::Projection => SimPartType::Projection,
             PartType::Purification => SimPartType::Purification,
-            PartType::Conduit => SimPartType::Conduit,
-            _ => { println!("{:?}", part.ty); return Err("unknown part type"); }
+            PartType::Animismus => SimPartType::Animismus,
+            PartType::Unification => SimPartType::Unification,
+            PartType::Dispersion => SimPartType::Dispersion,
+            PartType::Disposal => SimPartType::Disposal,
+            PartType::Conduit => SimPartType::Conduit(SimConduit{
+                index: part.conduit_index,
+                pos: part.pos,
+                rotation: HexRotation::from_signed(part.rotation),
+                hexes: part.conduit_hexes.iter().map(|h| *h + part.pos).collect()
+            }),
+            PartType::Unknown(name) => SimPartType::Custom(CustomPart{ name: name.clone(), behavior: None })
         })
     }
-}
\ No newline at end of file
+}
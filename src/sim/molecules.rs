@@ -0,0 +1,109 @@
+use alloc::collections::VecDeque;
+use crate::compat::{HashMap, HashSet};
+use crate::data::{Atom, Bond, BondType, HexIndex, Molecule};
+use super::SimMolecule;
+use crate::compat::prelude::*;
+
+// operations on the set of molecules on the board, which may merge or split molecules
+
+/// The index of the molecule with an atom at this position, if any.
+pub fn molecule_at(molecules: &[SimMolecule], pos: HexIndex) -> Option<usize>{
+    molecules.iter().position(|m| m.contains_pos(pos))
+}
+
+pub fn atom_at(molecules: &[SimMolecule], pos: HexIndex) -> Option<Atom>{
+    molecule_at(molecules, pos).map(|i| molecules[i].atom_at(pos))
+}
+
+/// Add a new unbonded atom to the board.
+pub fn add_atom(molecules: &mut Vec<SimMolecule>, pos: HexIndex, atom: Atom){
+    molecules.push(SimMolecule::new(Molecule{ atoms: HashMap::from_iter([(HexIndex::default(), atom)]), bonds: HashSet::default() }, pos));
+}
+
+/// Remove an atom and all of its bonds from the board, splitting its molecule if necessary.
+/// Returns the removed atom, if there was one.
+pub fn remove_atom(molecules: &mut Vec<SimMolecule>, pos: HexIndex) -> Option<Atom>{
+    let idx = molecule_at(molecules, pos)?;
+    let mut molecule = molecules.swap_remove(idx);
+    let rel = pos - molecule.pos;
+    let atom = molecule.layout.atoms.remove(&rel);
+    molecule.layout.bonds.retain(|b| b.start != rel && b.end != rel);
+    molecules.extend(molecule.split());
+    atom
+}
+
+/// Bond the atoms at these positions, merging their molecules if they're different.
+/// Triplex bonds add their colours to an existing triplex bond; nothing is added over an existing normal bond.
+/// Returns whether a bond was added or changed.
+pub fn add_bond(molecules: &mut Vec<SimMolecule>, a: HexIndex, b: HexIndex, ty: BondType) -> bool{
+    let (Some(ia), Some(ib)) = (molecule_at(molecules, a), molecule_at(molecules, b)) else { return false };
+    let existing = molecules[ia].bond_at(a, b);
+    let bond = match (existing.map(|e| e.ty), ty){
+        (None, ty) => Bond{ start: a, end: b, ty },
+        (Some(BondType::Triplex{ red, black, yellow }), BondType::Triplex{ red: r, black: bl, yellow: y }) => {
+            let merged = BondType::Triplex{ red: red || r, black: black || bl, yellow: yellow || y };
+            if Some(merged) == existing.map(|e| e.ty){
+                return false;
+            }
+            Bond{ start: a, end: b, ty: merged }
+        }
+        _ => return false
+    };
+    if ia != ib{
+        let other = molecules[ib].clone();
+        let placed = other.layout.translated(other.pos);
+        molecules[ia].merge_placed(&placed);
+        molecules[ia].grabbed |= other.grabbed;
+        molecules[ia].carried |= other.carried;
+        let offset = other.pos - molecules[ia].pos;
+        molecules[ia].provenance.extend(other.provenance.into_iter().map(|(rel, p)| (rel + offset, p)));
+        molecules.swap_remove(ib);
+    }
+    let ia = molecule_at(molecules, a).expect("bonded atom should still exist");
+    let molecule = &mut molecules[ia];
+    let offset = molecule.pos;
+    molecule.layout.bonds.retain(|e| !e.connects(a - offset, b - offset));
+    molecule.layout.bonds.insert(Bond{ start: bond.start - offset, end: bond.end - offset, ty: bond.ty });
+    true
+}
+
+/// Remove the bond between these positions, splitting the molecule if necessary.
+/// Returns whether there was a bond to remove.
+pub fn remove_bond(molecules: &mut Vec<SimMolecule>, a: HexIndex, b: HexIndex) -> bool{
+    let Some(idx) = molecule_at(molecules, a) else { return false };
+    if molecules[idx].bond_at(a, b).is_none(){
+        return false;
+    }
+    let mut molecule = molecules.swap_remove(idx);
+    let offset = molecule.pos;
+    molecule.layout.bonds.retain(|e| !e.connects(a - offset, b - offset));
+    molecules.extend(molecule.split());
+    true
+}
+
+impl SimMolecule{
+    /// Split this molecule into its connected components.
+    pub fn split(self) -> Vec<SimMolecule>{
+        let mut remaining: HashSet<HexIndex> = self.layout.atoms.keys().copied().collect();
+        let mut result = Vec::new();
+        while let Some(start) = remaining.iter().next().copied(){
+            remaining.remove(&start);
+            let mut component = HashSet::from_iter([start]);
+            let mut queue = VecDeque::from([start]);
+            while let Some(pos) = queue.pop_front(){
+                for bond in &self.layout.bonds{
+                    let other = if bond.start == pos{ bond.end } else if bond.end == pos{ bond.start } else { continue };
+                    if remaining.remove(&other){
+                        component.insert(other);
+                        queue.push_back(other);
+                    }
+                }
+            }
+            let atoms = component.iter().map(|p| (*p, self.layout.atoms[p])).collect();
+            let bonds = self.layout.bonds.iter().filter(|b| component.contains(&b.start)).copied().collect();
+            let provenance = self.provenance.iter().filter(|(p, _)| component.contains(*p)).map(|(p, prov)| (*p, prov.clone())).collect();
+            result.push(SimMolecule{ layout: Molecule{ atoms, bonds }, pos: self.pos, grabbed: self.grabbed, carried: self.carried, provenance });
+        }
+        result
+    }
+}
@@ -0,0 +1,89 @@
+use crate::data::{HexIndex, HexRotation, Molecule, PartType};
+use super::eligibility;
+use super::SimMolecule;
+use super::molecules::molecule_at;
+use crate::compat::prelude::*;
+
+/// How strictly outputs match molecules against their products.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum OutputMatching{
+    /// Match like the game: the molecule must be dropped and have exactly the product's atoms and bonds, in exactly the output's footprint.
+    /// Molecules that only partially overlap the output, or that extend past it, are ignored.
+    #[default]
+    GameAccurate,
+    /// Also match molecules that contain the product in the output's footprint, ignoring any extra atoms and bonds.
+    Greedy
+}
+
+/// The product of an output placed at `pos` with `rotation`, in board positions.
+pub fn placed_product(product: &Molecule, pos: HexIndex, rotation: HexRotation) -> Molecule{
+    product.placed(pos, rotation)
+}
+
+/// Whether an output with the given placed product (see [`placed_product`]) would consume this molecule.
+///
+/// Matching is done on board positions, so products with rotational symmetry match in any orientation that
+/// covers the same hexes with the same atoms and bonds, like the game does.
+pub fn matches_output(molecule: &SimMolecule, placed: &Molecule, mode: OutputMatching) -> bool{
+    if !eligibility::acts_on(&PartType::Output, molecule){
+        return false;
+    }
+    if mode == OutputMatching::GameAccurate
+    && (molecule.layout.atoms.len() != placed.atoms.len() || molecule.layout.bonds.len() != placed.bonds.len()){
+        return false;
+    }
+    for (pos, atom) in &placed.atoms{
+        if !molecule.contains_pos(*pos) || molecule.atom_at(*pos) != *atom{
+            return false;
+        }
+    }
+    for bond in &placed.bonds{
+        match molecule.bond_at(bond.start, bond.end){
+            Some(b) if b.ty == bond.ty => {}
+            _ => return false
+        }
+    }
+    true
+}
+
+/// The number of copies of a repeating product that a polymer output requires.
+pub const POLYMER_COPIES: usize = 6;
+
+/// The state of an output.
+#[derive(Clone, Debug)]
+pub struct SimOutput{
+    /// The product this output accepts, expanded if it repeats.
+    pub product: Molecule,
+    /// Index of the product in the puzzle.
+    pub index: usize,
+    /// Number of molecules consumed so far.
+    pub consumed: u64
+}
+
+impl SimOutput{
+    pub fn new(product: &Molecule, index: usize) -> SimOutput{
+        let product = match product.as_repeating(){
+            Some(repeating) => repeating.expand(POLYMER_COPIES),
+            None => product.clone()
+        };
+        SimOutput{ product, index, consumed: 0 }
+    }
+
+    /// Consume a matching molecule on this output, returning whether it did.
+    pub fn consume(&mut self, pos: HexIndex, rotation: HexRotation, molecules: &mut Vec<SimMolecule>, mode: OutputMatching) -> bool{
+        self.take(pos, rotation, molecules, mode).is_some()
+    }
+
+    /// Consume a matching molecule on this output, returning it if it did.
+    pub fn take(&mut self, pos: HexIndex, rotation: HexRotation, molecules: &mut Vec<SimMolecule>, mode: OutputMatching) -> Option<SimMolecule>{
+        let placed = placed_product(&self.product, pos, rotation);
+        let first = placed.atoms.keys().next()?;
+        match molecule_at(molecules, *first){
+            Some(idx) if matches_output(&molecules[idx], &placed, mode) => {
+                self.consumed += 1;
+                Some(molecules.swap_remove(idx))
+            }
+            _ => None
+        }
+    }
+}
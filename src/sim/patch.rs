@@ -0,0 +1,131 @@
+//! Changing a solution while it's being simulated, so search loops can try a tweak without starting over.
+//!
+//! A [`Rerun`] keeps snapshots as it runs, and after a patch resumes from the last one the patch can't have affected.
+
+use crate::data::Instruction;
+use super::{attach_tracks, tape_period, Sim, SimEvent, SimPart, SimPartType};
+use super::error::SimError;
+use crate::compat::prelude::*;
+
+impl Sim{
+    /// Replace the instruction arm `number` runs at `index` in its loop, padding its tape with blanks if it's too short.
+    /// Blanking the last instruction shortens the tape, as removing it from the solution would.
+    /// Instructions up to the end of the arm's last Repeat or Reset can't be patched (see [`SimArm::expansions_end`](super::arm::SimArm::expansions_end)),
+    /// since the expansion would change along with them.
+    /// Returns the first cycle that runs differently, so a snapshot from up to that cycle can be patched and resumed.
+    ///
+    /// Patching a simulation that has already run past that cycle leaves it in a state the new tape can't reach.
+    pub fn patch_instruction(&mut self, number: i32, index: usize, instr: Instruction) -> Result<u64, &'static str>{
+        if matches!(instr, Instruction::Repeat | Instruction::Reset){
+            return Err("can't patch in an instruction that needs expanding");
+        }
+        let Some(arm) = self.parts.iter_mut().find_map(|part| match &mut part.ty{
+            SimPartType::Arm(arm) if arm.number == number => Some(arm),
+            _ => None
+        }) else { return Err("no arm with that number") };
+        if index < arm.expansions_end{
+            return Err("can't patch an instruction that an expanded repeat or reset depends on");
+        }
+        if arm.tape.len() <= index{
+            arm.tape.resize(index + 1, Instruction::Blank);
+        }
+        arm.tape[index] = instr;
+        // like removing the instruction from the solution, blanking the last one shortens the tape
+        while arm.tape.last() == Some(&Instruction::Blank){
+            arm.tape.pop();
+        }
+        let period = tape_period(&self.parts);
+        // a different loop length changes which instruction every cycle past the shorter loop runs
+        let first = if period == self.period{ index as u64 }else{ self.period.min(period).min(index as u64) };
+        self.period = period;
+        Ok(first)
+    }
+
+    /// Place another part, before the simulation has started.
+    pub fn add_part(&mut self, part: SimPart) -> Result<(), &'static str>{
+        if self.cycle > 0{
+            return Err("parts can only be changed before the simulation starts");
+        }
+        self.parts.push(part);
+        self.reset_board();
+        Ok(())
+    }
+
+    /// Remove the part at this index in [`Sim::parts`], before the simulation has started.
+    pub fn remove_part(&mut self, idx: usize) -> Result<SimPart, &'static str>{
+        if self.cycle > 0{
+            return Err("parts can only be changed before the simulation starts");
+        }
+        if idx >= self.parts.len(){
+            return Err("no part at that index");
+        }
+        let part = self.parts.remove(idx);
+        self.reset_board();
+        Ok(part)
+    }
+
+    /// Set up the starting board again after the parts changed.
+    fn reset_board(&mut self){
+        attach_tracks(&mut self.parts);
+        self.period = tape_period(&self.parts);
+        self.molecules.clear();
+        self.area = self.parts.iter().flat_map(SimPart::footprint).collect();
+        self.events = vec![SimEvent::AreaGrew{ cycle: 0, area: self.area.len() }];
+        self.spawn_inputs();
+        self.record_area(Vec::new());
+    }
+}
+
+/// A simulation that keeps a snapshot every `interval` cycles, so it can be patched and resumed from before the change.
+#[derive(Clone, Debug)]
+pub struct Rerun{
+    /// The simulation in its current state.
+    pub sim: Sim,
+    pub interval: u64,
+    /// Snapshots in order of their cycle, starting with the first.
+    snapshots: Vec<Sim>
+}
+
+impl Rerun{
+    /// Start keeping snapshots of `sim`, normally before it has run any cycles.
+    pub fn new(sim: Sim, interval: u64) -> Rerun{
+        Rerun{ snapshots: vec![sim.clone()], sim, interval: interval.max(1) }
+    }
+
+    /// Run until the puzzle is completed, like [`Sim::run`], saving snapshots along the way.
+    pub fn run(&mut self) -> Result<u64, SimError>{
+        self.sim.criterion.check_possible(&self.sim).map_err(SimError::NeverCompletes)?;
+        loop{
+            if let Some(cycles) = self.sim.completed{
+                return Ok(cycles);
+            }
+            if self.sim.cycle >= self.sim.config.max_cycles{
+                return Err(SimError::CycleLimit{ cycles: self.sim.cycle });
+            }
+            if self.sim.cycle.is_multiple_of(self.interval) && self.snapshots.last().is_none_or(|last| last.cycle < self.sim.cycle){
+                self.snapshots.push(self.sim.clone());
+            }
+            self.sim.step()?;
+        }
+    }
+
+    /// Patch an instruction as with [`Sim::patch_instruction`], dropping the snapshots it affects,
+    /// and rewind to the last one left. Run again to see the patched result.
+    pub fn patch_instruction(&mut self, number: i32, index: usize, instr: Instruction) -> Result<(), &'static str>{
+        let first = self.snapshots[0].clone().patch_instruction(number, index, instr)?;
+        if self.snapshots[0].cycle > first{
+            return Err("the patch affects cycles before the first snapshot");
+        }
+        self.snapshots.retain(|snapshot| snapshot.cycle <= first);
+        for snapshot in &mut self.snapshots{
+            snapshot.patch_instruction(number, index, instr)?;
+        }
+        self.sim = self.snapshots.last().expect("the first snapshot is kept").clone();
+        Ok(())
+    }
+
+    /// The number of snapshots currently kept.
+    pub fn snapshots(&self) -> usize{
+        self.snapshots.len()
+    }
+}
@@ -0,0 +1,78 @@
+//! The paths arms trace as they move, for drawing motion trails and for analyses that need the swept arcs rather than
+//! just the hexes visited.
+
+use core::ops::Range;
+use crate::data::{HexIndex, HexRotation};
+use super::arm::{ArmMotion, SimArm};
+use super::collision::Movement;
+use super::error::SimError;
+use super::{Sim, SimPartType};
+use crate::compat::prelude::*;
+
+/// How an arm's base and grippers move during one cycle.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ArmPathStep{
+    pub cycle: u64,
+    pub base: Movement,
+    /// The movement of each gripper, in the order of [`SimArm::gripper_offsets`].
+    pub grippers: Vec<Movement>
+}
+
+/// The path an arm traces over a range of cycles.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ArmPath{
+    /// The index of the arm in [`Sim::parts`].
+    pub part: usize,
+    pub steps: Vec<ArmPathStep>
+}
+
+impl ArmPath{
+    /// The hexes each gripper occupies at the start of every cycle and at the end of the last one, by gripper.
+    pub fn gripper_positions(&self) -> Vec<Vec<HexIndex>>{
+        let Some(last) = self.steps.last() else { return Vec::new() };
+        (0..last.grippers.len()).map(|gripper| {
+            self.steps.iter().map(|step| step.grippers[gripper].start())
+                .chain(core::iter::once(last.grippers[gripper].end()))
+                .collect()
+        }).collect()
+    }
+}
+
+/// The movements of an arm's base and grippers, for an arm with its base at `base` making this motion.
+pub fn motion_path(arm: &SimArm, base: HexIndex, rotation: HexRotation, motion: Option<ArmMotion>) -> (Movement, Vec<Movement>){
+    let grippers = arm.grippers(base, rotation);
+    let base_movement = match motion{
+        Some(ArmMotion::Move(to)) => Movement::Translate{ start: base, end: to },
+        _ => Movement::Stay{ at: base }
+    };
+    let gripper_movements = grippers.into_iter().zip(SimArm::gripper_offsets(&arm.ty)).map(|(start, offset)| match motion{
+        Some(ArmMotion::Rotate(by)) => Movement::Rotate{ start, around: base, rotation: by },
+        Some(ArmMotion::Extend(by)) => Movement::Translate{ start, end: start + (rotation + *offset).to_unit_offset().scaled(by) },
+        Some(ArmMotion::Move(to)) => Movement::Translate{ start, end: start + (to - base) },
+        // pivoting turns what's held, but the gripper itself stays put
+        Some(ArmMotion::Pivot(_)) | None => Movement::Stay{ at: start }
+    }).collect();
+    (base_movement, gripper_movements)
+}
+
+impl Sim{
+    /// Simulate `cycles` on a copy of this sim, recording the path traced by the arm at index `part` in [`Sim::parts`].
+    /// Cycles before the start of the range are simulated but not recorded.
+    pub fn arm_path(&self, part: usize, cycles: Range<u64>) -> Result<ArmPath, SimError>{
+        if !matches!(self.parts.get(part).map(|p| &p.ty), Some(SimPartType::Arm(_))){
+            return Err(SimError::Setup("part is not an arm"));
+        }
+        let mut sim = self.clone();
+        sim.run_until(cycles.start)?;
+        let mut steps = Vec::new();
+        while sim.cycle < cycles.end{
+            let SimPartType::Arm(arm) = &sim.parts[part].ty else { unreachable!() };
+            let (pos, rotation) = (sim.parts[part].pos, sim.parts[part].rotation);
+            let motion = arm.motion(arm.instruction_at(sim.cycle, sim.period), pos);
+            let (base, grippers) = motion_path(arm, pos, rotation, motion);
+            steps.push(ArmPathStep{ cycle: sim.cycle, base, grippers });
+            sim.step()?;
+        }
+        Ok(ArmPath{ part, steps })
+    }
+}
@@ -0,0 +1,67 @@
+//! Where each atom came from, tracked when [`SimConfig::track_provenance`](super::config::SimConfig::track_provenance) is on.
+//!
+//! Each [`SimMolecule`] keeps the provenance of its atoms relative to its position, like its layout, so it follows them
+//! through every move, bond and split without the sim having to know which atom is which.
+
+use crate::data::{Atom, HexIndex, HexRotation};
+use crate::compat::HashMap;
+use super::SimMolecule;
+use crate::compat::prelude::*;
+
+/// Where an atom came from and which glyphs changed it.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Provenance{
+    /// The input that spawned this atom, as an index into [`Sim::parts`](super::Sim::parts), and the cycle it did so during.
+    /// Nothing if a glyph made the atom, or it was put on the board some other way.
+    pub spawned: Option<(usize, u64)>,
+    /// Every glyph that made or changed this atom, oldest first.
+    pub transmutations: Vec<Transmutation>
+}
+
+/// A glyph making or changing an atom.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Transmutation{
+    /// The glyph, as an index into [`Sim::parts`](super::Sim::parts).
+    pub part: usize,
+    pub cycle: u64,
+    /// What the atom was before, or nothing if the glyph made it.
+    pub from: Option<Atom>,
+    /// What the atom became.
+    pub to: Atom
+}
+
+/// A product an output consumed, with the provenance of each of its atoms in board positions.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConsumedProduct{
+    pub cycle: u64,
+    /// The product's index in the puzzle.
+    pub product: usize,
+    /// Each atom of the consumed molecule, sorted by position.
+    pub atoms: Vec<(HexIndex, Atom, Provenance)>
+}
+
+impl SimMolecule{
+    /// The provenance of the atom at this board position, if it's tracked.
+    pub fn provenance_at(&self, pos: HexIndex) -> Option<&Provenance>{
+        self.provenance.get(&(pos - self.pos))
+    }
+
+    /// The provenance of the atom at this board position, starting a blank one if it isn't tracked yet.
+    pub fn provenance_mut(&mut self, pos: HexIndex) -> &mut Provenance{
+        self.provenance.entry(pos - self.pos).or_default()
+    }
+
+    /// Describe this molecule as consumed by an output, see [`ConsumedProduct`].
+    pub fn consumed(&self, cycle: u64, product: usize) -> ConsumedProduct{
+        let mut atoms: Vec<_> = self.layout.atoms.iter()
+            .map(|(rel, atom)| (*rel + self.pos, *atom, self.provenance.get(rel).cloned().unwrap_or_default()))
+            .collect();
+        atoms.sort_by_key(|(pos, _, _)| (pos.q, pos.r));
+        ConsumedProduct{ cycle, product, atoms }
+    }
+}
+
+/// Rotate provenance kept relative to a molecule's position along with its layout.
+pub(super) fn rotated(provenance: HashMap<HexIndex, Provenance>, by: HexRotation) -> HashMap<HexIndex, Provenance>{
+    provenance.into_iter().map(|(rel, p)| (rel.rotated(HexIndex::default(), by), p)).collect()
+}
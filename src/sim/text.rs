@@ -0,0 +1,66 @@
+use crate::compat::HashMap;
+use crate::data::HexIndex;
+use crate::compat::prelude::*;
+use super::{Sim, SimPartType};
+
+impl Sim{
+    /// Draw the board as text, one line per row of hexes with the top row first, for debugging.
+    ///
+    /// Each hex is two characters: an atom's symbol, `()` for an arm base, `<>` for an empty gripper,
+    /// `==` for a track, `::` for any other part, or ` .` for an empty hex between these.
+    pub fn to_text(&self) -> String{
+        let mut cells: HashMap<HexIndex, &str> = HashMap::default();
+        // later layers overwrite earlier ones
+        for part in &self.parts{
+            let cell = match part.ty{
+                SimPartType::Arm(_) => continue,
+                SimPartType::Track(_) => "==",
+                _ => "::"
+            };
+            for hex in part.footprint(){
+                cells.insert(hex, cell);
+            }
+        }
+        for (part, arm) in self.arms(){
+            for gripper in arm.grippers(part.pos, part.rotation){
+                cells.insert(gripper, "<>");
+            }
+            cells.insert(part.pos, "()");
+        }
+        for molecule in &self.molecules{
+            for pos in molecule.positions(){
+                cells.insert(pos, molecule.atom_at(pos).symbol());
+            }
+        }
+        cells_to_text(&cells)
+    }
+}
+
+/// Lay out two-character cells by hex, as described for [`Sim::to_text`].
+pub(crate) fn cells_to_text(cells: &HashMap<HexIndex, &str>) -> String{
+    if cells.is_empty(){
+        return String::new();
+    }
+
+    // each step along q moves two cells right, and each step along r one cell, so rows line up like the board
+    let column = |hex: &HexIndex| 2 * hex.q + hex.r;
+    let min_column = cells.keys().map(column).min().unwrap_or(0);
+    let max_column = cells.keys().map(column).max().unwrap_or(0);
+    let min_r = cells.keys().map(|hex| hex.r).min().unwrap_or(0);
+    let max_r = cells.keys().map(|hex| hex.r).max().unwrap_or(0);
+    let mut lines = Vec::new();
+    for r in (min_r..=max_r).rev(){
+        let mut line = String::new();
+        for x in min_column..=max_column{
+            // only every other column is a hex in this row
+            if (x - r).rem_euclid(2) != 0{
+                line.push_str("  ");
+                continue;
+            }
+            let hex = HexIndex::new((x - r) / 2, r);
+            line.push_str(cells.get(&hex).copied().unwrap_or(" ."));
+        }
+        lines.push(line.trim_end().to_string());
+    }
+    lines.join("\n")
+}
@@ -0,0 +1,126 @@
+//! An experimental brute-force solver for trivial puzzles, e.g. for testing the whole pipeline end to end.
+//!
+//! It only handles puzzles with one small reagent and one small product, and only tries solutions with a single arm of
+//! length 1 next to the input, the output and at most one glyph around the arm, and a tape of Grab followed by turns and
+//! drops. Shorter tapes are tried first.
+
+use crate::data::{HexIndex, HexRotation, Instruction, Part, PartType, Permissions, Puzzle, Solution};
+use crate::sim::config::SimConfig;
+use crate::sim::Sim;
+use crate::verify::simulate;
+use crate::compat::prelude::*;
+
+/// The most atoms a reagent or product can have for the solver to try it.
+pub const MAX_ATOMS: usize = 4;
+
+/// The glyphs the solver tries placing, with the offsets of the hexes they cover and the permission they need.
+const GLYPHS: [(PartType, &[HexIndex], Permissions); 3] = [
+    (PartType::Calcification, &[HexIndex{ q: 0, r: 0 }], Permissions::CALCIFICATION),
+    (PartType::Bonding, &[HexIndex{ q: 0, r: 0 }, HexIndex{ q: 1, r: 0 }], Permissions::BONDER),
+    (PartType::Unbonding, &[HexIndex{ q: 0, r: 0 }, HexIndex{ q: 1, r: 0 }], Permissions::UNBONDER)
+];
+
+/// The instructions that can follow the first Grab.
+const INSTRUCTIONS: [Instruction; 4] = [Instruction::RotateClockwise, Instruction::RotateAnticlockwise, Instruction::Grab, Instruction::Drop];
+
+/// The arm's base. The input is at the origin, in the direction the arm starts out facing.
+const BASE: HexIndex = HexIndex{ q: 1, r: 0 };
+
+/// Search for a solution with a tape of at most `max_tape` instructions, returning the first valid one found,
+/// with its metrics recorded.
+pub fn solve(puzzle: &Puzzle, max_tape: usize) -> Result<Option<Solution>, &'static str>{
+    if puzzle.reagents.len() != 1 || puzzle.products.len() != 1{
+        return Err("solver only handles puzzles with one reagent and one product");
+    }
+    if puzzle.reagents[0].atoms.len() > MAX_ATOMS || puzzle.products[0].atoms.len() > MAX_ATOMS{
+        return Err("molecules are too big for the solver");
+    }
+    if !puzzle.permissions.contains(Permissions::SIMPLE_ARM | Permissions::GRAB_TURN_INSTRUCTIONS | Permissions::DROP_INSTRUCTION){
+        return Err("solver needs arms with grab, turn and drop instructions");
+    }
+    let layouts = layouts(puzzle);
+    for len in 1..=max_tape{
+        for tape in tapes(len){
+            for parts in &layouts{
+                let mut solution = Solution{ name: "solved".into(), puzzle_name: puzzle.name.clone(), metrics: None, parts: parts.clone() };
+                solution.parts.push(Part{
+                    ty: PartType::Arm, pos: BASE, rotation: HexRotation::R180.signed_turns() as i32, arm_number: 1, arm_length: 1, index: 0,
+                    conduit_index: 0, track_hexes: Vec::new(), conduit_hexes: Vec::new(),
+                    instructions: tape.iter().enumerate().map(|(idx, instr)| (*instr, idx as i32)).collect()
+                });
+                // making one product weeds out most tapes quickly, before checking the whole run
+                if !makes_a_product(puzzle, &solution, len){
+                    continue;
+                }
+                if let Ok((metrics, _)) = simulate(puzzle, &solution){
+                    solution.metrics = Some(metrics);
+                    return Ok(Some(solution));
+                }
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// Every placement of the input, output and glyph to try, without the arm.
+fn layouts(puzzle: &Puzzle) -> Vec<Vec<Part>>{
+    let part = |ty, pos, rotation: HexRotation| Part{
+        ty, pos, rotation: rotation.signed_turns() as i32, arm_number: 0, arm_length: 0, index: 0,
+        conduit_index: 0, track_hexes: Vec::new(), conduit_hexes: Vec::new(), instructions: Vec::new()
+    };
+    let ring: Vec<HexIndex> = HexRotation::ALL.iter().map(|rotation| BASE + rotation.to_unit_offset()).collect();
+    let mut layouts = Vec::new();
+    for input_rotation in HexRotation::ALL{
+        let input: Vec<HexIndex> = puzzle.reagents[0].placed(HexIndex::default(), input_rotation).atoms.into_keys().collect();
+        if !input.contains(&HexIndex::default()) || input.contains(&BASE){
+            continue;
+        }
+        for output_pos in ring.iter().copied().filter(|pos| !input.contains(pos)){
+            for output_rotation in HexRotation::ALL{
+                let output: Vec<HexIndex> = puzzle.products[0].placed(output_pos, output_rotation).atoms.into_keys().collect();
+                if output.iter().any(|pos| input.contains(pos) || *pos == BASE){
+                    continue;
+                }
+                let io = vec![part(PartType::Input, HexIndex::default(), input_rotation), part(PartType::Output, output_pos, output_rotation)];
+                layouts.push(io.clone());
+                for (ty, offsets, permission) in &GLYPHS{
+                    if !puzzle.permissions.contains(*permission){
+                        continue;
+                    }
+                    for glyph_pos in &ring{
+                        for glyph_rotation in HexRotation::ALL{
+                            // glyphs act on atoms the arm holds, so every hex of the glyph has to be somewhere it can reach
+                            let hexes: Vec<HexIndex> = offsets.iter().map(|offset| *glyph_pos + glyph_rotation.rotate_offset(*offset)).collect();
+                            if hexes.iter().all(|hex| ring.contains(hex) && !input.contains(hex) && !output.contains(hex)){
+                                let mut layout = io.clone();
+                                layout.push(part(ty.clone(), *glyph_pos, glyph_rotation));
+                                layouts.push(layout);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+    layouts
+}
+
+/// Every tape of this length that starts with a Grab and drops something.
+fn tapes(len: usize) -> Vec<Vec<Instruction>>{
+    let mut tapes = vec![vec![Instruction::Grab]];
+    for _ in 1..len{
+        tapes = tapes.into_iter().flat_map(|tape| INSTRUCTIONS.iter().map(move |instr| {
+            let mut tape = tape.clone();
+            tape.push(*instr);
+            tape
+        })).collect();
+    }
+    tapes.retain(|tape| tape.contains(&Instruction::Drop));
+    tapes
+}
+
+/// Whether the solution makes at least one product in a few loops of its tape.
+fn makes_a_product(puzzle: &Puzzle, solution: &Solution, len: usize) -> bool{
+    let config = SimConfig{ output_target: Some(1), max_cycles: 4 * len as u64 + 4, ..SimConfig::default() };
+    Sim::create_with_config(puzzle, solution, config).is_ok_and(|mut sim| sim.run().is_ok())
+}
@@ -0,0 +1,152 @@
+//! Recording a simulation as a sequence of board states, so it can be replayed elsewhere without simulating it again.
+//! See [`parse_trace`](crate::parse::parse_trace) and [`Trace::unparse`] for the binary format.
+
+use crate::data::{Atom, HexIndex, HexRotation, Molecule};
+use crate::sim::{Sim, SimEvent, SimPartType};
+use crate::sim::error::SimError;
+use crate::sim::text::cells_to_text;
+use crate::compat::HashMap;
+use crate::compat::prelude::*;
+
+/// Every board state of a simulation, from setup until it completed or failed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Trace{
+    /// The board after each cycle, starting with the board before the first cycle, so `frames[n]` is after `n` cycles.
+    pub frames: Vec<Frame>,
+    /// If the puzzle was completed, the number of cycles it took.
+    pub completed: Option<u64>,
+    /// If the simulation failed, why.
+    pub error: Option<String>
+}
+
+/// The board at the end of one cycle.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Frame{
+    /// The number of cycles run so far.
+    pub cycle: u64,
+    pub arms: Vec<TracedArm>,
+    /// Every molecule on the board, in board positions.
+    pub molecules: Vec<Molecule>,
+    /// What happened during the cycle leading up to this board.
+    pub events: Vec<SimEvent>
+}
+
+/// The state of an arm in a [`Frame`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct TracedArm{
+    /// The index of the arm in [`Sim::parts`].
+    pub part: usize,
+    pub pos: HexIndex,
+    pub rotation: HexRotation,
+    pub length: i32,
+    pub grabbing: bool
+}
+
+impl Trace{
+    /// Simulate until the puzzle is completed or the simulation fails, capturing the board after every cycle.
+    pub fn record(sim: &mut Sim) -> Trace{
+        let mut frames = vec![Frame::capture(sim, sim.events.clone())];
+        let error = loop{
+            if sim.completed.is_some(){
+                break None;
+            }
+            if sim.cycle >= sim.config.max_cycles{
+                break Some(SimError::CycleLimit{ cycles: sim.cycle });
+            }
+            let seen = sim.events.len();
+            if let Err(err) = sim.step(){
+                break Some(err);
+            }
+            frames.push(Frame::capture(sim, sim.events[seen..].to_vec()));
+        };
+        Trace{ frames, completed: sim.completed, error: error.map(|err| err.to_string()) }
+    }
+
+    /// The board after `cycle` cycles, if the simulation got that far.
+    pub fn frame(&self, cycle: u64) -> Option<&Frame>{
+        usize::try_from(cycle).ok().and_then(|cycle| self.frames.get(cycle))
+    }
+
+    /// The first board where this trace and `other` differ, e.g. two solutions, or the same solution recorded by two
+    /// versions of the simulator. `None` if every board and the outcome are the same.
+    pub fn divergence(&self, other: &Trace) -> Option<Divergence>{
+        for (cycle, (left, right)) in self.frames.iter().zip(&other.frames).enumerate(){
+            let reason = if left.arms != right.arms{
+                "arms differ"
+            }else if !same_molecules(&left.molecules, &right.molecules){
+                "molecules differ"
+            }else if left.events != right.events{
+                "events differ"
+            }else{
+                continue;
+            };
+            return Some(Divergence{ cycle: cycle as u64, reason, left: Some(left.clone()), right: Some(right.clone()) });
+        }
+        let shared = self.frames.len().min(other.frames.len());
+        if self.frames.len() != other.frames.len(){
+            return Some(Divergence{ cycle: shared as u64, reason: "one trace stops earlier", left: self.frames.get(shared).cloned(), right: other.frames.get(shared).cloned() });
+        }
+        if self.completed != other.completed || self.error != other.error{
+            let last = |trace: &Trace| trace.frames.last().cloned();
+            return Some(Divergence{ cycle: shared.saturating_sub(1) as u64, reason: "outcomes differ", left: last(self), right: last(other) });
+        }
+        None
+    }
+}
+
+/// Where two traces first differ, see [`Trace::divergence`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Divergence{
+    /// The number of cycles run before the boards differ.
+    pub cycle: u64,
+    /// What differs, e.g. "arms differ".
+    pub reason: &'static str,
+    /// The first trace's board after `cycle` cycles, or `None` if it stopped before then.
+    pub left: Option<Frame>,
+    /// The second trace's board after `cycle` cycles, or `None` if it stopped before then.
+    pub right: Option<Frame>
+}
+
+/// Whether both lists have the same molecules, in any order.
+fn same_molecules(left: &[Molecule], right: &[Molecule]) -> bool{
+    let mut unmatched: Vec<&Molecule> = right.iter().collect();
+    left.len() == right.len() && left.iter().all(|molecule| match unmatched.iter().position(|other| *other == molecule){
+        Some(idx) => {
+            unmatched.swap_remove(idx);
+            true
+        }
+        None => false
+    })
+}
+
+impl Frame{
+    fn capture(sim: &Sim, events: Vec<SimEvent>) -> Frame{
+        let arms = sim.parts.iter().enumerate().filter_map(|(part, p)| match &p.ty{
+            SimPartType::Arm(arm) => Some(TracedArm{ part, pos: p.pos, rotation: p.rotation, length: arm.length, grabbing: arm.grabbing }),
+            _ => None
+        }).collect();
+        let molecules = sim.molecules.iter().map(|molecule| molecule.layout.translated(molecule.pos)).collect();
+        Frame{ cycle: sim.cycle, arms, molecules, events }
+    }
+
+    /// Draw this board as text like [`Sim::to_text`], without the parts, which traces don't record.
+    /// Only the first gripper of each arm is drawn.
+    pub fn to_text(&self) -> String{
+        let mut cells: HashMap<HexIndex, &str> = HashMap::default();
+        for arm in &self.arms{
+            cells.insert(arm.pos + arm.rotation.to_unit_offset().scaled(arm.length), "<>");
+            cells.insert(arm.pos, "()");
+        }
+        for molecule in &self.molecules{
+            for (pos, atom) in &molecule.atoms{
+                cells.insert(*pos, atom.symbol());
+            }
+        }
+        cells_to_text(&cells)
+    }
+
+    /// The atom at this position on the board, if any.
+    pub fn atom_at(&self, pos: HexIndex) -> Option<Atom>{
+        self.molecules.iter().find_map(|molecule| molecule.atoms.get(&pos).copied())
+    }
+}
@@ -0,0 +1,196 @@
+//! Whole-solution symmetry transforms, for canonicalizing solutions and finding mirrored or rotated duplicates.
+
+use crate::data::{HexIndex, HexRotation, Instruction, Part, PartType, Puzzle, Solution};
+use crate::hash::stable_hash;
+use crate::verify::verify;
+use crate::compat::prelude::*;
+
+const fn hex(q: i32, r: i32) -> HexIndex{
+    HexIndex{ q, r }
+}
+
+impl Solution{
+    /// This solution rotated around the origin, with every part and track turned along with it.
+    ///
+    /// The game doesn't care which way up a machine is, but the puzzle stays put: in production puzzles the parts can
+    /// end up outside their chambers. Use [`rotated_verified`](Solution::rotated_verified) to check the result.
+    pub fn rotated(&self, by: HexRotation) -> Solution{
+        let mut rotated = self.clone();
+        for part in &mut rotated.parts{
+            part.pos = part.pos.rotated(HexIndex::default(), by);
+            part.rotation += by.turns() as i32;
+            for hex in part.track_hexes.iter_mut().chain(&mut part.conduit_hexes){
+                *hex = by.rotate_offset(*hex);
+            }
+        }
+        rotated
+    }
+
+    /// This solution [rotated](Solution::rotated), checked by simulating it against `puzzle`.
+    /// Fails unless both this solution and the rotated one solve the puzzle, with the same metrics.
+    pub fn rotated_verified(&self, puzzle: &Puzzle, by: HexRotation) -> Result<Solution, &'static str>{
+        let rotated = self.rotated(by);
+        match (verify(puzzle, self).result, verify(puzzle, &rotated).result){
+            (Ok(before), Ok(after)) if before == after => Ok(rotated),
+            (Ok(_), Ok(_)) => Err("rotated solution has different metrics"),
+            (Ok(_), Err(_)) => Err("rotated solution doesn't solve the puzzle"),
+            (Err(_), _) => Err("solution doesn't solve the puzzle")
+        }
+    }
+
+    /// This solution reflected across the horizontal line through the origin, with clockwise and anticlockwise
+    /// instructions swapped.
+    ///
+    /// Glyphs are placed so each of their hexes does the same job as before. Inputs and outputs are reflected in place,
+    /// so the result only solves the same puzzle if its molecules are symmetric; simulate it to check.
+    /// Fails for glyphs and wheels that have no mirror image, like dispersion and Van Berlo's wheel.
+    pub fn mirrored(&self) -> Result<Solution, &'static str>{
+        let mut mirrored = self.clone();
+        for part in &mut mirrored.parts{
+            let (pos, rotation) = mirror_placement(part)?;
+            part.pos = pos;
+            part.rotation = rotation;
+            for hex in part.track_hexes.iter_mut().chain(&mut part.conduit_hexes){
+                *hex = hex.mirrored();
+            }
+            for (instr, _) in &mut part.instructions{
+                *instr = match *instr{
+                    Instruction::RotateClockwise => Instruction::RotateAnticlockwise,
+                    Instruction::RotateAnticlockwise => Instruction::RotateClockwise,
+                    Instruction::PivotClockwise => Instruction::PivotAnticlockwise,
+                    Instruction::PivotAnticlockwise => Instruction::PivotClockwise,
+                    other => other
+                };
+            }
+        }
+        Ok(mirrored)
+    }
+}
+
+impl Solution{
+    /// This solution with everything that doesn't affect how it runs stripped or normalized:
+    /// no name or recorded metrics, instructions starting at index 0 with no shadowed or redundant blanks,
+    /// rotations between 0 and 5, no fields that don't apply to a part's type, and parts in a fixed order.
+    /// Arm numbers are only for display, so they're cleared too.
+    pub fn canonical(&self) -> Solution{
+        let start = self.tape_start();
+        let mut parts: Vec<(Vec<u8>, Part)> = self.parts.iter().map(|part| {
+            let part = canonical_part(part, start);
+            let key = Solution{ name: String::new(), puzzle_name: String::new(), metrics: None, parts: vec![part.clone()] }.unparse();
+            (key, part)
+        }).collect();
+        parts.sort_by(|(a, _), (b, _)| a.cmp(b));
+        Solution{ name: String::new(), puzzle_name: self.puzzle_name.clone(), metrics: None, parts: parts.into_iter().map(|(_, part)| part).collect() }
+    }
+
+    /// A hash of the [canonical](Solution::canonical) form of this solution, which is the same for solutions that
+    /// only differ in part order, instruction offsets, names, or recorded metrics.
+    ///
+    /// This is the [`stable_hash`] of the canonical solution's file contents, so it can be stored and compared later.
+    pub fn canonical_hash(&self) -> u64{
+        stable_hash(&self.canonical().unparse())
+    }
+
+    /// Like [`canonical_hash`](Solution::canonical_hash), but also the same for solutions that are rotated or moved
+    /// copies of each other. Don't use this for production puzzles, where parts must stay in their chambers.
+    pub fn symmetric_hash(&self) -> u64{
+        HexRotation::ALL.into_iter().map(|by| {
+            let mut solution = self.rotated(by);
+            // the lowest part position moves along with the solution, so putting it on the origin undoes any translation
+            if let Some(anchor) = solution.parts.iter().map(|p| p.pos).min_by_key(|p| (p.q, p.r)){
+                for part in &mut solution.parts{
+                    part.pos -= anchor;
+                }
+            }
+            solution.canonical_hash()
+        }).min().expect("there are six rotations")
+    }
+}
+
+fn is_arm(ty: &PartType) -> bool{
+    matches!(ty, PartType::Arm | PartType::BiArm | PartType::TriArm | PartType::HexArm | PartType::PistonArm | PartType::Berlo)
+}
+
+fn canonical_part(part: &Part, start: i32) -> Part{
+    let ty = part.ty.clone();
+    let arm = is_arm(&ty);
+    let instructions = if arm{
+        let tape = part.tape(start);
+        let last = tape.len().saturating_sub(1);
+        // gaps are blank anyway, but a blank at the end sets the tape's length
+        tape.into_iter().enumerate()
+            .filter(|(idx, instr)| *instr != Instruction::Blank || *idx == last)
+            .map(|(idx, instr)| (instr, idx as i32))
+            .collect()
+    }else{
+        Vec::new()
+    };
+    Part{
+        pos: part.pos,
+        rotation: part.rotation.rem_euclid(6),
+        arm_number: 0,
+        arm_length: if arm{ part.arm_length } else { 0 },
+        index: if matches!(ty, PartType::Input | PartType::Output | PartType::PolymerOutput){ part.index } else { 0 },
+        conduit_index: if ty == PartType::Conduit{ part.conduit_index } else { 0 },
+        track_hexes: if ty == PartType::Track{ part.track_hexes.clone() } else { Vec::new() },
+        conduit_hexes: if ty == PartType::Conduit{ part.conduit_hexes.clone() } else { Vec::new() },
+        instructions,
+        ty
+    }
+}
+
+/// The hexes of a glyph at the origin with no rotation, with a label for what each one does.
+/// Hexes with the same label are interchangeable.
+fn glyph_roles(ty: &PartType) -> Option<&'static [(HexIndex, u8)]>{
+    const SINGLE: &[(HexIndex, u8)] = &[(hex(0, 0), 0)];
+    const PAIR: &[(HexIndex, u8)] = &[(hex(0, 0), 0), (hex(1, 0), 0)];
+    const SOURCE_TARGET: &[(HexIndex, u8)] = &[(hex(0, 0), 0), (hex(1, 0), 1)];
+    const MULTI: &[(HexIndex, u8)] = &[(hex(0, 0), 0), (hex(1, 0), 1), (hex(0, -1), 1), (hex(-1, 1), 1)];
+    // each pair gets a different colour of bond
+    const TRIPLEX: &[(HexIndex, u8)] = &[(hex(0, 0), 0), (hex(1, 0), 1), (hex(0, 1), 2)];
+    const PURIFICATION: &[(HexIndex, u8)] = &[(hex(0, 0), 0), (hex(1, 0), 0), (hex(0, 1), 1)];
+    const ANIMISMUS: &[(HexIndex, u8)] = &[(hex(0, 0), 0), (hex(1, 0), 0), (hex(0, 1), 1), (hex(1, -1), 2)];
+    const UNIFICATION: &[(HexIndex, u8)] = &[(hex(0, 0), 0), (hex(0, 1), 1), (hex(-1, 1), 1), (hex(0, -1), 1), (hex(1, -1), 1)];
+    // each outer hex produces a different element
+    const DISPERSION: &[(HexIndex, u8)] = &[(hex(0, 0), 0), (hex(0, 1), 1), (hex(-1, 1), 2), (hex(0, -1), 3), (hex(1, -1), 4)];
+    const DISPOSAL: &[(HexIndex, u8)] = &[(hex(0, 0), 0), (hex(1, 0), 0), (hex(0, 1), 0), (hex(-1, 1), 0), (hex(-1, 0), 0), (hex(0, -1), 0), (hex(1, -1), 0)];
+    Some(match ty{
+        PartType::Equilibrium | PartType::Calcification => SINGLE,
+        PartType::Bonding | PartType::Unbonding => PAIR,
+        PartType::Duplication | PartType::Projection => SOURCE_TARGET,
+        PartType::MultiBonding => MULTI,
+        PartType::TriplexBonding => TRIPLEX,
+        PartType::Purification => PURIFICATION,
+        PartType::Animismus => ANIMISMUS,
+        PartType::Unification => UNIFICATION,
+        PartType::Dispersion => DISPERSION,
+        PartType::Disposal => DISPOSAL,
+        _ => return None
+    })
+}
+
+/// Where to place the mirror image of a part, as a position and rotation.
+fn mirror_placement(part: &Part) -> Result<(HexIndex, i32), &'static str>{
+    if part.ty == PartType::Berlo{
+        // the wheel's elements would run the other way around
+        return Err("Van Berlo's wheel has no mirror image");
+    }
+    let Some(roles) = glyph_roles(&part.ty) else {
+        return Ok((part.pos.mirrored(), -part.rotation));
+    };
+    let rotation = HexRotation::from_signed(part.rotation);
+    let mut target: Vec<(HexIndex, u8)> = roles.iter().map(|(offset, role)| ((part.pos + rotation.rotate_offset(*offset)).mirrored(), *role)).collect();
+    target.sort_by_key(|(h, role)| (h.q, h.r, *role));
+    // anchor the glyph's first hex on each mirrored hex in turn, and look for a rotation that lines everything up
+    for candidate in HexRotation::ALL{
+        for (anchor, _) in &target{
+            let pos = *anchor - candidate.rotate_offset(roles[0].0);
+            let mut placed: Vec<(HexIndex, u8)> = roles.iter().map(|(offset, role)| (pos + candidate.rotate_offset(*offset), *role)).collect();
+            placed.sort_by_key(|(h, role)| (h.q, h.r, *role));
+            if placed == target{
+                return Ok((pos, candidate.turns() as i32));
+            }
+        }
+    }
+    Err("glyph has no mirror image")
+}
@@ -0,0 +1,565 @@
+use alloc::collections::BTreeMap;
+use alloc::sync::Arc;
+use core::fmt::{Display, Formatter};
+use crate::analysis::overlap::find_overlaps;
+use crate::analysis::throughput::{check_area_bound, AreaBound};
+use crate::data::{Atom, Instruction, Metrics, Part, PartType, Puzzle, Solution};
+use crate::compat::prelude::*;
+use crate::sim::{Sim, SimEvent, SimPartType};
+use crate::sim::glyphs::glyph_offsets;
+use crate::sim::completion::{CompletionCriterion, StandardProducts};
+use crate::sim::config::SimConfig;
+use crate::sim::error::SimError;
+use crate::sim::provenance::ConsumedProduct;
+
+/// The version of the layout of [`Verdict::to_json`] and [`VerdictJson`], given as its `schema_version` field.
+///
+/// Within a version, fields are only ever added, so tools can rely on the ones they know. Removing, renaming or
+/// changing the type of a field bumps the version.
+pub const VERDICT_SCHEMA_VERSION: u32 = 1;
+
+/// The outcome of simulating a solution.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Verdict{
+    /// The metrics the solution achieved, or why it failed.
+    pub result: Result<Metrics, SimError>,
+    /// The metrics recorded in the solution file, if any.
+    pub recorded: Option<Metrics>,
+    /// Which completion criterion the solution met, and when, if it completed.
+    pub completion: Option<Completion>,
+    /// If the solution completed with metrics different from the recorded ones, how they differ and why.
+    pub divergences: Vec<MetricDivergence>,
+    /// Whether the solution was checked for collisions. If not, a valid result only means the solution
+    /// completes with these metrics assuming nothing collides.
+    pub collisions_checked: bool,
+    /// The number of atoms of each element destroyed by disposal glyphs, or nothing if the solution failed.
+    pub wasted: BTreeMap<Atom, u64>,
+    /// The number of molecules spawned from each reagent, by index in the puzzle's reagents, or nothing if the solution failed.
+    /// See [`SimConfig::input_quotas`] for limiting them.
+    pub spawned: BTreeMap<usize, u64>,
+    /// Secondary metrics, if the solution completed.
+    pub extended: Option<ExtendedMetrics>,
+    /// Properties of the solution's layout that some leaderboard categories require.
+    pub flags: SolutionFlags,
+    /// Every pair of parts that overlap, as indices into the solution's parts; see [`find_overlaps`].
+    /// Leaderboards usually rank solutions that use overlap separately.
+    pub overlaps: Vec<(usize, usize)>,
+    /// With [`SimConfig::track_provenance`], every product consumed and where each of its atoms came from.
+    pub consumed: Vec<ConsumedProduct>
+}
+
+/// Secondary metrics that some leaderboard categories are based on.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExtendedMetrics{
+    /// The cycle during which the last instruction on any tape first runs, counting from 0, with Repeats and Resets expanded.
+    /// Nothing if no part has any instructions.
+    pub last_instruction_cycle: Option<u64>,
+    /// The total length of every arm's tape as written, from the first cycle to its last instruction, blanks included.
+    pub tape_length: u64,
+    /// The number of arms, not counting Van Berlo's wheel.
+    pub arms: u64,
+    /// How the solution does when left running after completing, or nothing if it doesn't settle into a loop with bounded area
+    /// within the cycle limit, or fails along the way. Only worked out with [`SimConfig::asymptotic`].
+    pub asymptotic: Option<AsymptoticMetrics>,
+    /// How many times each input, output and glyph acted before the solution completed, in the order of the solution's parts.
+    /// Parts that never act are included with a count of 0, so optimizers can remove them.
+    pub activations: Vec<PartActivations>,
+    /// How far each arm's grippers got from its base before the solution completed, in the order of the solution's parts.
+    /// Only pistons can get further than they started, which some challenges restrict.
+    pub extensions: Vec<ArmExtension>
+}
+
+/// How many times a part acted: an input spawning its reagent, an output consuming a product, or a glyph changing atoms.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PartActivations{
+    /// The part's index in the solution.
+    pub part: usize,
+    pub count: u64
+}
+
+/// The furthest an arm reached during a run.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ArmExtension{
+    /// The arm's index in the solution.
+    pub part: usize,
+    pub number: i32,
+    pub max_length: i32
+}
+
+/// The metrics of a solution left running forever, which the rate leaderboards rank by.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct AsymptoticMetrics{
+    /// The number of cycles in each repetition of the solution's steady state.
+    pub period: u64,
+    /// The number of products consumed during each repetition, across all outputs.
+    pub products: u64,
+    /// The area the solution never grows past (area@∞).
+    pub area: u64,
+    /// The solution's cost.
+    pub cost: u64
+}
+
+impl AsymptoticMetrics{
+    /// The number of cycles per product (cycles@∞), or nothing if the solution stops making products.
+    pub fn rate(&self) -> Option<f64>{
+        (self.products > 0).then(|| self.period as f64 / self.products as f64)
+    }
+
+    /// The cost amortized over the products made in each repetition (cost@∞), or nothing if the solution stops making products.
+    pub fn cost_per_product(&self) -> Option<f64>{
+        (self.products > 0).then(|| self.cost as f64 / self.products as f64)
+    }
+}
+
+impl ExtendedMetrics{
+    fn of(solution: &Solution, sim: &Sim) -> ExtendedMetrics{
+        let last_instruction_cycle = sim.arms()
+            .filter_map(|(_, arm)| arm.tape.iter().rposition(|instr| !matches!(instr, Instruction::Blank | Instruction::PeriodOverride)))
+            .max()
+            .map(|cycle| cycle as u64);
+        let start = solution.tape_start();
+        let tape_length = solution.parts.iter()
+            .filter(|part| part.ty.is_arm() || part.ty == PartType::Berlo)
+            .map(|part| part.tape(start).len() as u64)
+            .sum();
+        let arms = solution.parts.iter().filter(|part| part.ty.is_arm()).count() as u64;
+        let asymptotic = match sim.config.asymptotic.then(|| check_area_bound(sim)){
+            Some(Ok(AreaBound::Bounded(steady))) => Some(AsymptoticMetrics{
+                period: steady.period,
+                products: steady.products.iter().sum(),
+                area: steady.area as u64,
+                cost: solution.parts.iter().map(Part::cost).sum::<i32>().max(0) as u64
+            }),
+            Some(Ok(AreaBound::Divergent{ .. }) | Err(_)) | None => None
+        };
+        let extensions = sim.parts.iter().enumerate().filter_map(|(part, p)| match &p.ty{
+            SimPartType::Arm(arm) if arm.ty.is_arm() => Some(ArmExtension{ part, number: arm.number, max_length: arm.max_length }),
+            _ => None
+        }).collect();
+        ExtendedMetrics{ last_instruction_cycle, tape_length, arms, asymptotic, activations: PartActivations::of(sim), extensions }
+    }
+}
+
+impl PartActivations{
+    fn of(sim: &Sim) -> Vec<PartActivations>{
+        let mut glyphs = BTreeMap::new();
+        for event in &sim.events{
+            if let SimEvent::GlyphApplied{ part, .. } = event{
+                *glyphs.entry(*part).or_insert(0) += 1;
+            }
+        }
+        sim.parts.iter().enumerate().filter_map(|(part, p)| {
+            let count = match &p.ty{
+                SimPartType::Input(input) => input.spawned,
+                SimPartType::Output(output) => output.consumed,
+                ty if glyph_offsets(ty).is_some() || matches!(ty, SimPartType::Custom(_)) => glyphs.get(&part).copied().unwrap_or(0),
+                _ => return None
+            };
+            Some(PartActivations{ part, count })
+        }).collect()
+    }
+}
+
+/// Properties of a solution's parts that some leaderboard categories are restricted to, computed without simulating it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SolutionFlags{
+    /// The solution has no tracks.
+    pub trackless: bool,
+    /// The solution has exactly one arm, not counting Van Berlo's wheel.
+    pub single_arm: bool,
+    /// No arm has a pivot instruction.
+    pub no_pivot: bool,
+    /// The types of glyph the solution uses, in the order they first appear.
+    pub glyphs: Vec<PartType>
+}
+
+impl SolutionFlags{
+    pub fn of(solution: &Solution) -> SolutionFlags{
+        let mut glyphs = Vec::new();
+        for part in solution.parts.iter().filter(|part| part.ty.is_glyph()){
+            if !glyphs.contains(&part.ty){
+                glyphs.push(part.ty.clone());
+            }
+        }
+        SolutionFlags{
+            trackless: !solution.parts.iter().any(|part| part.ty == PartType::Track),
+            single_arm: solution.parts.iter().filter(|part| part.ty.is_arm()).count() == 1,
+            no_pivot: !solution.parts.iter().flat_map(|part| &part.instructions)
+                .any(|(instr, _)| matches!(instr, Instruction::PivotClockwise | Instruction::PivotAnticlockwise)),
+            glyphs
+        }
+    }
+
+    /// Whether every glyph the solution uses is one of these types.
+    pub fn uses_only_glyphs(&self, allowed: &[PartType]) -> bool{
+        self.glyphs.iter().all(|glyph| allowed.contains(glyph))
+    }
+}
+
+/// A completion criterion that was met.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Completion{
+    /// The criterion's description, see [`CompletionCriterion::describe`].
+    pub criterion: String,
+    /// The number of cycles it took to meet it.
+    pub cycles: u64
+}
+
+/// A difference between simulated and recorded metrics, with what the simulation can tell about where it came from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MetricDivergence{
+    /// The solution took more cycles than recorded.
+    /// At the end of the cycle where the recorded run completed, these products were still outstanding,
+    /// as `(product, consumed, required)`.
+    Slower{ recorded: i32, simulated: i32, unsatisfied: Vec<(usize, u64, u64)> },
+    /// The solution took fewer cycles than recorded.
+    Faster{ recorded: i32, simulated: i32 },
+    /// The solution used more area than recorded, first exceeding the recorded area during `exceeded_at`.
+    AreaLarger{ recorded: i32, simulated: i32, exceeded_at: u64 },
+    AreaSmaller{ recorded: i32, simulated: i32 },
+    Cost{ recorded: i32, simulated: i32 },
+    Instructions{ recorded: i32, simulated: i32 }
+}
+
+impl Display for MetricDivergence{
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result{
+        match self{
+            MetricDivergence::Slower{ recorded, simulated, unsatisfied } => {
+                write!(f, "recorded {recorded} cycles, simulated {simulated}; first divergence at cycle {recorded} where ")?;
+                for (idx, (product, consumed, required)) in unsatisfied.iter().enumerate(){
+                    if idx > 0{
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "output {product} was not satisfied ({consumed} of {required})")?;
+                }
+                Ok(())
+            }
+            MetricDivergence::Faster{ recorded, simulated } =>
+                write!(f, "recorded {recorded} cycles, simulated {simulated}; every output was satisfied by cycle {simulated}"),
+            MetricDivergence::AreaLarger{ recorded, simulated, exceeded_at } =>
+                write!(f, "recorded area {recorded}, simulated {simulated}; area first exceeded {recorded} in cycle {exceeded_at}"),
+            MetricDivergence::AreaSmaller{ recorded, simulated } => write!(f, "recorded area {recorded}, simulated {simulated}"),
+            MetricDivergence::Cost{ recorded, simulated } => write!(f, "recorded cost {recorded}, simulated {simulated}"),
+            MetricDivergence::Instructions{ recorded, simulated } => write!(f, "recorded {recorded} instructions, simulated {simulated}")
+        }
+    }
+}
+
+impl Verdict{
+    pub fn is_valid(&self) -> bool{
+        self.result.is_ok()
+    }
+
+    /// Whether the solution completed with exactly the metrics recorded in its file.
+    /// Solutions without recorded metrics never match.
+    pub fn matches_recorded(&self) -> bool{
+        matches!((&self.result, &self.recorded), (Ok(actual), Some(recorded)) if actual == recorded)
+    }
+
+    /// Whether any parts overlap, which the game doesn't allow.
+    pub fn uses_overlap(&self) -> bool{
+        !self.overlaps.is_empty()
+    }
+
+    /// Whether the solution completed without destroying any atoms, for zero-waste challenges.
+    pub fn is_zero_waste(&self) -> bool{
+        self.is_valid() && self.wasted.is_empty()
+    }
+
+    /// A description of why the simulated metrics differ from the recorded ones, if they do.
+    pub fn explanation(&self) -> Option<String>{
+        if self.divergences.is_empty(){
+            return None;
+        }
+        Some(self.divergences.iter().map(ToString::to_string).collect::<Vec<_>>().join("; "))
+    }
+
+    /// The solution's score in each standard leaderboard category; see [`category_scores`](crate::analysis::categories::category_scores).
+    pub fn category_scores(&self) -> BTreeMap<&'static str, Vec<i64>>{
+        crate::analysis::categories::category_scores(self)
+    }
+
+    /// This verdict as a single-line JSON object, for tools that consume verdicts programmatically.
+    /// Divergences are included as their descriptions, and wasted atoms are keyed by element name.
+    /// The layout is versioned by [`VERDICT_SCHEMA_VERSION`], and modelled by [`VerdictJson`].
+    pub fn to_json(&self) -> String{
+        let model = self.to_json_model();
+        let optional = |value: Option<String>| value.unwrap_or_else(|| "null".to_string());
+        let mut fields = vec![format!("\"schema_version\":{}", model.schema_version), format!("\"valid\":{}", model.valid)];
+        if let Some(metrics) = &model.metrics{
+            fields.push(format!("\"metrics\":{}", metrics_json(metrics)));
+        }
+        if let Some(error) = &model.error{
+            fields.push(format!("\"error\":{}", json_string(error)));
+        }
+        fields.push(format!("\"recorded\":{}", optional(model.recorded.as_ref().map(metrics_json))));
+        fields.push(format!("\"collisions_checked\":{}", model.collisions_checked));
+        fields.push(format!("\"completion\":{}", optional(model.completion.as_ref().map(|completion| {
+            format!("{{\"criterion\":{},\"cycles\":{}}}", json_string(&completion.criterion), completion.cycles)
+        }))));
+        let divergences: Vec<String> = model.divergences.iter().map(|divergence| json_string(divergence)).collect();
+        fields.push(format!("\"divergences\":[{}]", divergences.join(",")));
+        let wasted: Vec<String> = model.wasted.iter().map(|(atom, count)| format!("{}:{count}", json_string(atom))).collect();
+        fields.push(format!("\"wasted\":{{{}}}", wasted.join(",")));
+        let spawned: Vec<String> = model.spawned.iter().map(|(reagent, count)| format!("\"{reagent}\":{count}")).collect();
+        fields.push(format!("\"spawned\":{{{}}}", spawned.join(",")));
+        fields.push(format!("\"extended\":{}", optional(model.extended.as_ref().map(|extended| {
+            let asymptotic = optional(extended.asymptotic.map(|asymptotic| {
+                let number = |value: Option<f64>| optional(value.map(|value| value.to_string()));
+                format!("{{\"period\":{},\"products\":{},\"area\":{},\"rate\":{},\"cost_per_product\":{}}}",
+                    asymptotic.period, asymptotic.products, asymptotic.area, number(asymptotic.rate), number(asymptotic.cost_per_product))
+            }));
+            let activations: Vec<String> = extended.activations.iter().map(|a| format!("{{\"part\":{},\"count\":{}}}", a.part, a.count)).collect();
+            let extensions: Vec<String> = extended.extensions.iter()
+                .map(|e| format!("{{\"part\":{},\"arm\":{},\"max_length\":{}}}", e.part, e.arm, e.max_length))
+                .collect();
+            format!("{{\"last_instruction_cycle\":{},\"tape_length\":{},\"arms\":{},\"asymptotic\":{asymptotic},\"activations\":[{}],\"extensions\":[{}]}}",
+                optional(extended.last_instruction_cycle.map(|cycle| cycle.to_string())), extended.tape_length, extended.arms, activations.join(","), extensions.join(","))
+        }))));
+        let glyphs: Vec<String> = model.flags.glyphs.iter().map(|glyph| json_string(glyph)).collect();
+        fields.push(format!("\"flags\":{{\"trackless\":{},\"single_arm\":{},\"no_pivot\":{},\"glyphs\":[{}]}}",
+            model.flags.trackless, model.flags.single_arm, model.flags.no_pivot, glyphs.join(",")));
+        let overlaps: Vec<String> = model.overlaps.iter().map(|(a, b)| format!("[{a},{b}]")).collect();
+        fields.push(format!("\"uses_overlap\":{},\"overlaps\":[{}]", model.uses_overlap, overlaps.join(",")));
+        format!("{{{}}}", fields.join(","))
+    }
+
+    /// This verdict in the layout of [`to_json`](Verdict::to_json).
+    pub fn to_json_model(&self) -> VerdictJson{
+        VerdictJson{
+            schema_version: VERDICT_SCHEMA_VERSION,
+            valid: self.is_valid(),
+            metrics: self.result.as_ref().ok().copied(),
+            error: self.result.as_ref().err().map(ToString::to_string),
+            recorded: self.recorded,
+            collisions_checked: self.collisions_checked,
+            completion: self.completion.clone(),
+            divergences: self.divergences.iter().map(ToString::to_string).collect(),
+            wasted: self.wasted.iter().map(|(atom, count)| (format!("{atom:?}"), *count)).collect(),
+            spawned: self.spawned.clone(),
+            extended: self.extended.as_ref().map(|extended| ExtendedMetricsJson{
+                last_instruction_cycle: extended.last_instruction_cycle,
+                tape_length: extended.tape_length,
+                arms: extended.arms,
+                asymptotic: extended.asymptotic.map(|asymptotic| AsymptoticMetricsJson{
+                    period: asymptotic.period,
+                    products: asymptotic.products,
+                    area: asymptotic.area,
+                    rate: asymptotic.rate(),
+                    cost_per_product: asymptotic.cost_per_product()
+                }),
+                activations: extended.activations.clone(),
+                extensions: extended.extensions.iter().map(|e| ArmExtensionJson{ part: e.part, arm: e.number, max_length: e.max_length }).collect()
+            }),
+            flags: SolutionFlagsJson{
+                trackless: self.flags.trackless,
+                single_arm: self.flags.single_arm,
+                no_pivot: self.flags.no_pivot,
+                glyphs: self.flags.glyphs.iter().map(|glyph| glyph.to_name().to_string()).collect()
+            },
+            uses_overlap: self.uses_overlap(),
+            overlaps: self.overlaps.clone()
+        }
+    }
+}
+
+/// A verdict in the layout of its JSON output, see [`Verdict::to_json`], for tools that read verdicts back.
+///
+/// With the `serde` feature, this (de)serializes to and from exactly that JSON. Like the layout, it only ever gains fields
+/// within a [`VERDICT_SCHEMA_VERSION`]; fields added since the version's first release default to empty when they're missing,
+/// so older verdicts still read.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VerdictJson{
+    pub schema_version: u32,
+    pub valid: bool,
+    /// The metrics the solution achieved, if it's valid.
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub metrics: Option<Metrics>,
+    /// Why the solution failed, if it's invalid.
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub error: Option<String>,
+    pub recorded: Option<Metrics>,
+    pub collisions_checked: bool,
+    pub completion: Option<Completion>,
+    /// The description of each divergence.
+    pub divergences: Vec<String>,
+    /// The number of atoms wasted, by element name.
+    pub wasted: BTreeMap<String, u64>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub spawned: BTreeMap<usize, u64>,
+    pub extended: Option<ExtendedMetricsJson>,
+    pub flags: SolutionFlagsJson,
+    pub uses_overlap: bool,
+    pub overlaps: Vec<(usize, usize)>
+}
+
+/// [`ExtendedMetrics`] in the layout of a verdict's JSON output.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ExtendedMetricsJson{
+    pub last_instruction_cycle: Option<u64>,
+    pub tape_length: u64,
+    pub arms: u64,
+    pub asymptotic: Option<AsymptoticMetricsJson>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub activations: Vec<PartActivations>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub extensions: Vec<ArmExtensionJson>
+}
+
+/// [`AsymptoticMetrics`] in the layout of a verdict's JSON output, with the rates worked out instead of the cost.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AsymptoticMetricsJson{
+    pub period: u64,
+    pub products: u64,
+    pub area: u64,
+    pub rate: Option<f64>,
+    pub cost_per_product: Option<f64>
+}
+
+/// [`ArmExtension`] in the layout of a verdict's JSON output.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ArmExtensionJson{
+    pub part: usize,
+    /// The arm's number.
+    pub arm: i32,
+    pub max_length: i32
+}
+
+/// [`SolutionFlags`] in the layout of a verdict's JSON output, with glyphs by name.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SolutionFlagsJson{
+    pub trackless: bool,
+    pub single_arm: bool,
+    pub no_pivot: bool,
+    pub glyphs: Vec<String>
+}
+
+fn metrics_json(metrics: &Metrics) -> String{
+    format!("{{\"cycles\":{},\"cost\":{},\"area\":{},\"instructions\":{}}}", metrics.cycles, metrics.cost, metrics.area, metrics.instructions)
+}
+
+pub(crate) fn json_string(s: &str) -> String{
+    let mut escaped = String::from("\"");
+    for c in s.chars(){
+        match c{
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c)
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+impl Display for Verdict{
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result{
+        match &self.result{
+            Ok(m) => {
+                write!(f, "valid: {} cycles, {} cost, {} area, {} instructions", m.cycles, m.cost, m.area, m.instructions)?;
+                if !self.collisions_checked{
+                    write!(f, " (collisions not checked)")?;
+                }
+                Ok(())
+            }
+            Err(err) => write!(f, "invalid: {err}")
+        }
+    }
+}
+
+/// Simulate a solution to completion with the default configuration.
+pub fn verify(puzzle: &Puzzle, solution: &Solution) -> Verdict{
+    verify_with_config(puzzle, solution, SimConfig::default())
+}
+
+pub fn verify_with_config(puzzle: &Puzzle, solution: &Solution, config: SimConfig) -> Verdict{
+    verify_with_criterion(puzzle, solution, config, Arc::new(StandardProducts))
+}
+
+/// Simulate a solution until it meets a custom completion criterion, e.g. for a challenge.
+pub fn verify_with_criterion(puzzle: &Puzzle, solution: &Solution, config: SimConfig, criterion: Arc<dyn CompletionCriterion>) -> Verdict{
+    let collisions_checked = !config.skip_collision;
+    let overlaps = find_overlaps(puzzle, solution);
+    let flags = puzzle.clean_solution(solution).map_or_else(|_| SolutionFlags::of(solution), |cleaned| SolutionFlags::of(&cleaned));
+    match run(puzzle, solution, config, criterion){
+        Ok((metrics, sim)) => {
+            let divergences = solution.metrics.map_or_else(Vec::new, |recorded| diverge(&metrics, &recorded, &sim));
+            let completion = sim.completed.map(|cycles| Completion{ criterion: sim.criterion.describe(), cycles });
+            let extended = Some(ExtendedMetrics::of(solution, &sim));
+            let spawned = sim.spawned();
+            Verdict{ result: Ok(metrics), recorded: solution.metrics, completion, divergences, collisions_checked, wasted: sim.wasted, spawned, extended, flags, overlaps, consumed: sim.consumed }
+        }
+        Err(err) => Verdict{
+            result: Err(err), recorded: solution.metrics, completion: None, divergences: Vec::new(), collisions_checked, wasted: BTreeMap::new(), spawned: BTreeMap::new(), extended: None,
+            flags, overlaps, consumed: Vec::new()
+        }
+    }
+}
+
+/// Simulate a solution to completion with the default configuration, keeping the finished sim.
+pub(crate) fn simulate(puzzle: &Puzzle, solution: &Solution) -> Result<(Metrics, Sim), SimError>{
+    run(puzzle, solution, SimConfig::default(), Arc::new(StandardProducts))
+}
+
+fn run(puzzle: &Puzzle, solution: &Solution, config: SimConfig, criterion: Arc<dyn CompletionCriterion>) -> Result<(Metrics, Sim), SimError>{
+    // setup would fail on these too, but without saying which part it was
+    if let Some(PartType::Unknown(name)) = solution.parts.iter().map(|part| &part.ty).find(|ty| matches!(ty, PartType::Unknown(_))){
+        return Err(SimError::UnsupportedPart(name.clone()));
+    }
+    let mut sim = Sim::create_with_config(puzzle, solution, config).map_err(SimError::Setup)?;
+    sim.criterion = criterion;
+    let cycles = sim.run()?;
+    let metrics = Metrics{
+        cycles: cycles as i32,
+        cost: solution.parts.iter().map(Part::cost).sum(),
+        area: sim.area.len() as i32,
+        instructions: solution.parts.iter().map(Part::instruction_count).sum()
+    };
+    Ok((metrics, sim))
+}
+
+/// Explain each difference between the simulated and recorded metrics using the simulation's events.
+fn diverge(actual: &Metrics, recorded: &Metrics, sim: &Sim) -> Vec<MetricDivergence>{
+    let mut divergences = Vec::new();
+    if actual.cycles > recorded.cycles{
+        // the recorded run completed at the end of cycle `recorded.cycles - 1`
+        let mut consumed = vec![0; sim.required.len()];
+        for event in &sim.events{
+            if let SimEvent::ProductConsumed{ cycle, product } = event{
+                if *cycle < recorded.cycles.max(0) as u64{
+                    consumed[*product] += 1;
+                }
+            }
+        }
+        let unsatisfied = consumed.iter().zip(&sim.required).enumerate()
+            .filter(|(_, (consumed, required))| consumed < required)
+            .map(|(product, (consumed, required))| (product, *consumed, *required))
+            .collect();
+        divergences.push(MetricDivergence::Slower{ recorded: recorded.cycles, simulated: actual.cycles, unsatisfied });
+    }else if actual.cycles < recorded.cycles{
+        divergences.push(MetricDivergence::Faster{ recorded: recorded.cycles, simulated: actual.cycles });
+    }
+    if actual.area > recorded.area{
+        let exceeded_at = sim.events.iter().find_map(|event| match event{
+            SimEvent::AreaGrew{ cycle, area } if *area as i32 > recorded.area => Some(*cycle),
+            _ => None
+        }).unwrap_or(0);
+        divergences.push(MetricDivergence::AreaLarger{ recorded: recorded.area, simulated: actual.area, exceeded_at });
+    }else if actual.area < recorded.area{
+        divergences.push(MetricDivergence::AreaSmaller{ recorded: recorded.area, simulated: actual.area });
+    }
+    if actual.cost != recorded.cost{
+        divergences.push(MetricDivergence::Cost{ recorded: recorded.cost, simulated: actual.cost });
+    }
+    if actual.instructions != recorded.instructions{
+        divergences.push(MetricDivergence::Instructions{ recorded: recorded.instructions, simulated: actual.instructions });
+    }
+    divergences
+}
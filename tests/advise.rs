@@ -0,0 +1,29 @@
+use omsim_rs::advise::{advise, Reason};
+use omsim_rs::parse::{parse_puzzle, parse_solution};
+use omsim_rs::verify::verify;
+
+#[test]
+fn late_arms_are_shifted_earlier(){
+    let puzzle = parse_puzzle(include_bytes!("../corpus/transfer.puzzle")).unwrap();
+    let mut solution = parse_solution(include_bytes!("../corpus/transfer.solution")).unwrap();
+    for (_, cycle) in &mut solution.parts[2].instructions{
+        *cycle += 3;
+    }
+    let suggestions = advise(&puzzle, &solution).unwrap();
+    let late = suggestions.iter().find(|s| matches!(s.reason, Reason::LateStart{ cycles: 3 })).unwrap();
+    assert_eq!((late.part, late.arm, late.shift), (2, 1, 3));
+    assert!(late.saves >= 3);
+    assert_eq!(late.to_string(), format!("arm 1 waits 3 cycles before its first instruction; shifting its tape left by 3 saves {} cycles", late.saves));
+    // the suggested solution really is that much faster
+    let before = verify(&puzzle, &solution).result.unwrap().cycles;
+    let after = verify(&puzzle, &late.solution).result.unwrap().cycles;
+    assert_eq!((before - after) as u64, late.saves);
+    assert!(suggestions.windows(2).all(|pair| pair[0].saves >= pair[1].saves));
+}
+
+#[test]
+fn tight_solutions_get_no_suggestions(){
+    let puzzle = parse_puzzle(include_bytes!("../corpus/transfer.puzzle")).unwrap();
+    let solution = parse_solution(include_bytes!("../corpus/transfer.solution")).unwrap();
+    assert_eq!(advise(&puzzle, &solution).unwrap(), Vec::new());
+}
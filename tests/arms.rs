@@ -0,0 +1,124 @@
+use omsim_rs::data::{HexIndex, HexRotation, Instruction, PartType, Puzzle, Solution};
+use omsim_rs::parse::{parse_puzzle, parse_solution};
+use omsim_rs::sim::{Sim, SimPartType};
+use omsim_rs::sim::config::SimConfig;
+
+fn sim() -> Sim{
+    let puzzle = parse_puzzle(include_bytes!("../corpus/transfer.puzzle")).unwrap();
+    let solution = parse_solution(include_bytes!("../corpus/transfer.solution")).unwrap();
+    Sim::create(&puzzle, &solution).unwrap()
+}
+
+#[test]
+fn arms_can_be_inspected_by_number(){
+    let mut sim = sim();
+    let arm = sim.arm(1).unwrap();
+    assert_eq!((arm.part, arm.pos, arm.rotation, arm.length), (2, HexIndex::new(1, 0), HexRotation::R180, 1));
+    assert_eq!((arm.grabbing, arm.held, arm.next), (false, vec![None], Instruction::Grab));
+    assert_eq!(arm.track_index, None);
+    assert!(sim.arm(2).is_none());
+
+    sim.step().unwrap();
+    let arm = sim.arm(1).unwrap();
+    assert!(arm.grabbing);
+    assert_eq!(arm.held, vec![sim.molecules.iter().position(|molecule| molecule.contains_pos(HexIndex::new(0, 0)))]);
+    assert_eq!(arm.next, Instruction::RotateClockwise);
+
+    sim.step().unwrap();
+    let arm = sim.arm(1).unwrap();
+    assert_eq!((arm.rotation, arm.grippers), (HexRotation::R120, vec![HexIndex::new(0, 1)]));
+    assert!(arm.held[0].is_some());
+}
+
+fn sim_with(ty: PartType, arm_length: i32, config: SimConfig) -> Result<Sim, &'static str>{
+    let puzzle = parse_puzzle(include_bytes!("../corpus/transfer.puzzle")).unwrap();
+    let mut solution = parse_solution(include_bytes!("../corpus/transfer.solution")).unwrap();
+    let arm = solution.parts.iter_mut().find(|part| part.ty.is_arm()).unwrap();
+    arm.ty = ty;
+    arm.arm_length = arm_length;
+    Sim::create_with_config(&puzzle, &solution, config)
+}
+
+#[test]
+fn modded_arm_lengths_are_rejected_by_default(){
+    assert!(sim_with(PartType::Arm, 3, SimConfig::default()).is_ok());
+    assert!(sim_with(PartType::Arm, 4, SimConfig::default()).unwrap_err().contains("allow_modded_values"));
+    assert!(sim_with(PartType::HexArm, 0, SimConfig::default()).is_err());
+}
+
+#[test]
+fn modded_arm_lengths_are_simulated_when_allowed(){
+    let config = SimConfig{ allow_modded_values: true, ..SimConfig::default() };
+    let sim = sim_with(PartType::HexArm, 5, config).unwrap();
+    let arm = sim.arm(1).unwrap();
+    assert_eq!(arm.length, 5);
+    assert_eq!(arm.grippers.len(), 6);
+    let distance = |hex: HexIndex| (hex.q.abs() + hex.r.abs() + (hex.q + hex.r).abs()) / 2;
+    assert!(arm.grippers.iter().all(|gripper| distance(*gripper - arm.pos) == 5));
+}
+
+fn piston(arm_length: i32, instructions: &[Instruction]) -> (Puzzle, Solution){
+    let puzzle = parse_puzzle(include_bytes!("../corpus/transfer.puzzle")).unwrap();
+    let mut solution = parse_solution(include_bytes!("../corpus/transfer.solution")).unwrap();
+    let arm = &mut solution.parts[2];
+    arm.ty = PartType::PistonArm;
+    arm.arm_length = arm_length;
+    arm.instructions = instructions.iter().enumerate().map(|(idx, instr)| (*instr, idx as i32)).collect();
+    (puzzle, solution)
+}
+
+#[test]
+fn pistons_track_their_max_extension(){
+    let (puzzle, solution) = piston(1, &[Instruction::Extend, Instruction::Extend, Instruction::Extend, Instruction::Retract]);
+    let mut sim = Sim::create(&puzzle, &solution).unwrap();
+    let SimPartType::Arm(arm) = &sim.parts[2].ty else { panic!("not an arm") };
+    assert_eq!(arm.max_length, 1);
+    for _ in 0..4{
+        sim.step().unwrap();
+    }
+    let SimPartType::Arm(arm) = &sim.parts[2].ty else { panic!("not an arm") };
+    // the third extend does nothing, since the piston is already at its longest
+    assert_eq!((arm.length, arm.max_length), (2, 3));
+}
+
+#[test]
+fn modded_pistons_extend_back_to_their_starting_length(){
+    let (puzzle, solution) = piston(5, &[Instruction::Retract, Instruction::Extend, Instruction::Extend]);
+    let mut sim = Sim::create_with_config(&puzzle, &solution, SimConfig{ allow_modded_values: true, ..SimConfig::default() }).unwrap();
+    let mut lengths = Vec::new();
+    for _ in 0..3{
+        sim.step().unwrap();
+        lengths.push(sim.arm(1).unwrap().length);
+    }
+    // extending past 3 is fine for a piston that started out longer, but not past where it started
+    assert_eq!(lengths, vec![4, 5, 5]);
+}
+
+#[test]
+fn piston_shafts_count_towards_area(){
+    let (puzzle, solution) = piston(3, &[Instruction::Blank]);
+    let mut sim = Sim::create(&puzzle, &solution).unwrap();
+    sim.step().unwrap();
+    // the base is at (1, 0) and the gripper at (-2, 0), so only the shaft covers (-1, 0)
+    assert!(sim.area.contains(&HexIndex::new(-1, 0)));
+
+    let (puzzle, mut solution) = piston(3, &[Instruction::Blank]);
+    solution.parts[2].ty = PartType::Arm;
+    let mut sim = Sim::create(&puzzle, &solution).unwrap();
+    sim.step().unwrap();
+    assert!(!sim.area.contains(&HexIndex::new(-1, 0)));
+}
+
+#[test]
+fn rotating_pistons_sweep_their_shaft(){
+    let (puzzle, solution) = piston(3, &[Instruction::RotateClockwise]);
+    let mut sim = Sim::create(&puzzle, &solution).unwrap();
+    sim.step().unwrap();
+    let SimPartType::Arm(arm) = &sim.parts[2].ty else { panic!("not an arm") };
+    let base = sim.parts[2].pos;
+    for hex in arm.shaft(base, sim.parts[2].rotation){
+        assert!(sim.area.contains(&hex), "{hex:?}");
+    }
+    // the middle of the shaft passes from (-1, 0) to (-1, 2) through (-1, 1) on the way
+    assert!(sim.area.contains(&HexIndex::new(-1, 1)), "{:?}", sim.area);
+}
@@ -0,0 +1,90 @@
+#![cfg(feature = "batch")]
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use omsim_rs::load::batch::SolutionBatch;
+use omsim_rs::parse::parse_solution;
+use zip::write::SimpleFileOptions;
+
+const SOLUTIONS: [(&str, &[u8]); 2] = [
+    ("bond.solution", include_bytes!("../corpus/bond.solution")),
+    ("transfer.solution", include_bytes!("../corpus/transfer.solution"))
+];
+
+/// A fresh path in the temporary directory. Each test writes its files there once and doesn't touch them while
+/// they're mapped, which is what `SolutionBatch::open` needs to be safe.
+fn scratch(name: &str) -> PathBuf{
+    let path = std::env::temp_dir().join(format!("omsim-rs-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&path);
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+fn check(batch: &SolutionBatch, dir: &Path){
+    assert_eq!(batch.len(), 2);
+    for (entry, (name, data)) in batch.iter().zip(SOLUTIONS){
+        let entry = entry.unwrap();
+        assert!(entry.path.ends_with(Path::new(dir).join(name)));
+        let solution = parse_solution(data).unwrap();
+        assert_eq!(entry.puzzle_name().unwrap(), solution.puzzle_name);
+        assert_eq!(entry.name().unwrap(), solution.name);
+        assert_eq!(entry.metrics().unwrap(), solution.metrics);
+        assert_eq!(entry.header().unwrap().name, solution.name);
+        assert_eq!(entry.solution().unwrap(), solution);
+    }
+}
+
+#[test]
+fn scans_directories_recursively(){
+    let root = scratch("dir");
+    std::fs::create_dir_all(root.join("nested")).unwrap();
+    std::fs::write(root.join("bond.solution"), SOLUTIONS[0].1).unwrap();
+    std::fs::write(root.join("nested").join("transfer.solution"), SOLUTIONS[1].1).unwrap();
+    std::fs::write(root.join("transfer.puzzle"), include_bytes!("../corpus/transfer.puzzle")).unwrap();
+    let batch = unsafe{ SolutionBatch::open(&root) }.unwrap();
+    assert_eq!(batch.get(1).unwrap().path, root.join("nested").join("transfer.solution"));
+    assert_eq!(batch.len(), 2);
+    assert_eq!(batch.get(0).unwrap().solution().unwrap(), parse_solution(SOLUTIONS[0].1).unwrap());
+    std::fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn scans_zip_archives(){
+    let path = scratch("archive.zip");
+    let mut writer = zip::ZipWriter::new(std::fs::File::create(&path).unwrap());
+    // one stored and one compressed entry, in reverse order
+    let methods = [zip::CompressionMethod::Stored, zip::CompressionMethod::Deflated];
+    for ((name, data), method) in SOLUTIONS.iter().zip(methods).rev(){
+        writer.start_file(format!("solutions/{name}"), SimpleFileOptions::default().compression_method(method)).unwrap();
+        writer.write_all(data).unwrap();
+    }
+    writer.start_file("readme.txt", SimpleFileOptions::default()).unwrap();
+    writer.finish().unwrap();
+    check(&unsafe{ SolutionBatch::open(&path) }.unwrap(), Path::new("solutions"));
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn headers_are_read_without_parsing_parts(){
+    let root = scratch("truncated");
+    std::fs::create_dir_all(&root).unwrap();
+    let (_, data) = SOLUTIONS[1];
+    // cut off in the middle of the parts list
+    std::fs::write(root.join("cut.solution"), &data[..data.len() - 8]).unwrap();
+    let entry = unsafe{ SolutionBatch::open(&root) }.unwrap().get(0).unwrap();
+    assert_eq!(entry.metrics().unwrap(), parse_solution(data).unwrap().metrics);
+    assert!(entry.solution().is_err());
+    std::fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn archive_names_are_sanitized(){
+    let path = scratch("hostile.zip");
+    let mut writer = zip::ZipWriter::new(std::fs::File::create(&path).unwrap());
+    writer.start_file("../escape.solution", SimpleFileOptions::default()).unwrap();
+    writer.write_all(SOLUTIONS[1].1).unwrap();
+    writer.finish().unwrap();
+    let entry = unsafe{ SolutionBatch::open(&path) }.unwrap().get(0).unwrap();
+    assert_eq!(entry.path, Path::new("_").join("escape.solution"));
+    std::fs::remove_file(path).unwrap();
+}
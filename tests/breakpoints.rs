@@ -0,0 +1,33 @@
+use omsim_rs::data::{Atom, HexIndex};
+use omsim_rs::parse::{parse_puzzle, parse_solution};
+use omsim_rs::sim::{Sim, SimEvent, Stop};
+
+fn sim() -> Sim{
+    let puzzle = parse_puzzle(include_bytes!("../corpus/transfer.puzzle")).unwrap();
+    let solution = parse_solution(include_bytes!("../corpus/transfer.solution")).unwrap();
+    Sim::create(&puzzle, &solution).unwrap()
+}
+
+#[test]
+fn breakpoints_match_events(){
+    let mut sim = sim();
+    let stop = sim.run_until_break(|_, event| matches!(event, Some(SimEvent::ProductConsumed{ .. }))).unwrap();
+    assert_eq!(stop, Stop::Break(Some(SimEvent::ProductConsumed{ cycle: 4, product: 0 })));
+    // the event happened during cycle 4, so five cycles have run
+    assert_eq!(sim.cycle, 5);
+    // resuming carries on from the next cycle
+    let stop = sim.run_until_break(|_, event| matches!(event, Some(SimEvent::ProductConsumed{ .. }))).unwrap();
+    assert!(matches!(stop, Stop::Break(Some(SimEvent::ProductConsumed{ cycle, .. })) if cycle > 4));
+}
+
+#[test]
+fn breakpoints_match_board_states(){
+    let mut sim = sim();
+    let target = HexIndex::new(1, 1);
+    let stop = sim.run_until_break(|sim, event| event.is_none()
+        && sim.lookup_atom(target, |found| found.atom_ty) == Some(Atom::Salt)).unwrap();
+    assert_eq!(stop, Stop::Break(None));
+    assert_eq!(sim.cycle, 3);
+    let stop = sim.run_until_break(|sim, _| sim.molecules.len() > 10).unwrap();
+    assert_eq!(stop, Stop::Completed(45));
+}
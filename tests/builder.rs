@@ -0,0 +1,60 @@
+use std::collections::{HashMap, HashSet};
+use omsim_rs::builder::PuzzleBuilder;
+use omsim_rs::data::{Atom, Bond, BondType, HexIndex, Molecule, MoleculeIssue};
+use omsim_rs::parse::{parse_puzzle, parse_puzzle_strict};
+
+fn bond(start: (i32, i32), end: (i32, i32)) -> Bond{
+    Bond{ start: HexIndex::new(start.0, start.1), end: HexIndex::new(end.0, end.1), ty: BondType::Normal }
+}
+
+fn molecule(atoms: &[(i32, i32)], bonds: &[Bond]) -> Molecule{
+    Molecule{
+        atoms: atoms.iter().map(|(q, r)| (HexIndex::new(*q, *r), Atom::Salt)).collect::<HashMap<_, _>>(),
+        bonds: bonds.iter().copied().collect::<HashSet<_>>()
+    }
+}
+
+#[test]
+fn validation_finds_every_issue(){
+    assert_eq!(molecule(&[(0, 0), (1, 0)], &[bond((0, 0), (1, 0))]).validate(false), Vec::new());
+    assert_eq!(molecule(&[], &[]).validate(false), vec![MoleculeIssue::Empty]);
+    assert_eq!(molecule(&[(0, 0), (2, 0)], &[]).validate(false), vec![MoleculeIssue::Disconnected]);
+    let dangling = bond((0, 0), (1, 0));
+    assert_eq!(molecule(&[(0, 0)], &[dangling]).validate(false), vec![MoleculeIssue::MissingAtom(dangling)]);
+    // the same pair of atoms, written both ways round
+    let duplicated = molecule(&[(0, 0), (1, 0)], &[bond((0, 0), (1, 0)), bond((1, 0), (0, 0))]);
+    assert_eq!(duplicated.validate(false), vec![MoleculeIssue::DuplicateBond(HexIndex::new(0, 0), HexIndex::new(1, 0))]);
+}
+
+#[test]
+fn quantum_bonds_are_only_valid_when_allowed(){
+    let quantum = bond((0, 0), (2, 0));
+    let molecule = molecule(&[(0, 0), (2, 0)], &[quantum]);
+    assert_eq!(molecule.validate(false), vec![MoleculeIssue::NonAdjacentBond(quantum)]);
+    assert_eq!(molecule.validate(true), Vec::new());
+    assert_eq!(PuzzleBuilder::new("quantum").product(molecule.clone()).build().unwrap_err(), "molecule has a bond between non-adjacent atoms");
+    assert!(PuzzleBuilder::new("quantum").product(molecule).allow_quantum_bonds(true).build().is_ok());
+}
+
+#[test]
+fn builders_reject_invalid_molecules(){
+    let pair = molecule(&[(0, 0), (1, 0)], &[bond((0, 0), (1, 0))]);
+    let puzzle = PuzzleBuilder::new("pair").reagent(pair.clone()).product(pair.clone()).product_multiplier(2).build().unwrap();
+    assert_eq!((puzzle.name.as_str(), puzzle.reagents.len(), puzzle.products.len(), puzzle.product_multiplier), ("pair", 1, 1, 2));
+    let apart = molecule(&[(0, 0), (2, 0)], &[]);
+    assert_eq!(PuzzleBuilder::new("apart").reagent(apart.clone()).product(pair.clone()).build().unwrap_err(), "molecule is not connected");
+    assert_eq!(PuzzleBuilder::new("apart").reagent(pair).product(apart).build().unwrap_err(), "molecule is not connected");
+}
+
+#[test]
+fn strict_parsing_rejects_invalid_molecules(){
+    let mut puzzle = parse_puzzle(include_bytes!("../corpus/transfer.puzzle")).unwrap();
+    assert_eq!(parse_puzzle_strict(&puzzle.unparse()), Ok(puzzle.clone()));
+    puzzle.products[0] = molecule(&[(0, 0), (2, 0)], &[]);
+    let data = puzzle.unparse();
+    // the game loads these, so only strict parsing rejects them
+    assert_eq!(parse_puzzle(&data), Ok(puzzle.clone()));
+    assert_eq!(parse_puzzle_strict(&data), Err("molecule is not connected"));
+    puzzle.products[0] = molecule(&[(0, 0)], &[bond((0, 0), (1, 0))]);
+    assert_eq!(parse_puzzle_strict(&puzzle.unparse()), Err("molecule has a bond to a missing atom"));
+}
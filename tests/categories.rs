@@ -0,0 +1,37 @@
+use omsim_rs::analysis::categories::{Category, Metric, Term, CATEGORIES};
+use omsim_rs::data::Metrics;
+use omsim_rs::parse::{parse_puzzle, parse_solution};
+use omsim_rs::verify::verify;
+
+#[test]
+fn scores_combine_metrics(){
+    let puzzle = parse_puzzle(include_bytes!("../corpus/transfer.puzzle")).unwrap();
+    let solution = parse_solution(include_bytes!("../corpus/transfer.solution")).unwrap();
+    // 45 cycles, 20 cost, 7 area, 8 instructions
+    let scores = verify(&puzzle, &solution).category_scores();
+    assert_eq!(scores.len(), CATEGORIES.len());
+    assert_eq!(scores["gc"], vec![20, 45, 7]);
+    assert_eq!(scores["cx"], vec![45, 20 * 45 * 7]);
+    assert_eq!(scores["ia"], vec![8, 7, 20]);
+    assert_eq!(scores["g·c"], vec![900, 20, 45]);
+    assert_eq!(scores["g+a"], vec![27, 20, 7]);
+    assert_eq!(scores["sum"], vec![72, 20, 45]);
+}
+
+#[test]
+fn failed_solutions_have_no_scores(){
+    let puzzle = parse_puzzle(include_bytes!("../corpus/transfer.puzzle")).unwrap();
+    let solution = parse_solution(include_bytes!("../corpus/collision.solution")).unwrap();
+    assert!(verify(&puzzle, &solution).category_scores().is_empty());
+}
+
+#[test]
+fn custom_categories_rank_lexicographically(){
+    let category = Category{ name: "i+c", terms: &[Term::Sum(&[Metric::Instructions, Metric::Cycles]), Term::Metric(Metric::Cost)] };
+    let fast = Metrics{ cycles: 10, cost: 100, area: 50, instructions: 15 };
+    let cheap = Metrics{ cycles: 20, cost: 30, area: 50, instructions: 10 };
+    assert!(category.score(&fast) < category.score(&cheap));
+    // ties go to the next term
+    let slow = Metrics{ cycles: 15, ..fast };
+    assert!(category.score(&cheap) < category.score(&slow));
+}
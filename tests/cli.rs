@@ -0,0 +1,116 @@
+use std::process::{Command, Output};
+
+fn verify(solution: &str, flag: &str) -> Output{
+    Command::new(env!("CARGO_BIN_EXE_omsim-rs"))
+        .args(["verify", "corpus/transfer.puzzle", solution, flag])
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .output().unwrap()
+}
+
+#[test]
+fn exit_codes_tell_results_apart(){
+    for (solution, code) in [
+        ("corpus/transfer.solution", 0),
+        ("corpus/collision.solution", 1),
+        ("corpus/transfer.puzzle", 2),
+        ("corpus/missing.solution", 2),
+        ("corpus/idle.solution", 4)
+    ]{
+        let output = verify(solution, "--quiet");
+        assert_eq!(output.status.code(), Some(code), "{solution}");
+        assert!(output.stdout.is_empty(), "{solution}");
+    }
+}
+
+#[test]
+fn porcelain_output_is_one_line_of_fields(){
+    assert_eq!(String::from_utf8(verify("corpus/transfer.solution", "--porcelain").stdout).unwrap(), "valid\t45\t20\t7\t8\n");
+    let invalid = String::from_utf8(verify("corpus/collision.solution", "--porcelain").stdout).unwrap();
+    assert!(invalid.starts_with("invalid\tcollision at "), "{invalid}");
+    assert_eq!(invalid.lines().count(), 1);
+}
+
+#[test]
+fn advise_says_when_it_has_nothing_to_suggest(){
+    let output = Command::new(env!("CARGO_BIN_EXE_omsim-rs"))
+        .args(["advise", "corpus/transfer.puzzle", "corpus/transfer.solution"])
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .output().unwrap();
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "no suggestions\n");
+}
+
+#[test]
+fn every_command_exits_with_2_on_unreadable_files(){
+    for args in [
+        &["diff", "corpus/transfer.solution", "corpus/missing.solution"][..],
+        &["check", "corpus/missing.puzzle"],
+        &["minify", "corpus/transfer.puzzle", "corpus/missing.solution"],
+        &["render", "corpus/transfer.puzzle", "corpus/missing.solution", "out.svg"],
+        &["optimize", "corpus/transfer.puzzle", "corpus/missing.solution", "cost"],
+        &["tournament", "corpus/missing.puzzle", "corpus/transfer.solution"],
+        &["trace", "corpus/transfer.puzzle", "corpus/missing.solution", "out.trace"],
+        &["tracediff", "corpus/missing.trace", "corpus/missing.trace"],
+        &["tracediff", "corpus/transfer.puzzle", "corpus/transfer.solution", "corpus/transfer.puzzle"],
+        &["debug", "corpus/missing.puzzle", "corpus/transfer.solution"],
+        &["corpus/transfer.puzzle", "corpus/transfer.solution", "--quota=0"],
+        &["render", "corpus/transfer.puzzle", "corpus/transfer.solution", "out.svg", "--frame-step=fast"]
+    ]{
+        let output = Command::new(env!("CARGO_BIN_EXE_omsim-rs"))
+            .args(args)
+            .current_dir(env!("CARGO_MANIFEST_DIR"))
+            .output().unwrap();
+        assert_eq!(output.status.code(), Some(2), "{args:?}");
+    }
+}
+
+#[test]
+fn missing_arguments_exit_with_2_and_the_usage(){
+    for args in [
+        &[][..],
+        &["verify", "corpus/transfer.puzzle"],
+        &["corpus/transfer.puzzle"],
+        &["diff", "corpus/transfer.solution"],
+        &["check"],
+        &["minify", "corpus/transfer.puzzle", "corpus/transfer.solution"],
+        &["render", "corpus/transfer.puzzle", "corpus/transfer.solution"],
+        &["optimize", "corpus/transfer.puzzle"],
+        &["advise", "corpus/transfer.puzzle"],
+        &["tournament"],
+        &["trace", "corpus/transfer.puzzle", "corpus/transfer.solution"],
+        &["tracediff", "corpus/transfer.puzzle"],
+        &["debug", "corpus/transfer.puzzle"],
+        &["crosscheck", "omsim"]
+    ]{
+        let output = Command::new(env!("CARGO_BIN_EXE_omsim-rs"))
+            .args(args)
+            .current_dir(env!("CARGO_MANIFEST_DIR"))
+            .output().unwrap();
+        assert_eq!(output.status.code(), Some(2), "{args:?}");
+        assert!(String::from_utf8(output.stderr).unwrap().starts_with("usage: omsim-rs"), "{args:?}");
+    }
+}
+
+#[test]
+fn solutions_that_cant_be_set_up_exit_with_1(){
+    let out = |name: &str| std::env::temp_dir().join(format!("omsim-rs-cli-{name}-{}", std::process::id())).to_str().unwrap().to_owned();
+    for args in [
+        vec!["render", "corpus/bond.puzzle", "corpus/missing-product.solution", &out("missing.svg")],
+        vec!["render", "corpus/bond.puzzle", "corpus/missing-product.solution", &out("missing.svg"), "--blueprint"],
+        vec!["trace", "corpus/bond.puzzle", "corpus/missing-product.solution", &out("missing.trace")],
+        vec!["tracediff", "corpus/bond.puzzle", "corpus/bond.solution", "corpus/missing-product.solution"]
+    ]{
+        let output = Command::new(env!("CARGO_BIN_EXE_omsim-rs"))
+            .args(&args)
+            .current_dir(env!("CARGO_MANIFEST_DIR"))
+            .output().unwrap();
+        assert_eq!(output.status.code(), Some(1), "{args:?}");
+        assert!(String::from_utf8(output.stdout).unwrap().starts_with("invalid: "), "{args:?}");
+    }
+    // blueprints don't run the solution, so ones that fail later still draw
+    let output = Command::new(env!("CARGO_BIN_EXE_omsim-rs"))
+        .args(["render", "corpus/transfer.puzzle", "corpus/collision.solution", &out("collision.svg"), "--blueprint"])
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .output().unwrap();
+    assert_eq!(output.status.code(), Some(0));
+}
@@ -0,0 +1,134 @@
+use std::collections::{HashMap, HashSet};
+use omsim_rs::data::{Atom, Bond, BondType, HexIndex, HexRotation, Instruction, Molecule, PartType};
+use omsim_rs::parse::{parse_puzzle, parse_solution};
+use omsim_rs::selftest::CORPUS;
+use omsim_rs::sim::{Sim, SimMolecule, SimPart, SimPartType};
+use omsim_rs::sim::arm::SimArm;
+use omsim_rs::sim::collision::{fixed_sin_cos, Collider, ColliderType, CollisionMath, Movement, FIXED_FRACTION_BITS};
+use omsim_rs::sim::config::SimConfig;
+use omsim_rs::sim::error::SimError;
+use omsim_rs::verify::{verify, verify_with_config};
+
+#[test]
+fn fixed_trig_is_accurate(){
+    let scale = (1u64 << 30) as f64;
+    for degrees in (-720..=720).step_by(15){
+        let radians = (degrees as f64).to_radians();
+        let (sin, cos) = fixed_sin_cos((radians * scale).round() as i64);
+        assert!((sin as f64 / scale - radians.sin()).abs() < 1e-6, "sin {degrees}");
+        assert!((cos as f64 / scale - radians.cos()).abs() < 1e-6, "cos {degrees}");
+    }
+}
+
+#[test]
+fn fixed_positions_follow_float_positions(){
+    let scale = (1u64 << FIXED_FRACTION_BITS) as f32;
+    let movements = [
+        Movement::Stay{ at: HexIndex::new(3, -2) },
+        Movement::Translate{ start: HexIndex::new(0, 0), end: HexIndex::new(-1, 2) },
+        Movement::Rotate{ start: HexIndex::new(2, 0), around: HexIndex::new(0, 1), rotation: HexRotation::R60 },
+        Movement::Rotate{ start: HexIndex::new(-1, 3), around: HexIndex::new(1, 1), rotation: HexRotation::R180 },
+        Movement::Rotate{ start: HexIndex::new(1, 0), around: HexIndex::new(0, 0), rotation: HexRotation::R300 }
+    ];
+    for movement in movements{
+        for step in 0..=8{
+            let float = movement.pos_at(step as f32 / 8.0);
+            let fixed = movement.fixed_pos_at(step, 8);
+            assert!((fixed.x as f32 / scale - float.x).abs() < 0.01, "{movement:?} at {step}");
+            assert!((fixed.y as f32 / scale - float.y).abs() < 0.01, "{movement:?} at {step}");
+        }
+    }
+}
+
+#[test]
+fn fixed_point_verdicts_match_corpus(){
+    let config = SimConfig{ collision_math: CollisionMath::FixedPoint, ..SimConfig::default() };
+    for case in CORPUS{
+        let puzzle = parse_puzzle(case.puzzle).unwrap();
+        let solution = parse_solution(case.solution).unwrap();
+        assert_eq!(verify_with_config(&puzzle, &solution, config.clone()), verify(&puzzle, &solution), "{}", case.name);
+    }
+}
+
+#[test]
+fn rotations_sweep_through_hexes_between_their_ends(){
+    let origin = HexIndex::default();
+    let near = Movement::Rotate{ start: HexIndex::new(1, 0), around: origin, rotation: HexRotation::R60 };
+    assert_eq!(near.swept_hexes(), vec![HexIndex::new(1, 0), near.end()]);
+    let far = Movement::Rotate{ start: HexIndex::new(2, 0), around: origin, rotation: HexRotation::R60 };
+    let swept = far.swept_hexes();
+    assert_eq!(swept.len(), 3);
+    assert_eq!((swept[0], swept[2]), (HexIndex::new(2, 0), far.end()));
+    assert_eq!(swept[1].distance(origin), 2);
+    let step = Movement::Translate{ start: origin, end: HexIndex::new(1, 0) };
+    assert_eq!(step.swept_hexes(), vec![origin, HexIndex::new(1, 0)]);
+}
+
+fn arm(tape: Vec<Instruction>) -> SimPart{
+    SimPart{ pos: HexIndex::default(), rotation: HexRotation::R0, ty: SimPartType::Arm(SimArm::new(PartType::Arm, 1, 1, tape)) }
+}
+
+/// Two bonded salt atoms, with the first at the arm's gripper and the second next to both the gripper and the arm's base.
+fn pair() -> SimMolecule{
+    let (gripper, beside) = (HexIndex::new(1, 0), HexIndex::new(1, -1));
+    let atoms = HashMap::from([(gripper, Atom::Salt), (beside, Atom::Salt)]);
+    let bonds = HashSet::from([Bond{ start: gripper, end: beside, ty: BondType::Normal }]);
+    SimMolecule::new(Molecule{ atoms, bonds }, HexIndex::default())
+}
+
+#[test]
+fn held_molecules_collide_with_their_own_arm(){
+    // pivoting one way swings the second atom onto the base holding it, and the other way away from it
+    let outcomes: Vec<bool> = [Instruction::PivotClockwise, Instruction::PivotAnticlockwise].into_iter().map(|pivot| {
+        let mut sim = Sim::new(vec![arm(vec![Instruction::Grab, pivot])], vec![pair()], SimConfig::default());
+        sim.step().unwrap();
+        matches!(sim.step(), Err(SimError::Collision{ cycle: 1, .. }))
+    }).collect();
+    assert_eq!(outcomes.iter().filter(|collided| **collided).count(), 1, "{outcomes:?}");
+}
+
+#[test]
+fn grippers_pass_over_atoms(){
+    // an empty gripper rotating onto a loose atom, then grabbing and carrying it away
+    let loose = SimMolecule::new(Molecule{ atoms: HashMap::from([(HexIndex::default(), Atom::Fire)]), bonds: HashSet::new() }, HexIndex::new(0, 1));
+    let tape = vec![Instruction::RotateAnticlockwise, Instruction::Grab, Instruction::RotateAnticlockwise];
+    let mut sim = Sim::new(vec![arm(tape)], vec![loose], SimConfig::default());
+    for _ in 0..3{
+        sim.step().unwrap();
+    }
+    assert_eq!(sim.molecules[0].positions().collect::<Vec<_>>(), vec![HexIndex::new(-1, 1)]);
+}
+
+#[test]
+fn colliders_of_one_body_never_collide(){
+    let atom = |at, body| Collider{ ty: ColliderType::Atom, movement: Movement::Stay{ at }, body };
+    let gripper = Collider{ ty: ColliderType::ArmGripper, movement: Movement::Stay{ at: HexIndex::default() }, body: 2 };
+    assert_eq!(atom(HexIndex::default(), 0).radius_with(&atom(HexIndex::new(1, 0), 0)), None);
+    assert_eq!(atom(HexIndex::default(), 0).radius_with(&atom(HexIndex::new(1, 0), 1)), Some(58.0));
+    assert_eq!(gripper.radius_with(&atom(HexIndex::default(), 0)), None);
+}
+
+#[test]
+fn bond_area_counts_the_hexes_the_far_atom_swings_through(){
+    let case = CORPUS.iter().find(|case| case.name == "bond").unwrap();
+    let mut sim = Sim::create(&parse_puzzle(case.puzzle).unwrap(), &parse_solution(case.solution).unwrap()).unwrap();
+    let resting = |sim: &Sim| -> HashSet<HexIndex>{
+        let mut hexes: HashSet<HexIndex> = sim.molecules.iter().flat_map(|molecule| molecule.positions()).collect();
+        for part in &sim.parts{
+            if let SimPartType::Arm(arm) = &part.ty{
+                hexes.insert(part.pos);
+                hexes.extend(arm.grippers(part.pos, part.rotation));
+                hexes.extend(arm.shaft(part.pos, part.rotation));
+            }
+        }
+        hexes
+    };
+    let mut rested = resting(&sim);
+    while sim.completed.is_none(){
+        sim.step().unwrap();
+        rested.extend(resting(&sim));
+    }
+    let swept: HashSet<HexIndex> = sim.area.difference(&rested).copied().collect();
+    assert_eq!(swept, HashSet::from([HexIndex::new(1, -1), HexIndex::new(0, -2), HexIndex::new(-2, -1)]));
+    assert_eq!(sim.area.len(), 14);
+}
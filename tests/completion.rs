@@ -0,0 +1,90 @@
+use std::sync::Arc;
+use omsim_rs::data::{PartType, Puzzle, Solution};
+use omsim_rs::parse::{parse_puzzle, parse_solution};
+use omsim_rs::sim::completion::{EmptyBoard, ProduceCount, SurviveCycles};
+use omsim_rs::sim::config::SimConfig;
+use omsim_rs::sim::error::SimError;
+use omsim_rs::sim::{ProductProgress, Sim};
+use omsim_rs::verify::{verify, verify_with_criterion, Completion};
+
+fn transfer() -> (Puzzle, Solution){
+    let puzzle = parse_puzzle(include_bytes!("../corpus/transfer.puzzle")).unwrap();
+    let solution = parse_solution(include_bytes!("../corpus/transfer.solution")).unwrap();
+    (puzzle, solution)
+}
+
+#[test]
+fn reports_progress_each_cycle(){
+    let (puzzle, solution) = transfer();
+    let mut sim = Sim::create(&puzzle, &solution).unwrap();
+    let mut last = 0;
+    loop{
+        let report = sim.step().unwrap();
+        let [progress] = report.products[..] else { panic!("expected one product") };
+        assert_eq!(progress.required, 6 * puzzle.product_multiplier as u64);
+        assert!(progress.consumed >= last);
+        last = progress.consumed;
+        if let Some(cycles) = report.completed{
+            assert!(progress.is_satisfied());
+            assert_eq!(cycles, report.cycle + 1);
+            break;
+        }
+        assert!(!progress.is_satisfied());
+    }
+}
+
+#[test]
+fn output_target_overrides_the_puzzle(){
+    let (puzzle, solution) = transfer();
+    let config = SimConfig{ output_target: Some(2), ..SimConfig::default() };
+    let mut sim = Sim::create_with_config(&puzzle, &solution, config).unwrap();
+    let cycles = sim.run().unwrap();
+    assert!(cycles < 45);
+    assert_eq!(sim.progress(), vec![ProductProgress{ product: 0, consumed: 2, required: 2 }]);
+}
+
+#[test]
+fn custom_criteria_complete_and_are_reported(){
+    let (puzzle, solution) = transfer();
+    let standard = verify(&puzzle, &solution);
+    assert_eq!(standard.completion, Some(Completion{ criterion: "produce every required product".to_string(), cycles: 45 }));
+
+    let survive = verify_with_criterion(&puzzle, &solution, SimConfig::default(), Arc::new(SurviveCycles(100)));
+    assert_eq!(survive.completion, Some(Completion{ criterion: "survive 100 cycles".to_string(), cycles: 100 }));
+
+    let two = verify_with_criterion(&puzzle, &solution, SimConfig::default(), Arc::new(ProduceCount{ product: 0, count: 2 }));
+    let cycles = two.completion.as_ref().unwrap().cycles;
+    assert!(cycles < 45);
+    assert_eq!(two.completion.unwrap().criterion, "produce 2 of output 0");
+
+    // inputs refill the board every cycle, so it's never empty
+    let config = SimConfig{ max_cycles: 200, ..SimConfig::default() };
+    let empty = verify_with_criterion(&puzzle, &solution, config, Arc::new(EmptyBoard));
+    assert!(!empty.is_valid());
+    assert_eq!(empty.completion, None);
+}
+
+#[test]
+fn puzzles_without_products_never_complete(){
+    let (mut puzzle, mut solution) = transfer();
+    puzzle.products.clear();
+    // the output has nothing to accept
+    solution.parts.retain(|part| part.ty != PartType::Output);
+    let verdict = verify(&puzzle, &solution);
+    assert_eq!(verdict.result, Err(SimError::NeverCompletes("puzzle has no products")));
+    // other goals can still be met
+    let verdict = verify_with_criterion(&puzzle, &solution, SimConfig::default(), Arc::new(SurviveCycles(10)));
+    assert!(verdict.is_valid(), "{verdict}");
+    let verdict = verify_with_criterion(&puzzle, &solution, SimConfig::default(), Arc::new(ProduceCount{ product: 0, count: 1 }));
+    assert_eq!(verdict.result, Err(SimError::NeverCompletes("puzzle has no such product")));
+}
+
+#[test]
+fn inputs_need_reagents(){
+    let (mut puzzle, solution) = transfer();
+    puzzle.reagents.clear();
+    assert_eq!(verify(&puzzle, &solution).result, Err(SimError::Setup("solution contains an input, but the puzzle has no reagents")));
+    // with no parts at all, there's nothing to go wrong until the end
+    let empty = Solution{ parts: Vec::new(), ..solution };
+    assert_eq!(verify(&puzzle, &empty).result.unwrap_err(), SimError::CycleLimit{ cycles: 100_000 });
+}
@@ -0,0 +1,161 @@
+use std::collections::HashMap;
+use omsim_rs::analysis::conduits::ConduitIssue;
+use omsim_rs::builder::PuzzleBuilder;
+use omsim_rs::data::{Atom, Bond, BondType, Chamber, ChamberType, Conduit, HexIndex, HexRotation, Instruction, Molecule, Part, PartType, ProductionInfo, Puzzle, Solution};
+use omsim_rs::sim::Sim;
+use omsim_rs::sim::config::SimConfig;
+use omsim_rs::sim::error::SimError;
+
+const FOOTPRINT: [HexIndex; 3] = [HexIndex::new(0, 0), HexIndex::new(1, 0), HexIndex::new(2, -1)];
+
+fn puzzle() -> Puzzle{
+    PuzzleBuilder::new("conduits")
+        .production_info(ProductionInfo{
+            isolation: false,
+            chambers: vec![Chamber{ pos: HexIndex::new(0, 0), ty: ChamberType::Small }, Chamber{ pos: HexIndex::new(6, 0), ty: ChamberType::Small }],
+            conduits: vec![Conduit{ pos_a: HexIndex::new(1, 1), pos_b: HexIndex::new(7, 1), hexes: FOOTPRINT.to_vec() }],
+            ..ProductionInfo::default()
+        })
+        .build().unwrap()
+}
+
+fn conduit(conduit_index: i32, pos: HexIndex, conduit_hexes: Vec<HexIndex>) -> Part{
+    Part{
+        ty: PartType::Conduit, pos, rotation: 0, arm_number: 1, arm_length: 1, index: 0,
+        conduit_index, track_hexes: Vec::new(), conduit_hexes, instructions: Vec::new()
+    }
+}
+
+fn solution(parts: Vec<Part>) -> Solution{
+    Solution{ name: "conduits".into(), puzzle_name: "conduits".into(), metrics: None, parts }
+}
+
+#[test]
+fn rotated_ends_are_legal(){
+    let rotated = FOOTPRINT.iter().map(|hex| hex.rotated(HexIndex::default(), HexRotation::R120)).collect();
+    let solution = solution(vec![conduit(0, HexIndex::new(1, 1), FOOTPRINT.to_vec()), conduit(0, HexIndex::new(7, 1), rotated)]);
+    assert_eq!(puzzle().conduit_issues(&solution), vec![]);
+}
+
+#[test]
+fn mismatched_conduits_are_reported(){
+    let mut stretched = FOOTPRINT.to_vec();
+    stretched.push(HexIndex::new(3, -1));
+    let solution = solution(vec![
+        conduit(0, HexIndex::new(1, 1), FOOTPRINT.to_vec()),
+        conduit(0, HexIndex::new(7, 1), stretched),
+        conduit(0, HexIndex::new(9, 1), FOOTPRINT.to_vec()),
+        conduit(1, HexIndex::new(1, 3), FOOTPRINT.to_vec())
+    ]);
+    assert_eq!(puzzle().conduit_issues(&solution), vec![
+        ConduitIssue::FootprintMismatch{ part: 1, conduit: 0 },
+        ConduitIssue::UnknownConduit{ part: 3, conduit_index: 1 },
+        ConduitIssue::WrongEndCount{ conduit: 0, ends: 3 }
+    ]);
+}
+
+#[test]
+fn missing_ends_are_reported(){
+    assert_eq!(puzzle().conduit_issues(&solution(Vec::new())), vec![ConduitIssue::WrongEndCount{ conduit: 0, ends: 0 }]);
+    let mut puzzle = puzzle();
+    puzzle.production_info = None;
+    let solution = solution(vec![conduit(0, HexIndex::new(1, 1), FOOTPRINT.to_vec())]);
+    assert_eq!(puzzle.conduit_issues(&solution), vec![ConduitIssue::UnknownConduit{ part: 0, conduit_index: 0 }]);
+}
+
+/// A sandbox with conduit 0 running from (1, 1) to (7, 1), its second end turned by `rotation`.
+fn connected(rotation: HexRotation, extra: Vec<Part>, molecules: &[Molecule]) -> Sim{
+    let rotated = FOOTPRINT.iter().map(|hex| hex.rotated(HexIndex::default(), rotation)).collect();
+    let mut far = conduit(0, HexIndex::new(7, 1), rotated);
+    far.rotation = rotation.turns() as i32;
+    let mut parts = vec![conduit(0, HexIndex::new(1, 1), FOOTPRINT.to_vec()), far];
+    parts.extend(extra);
+    Sim::sandbox(&parts, molecules, SimConfig::default()).unwrap()
+}
+
+fn molecule(atoms: &[(HexIndex, Atom)]) -> Molecule{
+    let bonds = atoms.windows(2).map(|pair| Bond{ start: pair[0].0, end: pair[1].0, ty: BondType::Normal }).collect();
+    Molecule{ atoms: atoms.iter().copied().collect::<HashMap<_, _>>(), bonds }
+}
+
+fn atom_at(sim: &Sim, pos: HexIndex) -> Option<Atom>{
+    sim.lookup_atom(pos, |r| r.atom_ty)
+}
+
+#[test]
+fn dropped_molecules_are_carried_and_turned(){
+    // salt on the end's origin bonded to fire one hex along it
+    let carried = molecule(&[(HexIndex::new(1, 1), Atom::Salt), (HexIndex::new(2, 1), Atom::Fire)]);
+    let mut sim = connected(HexRotation::R120, Vec::new(), &[carried]);
+    sim.step().unwrap();
+    let fire = HexIndex::new(7, 1) + HexIndex::new(1, 0).rotated(HexIndex::default(), HexRotation::R120);
+    assert_eq!(atom_at(&sim, HexIndex::new(7, 1)), Some(Atom::Salt));
+    assert_eq!(atom_at(&sim, fire), Some(Atom::Fire));
+    assert_eq!(atom_at(&sim, HexIndex::new(1, 1)), None);
+    assert_eq!(sim.molecules.len(), 1);
+    assert!(sim.molecules[0].bond_at(HexIndex::new(7, 1), fire).is_some());
+}
+
+#[test]
+fn held_molecules_stay_put(){
+    // an arm at (0, 1) grabs the salt on (1, 1) and never lets go
+    let arm = Part{
+        ty: PartType::Arm, pos: HexIndex::new(0, 1), rotation: 0, arm_number: 0, arm_length: 1, index: 0, conduit_index: 0,
+        track_hexes: Vec::new(), conduit_hexes: Vec::new(), instructions: vec![(Instruction::Grab, 0)]
+    };
+    let mut sim = connected(HexRotation::R0, vec![arm], &[molecule(&[(HexIndex::new(1, 1), Atom::Salt)])]);
+    sim.run_until(3).unwrap();
+    assert_eq!(atom_at(&sim, HexIndex::new(1, 1)), Some(Atom::Salt));
+    assert_eq!(atom_at(&sim, HexIndex::new(7, 1)), None);
+}
+
+#[test]
+fn molecules_must_lie_entirely_on_an_end(){
+    // the water hangs off the end, so the whole molecule stays
+    let overhanging = molecule(&[(HexIndex::new(1, 1), Atom::Salt), (HexIndex::new(0, 1), Atom::Water)]);
+    let mut sim = connected(HexRotation::R0, Vec::new(), &[overhanging]);
+    sim.step().unwrap();
+    assert_eq!(atom_at(&sim, HexIndex::new(1, 1)), Some(Atom::Salt));
+    assert_eq!(atom_at(&sim, HexIndex::new(0, 1)), Some(Atom::Water));
+}
+
+#[test]
+fn both_ends_carry_at_once(){
+    let atoms = [molecule(&[(HexIndex::new(1, 1), Atom::Salt)]), molecule(&[(HexIndex::new(7, 1), Atom::Fire)])];
+    let mut sim = connected(HexRotation::R0, Vec::new(), &atoms);
+    sim.step().unwrap();
+    assert_eq!(atom_at(&sim, HexIndex::new(1, 1)), Some(Atom::Fire));
+    assert_eq!(atom_at(&sim, HexIndex::new(7, 1)), Some(Atom::Salt));
+}
+
+#[test]
+fn carried_molecules_collide_with_what_stays(){
+    let atoms = [
+        molecule(&[(HexIndex::new(1, 1), Atom::Salt)]),
+        molecule(&[(HexIndex::new(7, 1), Atom::Fire), (HexIndex::new(6, 1), Atom::Water)])
+    ];
+    let mut sim = connected(HexRotation::R0, Vec::new(), &atoms);
+    assert_eq!(sim.step().err(), Some(SimError::Collision{ cycle: 0, pos: HexIndex::new(7, 1) }));
+}
+
+#[test]
+fn carried_molecules_stay_on_the_far_end(){
+    let mut sim = connected(HexRotation::R0, Vec::new(), &[molecule(&[(HexIndex::new(1, 1), Atom::Salt)])]);
+    sim.run_until(5).unwrap();
+    assert_eq!(atom_at(&sim, HexIndex::new(7, 1)), Some(Atom::Salt));
+    assert_eq!(atom_at(&sim, HexIndex::new(1, 1)), None);
+}
+
+#[test]
+fn regrabbed_molecules_are_carried_again(){
+    // an arm at (6, 1) grabs the salt after it arrives on (7, 1), then drops it where it is
+    let arm = Part{
+        ty: PartType::Arm, pos: HexIndex::new(6, 1), rotation: 0, arm_number: 0, arm_length: 1, index: 0, conduit_index: 0,
+        track_hexes: Vec::new(), conduit_hexes: Vec::new(), instructions: vec![(Instruction::Grab, 2), (Instruction::Drop, 3)]
+    };
+    let mut sim = connected(HexRotation::R0, vec![arm], &[molecule(&[(HexIndex::new(1, 1), Atom::Salt)])]);
+    sim.run_until(3).unwrap();
+    assert_eq!(atom_at(&sim, HexIndex::new(7, 1)), Some(Atom::Salt));
+    sim.run_until(4).unwrap();
+    assert_eq!(atom_at(&sim, HexIndex::new(1, 1)), Some(Atom::Salt));
+}
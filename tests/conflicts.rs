@@ -0,0 +1,80 @@
+use omsim_rs::data::{Atom, HexIndex, Instruction::{self, *}, PartType};
+use omsim_rs::scenario::Scenario;
+use omsim_rs::sim::error::SimError;
+
+/// Water at (0, 0) bonded to fire at (1, 0), with arm 0 gripping the water from the left
+/// and arm 1 gripping the fire from the right.
+fn pair(left: &[Instruction], right: &[Instruction]) -> Scenario{
+    Scenario::new()
+        .atom(Atom::Water, HexIndex::new(0, 0))
+        .atom(Atom::Fire, HexIndex::new(1, 0))
+        .bond(HexIndex::new(0, 0), HexIndex::new(1, 0))
+        .arm(PartType::Arm, HexIndex::new(-1, 0), 0, 1, left)
+        .arm(PartType::Arm, HexIndex::new(2, 0), 3, 1, right)
+}
+
+fn error(scenario: Scenario, cycles: u64) -> SimError{
+    let mut sim = scenario.board().unwrap();
+    sim.run_until(cycles).unwrap_err()
+}
+
+#[test]
+fn tug_of_war_conflicts(){
+    let err = error(pair(&[Grab, RotateClockwise], &[Grab, RotateAnticlockwise]), 2);
+    assert_eq!(err, SimError::ConflictingMovement{ cycle: 1, pos: HexIndex::new(1, 0), arms: (0, 1), held_still: false });
+    assert_eq!(err.to_string(), "arms 0 and 1 moved the molecule at (1, 0) in two ways at once in cycle 1");
+    // rotating the same way still pulls around two different bases
+    assert!(matches!(error(pair(&[Grab, RotateClockwise], &[Grab, RotateClockwise]), 2), SimError::ConflictingMovement{ held_still: false, .. }));
+}
+
+#[test]
+fn moving_a_molecule_another_arm_holds_conflicts(){
+    let err = error(pair(&[Grab, Blank], &[Grab, RotateClockwise]), 2);
+    assert_eq!(err, SimError::ConflictingMovement{ cycle: 1, pos: HexIndex::new(1, 0), arms: (1, 0), held_still: true });
+    assert_eq!(err.to_string(), "arm 1 moved the molecule at (1, 0) while arm 0 held it still in cycle 1");
+    let err = error(pair(&[Grab, RotateClockwise], &[Grab]), 2);
+    assert!(matches!(err, SimError::ConflictingMovement{ arms: (0, 1), held_still: true, .. }), "{err}");
+}
+
+#[test]
+fn letting_go_first_avoids_the_conflict(){
+    pair(&[Grab, Blank, RotateClockwise], &[Grab, Drop])
+        .expect_atom(3, HexIndex::new(0, -1), Atom::Water)
+        .expect_atom(3, HexIndex::new(1, -2), Atom::Fire)
+        .run()
+        .unwrap();
+}
+
+#[test]
+fn grippers_of_one_arm_co_rotate(){
+    // a bi-arm at (1, 0) holding both ends of a bent chain
+    Scenario::new()
+        .atom(Atom::Salt, HexIndex::new(0, 0))
+        .atom(Atom::Water, HexIndex::new(0, 1))
+        .atom(Atom::Fire, HexIndex::new(1, 1))
+        .atom(Atom::Air, HexIndex::new(2, 0))
+        .bond(HexIndex::new(0, 0), HexIndex::new(0, 1))
+        .bond(HexIndex::new(0, 1), HexIndex::new(1, 1))
+        .bond(HexIndex::new(1, 1), HexIndex::new(2, 0))
+        .arm(PartType::BiArm, HexIndex::new(1, 0), 0, 1, &[Grab, RotateAnticlockwise])
+        .expect_atom(2, HexIndex::new(1, -1), Atom::Salt)
+        .expect_atom(2, HexIndex::new(1, 1), Atom::Air)
+        .expect_molecules(2, 1)
+        .run()
+        .unwrap();
+}
+
+#[test]
+fn pivoting_around_two_grippers_conflicts(){
+    let err = error(Scenario::new()
+        .atom(Atom::Salt, HexIndex::new(0, 0))
+        .atom(Atom::Water, HexIndex::new(0, 1))
+        .atom(Atom::Fire, HexIndex::new(1, 1))
+        .atom(Atom::Air, HexIndex::new(2, 0))
+        .bond(HexIndex::new(0, 0), HexIndex::new(0, 1))
+        .bond(HexIndex::new(0, 1), HexIndex::new(1, 1))
+        .bond(HexIndex::new(1, 1), HexIndex::new(2, 0))
+        .arm(PartType::BiArm, HexIndex::new(1, 0), 0, 1, &[Grab, PivotClockwise]), 2);
+    assert!(matches!(err, SimError::ConflictingMovement{ arms: (0, 0), .. }), "{err}");
+    assert!(err.to_string().starts_with("arm 0 pivoted the molecule at"), "{err}");
+}
@@ -0,0 +1,45 @@
+use omsim_rs::constraints::{apply_plan, Model, ACTIONS};
+use omsim_rs::data::{HexIndex, Instruction, Solution};
+use omsim_rs::parse::{parse_puzzle, parse_solution};
+use omsim_rs::verify::verify;
+
+fn transfer() -> Solution{
+    parse_solution(include_bytes!("../corpus/transfer.solution")).unwrap()
+}
+
+#[test]
+fn layouts_are_modelled(){
+    let puzzle = parse_puzzle(include_bytes!("../corpus/transfer.puzzle")).unwrap();
+    let model = Model::new(&puzzle, &transfer(), 8).unwrap();
+    assert_eq!(model.arms.len(), 1);
+    let arm = &model.arms[0];
+    assert_eq!((arm.number, arm.pos, arm.length), (1, HexIndex::new(1, 0), 1));
+    assert!(arm.actions.contains(&Instruction::Grab) && !arm.actions.contains(&Instruction::Extend) && !arm.actions.contains(&Instruction::Advance));
+    assert_eq!(model.inputs.len(), 1);
+    assert_eq!(model.outputs[0].1[0].0, HexIndex::new(2, 0));
+    assert_eq!(model.required, vec![6]);
+
+    let json = model.to_json();
+    assert!(json.starts_with(r#"{"puzzle":"selftest-transfer","horizon":8,"actions":[" ","G","g","R","r""#));
+    assert!(json.contains(r#""inputs":[{"index":0,"atoms":[[0,0,"Sa"]]}],"outputs":[{"index":0,"atoms":[[2,0,"Sa"]]}]"#));
+
+    let smt = model.to_smtlib();
+    assert!(smt.contains("(declare-const arm1_act_7 Int)") && !smt.contains("arm1_act_8 "));
+    assert!(smt.contains("(define-fun arm1_g0_q_8 () Int"));
+    assert!(smt.contains("(assert (= arm1_rot_0 3))"));
+    // negative literals are written as negations
+    assert!(smt.contains("(- 1)") && !smt.contains(" -1"));
+    assert_eq!(smt.matches('(').count(), smt.matches(')').count());
+}
+
+#[test]
+fn plans_become_solutions(){
+    let puzzle = parse_puzzle(include_bytes!("../corpus/transfer.puzzle")).unwrap();
+    let mut layout = transfer();
+    layout.parts.iter_mut().for_each(|part| part.instructions.clear());
+    // the plan a solver would give back, as action numbers
+    let plan: Vec<Instruction> = [1, 3, 3, 3, 2, 3, 3, 3].into_iter().map(|action| ACTIONS[action]).collect();
+    let solution = apply_plan(&layout, &[(1, plan)]);
+    assert_eq!(solution.parts, transfer().parts);
+    assert!(verify(&puzzle, &solution).is_valid());
+}
@@ -0,0 +1,26 @@
+use omsim_rs::data::Metrics;
+use omsim_rs::parse::parse_solution;
+use omsim_rs::selftest::{run_case, CorpusCase, Expected, CORPUS};
+
+#[test]
+fn corpus_matches_expectations(){
+    let divergences: Vec<String> = CORPUS.iter().filter_map(run_case).map(|d| d.to_string()).collect();
+    assert!(divergences.is_empty(), "{}", divergences.join("\n"));
+}
+
+/// The transfer case, with these metrics written into its solution file and checked as recorded.
+fn recorded(metrics: Option<Metrics>) -> CorpusCase{
+    let transfer = CORPUS.iter().find(|case| case.name == "transfer").unwrap();
+    let mut solution = parse_solution(transfer.solution).unwrap();
+    solution.metrics = metrics;
+    CorpusCase{ name: "recorded", solution: Vec::leak(solution.unparse()), expected: Expected::Recorded, ..*transfer }
+}
+
+#[test]
+fn recorded_cases_check_the_metrics_in_the_file(){
+    let metrics = Metrics{ cycles: 45, cost: 20, area: 7, instructions: 8 };
+    assert_eq!(run_case(&recorded(Some(metrics))), None);
+    assert!(run_case(&recorded(Some(Metrics{ cycles: 44, ..metrics }))).is_some());
+    let missing = run_case(&recorded(None)).unwrap();
+    assert_eq!(missing.actual, "no metrics recorded in the solution file");
+}
@@ -0,0 +1,46 @@
+use omsim_rs::analysis::critical::{critical_path, CriticalEvent};
+use omsim_rs::data::PartType;
+use omsim_rs::parse::{parse_puzzle, parse_solution};
+use omsim_rs::sim::config::SimConfig;
+
+#[test]
+fn the_last_product_is_spawned_carried_and_consumed(){
+    let puzzle = parse_puzzle(include_bytes!("../corpus/transfer.puzzle")).unwrap();
+    let solution = parse_solution(include_bytes!("../corpus/transfer.solution")).unwrap();
+    let path = critical_path(&puzzle, &solution, SimConfig::default()).unwrap();
+    assert_eq!(path.cycles, 45);
+    assert_eq!(path.chain, vec![
+        CriticalEvent::Spawned{ cycle: 33, part: 0 },
+        // grabbed in cycle 40 and dropped in cycle 44
+        CriticalEvent::Carried{ first: 40, last: 43, part: 2 },
+        CriticalEvent::Consumed{ cycle: 44, product: 0 }
+    ]);
+    assert_eq!(path.arms.len(), 1);
+    assert_eq!((path.arms[0].part, path.arms[0].idle_cycles, path.arms[0].first_active), (2, 0, Some(0)));
+}
+
+#[test]
+fn late_arms_are_idle(){
+    let puzzle = parse_puzzle(include_bytes!("../corpus/transfer.puzzle")).unwrap();
+    let mut solution = parse_solution(include_bytes!("../corpus/transfer.solution")).unwrap();
+    for (_, cycle) in &mut solution.parts[2].instructions{
+        *cycle += 3;
+    }
+    let path = critical_path(&puzzle, &solution, SimConfig::default()).unwrap();
+    let arm = path.arms[0];
+    assert_eq!(arm.first_active, Some(3));
+    // three blanks in every repetition of the now 11-cycle tape
+    assert!(arm.idle_cycles >= 3 * (path.cycles / 11), "{arm:?}");
+    assert!(arm.idle_fraction(path.cycles) > 0.25 && arm.idle_fraction(path.cycles) < 0.3);
+}
+
+#[test]
+fn glyphs_are_on_the_critical_path(){
+    let puzzle = parse_puzzle(include_bytes!("../corpus/calcify.puzzle")).unwrap();
+    let solution = parse_solution(include_bytes!("../corpus/calcify.solution")).unwrap();
+    let glyph = solution.parts.iter().position(|part| part.ty == PartType::Calcification).unwrap();
+    let path = critical_path(&puzzle, &solution, SimConfig::default()).unwrap();
+    assert!(path.chain.iter().any(|event| matches!(event, CriticalEvent::Transmuted{ part, .. } if *part == glyph)), "{:?}", path.chain);
+    assert!(path.chain.windows(2).all(|pair| pair[0].cycle() <= pair[1].cycle()));
+    assert!(matches!(path.chain.last(), Some(CriticalEvent::Consumed{ .. })));
+}
@@ -0,0 +1,66 @@
+use std::ffi::OsStr;
+use std::path::Path;
+use omsim_rs::crosscheck::{crosscheck, parse_reference_output, reference_command};
+use omsim_rs::data::Metrics;
+
+// there's no reference binary in the test environment, so this output is written in the `<metric> is <value>` format
+// the reference prints for each `-m` flag rather than captured from a run of it
+const REFERENCE_OUTPUT: &str = "\
+cycles is 45
+cost is 20
+area is 7
+instructions is 8
+";
+
+#[test]
+fn reference_output_is_parsed_in_any_order(){
+    let transfer = Metrics{ cycles: 45, cost: 20, area: 7, instructions: 8 };
+    assert_eq!(parse_reference_output(REFERENCE_OUTPUT), Ok(transfer));
+    let reordered: String = REFERENCE_OUTPUT.lines().rev().map(|line| format!("{line}\n")).collect();
+    assert_eq!(parse_reference_output(&format!("loading puzzle\n{reordered}")), Ok(transfer));
+    // the reference may print metrics as floats
+    assert_eq!(parse_reference_output(&REFERENCE_OUTPUT.replace("45", "45.0")), Ok(transfer));
+}
+
+#[test]
+fn incomplete_reference_output_is_an_error(){
+    let missing: String = REFERENCE_OUTPUT.lines().skip(1).map(|line| format!("{line}\n")).collect();
+    assert_eq!(parse_reference_output(&missing), Err("reference omsim didn't print every metric"));
+    assert_eq!(parse_reference_output(&REFERENCE_OUTPUT.replace("20", "twenty")), Err("reference omsim printed an invalid metric"));
+}
+
+#[test]
+fn reference_is_asked_for_every_metric(){
+    let command = reference_command(Path::new("/opt/omsim"), Path::new("a.puzzle"), Path::new("b.solution"));
+    assert_eq!(command.get_program(), "/opt/omsim");
+    assert_eq!(command.get_args().collect::<Vec<&OsStr>>(), [
+        "-p", "a.puzzle", "-m", "cycles", "-m", "cost", "-m", "area", "-m", "instructions", "b.solution"
+    ]);
+}
+
+/// A stand-in for the reference binary that runs `script` as a shell script.
+#[cfg(unix)]
+fn fake_reference(name: &str, script: &str) -> std::path::PathBuf{
+    use std::os::unix::fs::PermissionsExt;
+    let path = std::env::temp_dir().join(format!("omsim-rs-fake-{name}-{}", std::process::id()));
+    std::fs::write(&path, format!("#!/bin/sh\n{script}")).unwrap();
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+    path
+}
+
+#[cfg(unix)]
+#[test]
+fn crosschecks_compare_both_verdicts(){
+    let corpus = |name: &str| Path::new(env!("CARGO_MANIFEST_DIR")).join("corpus").join(name);
+    let agreeing = fake_reference("agree", &format!("printf '{}'\n", REFERENCE_OUTPUT.replace('\n', "\\n")));
+    let check = crosscheck(&agreeing, &corpus("transfer.puzzle"), &corpus("transfer.solution")).unwrap();
+    assert!(check.agrees(), "{check}");
+
+    let rejecting = fake_reference("reject", "echo 'collision between atoms' >&2\nexit 1\n");
+    let check = crosscheck(&rejecting, &corpus("transfer.puzzle"), &corpus("transfer.solution")).unwrap();
+    assert_eq!(check.reference, Err("collision between atoms".to_owned()));
+    assert_eq!(check.differences(), vec!["valid here, but invalid in reference: collision between atoms".to_owned()]);
+    // failure reasons aren't compared
+    let check = crosscheck(&rejecting, &corpus("transfer.puzzle"), &corpus("collision.solution")).unwrap();
+    assert!(check.agrees());
+}
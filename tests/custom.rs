@@ -0,0 +1,72 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use omsim_rs::data::{Atom, HexIndex, HexRotation, Molecule, Part, PartType};
+use omsim_rs::parse::{parse_puzzle, parse_solution};
+use omsim_rs::sim::config::SimConfig;
+use omsim_rs::sim::custom::{CustomPart, PartBehavior};
+use omsim_rs::sim::error::SimError;
+use omsim_rs::sim::molecules::{add_atom, atom_at, remove_atom};
+use omsim_rs::sim::{Sim, SimEvent, SimMolecule, SimPart, SimPartType};
+
+/// Turns salt on its second hex into gold.
+#[derive(Debug)]
+struct Transmutation;
+
+impl PartBehavior for Transmutation{
+    fn footprint(&self) -> Vec<HexIndex>{
+        vec![HexIndex::new(0, 0), HexIndex::new(1, 0)]
+    }
+
+    fn apply(&self, pos: HexIndex, rotation: HexRotation, molecules: &mut Vec<SimMolecule>) -> bool{
+        let target = pos + rotation.rotate_offset(HexIndex::new(1, 0));
+        if atom_at(molecules, target) != Some(Atom::Salt){
+            return false;
+        }
+        remove_atom(molecules, target);
+        add_atom(molecules, target, Atom::Gold);
+        true
+    }
+}
+
+fn transmutation(pos: HexIndex, rotation: HexRotation) -> SimPart{
+    SimPart{ pos, rotation, ty: SimPartType::Custom(CustomPart{ name: "glyph-transmutation".to_string(), behavior: None }) }
+}
+
+fn single(atom: Atom, pos: HexIndex) -> SimMolecule{
+    SimMolecule::new(Molecule{ atoms: HashMap::from([(HexIndex::default(), atom)]), bonds: HashSet::new() }, pos)
+}
+
+#[test]
+fn unregistered_parts_fail_to_simulate(){
+    let puzzle = parse_puzzle(include_bytes!("../corpus/transfer.puzzle")).unwrap();
+    let mut solution = parse_solution(include_bytes!("../corpus/transfer.solution")).unwrap();
+    solution.parts.push(Part{ ty: PartType::Unknown("glyph-transmutation".to_string()), pos: HexIndex::new(5, 5), ..solution.parts[0].clone() });
+    let mut sim = Sim::create(&puzzle, &solution).unwrap();
+    assert_eq!(sim.step().err(), Some(SimError::UnsupportedPart("glyph-transmutation".to_string())));
+    // a behaviour for some other part doesn't help
+    sim.register_custom_part("glyph-other", |_| Arc::new(Transmutation));
+    assert!(sim.step().is_err());
+    sim.register_custom_part("glyph-transmutation", |_| Arc::new(Transmutation));
+    assert!(sim.run().is_ok());
+}
+
+#[test]
+fn registered_parts_act_like_glyphs(){
+    let rotation = HexRotation::R60;
+    let target = rotation.to_unit_offset();
+    let mut sim = Sim::new(vec![transmutation(HexIndex::default(), rotation)], vec![single(Atom::Salt, target)], SimConfig::default());
+    sim.register_custom_part("glyph-transmutation", |part| {
+        assert_eq!(part.rotation, HexRotation::R60);
+        Arc::new(Transmutation)
+    });
+    assert!(sim.area.contains(&target));
+    assert_eq!(sim.parts[0].footprint(), vec![HexIndex::default(), target]);
+
+    sim.step().unwrap();
+    assert_eq!(atom_at(&sim.molecules, target), Some(Atom::Gold));
+    assert!(sim.events.contains(&SimEvent::GlyphApplied{ cycle: 0, part: 0 }));
+    // nothing left to transmute
+    sim.events.clear();
+    sim.step().unwrap();
+    assert!(!sim.events.iter().any(|event| matches!(event, SimEvent::GlyphApplied{ .. })));
+}
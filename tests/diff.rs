@@ -0,0 +1,70 @@
+use std::process::Command;
+use omsim_rs::data::{HexIndex, Instruction, Metrics, Part, PartType, Solution};
+use omsim_rs::diff::diff_solutions;
+use omsim_rs::parse::parse_solution;
+
+fn transfer() -> Solution{
+    parse_solution(include_bytes!("../corpus/transfer.solution")).unwrap()
+}
+
+#[test]
+fn identical_solutions_have_no_differences(){
+    let mut renamed = transfer();
+    renamed.name = "renamed".into();
+    let diff = diff_solutions(&transfer(), &renamed);
+    assert!(diff.is_empty());
+    assert_eq!(diff.to_string(), "no differences\n");
+}
+
+#[test]
+fn diffs_pair_parts_by_placement_then_identity(){
+    let before = transfer();
+    let mut after = transfer();
+    // drop the input, move the output, and change the arm's tape without moving the arm
+    let input = after.parts.remove(0);
+    after.parts[0].pos = HexIndex::new(3, 0);
+    after.parts[0].rotation = 1;
+    after.parts[1].instructions[1].0 = Instruction::RotateAnticlockwise;
+    after.parts[1].instructions.push((Instruction::Grab, 8));
+    let bonder = Part{
+        ty: PartType::Bonding, pos: HexIndex::new(5, 5), rotation: 0, arm_number: 0, arm_length: 1, index: 0,
+        conduit_index: 0, track_hexes: Vec::new(), conduit_hexes: Vec::new(), instructions: Vec::new()
+    };
+    after.parts.push(bonder.clone());
+    let metrics = Metrics{ cycles: 45, cost: 20, area: 7, instructions: 9 };
+    after.metrics = Some(metrics);
+
+    let diff = diff_solutions(&before, &after);
+    assert_eq!(diff.removed, vec![input]);
+    assert_eq!(diff.added, vec![bonder]);
+    assert_eq!(diff.moved.len(), 1);
+    assert_eq!((diff.moved[0].before.pos, diff.moved[0].after.pos), (HexIndex::new(2, 0), HexIndex::new(3, 0)));
+    assert_eq!(diff.tapes.len(), 1);
+    assert_eq!(diff.tapes[0].changes, vec![
+        (1, Instruction::RotateClockwise, Instruction::RotateAnticlockwise),
+        (8, Instruction::Blank, Instruction::Grab)
+    ]);
+    assert_eq!(diff.metrics, Some((None, Some(metrics))));
+    assert_eq!(diff.to_string(), format!("\
+- input at (0, 0)
++ bonder at (5, 5)
+~ out-std moved from (2, 0) rotation 0 to (3, 0) rotation 1
+arm 1:
+  1: 'R' -> 'r'
+  8: ' ' -> 'G'
+metrics: None -> {:?}
+", Some(metrics)));
+}
+
+#[test]
+fn diff_command_prints_the_diff(){
+    let output = Command::new(env!("CARGO_BIN_EXE_omsim-rs"))
+        .args(["diff", "corpus/transfer.solution", "corpus/collision.solution"])
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .output().unwrap();
+    assert_eq!(output.status.code(), Some(0));
+    let collision = parse_solution(include_bytes!("../corpus/collision.solution")).unwrap();
+    let expected = diff_solutions(&transfer(), &collision).to_string();
+    assert_ne!(expected, "no differences\n");
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), expected);
+}
@@ -0,0 +1,42 @@
+use std::fs::File;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+use omsim_rs::load::discover::{base_dir, find_solutions_in, latest};
+
+fn scratch(name: &str) -> PathBuf{
+    let path = std::env::temp_dir().join(format!("omsim-rs-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&path);
+    std::fs::create_dir_all(&path).unwrap();
+    path
+}
+
+#[test]
+fn solutions_are_found_by_puzzle_name(){
+    let dir = scratch("discover");
+    std::fs::write(dir.join("selftest-transfer-1.solution"), include_bytes!("../corpus/transfer.solution")).unwrap();
+    std::fs::write(dir.join("selftest-transfer-2.solution"), include_bytes!("../corpus/collision.solution")).unwrap();
+    std::fs::write(dir.join("selftest-bond-1.solution"), include_bytes!("../corpus/bond.solution")).unwrap();
+    std::fs::write(dir.join("broken.solution"), b"not a solution").unwrap();
+    std::fs::write(dir.join("notes.txt"), include_bytes!("../corpus/transfer.solution")).unwrap();
+
+    let found = find_solutions_in(&dir, "selftest-transfer").unwrap();
+    assert_eq!(found, vec![dir.join("selftest-transfer-1.solution"), dir.join("selftest-transfer-2.solution")]);
+    assert_eq!(find_solutions_in(&dir, "selftest-bond").unwrap(), vec![dir.join("selftest-bond-1.solution")]);
+    assert!(find_solutions_in(&dir, "P007").unwrap().is_empty());
+    assert!(find_solutions_in(dir.join("missing"), "P007").is_err());
+
+    // the game saves the solution being edited last
+    let now = SystemTime::now();
+    File::options().write(true).open(&found[0]).unwrap().set_modified(now).unwrap();
+    File::options().write(true).open(&found[1]).unwrap().set_modified(now - Duration::from_secs(60)).unwrap();
+    assert_eq!(latest(&found), Some(&found[0]));
+    assert_eq!(latest(&[]), None);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn the_base_directory_is_named_after_the_game(){
+    if let Some(dir) = base_dir(){
+        assert!(dir.ends_with("Opus Magnum"), "{}", dir.display());
+    }
+}
@@ -0,0 +1,90 @@
+use std::collections::{HashMap, HashSet};
+use std::process::Command;
+use omsim_rs::analysis::feasibility::{obtainable_elements, FeasibilityIssue};
+use omsim_rs::data::{Atom, Bond, BondType, HexIndex, Molecule, Permissions, Puzzle};
+use omsim_rs::parse::parse_puzzle;
+use omsim_rs::selftest::CORPUS;
+
+fn single(atom: Atom) -> Molecule{
+    Molecule{ atoms: HashMap::from([(HexIndex::default(), atom)]), bonds: HashSet::new() }
+}
+
+fn pair(atom: Atom, ty: BondType) -> Molecule{
+    Molecule{
+        atoms: HashMap::from([(HexIndex::new(0, 0), atom), (HexIndex::new(1, 0), atom)]),
+        bonds: HashSet::from([Bond{ start: HexIndex::new(0, 0), end: HexIndex::new(1, 0), ty }])
+    }
+}
+
+fn puzzle(reagents: Vec<Molecule>, products: Vec<Molecule>, permissions: Permissions) -> Puzzle{
+    let mut puzzle = parse_puzzle(include_bytes!("../corpus/transfer.puzzle")).unwrap();
+    puzzle.reagents = reagents;
+    puzzle.products = products;
+    puzzle.permissions = permissions;
+    puzzle
+}
+
+#[test]
+fn corpus_puzzles_are_feasible(){
+    for case in CORPUS{
+        let puzzle = parse_puzzle(case.puzzle).unwrap();
+        assert_eq!(puzzle.feasibility_issues(), Vec::new(), "{}", case.name);
+    }
+}
+
+#[test]
+fn empty_and_armless_puzzles_are_infeasible(){
+    let salt = || vec![single(Atom::Salt)];
+    assert_eq!(puzzle(salt(), Vec::new(), Permissions::SIMPLE_ARM).feasibility_issues(), vec![FeasibilityIssue::NoProducts]);
+    assert_eq!(puzzle(Vec::new(), salt(), Permissions::SIMPLE_ARM).feasibility_issues(), vec![
+        FeasibilityIssue::NoReagents,
+        FeasibilityIssue::UnobtainableElement{ product: 0, atom: Atom::Salt }
+    ]);
+    assert_eq!(puzzle(salt(), salt(), Permissions::BONDER).feasibility_issues(), vec![FeasibilityIssue::NoArms]);
+}
+
+#[test]
+fn metals_need_purification_or_projection_with_quicksilver(){
+    let lead_to_tin = |reagents, permissions| puzzle(reagents, vec![single(Atom::Tin)], Permissions::SIMPLE_ARM | permissions).feasibility_issues();
+    let unobtainable = vec![FeasibilityIssue::UnobtainableElement{ product: 0, atom: Atom::Tin }];
+    assert_eq!(lead_to_tin(vec![single(Atom::Lead)], Permissions::empty()), unobtainable);
+    assert_eq!(lead_to_tin(vec![single(Atom::Lead)], Permissions::PURIFICATION), Vec::new());
+    assert_eq!(lead_to_tin(vec![single(Atom::Lead)], Permissions::PROJECTION), unobtainable);
+    assert_eq!(lead_to_tin(vec![single(Atom::Lead), single(Atom::Quicksilver)], Permissions::PROJECTION), Vec::new());
+}
+
+#[test]
+fn glyphs_chain_to_reach_elements(){
+    let fire = puzzle(vec![single(Atom::Fire)], Vec::new(), Permissions::CALCIFICATION | Permissions::ANIMISMUS);
+    assert_eq!(obtainable_elements(&fire), HashSet::from([Atom::Fire, Atom::Salt, Atom::Vitae, Atom::Mors]).into_iter().collect());
+    let salt = puzzle(vec![single(Atom::Salt)], Vec::new(), Permissions::BERLO | Permissions::DUPLICATION | Permissions::QUINTESSENCE);
+    assert!(obtainable_elements(&salt).contains(&Atom::Quintessence));
+    // the wheel alone doesn't copy anything
+    let salt = puzzle(vec![single(Atom::Salt)], Vec::new(), Permissions::BERLO | Permissions::QUINTESSENCE);
+    assert_eq!(obtainable_elements(&salt), HashSet::from([Atom::Salt]).into_iter().collect());
+}
+
+#[test]
+fn bonds_need_a_bonder_or_a_bonded_reagent(){
+    let normal = BondType::Normal;
+    let triplex = BondType::Triplex{ red: true, black: false, yellow: false };
+    let arms = Permissions::SIMPLE_ARM;
+    assert_eq!(puzzle(vec![single(Atom::Fire)], vec![pair(Atom::Fire, normal)], arms).feasibility_issues(), vec![FeasibilityIssue::MissingBonder{ product: 0 }]);
+    assert_eq!(puzzle(vec![single(Atom::Fire)], vec![pair(Atom::Fire, normal)], arms | Permissions::MULTI_BONDER).feasibility_issues(), Vec::new());
+    assert_eq!(puzzle(vec![pair(Atom::Fire, normal)], vec![pair(Atom::Fire, normal)], arms).feasibility_issues(), Vec::new());
+    let issues = puzzle(vec![single(Atom::Fire)], vec![pair(Atom::Fire, triplex)], arms | Permissions::BONDER).feasibility_issues();
+    assert_eq!(issues, vec![FeasibilityIssue::MissingTriplexBonder{ product: 0 }]);
+    assert_eq!(issues[0].to_string(), "product 0 has triplex bonds, but the triplex bonder is not enabled");
+}
+
+#[test]
+fn check_command_reports_feasible_puzzles(){
+    let check = |path: &str| {
+        let output = Command::new(env!("CARGO_BIN_EXE_omsim-rs"))
+            .args(["check", path])
+            .current_dir(env!("CARGO_MANIFEST_DIR"))
+            .output().unwrap();
+        String::from_utf8(output.stdout).unwrap()
+    };
+    assert_eq!(check("corpus/transfer.puzzle"), "no feasibility issues found\n");
+}
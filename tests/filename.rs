@@ -0,0 +1,41 @@
+use std::path::PathBuf;
+use omsim_rs::load::filename::{free_solution_path, parse_solution_file_name, sanitize, sanitize_path, solution_file_name, MAX_NAME_LEN};
+
+#[test]
+fn file_names_round_trip(){
+    assert_eq!(solution_file_name("P007", 1), "P007-1.solution");
+    assert_eq!(parse_solution_file_name("P007-1.solution"), Some(("P007", 1)));
+    // puzzle names can have dashes of their own
+    assert_eq!(parse_solution_file_name(&solution_file_name("selftest-transfer", 12)), Some(("selftest-transfer", 12)));
+    for name in ["P007.solution", "P007-.solution", "-1.solution", "P007-0.solution", "P007-+1.solution", "P007-1.puzzle"]{
+        assert_eq!(parse_solution_file_name(name), None, "{name}");
+    }
+}
+
+#[test]
+fn names_are_made_safe(){
+    assert_eq!(sanitize("Stabilized Water"), "Stabilized Water");
+    assert_eq!(sanitize("a/b\\c:d*e?f\"g<h>i|j\nk"), "a_b_c_d_e_f_g_h_i_j_k");
+    assert_eq!(sanitize(".."), "_");
+    assert_eq!(sanitize(""), "_");
+    assert_eq!(sanitize("trailing. "), "trailing_");
+    assert_eq!(sanitize("con"), "_con");
+    assert_eq!(sanitize("NUL.solution"), "_NUL.solution");
+    assert_eq!(sanitize("console"), "console");
+    let long = "é".repeat(MAX_NAME_LEN);
+    assert!(sanitize(&long).len() <= MAX_NAME_LEN);
+    assert_eq!(sanitize_path("../../etc/passwd.solution"), PathBuf::from("_/_/etc/passwd.solution"));
+    assert_eq!(sanitize_path("/solutions//P007-1.solution"), PathBuf::from("solutions/P007-1.solution"));
+}
+
+#[test]
+fn new_solutions_take_the_first_free_slot(){
+    let dir = std::env::temp_dir().join(format!("omsim-rs-filename-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    assert_eq!(free_solution_path(&dir, "P007"), dir.join("P007-1.solution"));
+    std::fs::write(dir.join("P007-1.solution"), b"").unwrap();
+    std::fs::write(dir.join("P007-3.solution"), b"").unwrap();
+    assert_eq!(free_solution_path(&dir, "P007"), dir.join("P007-2.solution"));
+    std::fs::remove_dir_all(dir).unwrap();
+}
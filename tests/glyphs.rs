@@ -0,0 +1,231 @@
+use std::collections::{HashMap, HashSet};
+use omsim_rs::data::{Atom, Bond, BondType, HexIndex, HexRotation, Molecule, PartType};
+use omsim_rs::sim::arm::SimArm;
+use omsim_rs::sim::config::SimConfig;
+use omsim_rs::sim::eligibility::{acts_on, eligibility, part_eligibility, ELIGIBILITY};
+use omsim_rs::sim::glyphs::{berlo_atoms, glyph_offsets, BERLO_ATOMS};
+use omsim_rs::sim::{Sim, SimMolecule, SimPart, SimPartType};
+
+fn single(atom: Atom, pos: HexIndex) -> SimMolecule{
+    SimMolecule::new(Molecule{ atoms: HashMap::from([(HexIndex::default(), atom)]), bonds: HashSet::new() }, pos)
+}
+
+fn part(ty: SimPartType, pos: HexIndex, rotation: HexRotation) -> SimPart{
+    SimPart{ pos, rotation, ty }
+}
+
+fn berlo() -> SimPartType{
+    SimPartType::Arm(SimArm::new(PartType::Berlo, 1, 1, Vec::new()))
+}
+
+fn board(parts: Vec<SimPart>, molecules: Vec<SimMolecule>) -> Sim{
+    Sim::new(parts, molecules, SimConfig::default())
+}
+
+fn atom_at(sim: &Sim, pos: HexIndex) -> Option<Atom>{
+    sim.lookup_atom(pos, |r| r.atom_ty)
+}
+
+/// The hex `n` steps away from the origin in the direction of `rotation`.
+fn ray(rotation: HexRotation, n: i32) -> HexIndex{
+    rotation.to_unit_offset().scaled(n)
+}
+
+#[test]
+fn duplication_copies_from_every_cardinal_on_the_wheel(){
+    for wheel_rotation in enum_iterator::all::<HexRotation>(){
+        for (i, expected) in BERLO_ATOMS.iter().enumerate(){
+            // duplication glyph pointing away from the wheel, with its source hex on one of the wheel's atoms
+            let dir = wheel_rotation + HexRotation::from_unsigned(i as u8);
+            let mut sim = board(
+                vec![
+                    part(berlo(), HexIndex::default(), wheel_rotation),
+                    part(SimPartType::Duplication, ray(dir, 1), dir)
+                ],
+                vec![single(Atom::Salt, ray(dir, 2))]
+            );
+            assert_eq!(berlo_atoms(HexIndex::default(), wheel_rotation)[i], (ray(dir, 1), *expected));
+            sim.apply_glyphs().unwrap();
+            // the wheel's own salt atoms aren't cardinal, so leave the target as salt
+            assert_eq!(atom_at(&sim, ray(dir, 2)), Some(*expected));
+        }
+    }
+}
+
+#[test]
+fn duplication_never_writes_to_the_wheel(){
+    // the target hex is the wheel's salt atom, and the source is a loose water atom
+    let mut sim = board(
+        vec![
+            part(berlo(), HexIndex::default(), HexRotation::R0),
+            part(SimPartType::Duplication, ray(HexRotation::R0, 2), HexRotation::R180)
+        ],
+        vec![single(Atom::Water, ray(HexRotation::R0, 2))]
+    );
+    sim.apply_glyphs().unwrap();
+    assert_eq!(atom_at(&sim, ray(HexRotation::R0, 1)), None);
+    assert_eq!(atom_at(&sim, ray(HexRotation::R0, 2)), Some(Atom::Water));
+}
+
+#[test]
+fn duplication_requires_a_cardinal_source(){
+    let mut sim = board(
+        vec![part(SimPartType::Duplication, HexIndex::default(), HexRotation::R0)],
+        vec![single(Atom::Lead, HexIndex::default()), single(Atom::Salt, ray(HexRotation::R0, 1))]
+    );
+    sim.apply_glyphs().unwrap();
+    assert_eq!(atom_at(&sim, ray(HexRotation::R0, 1)), Some(Atom::Salt));
+}
+
+#[test]
+fn calcification_ignores_wheel_atoms(){
+    // calcification over the wheel's fire atom, which isn't a molecule
+    let fire = berlo_atoms(HexIndex::default(), HexRotation::R0)[4].0;
+    let mut sim = board(
+        vec![
+            part(berlo(), HexIndex::default(), HexRotation::R0),
+            part(SimPartType::Calcification, fire, HexRotation::R0),
+            part(SimPartType::Duplication, fire, HexRotation::R240)
+        ],
+        vec![single(Atom::Salt, fire + ray(HexRotation::R240, 1))]
+    );
+    sim.apply_glyphs().unwrap();
+    assert_eq!(atom_at(&sim, fire), None);
+    assert_eq!(atom_at(&sim, fire + ray(HexRotation::R240, 1)), Some(Atom::Fire));
+}
+
+#[test]
+fn calcification_turns_cardinals_into_salt(){
+    let mut sim = board(
+        vec![part(SimPartType::Calcification, HexIndex::default(), HexRotation::R0)],
+        vec![single(Atom::Air, HexIndex::default())]
+    );
+    sim.apply_glyphs().unwrap();
+    assert_eq!(atom_at(&sim, HexIndex::default()), Some(Atom::Salt));
+
+    let mut sim = board(
+        vec![part(SimPartType::Calcification, HexIndex::default(), HexRotation::R0)],
+        vec![single(Atom::Quicksilver, HexIndex::default())]
+    );
+    sim.apply_glyphs().unwrap();
+    assert_eq!(atom_at(&sim, HexIndex::default()), Some(Atom::Quicksilver));
+}
+
+#[test]
+fn disposal_counts_wasted_atoms(){
+    let mut pair = single(Atom::Fire, HexIndex::default());
+    pair.merge_placed(&Molecule{ atoms: HashMap::from([(HexIndex::new(1, 0), Atom::Water)]), bonds: HashSet::new() });
+    let mut held = single(Atom::Salt, HexIndex::new(5, 0));
+    held.grabbed = true;
+    let mut sim = board(
+        vec![
+            part(SimPartType::Disposal, HexIndex::default(), HexRotation::R0),
+            part(SimPartType::Disposal, HexIndex::new(5, 0), HexRotation::R0)
+        ],
+        vec![pair, held]
+    );
+    sim.apply_glyphs().unwrap();
+    assert_eq!(sim.wasted.iter().map(|(atom, count)| (*atom, *count)).collect::<Vec<_>>(), vec![(Atom::Fire, 1), (Atom::Water, 1)]);
+    // held molecules aren't destroyed or counted
+    assert_eq!(atom_at(&sim, HexIndex::new(5, 0)), Some(Atom::Salt));
+}
+
+#[test]
+fn every_glyph_has_an_eligibility_rule(){
+    for ty in [
+        SimPartType::Equilibrium, SimPartType::Bonding, SimPartType::MultiBonding, SimPartType::TriplexBonding, SimPartType::Unbonding,
+        SimPartType::Calcification, SimPartType::Duplication, SimPartType::Projection, SimPartType::Purification,
+        SimPartType::Animismus, SimPartType::Unification, SimPartType::Dispersion, SimPartType::Disposal
+    ]{
+        assert!(glyph_offsets(&ty).is_some());
+        assert!(part_eligibility(&ty).is_some(), "{ty:?} has no rule");
+    }
+    assert_eq!(eligibility(&PartType::Output), eligibility(&PartType::PolymerOutput));
+    assert_eq!(eligibility(&PartType::HexArm), None);
+    // only duplication reads the wheel
+    assert_eq!(ELIGIBILITY.iter().filter(|(_, e)| e.berlo).map(|(ty, _)| ty.clone()).collect::<Vec<_>>(), vec![PartType::Duplication]);
+    // only glyphs that consume atoms need them free
+    assert_eq!(ELIGIBILITY.iter().filter(|(_, e)| e.free).map(|(ty, _)| ty.clone()).collect::<Vec<_>>(),
+        vec![PartType::Projection, PartType::Purification, PartType::Animismus, PartType::Unification, PartType::Dispersion]);
+}
+
+/// A glyph at the origin that consumes atoms, the atom it consumes first, and every atom it needs to act.
+fn consuming_glyph(ty: &PartType) -> (SimPartType, (HexIndex, Atom), Vec<(HexIndex, Atom)>){
+    let (glyph, atoms) = match ty{
+        PartType::Projection => (SimPartType::Projection, vec![(HexIndex::new(0, 0), Atom::Quicksilver), (HexIndex::new(1, 0), Atom::Lead)]),
+        PartType::Purification => (SimPartType::Purification, vec![(HexIndex::new(0, 0), Atom::Lead), (HexIndex::new(1, 0), Atom::Lead)]),
+        PartType::Animismus => (SimPartType::Animismus, vec![(HexIndex::new(0, 0), Atom::Salt), (HexIndex::new(1, 0), Atom::Salt)]),
+        PartType::Unification => (SimPartType::Unification, vec![
+            (HexIndex::new(0, 1), Atom::Air), (HexIndex::new(-1, 1), Atom::Water), (HexIndex::new(0, -1), Atom::Earth), (HexIndex::new(1, -1), Atom::Fire)
+        ]),
+        PartType::Dispersion => (SimPartType::Dispersion, vec![(HexIndex::new(0, 0), Atom::Quintessence)]),
+        _ => panic!("{ty:?} doesn't consume atoms")
+    };
+    (glyph, atoms[0], atoms)
+}
+
+#[test]
+fn glyphs_only_consume_free_atoms_if_their_rule_says_so(){
+    for (ty, rule) in ELIGIBILITY.iter().filter(|(_, e)| e.free){
+        let (glyph, (consumed, atom), atoms) = consuming_glyph(ty);
+        let mut sim = board(vec![part(glyph.clone(), HexIndex::default(), HexRotation::R0)], atoms.iter().map(|(pos, atom)| single(*atom, *pos)).collect());
+        sim.apply_glyphs().unwrap();
+        assert_ne!(atom_at(&sim, consumed), Some(atom), "{ty:?} should consume a free atom");
+
+        // the same atoms, but the first is bonded to salt off the glyph
+        let offsets = glyph_offsets(&glyph).unwrap();
+        let partner = enum_iterator::all::<HexRotation>().map(|r| consumed + r.to_unit_offset()).find(|pos| !offsets.contains(pos)).unwrap();
+        let mut bonded = single(atom, consumed);
+        bonded.merge_placed(&Molecule{ atoms: HashMap::from([(partner, Atom::Salt)]), bonds: HashSet::from([Bond{ start: consumed, end: partner, ty: BondType::Normal }]) });
+        let mut molecules: Vec<SimMolecule> = atoms[1..].iter().map(|(pos, atom)| single(*atom, *pos)).collect();
+        molecules.push(bonded);
+        let mut sim = board(vec![part(glyph, HexIndex::default(), HexRotation::R0)], molecules);
+        sim.apply_glyphs().unwrap();
+        assert_eq!(atom_at(&sim, consumed), Some(atom), "{ty:?} with {rule:?} shouldn't consume a bonded atom");
+    }
+}
+
+#[test]
+fn glyphs_act_on_held_atoms_unless_they_consume_whole_molecules(){
+    let mut held = single(Atom::Fire, HexIndex::default());
+    held.grabbed = true;
+    let loose = single(Atom::Fire, HexIndex::default());
+    for (ty, acts) in [(PartType::Calcification, true), (PartType::Bonding, true), (PartType::Purification, true), (PartType::Disposal, false), (PartType::Output, false)]{
+        assert_eq!(acts_on(&ty, &held), acts, "{ty:?}");
+        assert!(acts_on(&ty, &loose), "{ty:?}");
+    }
+}
+
+#[test]
+fn held_atoms_are_calcified_and_bonded(){
+    let mut held = single(Atom::Fire, HexIndex::default());
+    held.merge_placed(&Molecule{ atoms: HashMap::from([(HexIndex::new(1, 0), Atom::Water)]), bonds: HashSet::new() });
+    held.grabbed = true;
+    let mut sim = board(
+        vec![
+            part(SimPartType::Calcification, HexIndex::default(), HexRotation::R0),
+            part(SimPartType::Bonding, HexIndex::default(), HexRotation::R0)
+        ],
+        vec![held]
+    );
+    sim.apply_glyphs().unwrap();
+    assert_eq!(atom_at(&sim, HexIndex::default()), Some(Atom::Salt));
+    assert_eq!(sim.molecules[0].layout.bonds.len(), 1);
+}
+
+#[test]
+fn unification_needs_each_element_on_its_own_hex(){
+    let ring = [(HexIndex::new(0, 1), Atom::Air), (HexIndex::new(-1, 1), Atom::Water), (HexIndex::new(0, -1), Atom::Earth), (HexIndex::new(1, -1), Atom::Fire)];
+    let glyph = || vec![part(SimPartType::Unification, HexIndex::default(), HexRotation::R0)];
+    let mut sim = board(glyph(), ring.iter().map(|(pos, atom)| single(*atom, *pos)).collect());
+    sim.apply_glyphs().unwrap();
+    assert_eq!(atom_at(&sim, HexIndex::default()), Some(Atom::Quintessence));
+    assert_eq!(sim.molecules.len(), 1);
+
+    // air and water swapped
+    let permuted = [ring[1].1, ring[0].1, ring[2].1, ring[3].1];
+    let mut sim = board(glyph(), ring.iter().zip(permuted).map(|((pos, _), atom)| single(atom, *pos)).collect());
+    sim.apply_glyphs().unwrap();
+    assert_eq!(atom_at(&sim, HexIndex::default()), None);
+    assert_eq!(sim.molecules.len(), 4);
+}
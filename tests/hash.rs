@@ -0,0 +1,99 @@
+use omsim_rs::data::{Bond, HexIndex, HexRotation, Instruction, Metrics, ProductionInfo, Vial};
+use omsim_rs::hash::stable_hash;
+use omsim_rs::parse::{parse_puzzle, parse_solution};
+use omsim_rs::selftest::CORPUS;
+
+fn bond() -> omsim_rs::data::Solution{
+    parse_solution(CORPUS.iter().find(|c| c.name == "bond").unwrap().solution).unwrap()
+}
+
+#[test]
+fn stable_hash_is_fnv1a(){
+    assert_eq!(stable_hash(b""), 0xcbf2_9ce4_8422_2325);
+    assert_eq!(stable_hash(b"a"), 0xaf63_dc4c_8601_ec8c);
+    assert_eq!(stable_hash(b"foobar"), 0x8594_4171_f739_67e8);
+}
+
+#[test]
+fn canonical_hash_ignores_cosmetic_differences(){
+    let original = bond();
+    let mut cosmetic = original.clone();
+    cosmetic.name = "something else".into();
+    cosmetic.metrics = Some(Metrics{ cycles: 1, cost: 2, area: 3, instructions: 4 });
+    cosmetic.parts.reverse();
+    for part in &mut cosmetic.parts{
+        part.rotation += 12;
+        part.arm_number = 7;
+        // shift every tape back, which the timeline undoes
+        for (_, idx) in &mut part.instructions{
+            *idx -= 3;
+        }
+    }
+    // a blank shadowed by a later instruction at the same index
+    let arm = cosmetic.parts.iter_mut().find(|p| !p.instructions.is_empty()).unwrap();
+    arm.instructions.insert(0, (Instruction::Blank, -2));
+    assert_eq!(cosmetic.canonical(), original.canonical());
+    assert_eq!(cosmetic.canonical_hash(), original.canonical_hash());
+}
+
+#[test]
+fn canonical_hash_sees_real_differences(){
+    let original = bond();
+    let mut moved = original.clone();
+    moved.parts[0].pos += HexIndex::new(1, 0);
+    assert_ne!(moved.canonical_hash(), original.canonical_hash());
+    let mut longer = original.clone();
+    let arm = longer.parts.iter_mut().find(|p| !p.instructions.is_empty()).unwrap();
+    arm.instructions.push((Instruction::Blank, 20));
+    assert_ne!(longer.canonical_hash(), original.canonical_hash());
+}
+
+#[test]
+fn symmetric_hash_ignores_rotation_and_translation(){
+    let original = bond();
+    let mut shifted = original.rotated(HexRotation::R120);
+    for part in &mut shifted.parts{
+        part.pos += HexIndex::new(-4, 7);
+    }
+    assert_ne!(shifted.canonical_hash(), original.canonical_hash());
+    assert_eq!(shifted.symmetric_hash(), original.symmetric_hash());
+}
+
+#[test]
+fn puzzle_hash_ignores_name_and_creator(){
+    let puzzle = parse_puzzle(CORPUS.iter().find(|c| c.name == "bond").unwrap().puzzle).unwrap();
+    let mut renamed = puzzle.clone();
+    renamed.name = "renamed".into();
+    renamed.creator_id = 12345;
+    assert_eq!(renamed.content_hash(), puzzle.content_hash());
+    let mut harder = puzzle.clone();
+    harder.product_multiplier += 1;
+    assert_ne!(harder.content_hash(), puzzle.content_hash());
+    let other = parse_puzzle(CORPUS.iter().find(|c| c.name == "calcify").unwrap().puzzle).unwrap();
+    assert_ne!(other.content_hash(), puzzle.content_hash());
+}
+
+#[test]
+fn puzzle_hash_ignores_bond_direction(){
+    let puzzle = parse_puzzle(CORPUS.iter().find(|c| c.name == "bond").unwrap().puzzle).unwrap();
+    let mut reversed = puzzle.clone();
+    for molecule in reversed.reagents.iter_mut().chain(&mut reversed.products){
+        molecule.bonds = molecule.bonds.iter().map(|bond| Bond{ start: bond.end, end: bond.start, ty: bond.ty }).collect();
+    }
+    assert!(reversed.products.iter().any(|product| !product.bonds.is_empty()));
+    assert_ne!(reversed, puzzle);
+    assert_eq!(reversed.content_hash(), puzzle.content_hash());
+}
+
+#[test]
+fn puzzle_hash_ignores_how_the_board_is_drawn(){
+    let mut puzzle = parse_puzzle(CORPUS.iter().find(|c| c.name == "bond").unwrap().puzzle).unwrap();
+    puzzle.production_info = Some(ProductionInfo::default());
+    let mut drawn = puzzle.clone();
+    let info = drawn.production_info.as_mut().unwrap();
+    info.shrink_left = true;
+    info.vials.push(Vial{ pos: HexIndex::new(1, 2), top: true, count: 2 });
+    assert_eq!(drawn.content_hash(), puzzle.content_hash());
+    drawn.production_info.as_mut().unwrap().isolation = true;
+    assert_ne!(drawn.content_hash(), puzzle.content_hash());
+}
@@ -0,0 +1,90 @@
+use omsim_rs::data::{HexIndex, HexRotation};
+
+fn area(radius: i32) -> impl Iterator<Item = HexIndex>{
+    (-radius..=radius).flat_map(move |q| (-radius..=radius).map(move |r| HexIndex::new(q, r)))
+}
+
+#[test]
+fn cube_coordinates_round_trip(){
+    for hex in area(5){
+        let (q, r, s) = hex.to_cube();
+        assert_eq!(q + r + s, 0);
+        assert_eq!(HexIndex::from_cube(q, r, s), Some(hex));
+    }
+    assert_eq!(HexIndex::from_cube(1, 1, 1), None);
+}
+
+#[test]
+fn pixel_coordinates_round_trip(){
+    for hex in area(5){
+        let (x, y) = hex.to_pixel();
+        assert_eq!(HexIndex::from_pixel(x, y), hex);
+        // points near the centre of a hex are still in it
+        for (dx, dy) in [(20.0, 0.0), (-20.0, 0.0), (10.0, 20.0), (-10.0, -20.0)]{
+            assert_eq!(HexIndex::from_pixel(x + dx, y + dy), hex);
+        }
+    }
+}
+
+#[test]
+fn file_order_round_trip(){
+    for hex in area(3){
+        let (a, b) = hex.to_file_order();
+        assert_eq!(HexIndex::from_file_order(a, b), hex);
+    }
+}
+
+#[test]
+fn unit_offsets_match_directions(){
+    for rotation in HexRotation::ALL{
+        let offset = rotation.to_unit_offset();
+        assert!(HexIndex::default().is_adjacent(offset));
+        assert_eq!(offset, HexIndex::new(1, 0).rotated(HexIndex::default(), rotation));
+        assert_eq!(HexRotation::from_unit_offset(offset), Some(rotation));
+        let from = HexIndex::new(3, -2);
+        assert_eq!(from.direction_to(from + offset), Some(rotation));
+    }
+    assert_eq!(HexIndex::default().direction_to(HexIndex::new(2, 0)), None);
+    assert_eq!(HexIndex::default().direction_to(HexIndex::default()), None);
+}
+
+#[test]
+fn rotation_composition(){
+    for a in HexRotation::ALL{
+        assert_eq!(a + a.inverse(), HexRotation::R0);
+        for b in HexRotation::ALL{
+            assert_eq!(a + a.to(b), b);
+            assert_eq!(b.rotate_offset(a.to_unit_offset()), (a + b).to_unit_offset());
+        }
+    }
+}
+
+#[test]
+fn rotation_arithmetic_is_modular(){
+    for a in 0..6u8{
+        for b in 0..6u8{
+            let (ra, rb) = (HexRotation::from_unsigned(a), HexRotation::from_unsigned(b));
+            assert_eq!((ra + rb).turns(), (a + b) % 6);
+            assert_eq!((ra - rb).turns(), (a + 6 - b) % 6);
+            assert_eq!(ra - rb + rb, ra);
+            assert_eq!(ra - rb, -(rb - ra));
+            assert_eq!(ra + rb, rb + ra);
+            let mut assigned = ra;
+            assigned -= rb;
+            assert_eq!(assigned, ra - rb);
+            assigned += rb;
+            assert_eq!(assigned, ra);
+        }
+        assert_eq!((-HexRotation::from_unsigned(a)).turns(), (6 - a) % 6);
+    }
+}
+
+#[test]
+fn rotations_from_signed_integers(){
+    for turns in -18..18i32{
+        let rotation = HexRotation::from(turns);
+        assert_eq!(rotation, HexRotation::from_signed(turns));
+        assert_eq!(rotation.turns() as i32, turns.rem_euclid(6));
+        assert_eq!(rotation.signed_turns() as i32, (turns.rem_euclid(6) + 2).rem_euclid(6) - 2);
+    }
+}
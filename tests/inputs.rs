@@ -0,0 +1,78 @@
+use std::collections::{HashMap, HashSet};
+use omsim_rs::data::{Atom, Bond, BondType, HexIndex, HexRotation, Molecule};
+use omsim_rs::sim::SimMolecule;
+use omsim_rs::sim::input::SimInput;
+
+fn bond(start: HexIndex, end: HexIndex) -> Bond{
+    Bond{ start, end, ty: BondType::Normal }
+}
+
+/// An infinite chain of fire, each bonded to the next one to its right.
+fn fire_chain() -> Molecule{
+    Molecule{
+        atoms: HashMap::from([(HexIndex::new(0, 0), Atom::Fire), (HexIndex::new(1, 0), Atom::Repeat)]),
+        bonds: HashSet::from([bond(HexIndex::new(0, 0), HexIndex::new(1, 0))])
+    }
+}
+
+fn single(atom: Atom) -> Molecule{
+    Molecule{ atoms: HashMap::from([(HexIndex::default(), atom)]), bonds: HashSet::new() }
+}
+
+#[test]
+fn repeating_molecules_expand_into_bonded_chains(){
+    assert_eq!(single(Atom::Fire).as_repeating(), None);
+    let repeating = fire_chain().as_repeating().unwrap();
+    assert_eq!(repeating.offset, HexIndex::new(1, 0));
+    // the bond to the next copy dangles until there is one
+    assert_eq!(repeating.expand(1), single(Atom::Fire));
+    let three = repeating.expand(3);
+    assert_eq!(three.atoms, (0..3).map(|q| (HexIndex::new(q, 0), Atom::Fire)).collect());
+    assert_eq!(three.bonds, HashSet::from([bond(HexIndex::new(0, 0), HexIndex::new(1, 0)), bond(HexIndex::new(1, 0), HexIndex::new(2, 0))]));
+    assert!(repeating.expand(0).atoms.is_empty());
+}
+
+#[test]
+fn inputs_only_spawn_on_clear_footprints(){
+    let mut input = SimInput::new(0, single(Atom::Salt));
+    let pos = HexIndex::new(2, -1);
+    let mut molecules = Vec::new();
+    assert!(input.spawn(pos, HexRotation::R0, &mut molecules));
+    assert_eq!(molecules[0].positions().collect::<Vec<_>>(), vec![pos]);
+    // the first molecule hasn't moved away yet
+    assert!(!input.spawn(pos, HexRotation::R0, &mut molecules));
+    assert_eq!((molecules.len(), input.spawned), (1, 1));
+    molecules[0].translate(HexIndex::new(0, 1));
+    assert!(input.spawn(pos, HexRotation::R0, &mut molecules));
+    assert_eq!((molecules.len(), input.spawned), (2, 2));
+}
+
+#[test]
+fn repeating_inputs_extend_the_chain_pulled_away_from_them(){
+    for rotation in HexRotation::ALL{
+        let mut input = SimInput::new(0, fire_chain());
+        let pos = HexIndex::new(-1, 3);
+        let step = rotation.rotate_offset(HexIndex::new(1, 0));
+        let mut molecules = Vec::new();
+        assert!(input.spawn(pos, rotation, &mut molecules));
+        molecules[0].translate(step);
+        assert!(input.spawn(pos, rotation, &mut molecules));
+        assert_eq!(molecules.len(), 1, "{rotation:?}");
+        let chain = &molecules[0];
+        assert_eq!(chain.positions().collect::<HashSet<_>>(), HashSet::from([pos, pos + step]), "{rotation:?}");
+        assert!(chain.bond_at(pos, pos + step).is_some(), "{rotation:?}");
+        assert_eq!(input.spawned, 2);
+    }
+}
+
+#[test]
+fn chains_pulled_further_start_a_new_chain(){
+    let mut input = SimInput::new(0, fire_chain());
+    let pos = HexIndex::default();
+    let mut molecules = Vec::new();
+    input.spawn(pos, HexRotation::R0, &mut molecules);
+    molecules[0].translate(HexIndex::new(2, 0));
+    assert!(input.spawn(pos, HexRotation::R0, &mut molecules));
+    assert_eq!(molecules.len(), 2);
+    assert_eq!(molecules.iter().map(SimMolecule::positions).map(Iterator::count).sum::<usize>(), 2);
+}
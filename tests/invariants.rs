@@ -0,0 +1,67 @@
+use std::collections::{HashMap, HashSet};
+use omsim_rs::data::{Atom, Bond, BondType, HexIndex, HexRotation, Molecule};
+use omsim_rs::parse::{parse_puzzle, parse_solution};
+use omsim_rs::selftest::CORPUS;
+use omsim_rs::sim::config::SimConfig;
+use omsim_rs::sim::error::SimError;
+use omsim_rs::sim::invariants::{changes_atom_count, check_molecules};
+use omsim_rs::sim::{Sim, SimMolecule, SimPart, SimPartType};
+use omsim_rs::verify::verify_with_config;
+
+fn single(atom: Atom, pos: HexIndex) -> SimMolecule{
+    SimMolecule::new(Molecule{ atoms: HashMap::from([(HexIndex::default(), atom)]), bonds: HashSet::new() }, pos)
+}
+
+#[test]
+fn the_corpus_keeps_every_invariant(){
+    // the idle solution would otherwise run to the default limit twice
+    let config = SimConfig{ max_cycles: 1000, ..SimConfig::default() };
+    for case in CORPUS{
+        let (Ok(puzzle), Ok(solution)) = (parse_puzzle(case.puzzle), parse_solution(case.solution)) else { continue };
+        let checked = verify_with_config(&puzzle, &solution, SimConfig{ check_invariants: true, ..config.clone() });
+        assert_eq!(checked.result, verify_with_config(&puzzle, &solution, config.clone()).result, "{}", case.name);
+    }
+}
+
+#[test]
+fn broken_boards_are_caught(){
+    let overlapping = vec![single(Atom::Salt, HexIndex::new(0, 0)), single(Atom::Fire, HexIndex::new(0, 0))];
+    assert_eq!(check_molecules(&overlapping), Err("molecules 0 and 1 overlap at (0, 0)".to_string()));
+
+    let mut dangling = single(Atom::Salt, HexIndex::new(1, 1));
+    dangling.layout.bonds.insert(Bond{ start: HexIndex::new(0, 0), end: HexIndex::new(1, 0), ty: BondType::Normal });
+    assert_eq!(check_molecules(&[dangling]), Err("bond between (1, 1) and (2, 1) is missing an atom".to_string()));
+
+    // collision checking would notice the overlap too
+    let unchecked = SimConfig{ skip_collision: true, ..SimConfig::default() };
+    let mut sim = Sim::new(Vec::new(), overlapping.clone(), SimConfig{ check_invariants: true, ..unchecked.clone() });
+    let result = sim.step();
+    assert!(matches!(result, Err(SimError::Invariant{ cycle: 0, .. })), "{result:?}");
+    // without the check, nothing notices
+    let mut sim = Sim::new(Vec::new(), overlapping, unchecked);
+    assert!(sim.step().is_ok());
+}
+
+#[test]
+fn only_some_glyphs_change_the_atom_count(){
+    assert!(changes_atom_count(&SimPartType::Disposal));
+    assert!(changes_atom_count(&SimPartType::Dispersion));
+    assert!(!changes_atom_count(&SimPartType::Bonding));
+    assert!(!changes_atom_count(&SimPartType::Animismus));
+}
+
+#[test]
+fn glyph_violations_are_returned_from_apply_glyphs(){
+    // a molecule overlapping another at (0, 0), so bonding the two merges one atom away
+    let mut overlapping = single(Atom::Fire, HexIndex::new(1, 0));
+    overlapping.layout.atoms.insert(HexIndex::new(-1, 0), Atom::Water);
+    let molecules = vec![single(Atom::Salt, HexIndex::new(0, 0)), overlapping];
+    let bonder = SimPart{ pos: HexIndex::new(0, 0), rotation: HexRotation::R0, ty: SimPartType::Bonding };
+    let unchecked = SimConfig{ skip_collision: true, ..SimConfig::default() };
+    let mut sim = Sim::new(vec![bonder.clone()], molecules.clone(), SimConfig{ check_invariants: true, ..unchecked.clone() });
+    let result = sim.apply_glyphs();
+    assert!(matches!(result, Err(SimError::Invariant{ cycle: 0, .. })), "{result:?}");
+    let mut sim = Sim::new(vec![bonder], molecules, unchecked);
+    assert_eq!(sim.apply_glyphs(), Ok(()));
+    assert_eq!(sim.molecules.len(), 1);
+}
@@ -0,0 +1,110 @@
+use std::collections::BTreeMap;
+use omsim_rs::parse::{parse_puzzle, parse_solution};
+use omsim_rs::sim::Sim;
+use omsim_rs::sim::config::{CancelToken, ProgressCallback, SimConfig};
+use omsim_rs::sim::error::SimError;
+use omsim_rs::verify::{verify, verify_with_config};
+
+fn sim(config: SimConfig) -> Sim{
+    let puzzle = parse_puzzle(include_bytes!("../corpus/transfer.puzzle")).unwrap();
+    let solution = parse_solution(include_bytes!("../corpus/transfer.solution")).unwrap();
+    Sim::create_with_config(&puzzle, &solution, config).unwrap()
+}
+
+#[test]
+fn cancelled_sims_stop_at_the_next_cycle(){
+    let cancel = CancelToken::new();
+    // cancel from inside the run, as another thread would
+    let progress = ProgressCallback::new(10, {
+        let cancel = cancel.clone();
+        move |_| cancel.cancel()
+    });
+    let mut sim = sim(SimConfig{ progress: Some(progress), cancel: Some(cancel.clone()), ..SimConfig::default() });
+    assert_eq!(sim.run(), Err(SimError::Cancelled{ cycle: 10 }));
+    assert!(cancel.is_cancelled());
+    assert_eq!(sim.cycle, 10);
+}
+
+#[test]
+fn uncancelled_sims_run_as_usual(){
+    let mut sim = sim(SimConfig{ cancel: Some(CancelToken::new()), ..SimConfig::default() });
+    assert_eq!(sim.run(), Ok(45));
+}
+
+#[cfg(feature = "batch")]
+#[test]
+fn cancelled_batches_skip_the_rest(){
+    use omsim_rs::load::batch::{verify_batch, SolutionBatch};
+    let dir = std::env::temp_dir().join(format!("omsim-rs-cancel-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    for name in ["a", "b", "c"]{
+        std::fs::write(dir.join(format!("{name}.solution")), include_bytes!("../corpus/transfer.solution")).unwrap();
+    }
+    // safety: nothing writes to the files once they're in place
+    let batch = unsafe{ SolutionBatch::open(&dir) }.unwrap();
+    let puzzle = parse_puzzle(include_bytes!("../corpus/transfer.puzzle")).unwrap();
+
+    let verdicts = verify_batch(&batch, &puzzle, &SimConfig{ cancel: Some(CancelToken::new()), ..SimConfig::default() });
+    assert_eq!(verdicts.len(), 3);
+    assert!(verdicts.iter().all(|verdict| verdict.as_ref().unwrap().is_valid()));
+
+    let cancel = CancelToken::new();
+    cancel.cancel();
+    assert!(verify_batch(&batch, &puzzle, &SimConfig{ cancel: Some(cancel), ..SimConfig::default() }).is_empty());
+
+    // cancelling partway through a solution fails that one and skips the rest
+    let cancel = CancelToken::new();
+    let progress = ProgressCallback::new(10, {
+        let cancel = cancel.clone();
+        move |_| cancel.cancel()
+    });
+    let verdicts = verify_batch(&batch, &puzzle, &SimConfig{ progress: Some(progress), cancel: Some(cancel), ..SimConfig::default() });
+    assert_eq!(verdicts.len(), 1);
+    assert_eq!(verdicts[0].as_ref().unwrap().result, Err(SimError::Cancelled{ cycle: 10 }));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn memory_limits_stop_sims(){
+    let usage = sim(SimConfig::default()).memory_usage();
+    assert!(usage > 0);
+    let mut limited = sim(SimConfig{ max_memory: Some(usage - 1), ..SimConfig::default() });
+    assert!(matches!(limited.run(), Err(SimError::MemoryLimit{ cycle: 0, bytes, limit }) if bytes > limit && limit == usage - 1));
+    let mut roomy = sim(SimConfig{ max_memory: Some(1 << 20), ..SimConfig::default() });
+    assert_eq!(roomy.run(), Ok(45));
+}
+
+#[test]
+fn the_event_log_does_not_count_towards_memory_limits(){
+    let mut unlimited = sim(SimConfig::default());
+    let mut peak = unlimited.memory_usage();
+    while unlimited.completed.is_none(){
+        unlimited.step().unwrap();
+        peak = peak.max(unlimited.memory_usage());
+    }
+    assert!(!unlimited.events.is_empty());
+    // the board never takes more than its peak, however long the log gets
+    let mut limited = sim(SimConfig{ max_memory: Some(peak), ..SimConfig::default() });
+    limited.run_until(500).unwrap();
+    assert!(limited.events.len() > unlimited.events.len());
+}
+
+#[test]
+fn input_quotas_limit_spawns(){
+    let puzzle = parse_puzzle(include_bytes!("../corpus/transfer.puzzle")).unwrap();
+    let solution = parse_solution(include_bytes!("../corpus/transfer.solution")).unwrap();
+    // the input spawns once more after the sixth product is taken
+    let verdict = verify(&puzzle, &solution);
+    assert_eq!(verdict.spawned, BTreeMap::from([(0, 7)]));
+    assert!(verdict.to_json().contains(r#""spawned":{"0":7}"#));
+
+    let quota = |quota| SimConfig{ input_quotas: BTreeMap::from([(0, quota)]), max_cycles: 200, ..SimConfig::default() };
+    let verdict = verify_with_config(&puzzle, &solution, quota(6));
+    assert_eq!(verdict.result.map(|metrics| metrics.cycles), Ok(45));
+    assert_eq!(verdict.spawned, BTreeMap::from([(0, 6)]));
+
+    let mut sim = sim(quota(5));
+    assert_eq!(sim.run(), Err(SimError::CycleLimit{ cycles: 200 }));
+    assert_eq!(sim.spawned(), BTreeMap::from([(0, 5)]));
+}
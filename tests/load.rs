@@ -0,0 +1,66 @@
+use omsim_rs::load::{parse_puzzle_file, parse_puzzle_from, parse_solution_file, parse_solution_from, LoadError};
+use omsim_rs::parse::{parse_puzzle, parse_puzzle_ref, parse_solution, parse_solution_header, parse_solution_ref, SolutionHeader};
+
+const PUZZLE: &[u8] = include_bytes!("../corpus/bond.puzzle");
+const SOLUTION: &[u8] = include_bytes!("../corpus/bond.solution");
+
+#[test]
+fn files_and_readers_match_bytes(){
+    let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/corpus");
+    let solution = parse_solution(SOLUTION).unwrap();
+    assert_eq!(parse_solution_file(format!("{dir}/bond.solution")).unwrap(), solution);
+    assert_eq!(parse_solution_from(SOLUTION).unwrap(), solution);
+    let puzzle = parse_puzzle(PUZZLE).unwrap();
+    assert_eq!(parse_puzzle_file(format!("{dir}/bond.puzzle")).unwrap().content_hash(), puzzle.content_hash());
+    assert_eq!(parse_puzzle_from(PUZZLE).unwrap().name, puzzle.name);
+    assert!(matches!(parse_puzzle_file(format!("{dir}/missing.puzzle")), Err(LoadError::Io(_))));
+    assert!(matches!(parse_solution_from(PUZZLE), Err(LoadError::Parse(_))));
+}
+
+#[test]
+fn borrowed_parsing_points_into_the_buffer(){
+    let borrowed = parse_solution_ref(SOLUTION).unwrap();
+    let range = SOLUTION.as_ptr_range();
+    assert!(range.contains(&borrowed.name.as_ptr()));
+    assert!(range.contains(&borrowed.puzzle_name.as_ptr()));
+    assert_eq!(borrowed.into_owned(), parse_solution(SOLUTION).unwrap());
+    let puzzle = parse_puzzle_ref(PUZZLE).unwrap();
+    assert!(PUZZLE.as_ptr_range().contains(&puzzle.name.as_ptr()));
+    assert_eq!(puzzle.into_owned().name, parse_puzzle(PUZZLE).unwrap().name);
+}
+
+#[test]
+fn headers_stop_before_parts(){
+    let solution = parse_solution(SOLUTION).unwrap();
+    let expected = SolutionHeader{ puzzle_name: solution.puzzle_name, name: solution.name, metrics: solution.metrics };
+    assert_eq!(parse_solution_header(SOLUTION).unwrap(), expected);
+    // everything after the header is ignored, even if it isn't a valid parts list
+    let header_len = 4 + 1 + expected.puzzle_name.len() + 1 + expected.name.len() + 4 + if expected.metrics.is_some(){ 32 }else{ 0 };
+    assert_eq!(parse_solution_header(&SOLUTION[..header_len]).unwrap(), expected);
+    assert!(parse_solution(&SOLUTION[..header_len]).is_err());
+    assert!(parse_solution_header(&SOLUTION[..header_len - 1]).is_err());
+}
+
+#[test]
+fn truncated_names_are_errors(){
+    // the puzzle name's length is right after the version
+    assert_eq!(parse_solution(&SOLUTION[..6]), Err("not enough bytes to read string"));
+}
+
+#[test]
+fn overlong_lengths_are_errors(){
+    // a puzzle name length whose every byte says another one follows
+    let overlong = [&SOLUTION[..4], &[0xFF; 12]].concat();
+    assert_eq!(parse_solution(&overlong), Err("invalid varint"));
+    assert_eq!(parse_solution_header(&overlong), Err("invalid varint"));
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn async_readers_match_bytes(){
+    use omsim_rs::load::{parse_puzzle_async, parse_solution_async};
+    let solution = futures_lite::future::block_on(parse_solution_async(SOLUTION)).unwrap();
+    assert_eq!(solution, parse_solution(SOLUTION).unwrap());
+    let puzzle = futures_lite::future::block_on(parse_puzzle_async(PUZZLE)).unwrap();
+    assert_eq!(puzzle.content_hash(), parse_puzzle(PUZZLE).unwrap().content_hash());
+}
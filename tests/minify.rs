@@ -0,0 +1,64 @@
+use omsim_rs::data::{HexIndex, Instruction, Part, PartType, Puzzle, Solution};
+use omsim_rs::minify::minify;
+use omsim_rs::parse::{parse_puzzle, parse_solution};
+use omsim_rs::verify::verify;
+
+fn bond() -> (Puzzle, Solution){
+    let puzzle = parse_puzzle(include_bytes!("../corpus/bond.puzzle")).unwrap();
+    let solution = parse_solution(include_bytes!("../corpus/bond.solution")).unwrap();
+    (puzzle, solution)
+}
+
+fn part(ty: PartType, pos: HexIndex) -> Part{
+    Part{
+        ty, pos, rotation: 0, arm_number: 1, arm_length: 1, index: 0,
+        conduit_index: 0, track_hexes: Vec::new(), conduit_hexes: Vec::new(), instructions: Vec::new()
+    }
+}
+
+fn arm(solution: &mut Solution) -> &mut Part{
+    solution.parts.iter_mut().find(|p| p.ty == PartType::Arm).unwrap()
+}
+
+#[test]
+fn strips_unused_parts_and_blanks(){
+    let (puzzle, original) = bond();
+    let mut padded = original.clone();
+    padded.parts.push(part(PartType::Calcification, HexIndex::new(6, 6)));
+    let mut track = part(PartType::Track, HexIndex::new(-6, 6));
+    track.track_hexes = (0..4).map(|q| HexIndex::new(q, 0)).collect();
+    padded.parts.push(track);
+    // shadowed by the rotation at the same index, so it never runs
+    arm(&mut padded).instructions.insert(0, (Instruction::Blank, 1));
+
+    let minified = minify(&puzzle, &padded).unwrap();
+    assert_eq!(minified.removed_parts, 2);
+    assert_eq!(minified.removed_blanks, 1);
+    assert_eq!(minified.solution, original);
+    assert_eq!(Ok(minified.metrics), verify(&puzzle, &original).result);
+}
+
+#[test]
+fn keeps_blanks_that_set_the_period(){
+    let (puzzle, mut solution) = bond();
+    arm(&mut solution).instructions.push((Instruction::Blank, 8));
+    let before = verify(&puzzle, &solution).result.unwrap();
+    let minified = minify(&puzzle, &solution).unwrap();
+    assert_eq!(minified.removed_blanks, 0);
+    assert_eq!(minified.metrics, before);
+    assert_eq!(minified.solution, solution);
+}
+
+#[test]
+fn removes_tracks_the_arm_never_moves_along(){
+    let (puzzle, original) = bond();
+    let mut solution = original.clone();
+    let arm_pos = arm(&mut solution).pos;
+    let mut track = part(PartType::Track, arm_pos + HexIndex::new(0, -2));
+    // runs up through the arm's base
+    track.track_hexes = (0..5).map(|r| HexIndex::new(0, r)).collect();
+    solution.parts.push(track);
+    let minified = minify(&puzzle, &solution).unwrap();
+    assert_eq!(minified.removed_parts, 1);
+    assert_eq!(minified.solution, original);
+}
@@ -0,0 +1,53 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use omsim_rs::data::{Atom, Bond, BondType, HexIndex, Molecule};
+
+fn bond(start: (i32, i32), end: (i32, i32), ty: BondType) -> Bond{
+    Bond{ start: HexIndex::new(start.0, start.1), end: HexIndex::new(end.0, end.1), ty }
+}
+
+/// Fire at the origin bonded to water on its right, and salt on the row below bonded to neither.
+fn with_loose_salt() -> Molecule{
+    Molecule{
+        atoms: HashMap::from([(HexIndex::new(0, 0), Atom::Fire), (HexIndex::new(1, 0), Atom::Water), (HexIndex::new(0, 1), Atom::Salt)]),
+        bonds: HashSet::from([bond((0, 0), (1, 0), BondType::Normal)])
+    }
+}
+
+#[test]
+fn composition_counts_each_element(){
+    let mut molecule = with_loose_salt();
+    molecule.atoms.insert(HexIndex::new(1, 1), Atom::Salt);
+    assert_eq!(molecule.composition(), BTreeMap::from([(Atom::Salt, 2), (Atom::Fire, 1), (Atom::Water, 1)]));
+    assert_eq!((molecule.atom_count(), molecule.bond_count()), (4, 1));
+    assert!(!molecule.is_single_atom());
+    let single = Molecule{ atoms: HashMap::from([(HexIndex::default(), Atom::Gold)]), bonds: HashSet::new() };
+    assert!(single.is_single_atom());
+    assert_eq!(single.composition(), BTreeMap::from([(Atom::Gold, 1)]));
+}
+
+#[test]
+fn molecules_are_connected_only_through_bonds(){
+    let mut molecule = with_loose_salt();
+    // the salt is next to the fire, but not bonded to it
+    assert!(!molecule.is_connected());
+    molecule.bonds.insert(bond((0, 1), (0, 0), BondType::Normal));
+    assert!(molecule.is_connected());
+    // bonds to hexes without atoms don't connect anything
+    let dangling = Molecule{
+        atoms: HashMap::from([(HexIndex::new(0, 0), Atom::Fire), (HexIndex::new(2, 0), Atom::Fire)]),
+        bonds: HashSet::from([bond((0, 0), (1, 0), BondType::Normal), bond((1, 0), (2, 0), BondType::Normal)])
+    };
+    assert!(!dangling.is_connected());
+    assert!(Molecule{ atoms: HashMap::new(), bonds: HashSet::new() }.is_connected());
+}
+
+#[test]
+fn molecules_display_atoms_then_bonds_in_rows(){
+    let mut molecule = with_loose_salt();
+    assert_eq!(molecule.to_string(), "Fi(0,0) Wa(1,0) Sa(0,1) | (0,0)-(1,0)");
+    molecule.bonds.insert(bond((0, 1), (1, 0), BondType::Triplex{ red: true, black: false, yellow: false }));
+    molecule.atoms.insert(HexIndex::new(-1, -1), Atom::Quintessence);
+    assert_eq!(molecule.to_string(), "Qt(-1,-1) Fi(0,0) Wa(1,0) Sa(0,1) | (0,0)-(1,0) (0,1)~(1,0)");
+    let single = Molecule{ atoms: HashMap::from([(HexIndex::default(), Atom::Lead)]), bonds: HashSet::new() };
+    assert_eq!(single.to_string(), "Pb(0,0)");
+}
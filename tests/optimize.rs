@@ -0,0 +1,50 @@
+use omsim_rs::data::{Instruction, Solution};
+use omsim_rs::optimize::{optimize, optimize_parallel, Goal};
+use omsim_rs::parse::{parse_puzzle, parse_solution};
+
+fn transfer() -> Solution{
+    parse_solution(include_bytes!("../corpus/transfer.solution")).unwrap()
+}
+
+fn arm(solution: &Solution) -> usize{
+    solution.parts.iter().position(|part| !part.instructions.is_empty()).unwrap()
+}
+
+#[test]
+fn repeated_runs_become_repeats(){
+    let puzzle = parse_puzzle(include_bytes!("../corpus/transfer.puzzle")).unwrap();
+    let mut solution = transfer();
+    let arm = arm(&solution);
+    // the whole loop twice, then a stray grab that slows it down
+    let copy: Vec<_> = solution.parts[arm].instructions.iter().map(|(instr, idx)| (*instr, idx + 8)).collect();
+    solution.parts[arm].instructions.extend(copy);
+    solution.parts[arm].instructions.push((Instruction::Grab, 20));
+    for goal in [Goal::Cycles, Goal::Instructions]{
+        let optimized = optimize(&puzzle, &solution, goal).unwrap();
+        assert_eq!((optimized.metrics.cycles, optimized.metrics.instructions), (45, 9));
+        assert_eq!((optimized.removed_instructions, optimized.repeats), (1, 1));
+        let mut expected = transfer().parts[arm].instructions.clone();
+        expected.push((Instruction::Repeat, 8));
+        assert_eq!(optimized.solution.parts[arm].instructions, expected);
+    }
+}
+
+#[test]
+fn late_arms_are_shifted_earlier(){
+    let puzzle = parse_puzzle(include_bytes!("../corpus/transfer.puzzle")).unwrap();
+    let mut solution = transfer();
+    let arm = arm(&solution);
+    solution.parts[arm].instructions.iter_mut().for_each(|(_, idx)| *idx += 2);
+    let optimized = optimize_parallel(&puzzle, &solution, Goal::Cycles, 4).unwrap();
+    assert_eq!(optimized.shifts, 2);
+    assert_eq!(optimized.metrics.cycles, 45);
+    assert_eq!(optimized, optimize(&puzzle, &solution, Goal::Cycles).unwrap());
+}
+
+#[test]
+fn optimal_solutions_are_left_alone(){
+    let puzzle = parse_puzzle(include_bytes!("../corpus/transfer.puzzle")).unwrap();
+    let optimized = optimize(&puzzle, &transfer(), Goal::Cycles).unwrap();
+    assert_eq!(optimized.solution, transfer());
+    assert_eq!((optimized.removed_instructions, optimized.shifts, optimized.repeats), (0, 0, 0));
+}
@@ -0,0 +1,137 @@
+use std::collections::{HashMap, HashSet};
+use omsim_rs::data::{Atom, Bond, BondType, HexIndex, HexRotation, Molecule};
+use omsim_rs::sim::SimMolecule;
+use omsim_rs::sim::output::{matches_output, placed_product, OutputMatching};
+
+const NEIGHBOURS: [HexIndex; 6] = [
+    HexIndex{ q: 1, r: 0 }, HexIndex{ q: 0, r: 1 }, HexIndex{ q: -1, r: 1 },
+    HexIndex{ q: -1, r: 0 }, HexIndex{ q: 0, r: -1 }, HexIndex{ q: 1, r: -1 }
+];
+
+fn normal(start: HexIndex, end: HexIndex) -> Bond{
+    Bond{ start, end, ty: BondType::Normal }
+}
+
+/// A ring of six atoms around the origin, with the given elements going anticlockwise from (1, 0).
+fn ring(elements: [Atom; 6], centre: Option<Atom>) -> Molecule{
+    let mut atoms: HashMap<HexIndex, Atom> = NEIGHBOURS.iter().copied().zip(elements).collect();
+    let mut bonds: HashSet<Bond> = (0..6).map(|i| normal(NEIGHBOURS[i], NEIGHBOURS[(i + 1) % 6])).collect();
+    if let Some(centre) = centre{
+        atoms.insert(HexIndex::default(), centre);
+        bonds.insert(normal(HexIndex::default(), NEIGHBOURS[0]));
+    }
+    Molecule{ atoms, bonds }
+}
+
+fn pair(a: Atom, b: Atom) -> Molecule{
+    Molecule{
+        atoms: HashMap::from([(HexIndex::default(), a), (NEIGHBOURS[0], b)]),
+        bonds: HashSet::from([normal(HexIndex::default(), NEIGHBOURS[0])])
+    }
+}
+
+/// The product as it would be dropped on the board after being rotated around the output's centre.
+fn dropped(product: &Molecule, pos: HexIndex, turns: i32) -> SimMolecule{
+    SimMolecule::new(product.rotated(HexIndex::default(), HexRotation::from_signed(turns)), pos)
+}
+
+fn matches_each_rotation(product: &Molecule, mode: OutputMatching) -> Vec<bool>{
+    let pos = HexIndex{ q: 3, r: -2 };
+    let placed = placed_product(product, pos, HexRotation::R0);
+    (0..6).map(|turns| matches_output(&dropped(product, pos, turns), &placed, mode)).collect()
+}
+
+#[test]
+fn uniform_ring_matches_in_every_orientation(){
+    let product = ring([Atom::Salt; 6], None);
+    assert_eq!(matches_each_rotation(&product, OutputMatching::GameAccurate), [true; 6]);
+}
+
+#[test]
+fn alternating_ring_matches_every_third_turn(){
+    let product = ring([Atom::Fire, Atom::Water, Atom::Fire, Atom::Water, Atom::Fire, Atom::Water], None);
+    assert_eq!(
+        matches_each_rotation(&product, OutputMatching::GameAccurate),
+        [true, false, true, false, true, false]
+    );
+}
+
+#[test]
+fn ring_with_spoke_only_matches_unrotated(){
+    // the single spoke bond breaks the ring's symmetry even though every atom is the same
+    let product = ring([Atom::Salt; 6], Some(Atom::Salt));
+    assert_eq!(
+        matches_each_rotation(&product, OutputMatching::GameAccurate),
+        [true, false, false, false, false, false]
+    );
+}
+
+#[test]
+fn symmetric_pair_matches_half_turn(){
+    let product = pair(Atom::Fire, Atom::Fire);
+    let pos = HexIndex{ q: 0, r: 0 };
+    let placed = placed_product(&product, pos, HexRotation::R0);
+    // a half turn swaps the two ends, shifted back onto the same pair of hexes
+    let flipped = SimMolecule::new(product.rotated(HexIndex::default(), HexRotation::R180), NEIGHBOURS[0]);
+    assert!(matches_output(&flipped, &placed, OutputMatching::GameAccurate));
+
+    let asymmetric = pair(Atom::Fire, Atom::Water);
+    let placed = placed_product(&asymmetric, pos, HexRotation::R0);
+    let flipped = SimMolecule::new(asymmetric.rotated(HexIndex::default(), HexRotation::R180), NEIGHBOURS[0]);
+    assert!(!matches_output(&flipped, &placed, OutputMatching::GameAccurate));
+}
+
+#[test]
+fn output_rotation_is_respected(){
+    let product = pair(Atom::Fire, Atom::Water);
+    let pos = HexIndex{ q: 1, r: 1 };
+    let placed = placed_product(&product, pos, HexRotation::R120);
+    assert!(matches_output(&dropped(&product, pos, 2), &placed, OutputMatching::GameAccurate));
+    assert!(!matches_output(&dropped(&product, pos, 0), &placed, OutputMatching::GameAccurate));
+}
+
+#[test]
+fn extra_atoms_only_match_greedily(){
+    let product = pair(Atom::Fire, Atom::Water);
+    let mut bigger = product.clone();
+    bigger.atoms.insert(HexIndex{ q: -1, r: 0 }, Atom::Salt);
+    bigger.bonds.insert(normal(HexIndex::default(), HexIndex{ q: -1, r: 0 }));
+    let pos = HexIndex::default();
+    let placed = placed_product(&product, pos, HexRotation::R0);
+    let molecule = SimMolecule::new(bigger, pos);
+    assert!(!matches_output(&molecule, &placed, OutputMatching::GameAccurate));
+    assert!(matches_output(&molecule, &placed, OutputMatching::Greedy));
+}
+
+#[test]
+fn partial_overlap_never_matches(){
+    let product = ring([Atom::Salt; 6], None);
+    let pos = HexIndex::default();
+    let placed = placed_product(&product, pos, HexRotation::R0);
+    for mode in [OutputMatching::GameAccurate, OutputMatching::Greedy]{
+        let shifted = SimMolecule::new(product.clone(), NEIGHBOURS[0]);
+        assert!(!matches_output(&shifted, &placed, mode));
+        let half = SimMolecule::new(pair(Atom::Salt, Atom::Salt), NEIGHBOURS[3]);
+        assert!(!matches_output(&half, &placed, mode));
+    }
+}
+
+#[test]
+fn bond_types_must_match(){
+    let product = pair(Atom::Fire, Atom::Fire);
+    let mut triplex = product.clone();
+    triplex.bonds = HashSet::from([Bond{ start: HexIndex::default(), end: NEIGHBOURS[0], ty: BondType::Triplex{ red: true, black: false, yellow: false } }]);
+    let placed = placed_product(&product, HexIndex::default(), HexRotation::R0);
+    let molecule = SimMolecule::new(triplex, HexIndex::default());
+    assert!(!matches_output(&molecule, &placed, OutputMatching::GameAccurate));
+    assert!(!matches_output(&molecule, &placed, OutputMatching::Greedy));
+}
+
+#[test]
+fn grabbed_molecules_never_match(){
+    let product = pair(Atom::Fire, Atom::Water);
+    let placed = placed_product(&product, HexIndex::default(), HexRotation::R0);
+    let mut molecule = dropped(&product, HexIndex::default(), 0);
+    molecule.grabbed = true;
+    assert!(!matches_output(&molecule, &placed, OutputMatching::GameAccurate));
+}
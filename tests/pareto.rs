@@ -0,0 +1,40 @@
+use omsim_rs::analysis::pareto::{dominates, pareto_frontier, pareto_frontier_of, ParetoStatus};
+use omsim_rs::data::Metrics;
+use omsim_rs::parse::{parse_puzzle, parse_solution};
+use omsim_rs::verify::verify;
+
+const fn metrics(cost: i32, cycles: i32, area: i32, instructions: i32) -> Metrics{
+    Metrics{ cycles, cost, area, instructions }
+}
+
+#[test]
+fn dominated_solutions_are_flagged(){
+    let set = [
+        Some(metrics(20, 45, 7, 8)),
+        Some(metrics(20, 50, 7, 8)),
+        Some(metrics(40, 30, 9, 8)),
+        None,
+        Some(metrics(20, 45, 7, 8)),
+        Some(metrics(10, 60, 7, 9))
+    ];
+    assert_eq!(pareto_frontier(&set), vec![
+        ParetoStatus::Frontier,
+        ParetoStatus::Dominated{ by: 0 },
+        ParetoStatus::Frontier,
+        ParetoStatus::Invalid,
+        ParetoStatus::Duplicate{ of: 0 },
+        ParetoStatus::Frontier
+    ]);
+    assert!(dominates(&metrics(20, 45, 7, 8), &metrics(20, 45, 7, 9)));
+    assert!(!dominates(&metrics(20, 45, 7, 8), &metrics(20, 45, 7, 8)));
+}
+
+#[test]
+fn verdicts_can_be_ranked_directly(){
+    let puzzle = parse_puzzle(include_bytes!("../corpus/transfer.puzzle")).unwrap();
+    let verdicts: Vec<_> = ["transfer", "collision"].into_iter().map(|name| {
+        let data = std::fs::read(format!("{}/corpus/{name}.solution", env!("CARGO_MANIFEST_DIR"))).unwrap();
+        verify(&puzzle, &parse_solution(&data).unwrap())
+    }).collect();
+    assert_eq!(pareto_frontier_of(&verdicts), vec![ParetoStatus::Frontier, ParetoStatus::Invalid]);
+}
@@ -0,0 +1,85 @@
+use omsim_rs::data::Solution;
+use omsim_rs::parse::{parse_puzzle, parse_puzzle_with_options, parse_solution, parse_solution_with_options, Anomaly, ParseOptions};
+
+const PUZZLE: &[u8] = include_bytes!("../corpus/transfer.puzzle");
+const SOLUTION: &[u8] = include_bytes!("../corpus/transfer.solution");
+
+const LENIENT: ParseOptions = ParseOptions{ strict: false };
+const STRICT: ParseOptions = ParseOptions{ strict: true };
+
+fn transfer() -> Solution{
+    parse_solution(SOLUTION).unwrap()
+}
+
+#[test]
+fn clean_files_have_no_anomalies(){
+    for options in [LENIENT, STRICT]{
+        assert_eq!(parse_solution_with_options(SOLUTION, options).unwrap().anomalies, []);
+        assert_eq!(parse_puzzle_with_options(PUZZLE, options).unwrap().anomalies, []);
+    }
+}
+
+#[test]
+fn empty_names(){
+    let mut solution = transfer();
+    solution.name.clear();
+    let bytes = solution.unparse();
+    let parsed = parse_solution_with_options(&bytes, LENIENT).unwrap();
+    assert_eq!(parsed.value, solution);
+    assert_eq!(parsed.anomalies, [Anomaly::EmptySolutionName]);
+    assert_eq!(parse_solution_with_options(&bytes, STRICT), Err("solution name is empty"));
+}
+
+#[test]
+fn instructions_out_of_order(){
+    let mut solution = transfer();
+    let arm = solution.parts.iter().position(|part| !part.instructions.is_empty()).unwrap();
+    solution.parts[arm].instructions.swap(0, 1);
+    let bytes = solution.unparse();
+    let parsed = parse_solution_with_options(&bytes, LENIENT).unwrap();
+    // kept as written, since the tape comes out the same either way
+    assert_eq!(parsed.value.parts[arm].instructions, solution.parts[arm].instructions);
+    assert_eq!(parsed.value.parts[arm].tape(0), transfer().parts[arm].tape(0));
+    assert_eq!(parsed.anomalies, [Anomaly::InstructionsOutOfOrder(arm)]);
+    assert_eq!(parse_solution_with_options(&bytes, STRICT), Err("instructions are out of order"));
+}
+
+#[test]
+fn trailing_bytes(){
+    let mut bytes = SOLUTION.to_vec();
+    bytes.extend_from_slice(&[0, 0, 0, 7, 42]);
+    // the plain parser ignores them, as the game does
+    assert_eq!(parse_solution(&bytes).unwrap(), transfer());
+    let parsed = parse_solution_with_options(&bytes, LENIENT).unwrap();
+    assert_eq!(parsed.value, transfer());
+    assert_eq!(parsed.anomalies, [Anomaly::TrailingBytes(5)]);
+    assert_eq!(parse_solution_with_options(&bytes, STRICT), Err("trailing data after solution"));
+}
+
+#[test]
+fn invalid_utf8_names(){
+    let mut solution = transfer();
+    solution.name = "caf\u{e9}".into();
+    let mut bytes = solution.unparse();
+    // the same name in Latin-1, as some old tools wrote it
+    let at = bytes.windows(5).position(|window| window == "caf\u{e9}".as_bytes()).unwrap();
+    bytes.splice(at - 1..at + 5, [4, b'c', b'a', b'f', 0xe9]);
+    assert_eq!(parse_solution(&bytes), Err("invalid utf8"));
+    let parsed = parse_solution_with_options(&bytes, LENIENT).unwrap();
+    assert_eq!(parsed.value.name, "caf\u{fffd}");
+    assert_eq!(parsed.value.metrics, transfer().metrics);
+    assert_eq!(parsed.anomalies, [Anomaly::InvalidUtf8Name]);
+    assert_eq!(parse_solution_with_options(&bytes, STRICT), Err("invalid utf8"));
+}
+
+#[test]
+fn impossible_list_lengths(){
+    // the number of reagents is right after the version, name, creator and permissions
+    let at = 4 + 1 + parse_puzzle(PUZZLE).unwrap().name.len() + 16;
+    let with_reagents = |count: i32| [&PUZZLE[..at], &count.to_le_bytes(), &PUZZLE[at + 4..]].concat();
+    for options in [LENIENT, STRICT]{
+        assert_eq!(parse_puzzle_with_options(&with_reagents(-1), options).map(|parsed| parsed.value), Err("negative list length"));
+        // far more than the file has room for, which mustn't be allocated up front
+        assert!(parse_puzzle_with_options(&with_reagents(i32::MAX), options).is_err());
+    }
+}
@@ -0,0 +1,73 @@
+use omsim_rs::data::{HexIndex, Instruction, Part, PartType};
+use omsim_rs::parse::{parse_puzzle, parse_solution};
+use omsim_rs::sim::patch::Rerun;
+use omsim_rs::sim::config::SimConfig;
+use omsim_rs::sim::{Sim, SimPartType};
+
+fn sim() -> Sim{
+    let puzzle = parse_puzzle(include_bytes!("../corpus/transfer.puzzle")).unwrap();
+    let solution = parse_solution(include_bytes!("../corpus/transfer.solution")).unwrap();
+    Sim::create(&puzzle, &solution).unwrap()
+}
+
+#[test]
+fn patched_reruns_match_running_from_scratch(){
+    let mut rerun = Rerun::new(sim(), 2);
+    assert_eq!(rerun.run(), Ok(45));
+    assert_eq!(rerun.snapshots(), 23);
+
+    // the tape is grab, three clockwise turns, drop, and three more turns back; this removes the last turn
+    rerun.patch_instruction(1, 7, Instruction::Blank).unwrap();
+    assert_eq!((rerun.sim.cycle, rerun.sim.period), (6, 7));
+    assert_eq!(rerun.snapshots(), 4);
+    let mut fresh = sim();
+    assert_eq!(fresh.patch_instruction(1, 7, Instruction::Blank), Ok(7));
+    assert_eq!(rerun.run(), fresh.run());
+
+    // a longer loop changes every cycle past the old one
+    let mut rerun = Rerun::new(sim(), 2);
+    rerun.run().unwrap();
+    rerun.patch_instruction(1, 10, Instruction::PeriodOverride).unwrap();
+    assert_eq!((rerun.sim.cycle, rerun.sim.period), (8, 11));
+    let mut fresh = sim();
+    assert_eq!(fresh.patch_instruction(1, 10, Instruction::PeriodOverride), Ok(8));
+    assert_eq!(rerun.run(), fresh.run());
+}
+
+#[test]
+fn bad_patches_are_rejected(){
+    let mut rerun = Rerun::new(sim(), 4);
+    assert!(rerun.patch_instruction(2, 0, Instruction::Grab).is_err());
+    assert!(rerun.patch_instruction(1, 0, Instruction::Repeat).is_err());
+}
+
+#[test]
+fn parts_can_only_change_before_the_start(){
+    let mut sim = sim();
+    let output = sim.parts.iter().position(|part| matches!(part.ty, SimPartType::Output(_))).unwrap();
+    let part = sim.remove_part(output).unwrap();
+    assert_eq!(sim.parts.len(), 2);
+    sim.add_part(part).unwrap();
+    assert_eq!(sim.area, self::sim().area);
+    assert_eq!(sim.clone().run(), Ok(45));
+
+    sim.step().unwrap();
+    assert!(sim.remove_part(0).is_err());
+}
+
+#[test]
+fn instructions_feeding_an_expansion_cant_be_patched(){
+    // rotate clockwise, then reset, which expands to rotating anticlockwise
+    let arm = Part{
+        ty: PartType::Arm, pos: HexIndex::new(0, 0), rotation: 0, arm_number: 0, arm_length: 1, index: 0, conduit_index: 0,
+        track_hexes: Vec::new(), conduit_hexes: Vec::new(), instructions: vec![(Instruction::RotateClockwise, 0), (Instruction::Reset, 1)]
+    };
+    let mut sim = Sim::sandbox(&[arm], &[], SimConfig::default()).unwrap();
+    let tape = |sim: &Sim| sim.arms().next().unwrap().1.tape.clone();
+    assert_eq!(tape(&sim), vec![Instruction::RotateClockwise, Instruction::RotateAnticlockwise]);
+    // extending first would leave the reset expanded as if the arm had rotated
+    assert!(sim.patch_instruction(0, 0, Instruction::Extend).is_err());
+    assert!(sim.patch_instruction(0, 1, Instruction::Grab).is_err());
+    assert_eq!(sim.patch_instruction(0, 2, Instruction::Grab), Ok(2));
+    assert_eq!(tape(&sim), vec![Instruction::RotateClockwise, Instruction::RotateAnticlockwise, Instruction::Grab]);
+}
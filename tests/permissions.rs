@@ -0,0 +1,59 @@
+use omsim_rs::data::{HexIndex, Part, PartType, Permissions, Puzzle, Solution};
+use omsim_rs::parse::{parse_puzzle, parse_puzzle_with_options, parse_solution, Anomaly, ParseOptions};
+
+fn puzzle(permissions: Permissions) -> Puzzle{
+    let mut puzzle = parse_puzzle(include_bytes!("../corpus/transfer.puzzle")).unwrap();
+    puzzle.permissions = permissions;
+    puzzle
+}
+
+/// The transfer solution with one more part, away from everything else.
+fn solution_with(ty: PartType) -> Solution{
+    let mut solution = parse_solution(include_bytes!("../corpus/transfer.solution")).unwrap();
+    solution.parts.push(Part{
+        ty, pos: HexIndex::new(-5, 5), rotation: 0, arm_number: 1, arm_length: 1, index: 0,
+        conduit_index: 0, track_hexes: Vec::new(), conduit_hexes: Vec::new(), instructions: Vec::new()
+    });
+    solution
+}
+
+#[test]
+fn parts_need_their_permission(){
+    for (ty, permission, name) in [
+        (PartType::Unification, Permissions::QUINTESSENCE, "QUINTESSENCE"),
+        (PartType::Dispersion, Permissions::QUINTESSENCE, "QUINTESSENCE"),
+        (PartType::Berlo, Permissions::BERLO, "BERLO"),
+        (PartType::Disposal, Permissions::DISPOSAL, "DISPOSAL")
+    ]{
+        let solution = solution_with(ty.clone());
+        let error = puzzle(Permissions::all() - permission).clean_solution(&solution).unwrap_err();
+        assert!(error.contains(name), "{ty:?}: {error}");
+        assert!(puzzle(Permissions::DEFAULT_PERMISSIONS | permission).clean_solution(&solution).is_ok(), "{ty:?}");
+    }
+}
+
+#[test]
+fn other_parts_are_not_gated(){
+    assert!(puzzle(Permissions::empty()).clean_solution(&solution_with(PartType::Bonding)).is_ok());
+}
+
+#[test]
+fn unknown_bits_are_reported(){
+    let bits = Permissions::DEFAULT_PERMISSIONS.bits() | 0x0008_0000 | 1 << 40;
+    let (known, unknown) = Permissions::from_bits_report(bits);
+    assert_eq!(known, Permissions::DEFAULT_PERMISSIONS);
+    assert_eq!(unknown, 0x0008_0000 | 1 << 40);
+    assert_eq!(Permissions::from_bits_report(Permissions::DEFAULT_PERMISSIONS.bits()), (Permissions::DEFAULT_PERMISSIONS, 0));
+}
+
+#[test]
+fn unknown_bits_are_a_parse_anomaly(){
+    let mut modded = puzzle(Permissions::from_bits_retain(Permissions::DEFAULT_PERMISSIONS.bits() | 1 << 40));
+    modded.name = "modded".into();
+    let bytes = modded.unparse();
+    let parsed = parse_puzzle_with_options(&bytes, ParseOptions::default()).unwrap();
+    assert_eq!(parsed.anomalies, vec![Anomaly::UnknownPermissions(1 << 40)]);
+    // the bits are kept, so the puzzle is written back unchanged
+    assert_eq!(parsed.value.unparse(), bytes);
+    assert!(parse_puzzle_with_options(&bytes, ParseOptions{ strict: true }).is_err());
+}
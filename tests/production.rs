@@ -0,0 +1,37 @@
+use omsim_rs::data::{Chamber, ChamberType, HexIndex, ProductionInfo, Puzzle};
+use omsim_rs::parse::{parse_puzzle, parse_solution};
+use omsim_rs::sim::error::SimError;
+use omsim_rs::verify::verify;
+
+fn transfer_in(chambers: Vec<Chamber>) -> Puzzle{
+    let mut puzzle = parse_puzzle(include_bytes!("../corpus/transfer.puzzle")).unwrap();
+    puzzle.production_info = Some(ProductionInfo{ chambers, ..ProductionInfo::default() });
+    puzzle
+}
+
+#[test]
+fn chambers_are_rectangles_on_screen(){
+    let chamber = Chamber{ pos: HexIndex::new(0, 0), ty: ChamberType::Small };
+    let rows: Vec<Vec<i32>> = (-1..4).map(|r| (-3..6).filter(|&q| chamber.contains(HexIndex::new(q, r))).collect()).collect();
+    assert_eq!(rows, vec![vec![], vec![0, 1, 2, 3], vec![0, 1, 2, 3], vec![-1, 0, 1, 2], vec![]]);
+}
+
+#[test]
+fn solutions_inside_their_chamber_are_valid(){
+    let solution = parse_solution(include_bytes!("../corpus/transfer.solution")).unwrap();
+    let verdict = verify(&transfer_in(vec![Chamber{ pos: HexIndex::new(0, -1), ty: ChamberType::Small }]), &solution);
+    assert_eq!(verdict.result, verify(&transfer_in(Vec::new()), &solution).result);
+    assert!(verdict.is_valid());
+}
+
+#[test]
+fn atoms_may_not_leave_their_chamber(){
+    let solution = parse_solution(include_bytes!("../corpus/transfer.solution")).unwrap();
+    // covers the input and output, but not the row above them that the arm swings the atom through
+    let chamber = Chamber{ pos: HexIndex::new(0, -2), ty: ChamberType::Small };
+    let mut puzzle = transfer_in(vec![chamber]);
+    let verdict = verify(&puzzle, &solution);
+    assert!(matches!(verdict.result, Err(SimError::OutOfBounds{ pos, .. }) if pos.r == 1), "{verdict}");
+    puzzle.production_info.as_mut().unwrap().chambers[0].pos = HexIndex::new(10, 10);
+    assert_eq!(verify(&puzzle, &solution).result, Err(SimError::OutOfBounds{ cycle: 0, pos: HexIndex::new(0, 0) }));
+}
@@ -0,0 +1,67 @@
+use omsim_rs::data::{Atom, PartType};
+use omsim_rs::parse::{parse_puzzle, parse_solution, parse_trace};
+use omsim_rs::sim::config::SimConfig;
+use omsim_rs::sim::{Sim, SimEvent};
+use omsim_rs::trace::Trace;
+use omsim_rs::verify::verify_with_config;
+
+fn tracked() -> SimConfig{
+    SimConfig{ track_provenance: true, ..SimConfig::default() }
+}
+
+#[test]
+fn consumed_products_know_their_inputs_and_glyphs(){
+    let puzzle = parse_puzzle(include_bytes!("../corpus/calcify.puzzle")).unwrap();
+    let solution = parse_solution(include_bytes!("../corpus/calcify.solution")).unwrap();
+    let verdict = verify_with_config(&puzzle, &solution, tracked());
+    assert!(verdict.is_valid());
+    assert!(!verdict.consumed.is_empty());
+    let input = solution.parts.iter().position(|part| part.ty == PartType::Input).unwrap();
+    let glyph = solution.parts.iter().position(|part| part.ty == PartType::Calcification).unwrap();
+    for product in &verdict.consumed{
+        for (_, atom, provenance) in &product.atoms{
+            assert_eq!(*atom, Atom::Salt);
+            let (part, spawned) = provenance.spawned.unwrap();
+            assert_eq!(part, input);
+            let [transmutation] = provenance.transmutations[..] else { panic!("{provenance:?}") };
+            assert_eq!(transmutation.part, glyph);
+            assert_eq!(transmutation.to, Atom::Salt);
+            assert!(transmutation.from.unwrap().is_cardinal());
+            assert!(spawned < transmutation.cycle && transmutation.cycle <= product.cycle);
+        }
+    }
+}
+
+#[test]
+fn provenance_is_off_by_default(){
+    let puzzle = parse_puzzle(include_bytes!("../corpus/calcify.puzzle")).unwrap();
+    let solution = parse_solution(include_bytes!("../corpus/calcify.solution")).unwrap();
+    let mut sim = Sim::create(&puzzle, &solution).unwrap();
+    sim.run().unwrap();
+    assert!(sim.consumed.is_empty());
+    assert!(!sim.events.iter().any(|event| matches!(event, SimEvent::AtomSpawned{ .. } | SimEvent::AtomTransmuted{ .. })));
+}
+
+#[test]
+fn provenance_follows_held_atoms_and_round_trips_in_traces(){
+    let puzzle = parse_puzzle(include_bytes!("../corpus/calcify.puzzle")).unwrap();
+    let solution = parse_solution(include_bytes!("../corpus/calcify.solution")).unwrap();
+    let mut sim = Sim::create_with_config(&puzzle, &solution, tracked()).unwrap();
+    let trace = Trace::record(&mut sim.clone());
+    assert!(trace.frames.iter().flat_map(|frame| &frame.events).any(|event| matches!(event, SimEvent::AtomTransmuted{ .. })));
+    assert_eq!(parse_trace(&trace.unparse()).unwrap(), trace);
+    sim.run().unwrap();
+    // every transmutation happened where the glyph is, but the atoms have moved on since
+    let glyph = solution.parts.iter().position(|part| part.ty == PartType::Calcification).unwrap();
+    let transmuted: Vec<_> = sim.events.iter().filter_map(|event| match event{
+        SimEvent::AtomTransmuted{ part, pos, .. } => Some((*part, *pos)),
+        _ => None
+    }).collect();
+    assert!(!transmuted.is_empty());
+    assert!(transmuted.iter().all(|(part, pos)| *part == glyph && *pos == solution.parts[glyph].pos));
+    for molecule in &sim.molecules{
+        for pos in molecule.positions(){
+            assert!(molecule.provenance_at(pos).is_some_and(|p| p.spawned.is_some()));
+        }
+    }
+}
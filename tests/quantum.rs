@@ -0,0 +1,55 @@
+use std::collections::{HashMap, HashSet};
+use omsim_rs::data::{Atom, Bond, BondType, HexIndex, HexRotation, Instruction, Molecule, Part, PartType};
+use omsim_rs::parse::{parse_puzzle, parse_solution};
+use omsim_rs::sim::{Sim, SimMolecule};
+use omsim_rs::sim::config::{QuantumBondPolicy, SimConfig};
+
+/// Fire at `start` bonded to water two hexes to its right, which the game never allows.
+fn quantum(start: HexIndex) -> Molecule{
+    let end = start + HexIndex::new(2, 0);
+    Molecule{
+        atoms: HashMap::from([(start, Atom::Fire), (end, Atom::Water)]),
+        bonds: HashSet::from([Bond{ start, end, ty: BondType::Normal }])
+    }
+}
+
+fn rigid() -> SimConfig{
+    SimConfig{ quantum_bonds: QuantumBondPolicy::Rigid, ..SimConfig::default() }
+}
+
+#[test]
+fn quantum_bonds_are_rejected_by_default(){
+    let mut puzzle = parse_puzzle(include_bytes!("../corpus/transfer.puzzle")).unwrap();
+    let solution = parse_solution(include_bytes!("../corpus/transfer.solution")).unwrap();
+    puzzle.products[0] = quantum(HexIndex::default());
+    assert_eq!(Sim::create(&puzzle, &solution).unwrap_err(), "puzzle contains bonds between non-adjacent atoms");
+    assert!(Sim::create_with_config(&puzzle, &solution, rigid()).is_ok());
+    assert_eq!(Sim::sandbox(&[], &[quantum(HexIndex::default())], SimConfig::default()).unwrap_err(), "sandbox contains an invalid molecule");
+}
+
+#[test]
+fn rotated_molecules_keep_their_layout(){
+    let around = HexIndex::new(-1, 2);
+    let mut molecule = SimMolecule::new(quantum(HexIndex::default()), HexIndex::new(1, 1));
+    molecule.rotate(around, HexRotation::R120);
+    let fire = HexIndex::new(1, 1).rotated(around, HexRotation::R120);
+    let water = HexIndex::new(3, 1).rotated(around, HexRotation::R120);
+    assert_eq!(molecule.positions().collect::<HashSet<_>>(), HashSet::from([fire, water]));
+    assert_eq!((molecule.atom_at(fire), molecule.atom_at(water)), (Atom::Fire, Atom::Water));
+    assert!(molecule.bond_at(water, fire).is_some_and(|bond| bond.is_quantum()));
+}
+
+#[test]
+fn rigid_quantum_molecules_move_as_one(){
+    let arm = Part{
+        ty: PartType::Arm, pos: HexIndex::default(), rotation: 0, arm_number: 0, arm_length: 1, index: 0, conduit_index: 0,
+        track_hexes: Vec::new(), conduit_hexes: Vec::new(), instructions: vec![(Instruction::Grab, 0), (Instruction::RotateClockwise, 1)]
+    };
+    let mut sim = Sim::sandbox(&[arm], &[quantum(HexIndex::new(1, 0))], rigid()).unwrap();
+    sim.step().unwrap();
+    sim.step().unwrap();
+    let turned = |pos: HexIndex| pos.rotated(HexIndex::default(), HexRotation::R300);
+    assert_eq!(sim.molecules.len(), 1);
+    assert_eq!(sim.molecules[0].positions().collect::<HashSet<_>>(), HashSet::from([turned(HexIndex::new(1, 0)), turned(HexIndex::new(3, 0))]));
+    assert!(sim.molecules[0].bond_at(turned(HexIndex::new(1, 0)), turned(HexIndex::new(3, 0))).is_some());
+}
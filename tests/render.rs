@@ -0,0 +1,180 @@
+use std::collections::{HashMap, HashSet};
+use omsim_rs::data::{Atom, Bond, BondType, HexIndex, Metrics, Molecule};
+use omsim_rs::parse::{parse_puzzle, parse_solution};
+use omsim_rs::render::{render_blueprint, render_molecule, render_svg, Card, Color, RenderOptions, Scene, Shape};
+use omsim_rs::render::raster::Canvas;
+use omsim_rs::render::theme::RenderTheme;
+use omsim_rs::sim::Sim;
+
+fn transfer() -> Sim{
+    let puzzle = parse_puzzle(include_bytes!("../corpus/transfer.puzzle")).unwrap();
+    let solution = parse_solution(include_bytes!("../corpus/transfer.solution")).unwrap();
+    Sim::create(&puzzle, &solution).unwrap()
+}
+
+fn card() -> Card{
+    Card{ puzzle: "Transfer <1>".into(), solution: "fast & small".into(), metrics: Some(Metrics{ cost: 20, cycles: 45, area: 5, instructions: 8 }) }
+}
+
+#[test]
+fn svg_has_the_card(){
+    let svg = render_svg(&transfer(), &RenderOptions{ card: Some(card()), ..RenderOptions::default() });
+    assert!(svg.starts_with("<svg"));
+    assert!(svg.contains("Transfer &lt;1&gt; - fast &amp; small"));
+    assert!(svg.contains("45 cycles, 20 cost, 5 area, 8 instructions"));
+    let plain = render_svg(&transfer(), &RenderOptions::default());
+    assert!(!plain.contains("cycles"));
+}
+
+#[test]
+fn card_adds_a_strip_below(){
+    let sim = transfer();
+    let scene = Scene::of_sim(&sim, &RenderTheme::default());
+    let bounds = scene.bounds().unwrap();
+    let (_, plain) = scene.framed(bounds, &RenderOptions::default());
+    let (_, carded) = scene.framed(bounds, &RenderOptions{ card: Some(card()), ..RenderOptions::default() });
+    assert_eq!(plain.min_y, carded.min_y);
+    assert!(carded.max_y > plain.max_y);
+    let canvas = Canvas::draw(&scene, carded, 0.5);
+    assert!(canvas.height > canvas.width / 4);
+}
+
+#[test]
+fn themes_change_colours_and_labels(){
+    let sim = transfer();
+    let mut theme = RenderTheme::default();
+    theme.atoms.insert(Atom::Salt, Color(1, 2, 3));
+    let svg = render_svg(&sim, &RenderOptions{ theme: theme.clone(), ..RenderOptions::default() });
+    assert!(svg.contains("fill=\"#010203\""));
+    assert!(svg.contains(">Sa</text>"));
+    theme.labels = false;
+    let svg = render_svg(&sim, &RenderOptions{ theme, ..RenderOptions::default() });
+    assert!(!svg.contains("<text"));
+}
+
+#[test]
+fn grid_covers_the_board(){
+    let sim = transfer();
+    let plain = Scene::of_sim(&sim, &RenderTheme::classic());
+    let bounds = plain.bounds().unwrap();
+    let (framed, _) = plain.framed(bounds, &RenderOptions::default());
+    let theme = RenderTheme::colorblind();
+    let grid = theme.grid.unwrap();
+    let (gridded, _) = Scene::of_sim(&sim, &theme).framed(bounds, &RenderOptions{ theme, ..RenderOptions::default() });
+    let grid_lines = gridded.shapes.iter().filter(|shape| matches!(shape, Shape::Line{ color, .. } if *color == grid)).count();
+    assert!(grid_lines >= 6 * 6);
+    assert!(!framed.shapes.iter().any(|shape| matches!(shape, Shape::Line{ color, .. } if *color == grid)));
+}
+
+#[test]
+fn blueprint_leaves_out_molecules(){
+    let puzzle = parse_puzzle(include_bytes!("../corpus/transfer.puzzle")).unwrap();
+    let solution = parse_solution(include_bytes!("../corpus/transfer.solution")).unwrap();
+    let theme = RenderTheme::default();
+    let mut sim = Sim::create(&puzzle, &solution).unwrap();
+    let salt = theme.atom_color(Atom::Salt);
+    let atoms = |scene: &Scene| scene.shapes.iter().filter(|shape| matches!(shape, Shape::Circle{ fill, .. } if *fill == salt)).count();
+    // the spawned reagent is drawn over the input's ghost
+    assert_eq!(atoms(&Scene::of_sim(&sim, &theme)), 1);
+    assert_eq!(atoms(&Scene::blueprint(&sim, &theme)), 0);
+    // molecules the arm has picked up are left out too
+    sim.step().unwrap();
+    assert_eq!(atoms(&Scene::blueprint(&sim, &theme)), 0);
+    let svg = render_blueprint(&puzzle, &solution, &RenderOptions::default()).unwrap();
+    assert!(svg.starts_with("<svg") && svg.contains("<polygon"));
+}
+
+#[test]
+fn molecules_show_triplex_bonds(){
+    let (a, b, c) = (HexIndex{ q: 0, r: 0 }, HexIndex{ q: 1, r: 0 }, HexIndex{ q: 0, r: 1 });
+    let molecule = Molecule{
+        atoms: HashMap::from([(a, Atom::Fire), (b, Atom::Fire), (c, Atom::Salt)]),
+        bonds: HashSet::from([
+            Bond{ start: a, end: b, ty: BondType::Triplex{ red: true, black: true, yellow: true } },
+            Bond{ start: a, end: c, ty: BondType::Normal }
+        ])
+    };
+    let theme = RenderTheme::default();
+    let scene = Scene::of_molecule(&molecule, &theme);
+    let lines: Vec<Color> = scene.shapes.iter().filter_map(|shape| match shape{
+        Shape::Line{ color, .. } => Some(*color),
+        _ => None
+    }).collect();
+    assert_eq!(lines.len(), 4);
+    for color in theme.bonds.triplex.into_iter().chain([theme.bonds.color]){
+        assert!(lines.contains(&color));
+    }
+    let svg = render_molecule(&molecule, &RenderOptions::default());
+    assert_eq!(svg.matches("<circle").count(), 3);
+    assert_eq!(svg.matches("<line").count(), 4);
+}
+
+#[test]
+fn coordinates_and_counter(){
+    let mut sim = transfer();
+    let options = RenderOptions{ coordinates: true, counter: true, ..RenderOptions::default() };
+    let svg = render_svg(&sim, &options);
+    assert!(svg.contains(">0,0</text>") && svg.contains(">1,0</text>") && svg.contains(">-1,1</text>"));
+    assert!(svg.contains(">cycle 0</text>"));
+    for _ in 0..3{
+        sim.step().unwrap();
+    }
+    assert!(render_svg(&sim, &options).contains(">cycle 3</text>"));
+    let plain = render_svg(&sim, &RenderOptions::default());
+    assert!(!plain.contains(">0,0</text>") && !plain.contains("cycle"));
+    // molecules and blueprints aren't at any cycle
+    assert!(!render_blueprint(&parse_puzzle(include_bytes!("../corpus/transfer.puzzle")).unwrap(),
+        &parse_solution(include_bytes!("../corpus/transfer.solution")).unwrap(), &options).unwrap().contains("cycle"));
+}
+
+#[cfg(feature = "gif")]
+#[test]
+fn gif_animates_the_run(){
+    use omsim_rs::render::gif::{frames, render_gif};
+    let sim = transfer();
+    let options = RenderOptions{ card: Some(card()), scale: 0.25, ..RenderOptions::default() };
+    // the starting board, then one frame per cycle until the puzzle is completed
+    let frames = frames(&sim, &options);
+    assert_eq!(frames.len(), 46);
+    assert!(frames.iter().enumerate().all(|(cycle, frame)| frame.cycle == Some(cycle as u64)));
+    let mut gif = Vec::new();
+    render_gif(&sim, &options, &mut gif).unwrap();
+    assert!(gif.starts_with(b"GIF89a"));
+}
+
+#[cfg(feature = "gif")]
+#[test]
+fn steady_state_gifs_loop_seamlessly(){
+    use omsim_rs::analysis::throughput::find_steady_state;
+    use omsim_rs::render::gif::frames;
+    let sim = transfer();
+    let steady = find_steady_state(&sim).unwrap();
+    let options = RenderOptions{ steady_state: true, ..RenderOptions::default() };
+    let frames = frames(&sim, &options);
+    assert_eq!(frames.len() as u64, steady.period);
+    assert_eq!(frames[0].cycle, Some(steady.start.cycle));
+    // stepping on from the last frame gives the first one again
+    let mut after = steady.start.clone();
+    for _ in 0..steady.period{
+        after.step().unwrap();
+    }
+    assert_eq!(Scene::of_sim(&after, &options.theme).shapes, frames[0].shapes);
+}
+
+#[cfg(feature = "gif")]
+#[test]
+fn gifs_render_the_same_on_many_threads(){
+    use omsim_rs::render::gif::{frames, render_gif};
+    let sim = transfer();
+    let options = RenderOptions{ scale: 0.25, ..RenderOptions::default() };
+    let mut single = Vec::new();
+    render_gif(&sim, &options, &mut single).unwrap();
+    let mut threaded = Vec::new();
+    render_gif(&sim, &RenderOptions{ threads: 4, ..options.clone() }, &mut threaded).unwrap();
+    assert_eq!(single, threaded);
+
+    // previews skip cycles
+    let preview = RenderOptions{ frame_step: 5, ..options };
+    let frames = frames(&sim, &preview);
+    assert_eq!(frames.iter().map(|frame| frame.cycle.unwrap()).collect::<Vec<_>>(), (0..=45).step_by(5).collect::<Vec<_>>());
+}
@@ -0,0 +1,154 @@
+use std::collections::{HashMap, HashSet};
+use proptest::prelude::*;
+use omsim_rs::data::*;
+use omsim_rs::parse::{parse_puzzle, parse_solution};
+use omsim_rs::selftest::CORPUS;
+
+const PART_NAMES: &[&str] = &[
+    "input", "out-std", "out-rep", "arm1", "arm2", "arm3", "arm6", "piston", "track", "baron",
+    "glyph-marker", "bonder", "bonder-speed", "unbonder", "glyph-calcification", "glyph-projection", "glyph-purification",
+    "glyph-duplication", "glyph-life-and-death", "glyph-unification", "glyph-dispersion", "bonder-prisma", "glyph-disposal", "pipe"
+];
+const INSTRUCTIONS: &[u8] = b" GgRrEePpAaOXC";
+const CHAMBERS: &[&str] = &["Small", "SmallWide", "SmallWider", "Medium", "MediumWide", "Large"];
+
+fn name() -> impl Strategy<Value = String>{
+    "\\PC{0,24}"
+}
+
+/// Hexes as stored in puzzles, with byte coordinates.
+fn small_hex() -> impl Strategy<Value = HexIndex>{
+    (-60..60, -60..60).prop_map(|(q, r)| HexIndex::new(q, r))
+}
+
+fn hex() -> impl Strategy<Value = HexIndex>{
+    (any::<i32>(), any::<i32>()).prop_map(|(q, r)| HexIndex::new(q, r))
+}
+
+fn bond_type() -> impl Strategy<Value = BondType>{
+    prop_oneof![
+        Just(BondType::Normal),
+        (any::<bool>(), any::<bool>(), any::<bool>()).prop_map(|(red, black, yellow)| BondType::Triplex{ red, black, yellow })
+    ]
+}
+
+fn molecule() -> impl Strategy<Value = Molecule>{
+    let atoms = prop::collection::hash_map(small_hex(), (1..=16u8).prop_filter_map("not an atom", Atom::from_id), 0..8);
+    atoms.prop_flat_map(|atoms| {
+        let positions: Vec<HexIndex> = atoms.keys().copied().collect();
+        let bonds = if positions.is_empty(){
+            Just(HashSet::new()).boxed()
+        }else{
+            let end = prop::sample::select(positions.clone());
+            // writing sorts bonds by their ends, so at most one bond per pair of ends keeps the output deterministic
+            prop::collection::vec((prop::sample::select(positions), end, bond_type()), 0..6)
+                .prop_map(|bonds| {
+                    let mut seen = HashSet::new();
+                    bonds.into_iter().filter(|(start, end, _)| seen.insert((*start, *end))).map(|(start, end, ty)| Bond{ start, end, ty }).collect()
+                }).boxed()
+        };
+        (Just(atoms), bonds)
+    }).prop_map(|(atoms, bonds)| Molecule{ atoms: HashMap::from_iter(atoms), bonds })
+}
+
+fn production_info() -> impl Strategy<Value = ProductionInfo>{
+    let chamber = (small_hex(), prop::sample::select(CHAMBERS)).prop_map(|(pos, ty)| Chamber{ pos, ty: ChamberType::from_name(ty).unwrap() });
+    let conduit = (small_hex(), small_hex(), prop::collection::vec(small_hex(), 0..4)).prop_map(|(pos_a, pos_b, hexes)| Conduit{ pos_a, pos_b, hexes });
+    let vial = (small_hex(), any::<bool>(), any::<i32>()).prop_map(|(pos, top, count)| Vial{ pos, top, count });
+    (any::<bool>(), prop::collection::vec(chamber, 0..4), prop::collection::vec(conduit, 0..3), any::<bool>(), any::<bool>(), prop::collection::vec(vial, 0..3))
+        .prop_map(|(isolation, chambers, conduits, shrink_left, shrink_right, vials)| ProductionInfo{ isolation, chambers, conduits, shrink_left, shrink_right, vials })
+}
+
+fn puzzle() -> impl Strategy<Value = Puzzle>{
+    (name(), any::<u64>(), prop::collection::vec(molecule(), 0..3), prop::collection::vec(molecule(), 0..3), any::<i32>(), any::<u64>(), prop::option::of(production_info()))
+        .prop_map(|(name, creator_id, reagents, products, product_multiplier, permissions, production_info)| Puzzle{
+            name, creator_id, reagents, products, product_multiplier, permissions: Permissions::from_bits_retain(permissions), production_info
+        })
+}
+
+fn part() -> impl Strategy<Value = Part>{
+    let ty = prop_oneof![
+        prop::sample::select(PART_NAMES).prop_map(|name| PartType::from_name(name).unwrap()),
+        "[a-z-]{1,12}".prop_filter_map("a known part", |name| PartType::from_name(&name).is_none().then_some(PartType::Unknown(name)))
+    ];
+    let instruction = (prop::sample::select(INSTRUCTIONS).prop_map(|id| Instruction::from_id(id).unwrap()), any::<i32>());
+    // arm numbers are stored one lower, so the extremes would overflow
+    (ty, hex(), any::<i32>(), any::<i32>(), any::<i32>(), -1000..1000, prop::collection::vec(instruction, 0..8),
+        any::<i32>(), prop::collection::vec(hex(), 0..5), prop::collection::vec(hex(), 0..5))
+        .prop_map(|(ty, pos, rotation, arm_length, index, arm_number, instructions, conduit_index, track_hexes, conduit_hexes)| {
+            // only tracks store their hexes, and only conduits their index and hexes
+            let track_hexes = if ty == PartType::Track{ track_hexes }else{ Vec::new() };
+            let (conduit_index, conduit_hexes) = if ty == PartType::Conduit{ (conduit_index, conduit_hexes) }else{ (0, Vec::new()) };
+            Part{ ty, pos, rotation, arm_number, arm_length, index, conduit_index, track_hexes, conduit_hexes, instructions }
+        })
+}
+
+fn metrics() -> impl Strategy<Value = Metrics>{
+    (any::<i32>(), any::<i32>(), any::<i32>(), any::<i32>()).prop_map(|(cycles, cost, area, instructions)| Metrics{ cycles, cost, area, instructions })
+}
+
+fn solution() -> impl Strategy<Value = Solution>{
+    (name(), name(), prop::option::of(metrics()), prop::collection::vec(part(), 0..6))
+        .prop_map(|(name, puzzle_name, metrics, parts)| Solution{ name, puzzle_name, metrics, parts })
+}
+
+proptest!{
+    #[test]
+    fn puzzles_round_trip(puzzle in puzzle()){
+        let bytes = puzzle.unparse();
+        prop_assert_eq!(parse_puzzle(&bytes).unwrap(), puzzle);
+    }
+
+    #[test]
+    fn solutions_round_trip(solution in solution()){
+        let bytes = solution.unparse();
+        prop_assert_eq!(parse_solution(&bytes).unwrap(), solution);
+    }
+}
+
+/// The corpus is synthetic, written by this crate rather than saved by the game, so this only checks that the writer
+/// still produces the files it wrote before, not that it matches the game byte for byte. Files saved by the game should
+/// be added to the corpus (see [`CORPUS`]) to check that too.
+#[test]
+fn synthetic_corpus_files_are_rewritten_unchanged(){
+    for case in CORPUS{
+        assert_eq!(parse_puzzle(case.puzzle).unwrap().unparse(), case.puzzle, "{} puzzle", case.name);
+        assert_eq!(parse_solution(case.solution).unwrap().unparse(), case.solution, "{} solution", case.name);
+    }
+}
+
+/// A production puzzle with every purely visual field set, written out field by field in the game's puzzle format.
+fn drawn_production_puzzle() -> Vec<u8>{
+    let mut bytes = Vec::new();
+    bytes.extend(3i32.to_le_bytes());
+    bytes.extend([4, b'v', b'i', b'a', b'l']);
+    // creator and permissions
+    bytes.extend(0u64.to_le_bytes());
+    bytes.extend(0u64.to_le_bytes());
+    // no reagents or products, and a product multiplier of 1
+    bytes.extend(0i32.to_le_bytes());
+    bytes.extend(0i32.to_le_bytes());
+    bytes.extend(1i32.to_le_bytes());
+    // production info, shrunk on the left and right, without isolation, chambers or conduits
+    bytes.extend([1, 1, 1, 0]);
+    bytes.extend(0i32.to_le_bytes());
+    bytes.extend(0i32.to_le_bytes());
+    // two vials, at (2,-1) with a count of 3 and at the top at (-3,4) with a count of 1
+    bytes.extend(2i32.to_le_bytes());
+    bytes.extend([2, 0xFF, 0, 3, 0, 0, 0]);
+    bytes.extend([0xFD, 4, 1, 1, 0, 0, 0]);
+    bytes
+}
+
+#[test]
+fn production_visuals_round_trip(){
+    let bytes = drawn_production_puzzle();
+    let puzzle = parse_puzzle(&bytes).unwrap();
+    let info = puzzle.production_info.as_ref().unwrap();
+    assert!(info.shrink_left && info.shrink_right && !info.isolation);
+    assert_eq!(info.vials, [
+        Vial{ pos: HexIndex::new(2, -1), top: false, count: 3 },
+        Vial{ pos: HexIndex::new(-3, 4), top: true, count: 1 }
+    ]);
+    assert_eq!(puzzle.unparse(), bytes);
+}
@@ -0,0 +1,48 @@
+use std::collections::{HashMap, HashSet};
+use omsim_rs::data::{Atom, HexIndex, Instruction, Molecule, Part, PartType};
+use omsim_rs::sim::Sim;
+use omsim_rs::sim::config::SimConfig;
+use omsim_rs::sim::error::SimError;
+
+fn part(ty: PartType, pos: HexIndex, instructions: &[Instruction]) -> Part{
+    Part{
+        ty, pos, rotation: 0, arm_number: 0, arm_length: 1, index: 0, conduit_index: 0,
+        track_hexes: Vec::new(), conduit_hexes: Vec::new(),
+        instructions: instructions.iter().enumerate().map(|(idx, instr)| (*instr, idx as i32)).collect()
+    }
+}
+
+fn single(atom: Atom, pos: HexIndex) -> Molecule{
+    Molecule{ atoms: HashMap::from([(pos, atom)]), bonds: HashSet::new() }
+}
+
+#[test]
+fn sandboxes_need_no_puzzle(){
+    let bonder = part(PartType::Bonding, HexIndex::new(0, 0), &[]);
+    let atoms = [single(Atom::Salt, HexIndex::new(0, 0)), single(Atom::Fire, HexIndex::new(1, 0))];
+    let mut sim = Sim::sandbox(&[bonder], &atoms, SimConfig::default()).unwrap();
+    assert_eq!(sim.molecules.len(), 2);
+    sim.step().unwrap();
+    assert_eq!(sim.molecules.len(), 1);
+    assert_eq!(sim.molecules[0].layout.bond_count(), 1);
+    // with no products there's nothing to complete
+    assert!(matches!(sim.run(), Err(SimError::NeverCompletes(_))));
+}
+
+#[test]
+fn sandbox_arms_run_their_tapes(){
+    // an arm at (1, 0) pointing at the atom on (2, 0), rotating it anticlockwise to (1, 1)
+    let arm = part(PartType::Arm, HexIndex::new(1, 0), &[Instruction::Grab, Instruction::RotateAnticlockwise, Instruction::Drop]);
+    let mut sim = Sim::sandbox(&[arm], &[single(Atom::Water, HexIndex::new(2, 0))], SimConfig::default()).unwrap();
+    sim.run_until(3).unwrap();
+    assert_eq!(sim.lookup_atom(HexIndex::new(1, 1), |r| r.atom_ty), Some(Atom::Water));
+    assert!(sim.area.contains(&HexIndex::new(2, 0)));
+}
+
+#[test]
+fn sandboxes_reject_what_needs_a_puzzle(){
+    let input = part(PartType::Input, HexIndex::new(0, 0), &[]);
+    assert!(Sim::sandbox(&[input], &[], SimConfig::default()).is_err());
+    let atoms = [single(Atom::Salt, HexIndex::new(0, 0)), single(Atom::Fire, HexIndex::new(0, 0))];
+    assert_eq!(Sim::sandbox(&[], &atoms, SimConfig::default()).err(), Some("sandbox contains overlapping molecules"));
+}
@@ -0,0 +1,69 @@
+use omsim_rs::data::{Atom, HexIndex, Instruction, PartType};
+use omsim_rs::scenario::{Expectation, Scenario, ScenarioError};
+
+#[test]
+fn scenarios_run_from_text(){
+    let scenario = Scenario::parse("
+        # an arm carries water a sixth of a turn anticlockwise
+        atom Wa 2,0
+        part arm1 1,0 tape=Gr.g
+        expect 4 atom Wa 1,1
+        expect 4 empty 2,0
+        expect 0 molecules 1
+    ").unwrap();
+    assert_eq!(scenario.parts[0].instructions.len(), 4);
+    scenario.run().unwrap();
+}
+
+#[test]
+fn scenarios_run_from_builders(){
+    let sim = Scenario::new()
+        .atom(Atom::Water, HexIndex::new(0, 0))
+        .atom(Atom::Fire, HexIndex::new(1, 0))
+        .atom(Atom::Salt, HexIndex::new(2, 0))
+        .bond(HexIndex::new(1, 0), HexIndex::new(2, 0))
+        .glyph(PartType::Unbonding, HexIndex::new(1, 0), 0)
+        .glyph(PartType::Bonding, HexIndex::new(-1, 0), 0)
+        .expect_molecules(0, 2)
+        .expect_bond(1, HexIndex::new(-1, 0), HexIndex::new(0, 0))
+        .run();
+    // the bonder's other hex has no atom, so nothing bonds
+    assert!(matches!(sim, Err(ScenarioError::Unmet{ cycle: 1, expectation: Expectation::Bonded(..), .. })), "{sim:?}");
+
+    let sim = Scenario::new()
+        .atom(Atom::Fire, HexIndex::new(1, 0))
+        .atom(Atom::Salt, HexIndex::new(2, 0))
+        .bond(HexIndex::new(1, 0), HexIndex::new(2, 0))
+        .glyph(PartType::Unbonding, HexIndex::new(1, 0), 0)
+        .expect_molecules(0, 1)
+        .expect_molecules(1, 2)
+        .run()
+        .unwrap();
+    assert_eq!(sim.cycle, 1);
+}
+
+#[test]
+fn scenarios_can_expect_failures(){
+    let collision = Scenario::new()
+        .atom(Atom::Water, HexIndex::new(2, 0))
+        .atom(Atom::Fire, HexIndex::new(1, 1))
+        .arm(PartType::Arm, HexIndex::new(1, 0), 0, 1, &[Instruction::Grab, Instruction::RotateAnticlockwise]);
+    collision.clone().expect_failure(2).run().unwrap();
+    let err = collision.expect_atom(2, HexIndex::new(1, 1), Atom::Water).run().unwrap_err();
+    assert!(matches!(err, ScenarioError::Sim(_)), "{err}");
+}
+
+#[test]
+fn unmet_expectations_show_the_board(){
+    let err = Scenario::parse("atom Sa 0,0\nexpect 1 atom Fi 0,0").unwrap().run().unwrap_err();
+    assert_eq!(err.to_string(), "expected Fi at (0, 0) after 1 cycles, but the board was:\nSa");
+}
+
+#[test]
+fn bad_scenarios_say_which_line(){
+    assert_eq!(Scenario::parse("atom Sa 0,0\natom Xx 1,0"), Err(ScenarioError::Parse{ line: 2, reason: "unknown atom symbol" }));
+    assert_eq!(Scenario::parse("part bonder 0,0 tape=G"), Err(ScenarioError::Parse{ line: 1, reason: "only arms have tapes" }));
+    assert_eq!(Scenario::parse("expect soon fails"), Err(ScenarioError::Parse{ line: 1, reason: "cycle should be a number" }));
+    let err = Scenario::parse("atom Sa 0,0\natom Fi 0,0").unwrap().run().unwrap_err();
+    assert_eq!(err, ScenarioError::Setup("two atoms are placed on the same hex"));
+}
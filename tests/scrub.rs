@@ -0,0 +1,76 @@
+use omsim_rs::data::HexIndex;
+use omsim_rs::parse::{parse_puzzle, parse_solution};
+use omsim_rs::sim::collision::Movement;
+use omsim_rs::sim::{Sim, SimPartType};
+
+fn transfer() -> Sim{
+    let puzzle = parse_puzzle(include_bytes!("../corpus/transfer.puzzle")).unwrap();
+    let solution = parse_solution(include_bytes!("../corpus/transfer.solution")).unwrap();
+    Sim::create(&puzzle, &solution).unwrap()
+}
+
+type Board = (Vec<(HexIndex, u8, i32, bool)>, Vec<HexIndex>, Vec<u64>);
+
+/// Everything a scrubber would show: arm positions and rotations, atom positions, and output counters.
+fn board(sim: &Sim) -> Board{
+    let arms = sim.arms().map(|(part, arm)| (part.pos, part.rotation.turns(), arm.length, arm.grabbing)).collect();
+    let mut atoms: Vec<HexIndex> = sim.molecules().iter().flat_map(|m| m.positions()).collect();
+    atoms.sort_by_key(|h| (h.q, h.r));
+    let outputs = sim.outputs().map(|(_, output)| output.consumed).collect();
+    (arms, atoms, outputs)
+}
+
+#[test]
+fn run_until_matches_stepping(){
+    let mut stepped = transfer();
+    for _ in 0..20{
+        stepped.step().unwrap();
+    }
+    let mut seeked = transfer();
+    seeked.run_until(20).unwrap();
+    assert_eq!(seeked.cycle, 20);
+    assert_eq!(board(&seeked), board(&stepped));
+}
+
+#[test]
+fn snapshots_resume_where_they_left_off(){
+    let mut sim = transfer();
+    sim.run_until(10).unwrap();
+    let snapshot = sim.clone();
+    sim.run_until(30).unwrap();
+    let mut resumed = snapshot.clone();
+    resumed.run_until(30).unwrap();
+    assert_eq!(board(&resumed), board(&sim));
+    // seeking backwards is a no-op, so go back to the snapshot instead
+    sim.run_until(10).unwrap();
+    assert_eq!(sim.cycle, 30);
+}
+
+#[test]
+fn run_until_continues_past_completion(){
+    let mut sim = transfer();
+    sim.run_until(60).unwrap();
+    assert_eq!(sim.completed, Some(45));
+    assert_eq!(sim.cycle, 60);
+    assert!(sim.outputs().map(|(_, output)| output.consumed).sum::<u64>() >= 6);
+}
+
+#[test]
+fn arm_path_ends_where_the_grippers_are(){
+    let sim = transfer();
+    let (part, _) = sim.parts.iter().enumerate().find(|(_, p)| matches!(p.ty, SimPartType::Arm(_))).unwrap();
+    let path = sim.arm_path(part, 5..25).unwrap();
+    assert_eq!(path.steps.len(), 20);
+    assert_eq!(path.steps[0].cycle, 5);
+    for (cycle, positions) in (5..=25).zip(&path.gripper_positions()[0]){
+        let mut at = sim.clone();
+        at.run_until(cycle).unwrap();
+        let (part, arm) = at.arms().next().unwrap();
+        assert_eq!(arm.grippers(part.pos, part.rotation)[0], *positions, "cycle {cycle}");
+    }
+    // consecutive steps join up
+    for pair in path.steps.windows(2){
+        assert_eq!(pair[0].grippers[0].end(), pair[1].grippers[0].start());
+    }
+    assert!(path.steps.iter().any(|step| matches!(step.grippers[0], Movement::Rotate{ .. })));
+}
@@ -0,0 +1,27 @@
+use omsim_rs::data::PartType;
+use omsim_rs::parse::parse_puzzle;
+use omsim_rs::solver::solve;
+use omsim_rs::verify::verify;
+
+#[test]
+fn trivial_puzzles_are_solved(){
+    for (puzzle, glyph) in [(&include_bytes!("../corpus/transfer.puzzle")[..], None), (include_bytes!("../corpus/calcify.puzzle"), Some(PartType::Calcification))]{
+        let puzzle = parse_puzzle(puzzle).unwrap();
+        let solution = solve(&puzzle, 3).unwrap().unwrap();
+        assert_eq!(verify(&puzzle, &solution).result, Ok(solution.metrics.unwrap()));
+        assert_eq!(solution.parts.iter().find(|part| !matches!(part.ty, PartType::Input | PartType::Output | PartType::Arm)).map(|part| part.ty.clone()), glyph);
+    }
+}
+
+#[test]
+fn short_tapes_may_not_be_enough(){
+    let puzzle = parse_puzzle(include_bytes!("../corpus/transfer.puzzle")).unwrap();
+    assert_eq!(solve(&puzzle, 2), Ok(None));
+}
+
+#[test]
+fn only_trivial_puzzles_are_tried(){
+    let mut puzzle = parse_puzzle(include_bytes!("../corpus/transfer.puzzle")).unwrap();
+    puzzle.products.push(puzzle.products[0].clone());
+    assert!(solve(&puzzle, 3).is_err());
+}
@@ -0,0 +1,123 @@
+use omsim_rs::data::{HexIndex, Instruction, Part, PartType, Solution};
+use omsim_rs::parse::{parse_puzzle, parse_solution};
+use omsim_rs::verify::verify;
+
+fn arm(instructions: Vec<(Instruction, i32)>) -> Part{
+    Part{
+        ty: PartType::Arm, pos: HexIndex::default(), rotation: 0, arm_number: 1, arm_length: 1, index: 0,
+        conduit_index: 0, track_hexes: Vec::new(), conduit_hexes: Vec::new(), instructions
+    }
+}
+
+fn solution(parts: Vec<Part>) -> Solution{
+    Solution{ name: "tapes".into(), puzzle_name: "tapes".into(), metrics: None, parts }
+}
+
+#[test]
+fn gaps_are_filled_with_blanks(){
+    let part = arm(vec![(Instruction::Grab, 1), (Instruction::Drop, 4)]);
+    assert_eq!(part.tape(0), vec![Instruction::Blank, Instruction::Grab, Instruction::Blank, Instruction::Blank, Instruction::Drop]);
+    assert_eq!(solution(vec![part]).tape_start(), 0);
+}
+
+#[test]
+fn negative_indices_shift_every_arm(){
+    let early = arm(vec![(Instruction::Grab, -2), (Instruction::Drop, 0)]);
+    let late = arm(vec![(Instruction::RotateClockwise, 1)]);
+    let sol = solution(vec![early.clone(), late.clone()]);
+    assert_eq!(sol.tape_start(), -2);
+    assert_eq!(early.tape(-2), vec![Instruction::Grab, Instruction::Blank, Instruction::Drop]);
+    assert_eq!(late.tape(-2), vec![Instruction::Blank, Instruction::Blank, Instruction::Blank, Instruction::RotateClockwise]);
+}
+
+fn shifted_cycles(by: i32) -> i32{
+    let puzzle = parse_puzzle(include_bytes!("../corpus/transfer.puzzle")).unwrap();
+    let mut sol = parse_solution(include_bytes!("../corpus/transfer.solution")).unwrap();
+    for part in &mut sol.parts{
+        for (_, idx) in &mut part.instructions{
+            *idx += by;
+        }
+    }
+    verify(&puzzle, &sol).result.unwrap().cycles
+}
+
+#[test]
+fn start_cycle_matches_the_game(){
+    // shifting everything before the start of the timeline changes nothing
+    assert_eq!(shifted_cycles(-3), shifted_cycles(0));
+    // but leading blanks are part of the loop, so they delay each of the 6 products
+    assert_eq!(shifted_cycles(0), 45);
+    assert_eq!(shifted_cycles(2), 45 + 6 * 2);
+}
+
+fn tape(s: &str) -> Vec<Instruction>{
+    s.bytes().map(|b| Instruction::from_id(b).unwrap()).collect()
+}
+
+#[test]
+fn repeats_copy_everything_since_the_last_reset(){
+    assert_eq!(Instruction::expand_repeats(&tape("  GRgC")).unwrap(), tape("  GRgGRg"));
+    // repeats include earlier repeats' expansions
+    assert_eq!(Instruction::expand_repeats(&tape("Gg C  C")).unwrap(), tape("Gg Gg Gg Gg "));
+    assert_eq!(Instruction::expand_repeats(&tape("GXRC")).unwrap(), tape("GXRR"));
+    assert!(Instruction::expand_repeats(&tape("GRCg")).is_err());
+}
+
+#[test]
+fn loop_periods(){
+    let sol = solution(vec![
+        arm(vec![(Instruction::Grab, 0), (Instruction::Drop, 1), (Instruction::Grab, 2), (Instruction::Drop, 3), (Instruction::Grab, 4), (Instruction::Drop, 5)]),
+        arm(vec![(Instruction::RotateClockwise, 0), (Instruction::RotateClockwise, 3)]),
+        arm(vec![(Instruction::Grab, 1)])
+    ]);
+    let stats = sol.tape_stats().unwrap();
+    assert_eq!(stats.tape_length, 6);
+    assert_eq!(stats.arms.iter().map(|a| a.period).collect::<Vec<_>>(), vec![2, 3, 6]);
+    assert_eq!(stats.period, 6);
+
+    let sol = solution(vec![sol.parts[0].clone(), sol.parts[1].clone()]);
+    assert_eq!(sol.tape_stats().unwrap().period, 6);
+    let sol = solution(vec![sol.parts[0].clone()]);
+    assert_eq!(sol.tape_stats().unwrap().period, 2);
+    assert_eq!(solution(Vec::new()).tape_stats().unwrap().period, 0);
+}
+
+#[test]
+fn resets_undo_the_net_movement(){
+    use omsim_rs::data::ArmState;
+    assert_eq!(Instruction::expand_reset(ArmState::default()), tape(" "));
+    assert_eq!(Instruction::expand_reset(ArmState{ grabbing: true, rotation: -3, extension: 0, track_steps: 0 }), tape("grrr"));
+    // a full turn is unwound rather than skipped
+    assert_eq!(Instruction::expand_reset(ArmState{ grabbing: false, rotation: 6, extension: -1, track_steps: 2 }), tape("ERRRRRRaa"));
+}
+
+fn with_tape(tape: &str) -> i32{
+    let puzzle = parse_puzzle(include_bytes!("../corpus/transfer.puzzle")).unwrap();
+    let mut sol = parse_solution(include_bytes!("../corpus/transfer.solution")).unwrap();
+    let arm = sol.parts.iter_mut().find(|p| p.ty == PartType::Arm).unwrap();
+    arm.instructions = tape.bytes().enumerate()
+        .filter(|(_, b)| *b != b' ')
+        .map(|(idx, b)| (Instruction::from_id(b).unwrap(), idx as i32))
+        .collect();
+    verify(&puzzle, &sol).result.unwrap().cycles
+}
+
+#[test]
+fn resets_run_in_the_sim(){
+    // dropping then resetting, and resetting while holding, both unwind the three rotations
+    assert_eq!(with_tape("GRRRgX"), with_tape("GRRRgrrr"));
+    assert_eq!(with_tape("GRRRX"), with_tape("GRRRgrrr"));
+    assert_eq!(with_tape("GRRRgrrr"), 45);
+}
+
+#[test]
+fn instructions_are_counted_as_written(){
+    use Instruction::*;
+    let part = arm(vec![(Grab, 0), (Blank, 1), (RotateClockwise, 2), (Repeat, 3), (Reset, 4), (PeriodOverride, 5)]);
+    assert_eq!(part.instruction_count(), 4);
+    // a later instruction at the same index replaces the earlier one
+    assert_eq!(arm(vec![(Grab, 0), (Drop, 0), (Blank, 1), (Extend, 1)]).instruction_count(), 2);
+    assert_eq!(arm(vec![(Grab, 0), (Blank, 0)]).instruction_count(), 0);
+    let glyph = Part{ ty: PartType::Bonding, ..arm(vec![(Grab, 0)]) };
+    assert_eq!(glyph.instruction_count(), 0);
+}
@@ -0,0 +1,24 @@
+use omsim_rs::parse::{parse_puzzle, parse_solution};
+use omsim_rs::sim::Sim;
+
+fn sim() -> Sim{
+    let puzzle = parse_puzzle(include_bytes!("../corpus/transfer.puzzle")).unwrap();
+    let solution = parse_solution(include_bytes!("../corpus/transfer.solution")).unwrap();
+    Sim::create(&puzzle, &solution).unwrap()
+}
+
+#[test]
+fn boards_are_drawn_as_text(){
+    let mut sim = sim();
+    // the input's atom, the arm, and the output
+    assert_eq!(sim.to_text(), "Sa  ()  ::");
+    sim.run_until(3).unwrap();
+    // the arm has carried an atom up a row, and a new one has spawned
+    assert_eq!(sim.to_text(), "   .  Sa\nSa  ()  ::");
+}
+
+#[test]
+fn empty_boards_draw_nothing(){
+    let sim = Sim::new(Vec::new(), Vec::new(), Default::default());
+    assert_eq!(sim.to_text(), "");
+}
@@ -0,0 +1,100 @@
+use omsim_rs::analysis::throughput::{check_area_bound, find_steady_state, find_steady_state_parallel, AreaBound};
+use omsim_rs::data::{HexIndex, Instruction, Part, PartType};
+use omsim_rs::parse::{parse_puzzle, parse_solution};
+use omsim_rs::sim::Sim;
+use omsim_rs::sim::config::SimConfig;
+use omsim_rs::sim::error::SimError;
+
+fn sim(puzzle: &[u8], solution: &[u8], config: SimConfig) -> Sim{
+    Sim::create_with_config(&parse_puzzle(puzzle).unwrap(), &parse_solution(solution).unwrap(), config).unwrap()
+}
+
+#[test]
+fn steady_states_are_found(){
+    let sim = sim(include_bytes!("../corpus/bond.puzzle"), include_bytes!("../corpus/bond.solution"), SimConfig::default());
+    let steady = find_steady_state(&sim).unwrap();
+    // the board after the first cycle is the first one the loop comes back to
+    assert_eq!((steady.transient, steady.period, steady.products.clone()), (1, 8, vec![1]));
+    assert_eq!(steady.start.cycle, 1);
+    assert_eq!(steady.cycles_per_product(0), Some(8.0));
+    assert_eq!(steady.cycles_per_product(1), None);
+}
+
+#[test]
+fn parallel_search_agrees(){
+    for (puzzle, solution) in [
+        (&include_bytes!("../corpus/transfer.puzzle")[..], &include_bytes!("../corpus/transfer.solution")[..]),
+        (&include_bytes!("../corpus/bond.puzzle")[..], &include_bytes!("../corpus/bond.solution")[..]),
+        (&include_bytes!("../corpus/calcify.puzzle")[..], &include_bytes!("../corpus/calcify.solution")[..])
+    ]{
+        let sim = sim(puzzle, solution, SimConfig::default());
+        let sequential = find_steady_state(&sim).unwrap();
+        for threads in [1, 4]{
+            let parallel = find_steady_state_parallel(&sim, threads).unwrap();
+            assert_eq!((parallel.transient, parallel.period, parallel.products), (sequential.transient, sequential.period, sequential.products.clone()));
+        }
+    }
+}
+
+#[test]
+fn idle_solutions_repeat_immediately(){
+    let sim = sim(include_bytes!("../corpus/transfer.puzzle"), include_bytes!("../corpus/idle.solution"), SimConfig::default());
+    let steady = find_steady_state(&sim).unwrap();
+    assert_eq!((steady.transient, steady.period, steady.products), (0, 1, vec![0]));
+}
+
+#[test]
+fn searches_stop_at_the_cycle_limit(){
+    let config = SimConfig{ max_cycles: 5, ..SimConfig::default() };
+    let sim = sim(include_bytes!("../corpus/transfer.puzzle"), include_bytes!("../corpus/transfer.solution"), config);
+    assert!(matches!(find_steady_state(&sim), Err(SimError::CycleLimit{ cycles: 5 })));
+}
+
+#[test]
+fn repeating_boards_have_bounded_area(){
+    let sim = sim(include_bytes!("../corpus/bond.puzzle"), include_bytes!("../corpus/bond.solution"), SimConfig::default());
+    let AreaBound::Bounded(steady) = check_area_bound(&sim).unwrap() else { panic!("bond should be bounded") };
+    assert_eq!((steady.transient, steady.period), (1, 8));
+}
+
+#[test]
+fn growing_waste_is_divergent(){
+    // a piston pushes each reagent onto a bonder, which bonds it to the last one, so the chain grows forever
+    let puzzle = parse_puzzle(include_bytes!("../corpus/transfer.puzzle")).unwrap();
+    let mut solution = parse_solution(include_bytes!("../corpus/transfer.solution")).unwrap();
+    let arm = solution.parts.pop().unwrap();
+    solution.parts[1].pos = HexIndex::new(10, 10);
+    solution.parts.push(Part{
+        ty: PartType::PistonArm,
+        pos: HexIndex::new(-1, 0),
+        rotation: 0,
+        instructions: vec![(Instruction::Grab, 0), (Instruction::Extend, 1), (Instruction::Drop, 2), (Instruction::Retract, 3)],
+        ..arm.clone()
+    });
+    solution.parts.push(Part{ ty: PartType::Bonding, pos: HexIndex::new(0, 0), rotation: 0, instructions: Vec::new(), ..arm });
+    let config = SimConfig{ max_cycles: 200, ..SimConfig::default() };
+    let sim = Sim::create_with_config(&puzzle, &solution, config).unwrap();
+    let AreaBound::Divergent{ molecule, drift, .. } = check_area_bound(&sim).unwrap() else { panic!("the chain should diverge") };
+    assert!(molecule.atoms.len() > 40, "{}", molecule.atoms.len());
+    // the chain changes shape as it grows, so it isn't the same molecule moved
+    assert_eq!(drift, None);
+}
+
+#[test]
+fn progress_is_reported_every_interval(){
+    use std::sync::{Arc, Mutex};
+    use omsim_rs::sim::config::{Progress, ProgressCallback};
+    let reports = Arc::new(Mutex::new(Vec::new()));
+    let progress = ProgressCallback::new(10, {
+        let reports = Arc::clone(&reports);
+        move |progress: &Progress| reports.lock().unwrap().push(progress.clone())
+    });
+    let mut sim = sim(include_bytes!("../corpus/transfer.puzzle"), include_bytes!("../corpus/transfer.solution"), SimConfig{ progress: Some(progress), ..SimConfig::default() });
+    while sim.completed.is_none(){
+        sim.step().unwrap();
+    }
+    let reports = reports.lock().unwrap();
+    assert_eq!(reports.iter().map(|progress| progress.cycle).collect::<Vec<_>>(), vec![10, 20, 30, 40]);
+    assert!(reports.iter().all(|progress| progress.area > 0 && progress.products.len() == 1));
+    assert!(reports.windows(2).all(|pair| pair[0].products[0] <= pair[1].products[0]));
+}
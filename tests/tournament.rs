@@ -0,0 +1,48 @@
+use omsim_rs::analysis::tournament::Tournament;
+use omsim_rs::data::Metrics;
+use omsim_rs::parse::{parse_puzzle, parse_solution};
+
+fn tournament() -> Tournament{
+    let puzzle = parse_puzzle(include_bytes!("../corpus/transfer.puzzle")).unwrap();
+    let transfer = parse_solution(include_bytes!("../corpus/transfer.solution")).unwrap();
+    let collision = parse_solution(include_bytes!("../corpus/collision.solution")).unwrap();
+    Tournament::run(&puzzle, [
+        ("first".to_string(), transfer.clone()),
+        ("crash | burn".to_string(), collision),
+        ("second".to_string(), transfer)
+    ])
+}
+
+#[test]
+fn ties_share_a_place(){
+    let tournament = tournament();
+    assert_eq!(tournament.rankings.len(), 16);
+    for ranking in &tournament.rankings{
+        // the invalid entry isn't ranked
+        assert_eq!(ranking.places, vec![(1, 0), (1, 2)], "{}", ranking.category);
+    }
+}
+
+#[test]
+fn better_scores_rank_higher(){
+    let mut tournament = tournament();
+    let Ok(metrics) = &mut tournament.entries[2].verdict.result else { panic!() };
+    *metrics = Metrics{ cycles: 40, ..*metrics };
+    let tournament = Tournament::new(tournament.entries);
+    let ranking = |name| tournament.rankings.iter().find(|ranking| ranking.category == name).unwrap().places.clone();
+    assert_eq!(ranking("gc"), vec![(1, 2), (2, 0)]);
+    assert_eq!(ranking("ia"), vec![(1, 0), (1, 2)]);
+    assert_eq!(ranking("ic"), vec![(1, 2), (2, 0)]);
+}
+
+#[test]
+fn reports_list_every_entry(){
+    let tournament = tournament();
+    let markdown = tournament.to_markdown();
+    assert!(markdown.contains("| first | 20 | 45 | 7 | 8 | 8 | 7 |\n"), "{markdown}");
+    assert!(markdown.contains("| crash \\| burn | invalid: collision at (0, 0) in cycle 1 |"), "{markdown}");
+    assert!(markdown.contains("| gc | 1. first, 1. second |\n"), "{markdown}");
+    let json = tournament.to_json();
+    assert!(json.starts_with(r#"{"entries":[{"name":"first","verdict":{"schema_version":1,"valid":true,"#), "{json}");
+    assert!(json.ends_with(r#""x":[{"place":1,"entry":0},{"place":1,"entry":2}]}}"#), "{json}");
+}
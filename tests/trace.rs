@@ -0,0 +1,82 @@
+use omsim_rs::data::{Atom, HexIndex, Instruction};
+use omsim_rs::parse::{parse_puzzle, parse_solution, parse_trace};
+use omsim_rs::sim::Sim;
+use omsim_rs::trace::Trace;
+
+fn record(solution: &[u8]) -> Trace{
+    let puzzle = parse_puzzle(include_bytes!("../corpus/transfer.puzzle")).unwrap();
+    let solution = parse_solution(solution).unwrap();
+    Trace::record(&mut Sim::create(&puzzle, &solution).unwrap())
+}
+
+#[test]
+fn traces_round_trip(){
+    let trace = record(include_bytes!("../corpus/transfer.solution"));
+    assert_eq!(trace.completed, Some(45));
+    assert_eq!(trace.error, None);
+    assert_eq!(trace.frames.len(), 46);
+    let bytes = trace.unparse();
+    assert_eq!(parse_trace(&bytes).unwrap(), trace);
+    // a handful of bytes per frame for one arm and a few atoms
+    assert!(bytes.len() < 46 * 40, "{} bytes", bytes.len());
+}
+
+#[test]
+fn frames_replay_the_board(){
+    let puzzle = parse_puzzle(include_bytes!("../corpus/transfer.puzzle")).unwrap();
+    let solution = parse_solution(include_bytes!("../corpus/transfer.solution")).unwrap();
+    let mut sim = Sim::create(&puzzle, &solution).unwrap();
+    let trace = Trace::record(&mut sim.clone());
+    for cycle in [0, 1, 2, 17, 45]{
+        sim.run_until(cycle).unwrap();
+        let frame = trace.frame(cycle).unwrap();
+        assert_eq!(frame.cycle, cycle);
+        for molecule in &sim.molecules{
+            for pos in molecule.positions(){
+                assert_eq!(frame.atom_at(pos), Some(molecule.atom_at(pos)));
+            }
+        }
+        let (part, arm) = sim.arms().next().unwrap();
+        assert_eq!((frame.arms[0].pos, frame.arms[0].rotation, frame.arms[0].grabbing), (part.pos, part.rotation, arm.grabbing));
+    }
+    assert_eq!(trace.frame(46), None);
+    assert!(trace.frames[0].atom_at(HexIndex::new(0, 0)).is_some_and(|atom| atom != Atom::Repeat));
+}
+
+#[test]
+fn failed_runs_keep_their_error(){
+    let trace = record(include_bytes!("../corpus/collision.solution"));
+    assert_eq!(trace.completed, None);
+    assert!(trace.error.as_ref().is_some_and(|error| error.starts_with("collision")));
+    assert_eq!(parse_trace(&trace.unparse()).unwrap(), trace);
+}
+
+#[test]
+fn invalid_traces_are_rejected(){
+    let bytes = record(include_bytes!("../corpus/transfer.solution")).unparse();
+    assert_eq!(parse_trace(&bytes[1..]), Err("not an omsim-rs trace"));
+    let mut extended = bytes.clone();
+    extended.push(0);
+    assert_eq!(parse_trace(&extended), Err("trailing data after trace"));
+}
+
+#[test]
+fn divergence_finds_the_first_difference(){
+    let trace = record(include_bytes!("../corpus/transfer.solution"));
+    assert_eq!(trace.divergence(&trace.clone()), None);
+
+    // the arm turns the other way on its second instruction
+    let puzzle = parse_puzzle(include_bytes!("../corpus/transfer.puzzle")).unwrap();
+    let mut solution = parse_solution(include_bytes!("../corpus/transfer.solution")).unwrap();
+    let arm = solution.parts.iter_mut().find(|part| !part.instructions.is_empty()).unwrap();
+    arm.instructions[1].0 = Instruction::RotateAnticlockwise;
+    let other = Trace::record(&mut Sim::create(&puzzle, &solution).unwrap());
+    let divergence = trace.divergence(&other).unwrap();
+    assert_eq!((divergence.cycle, divergence.reason), (2, "arms differ"));
+    assert_ne!(divergence.left.unwrap().to_text(), divergence.right.unwrap().to_text());
+
+    let mut short = trace.clone();
+    short.frames.truncate(10);
+    let divergence = trace.divergence(&short).unwrap();
+    assert_eq!((divergence.cycle, divergence.reason, divergence.right), (10, "one trace stops earlier", None));
+}
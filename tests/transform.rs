@@ -0,0 +1,76 @@
+use omsim_rs::data::{Chamber, ChamberType, HexIndex, HexRotation, Instruction, Part, PartType, ProductionInfo, Solution};
+use omsim_rs::parse::{parse_puzzle, parse_solution};
+use omsim_rs::selftest::{Expected, CORPUS};
+use omsim_rs::verify::verify;
+
+fn part(ty: PartType, pos: HexIndex, rotation: i32) -> Part{
+    Part{
+        ty, pos, rotation, arm_number: 1, arm_length: 1, index: 0,
+        conduit_index: 0, track_hexes: Vec::new(), conduit_hexes: Vec::new(), instructions: Vec::new()
+    }
+}
+
+fn solution(parts: Vec<Part>) -> Solution{
+    Solution{ name: "transform".into(), puzzle_name: "transform".into(), metrics: None, parts }
+}
+
+#[test]
+fn rotated_solutions_solve_the_same(){
+    for case in CORPUS.iter().filter(|case| matches!(case.expected, Expected::Valid(_))){
+        let puzzle = parse_puzzle(case.puzzle).unwrap();
+        let original = parse_solution(case.solution).unwrap();
+        let expected = verify(&puzzle, &original).result;
+        for by in HexRotation::ALL{
+            assert_eq!(verify(&puzzle, &original.rotated(by)).result, expected, "{} rotated by {by:?}", case.name);
+        }
+    }
+}
+
+#[test]
+fn rotations_are_verified_against_the_puzzle(){
+    let case = CORPUS.iter().find(|case| case.name == "transfer").unwrap();
+    let mut puzzle = parse_puzzle(case.puzzle).unwrap();
+    let original = parse_solution(case.solution).unwrap();
+    assert_eq!(original.rotated_verified(&puzzle, HexRotation::R60), Ok(original.rotated(HexRotation::R60)));
+    // the chamber doesn't turn with the solution
+    puzzle.production_info = Some(ProductionInfo{ chambers: vec![Chamber{ pos: HexIndex::new(0, -1), ty: ChamberType::Small }], ..ProductionInfo::default() });
+    assert!(verify(&puzzle, &original).is_valid());
+    assert_eq!(original.rotated_verified(&puzzle, HexRotation::R180), Err("rotated solution doesn't solve the puzzle"));
+    assert_eq!(original.rotated_verified(&puzzle, HexRotation::R0), Ok(original.clone()));
+}
+
+#[test]
+fn mirrored_symmetric_solutions_solve_the_same(){
+    for name in ["transfer", "bond", "calcify"]{
+        let case = CORPUS.iter().find(|case| case.name == name).unwrap();
+        let puzzle = parse_puzzle(case.puzzle).unwrap();
+        let original = parse_solution(case.solution).unwrap();
+        let mirrored = original.mirrored().unwrap();
+        assert_eq!(verify(&puzzle, &mirrored).result, verify(&puzzle, &original).result, "{name}");
+        assert_ne!(mirrored, original);
+        assert_eq!(mirrored.mirrored().unwrap(), original, "{name}");
+    }
+}
+
+#[test]
+fn mirroring_swaps_directions(){
+    let mut arm = part(PartType::Arm, HexIndex::new(1, 1), 1);
+    arm.instructions = vec![(Instruction::RotateClockwise, 0), (Instruction::PivotAnticlockwise, 1), (Instruction::Extend, 2)];
+    let mut track = part(PartType::Track, HexIndex::new(1, 1), 0);
+    track.track_hexes = vec![HexIndex::new(0, 0), HexIndex::new(0, 1), HexIndex::new(-1, 2)];
+    let mirrored = solution(vec![arm, track]).mirrored().unwrap();
+    assert_eq!(mirrored.parts[0].pos, HexIndex::new(2, -1));
+    assert_eq!(mirrored.parts[0].rotation, -1);
+    assert_eq!(mirrored.parts[0].instructions, vec![(Instruction::RotateAnticlockwise, 0), (Instruction::PivotClockwise, 1), (Instruction::Extend, 2)]);
+    assert_eq!(mirrored.parts[1].track_hexes, vec![HexIndex::new(0, 0), HexIndex::new(1, -1), HexIndex::new(1, -2)]);
+}
+
+#[test]
+fn mirrored_glyphs_keep_their_roles(){
+    // vitae and mors swap sides, so the glyph has to turn around
+    let mirrored = solution(vec![part(PartType::Animismus, HexIndex::default(), 0)]).mirrored().unwrap();
+    assert_eq!((mirrored.parts[0].pos, mirrored.parts[0].rotation), (HexIndex::new(1, 0), 3));
+    for ty in [PartType::Dispersion, PartType::Berlo]{
+        assert!(solution(vec![part(ty.clone(), HexIndex::default(), 0)]).mirrored().is_err(), "{ty:?}");
+    }
+}
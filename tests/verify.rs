@@ -0,0 +1,241 @@
+use omsim_rs::data::{HexIndex, Instruction, Metrics, Part, PartType};
+use omsim_rs::parse::{parse_puzzle, parse_solution};
+use omsim_rs::selftest::{Expected, CORPUS};
+use omsim_rs::sim::config::SimConfig;
+use omsim_rs::sim::error::SimError;
+use omsim_rs::verify::{verify, Verdict, verify_with_config, AsymptoticMetrics, VERDICT_SCHEMA_VERSION, ArmExtension, ExtendedMetrics, MetricDivergence, PartActivations, SolutionFlags};
+
+fn transfer_with_recorded(recorded: Metrics) -> omsim_rs::verify::Verdict{
+    let puzzle = parse_puzzle(include_bytes!("../corpus/transfer.puzzle")).unwrap();
+    let mut solution = parse_solution(include_bytes!("../corpus/transfer.solution")).unwrap();
+    solution.metrics = Some(recorded);
+    verify_with_config(&puzzle, &solution, SimConfig{ asymptotic: true, ..SimConfig::default() })
+}
+
+#[test]
+fn matching_metrics_need_no_explanation(){
+    let verdict = transfer_with_recorded(Metrics{ cycles: 45, cost: 20, area: 7, instructions: 8 });
+    assert!(verdict.matches_recorded());
+    assert_eq!(verdict.explanation(), None);
+}
+
+#[test]
+fn slower_solution_names_unsatisfied_output(){
+    let verdict = transfer_with_recorded(Metrics{ cycles: 30, cost: 20, area: 7, instructions: 8 });
+    assert!(!verdict.matches_recorded());
+    let [MetricDivergence::Slower{ recorded: 30, simulated: 45, unsatisfied }] = &verdict.divergences[..] else {
+        panic!("unexpected divergences: {:?}", verdict.divergences)
+    };
+    assert_eq!(unsatisfied.len(), 1);
+    assert_eq!(unsatisfied[0].0, 0);
+    assert!(unsatisfied[0].1 < unsatisfied[0].2);
+    let explanation = verdict.explanation().unwrap();
+    assert!(explanation.starts_with("recorded 30 cycles, simulated 45; first divergence at cycle 30 where output 0 was not satisfied"), "{explanation}");
+}
+
+#[test]
+fn larger_area_reports_when_it_was_exceeded(){
+    let verdict = transfer_with_recorded(Metrics{ cycles: 45, cost: 20, area: 3, instructions: 7 });
+    let [MetricDivergence::AreaLarger{ recorded: 3, simulated: 7, .. }, MetricDivergence::Instructions{ recorded: 7, simulated: 8 }] = &verdict.divergences[..] else {
+        panic!("unexpected divergences: {:?}", verdict.divergences)
+    };
+}
+
+#[test]
+fn skipping_collision_is_marked_on_the_verdict(){
+    let puzzle = parse_puzzle(include_bytes!("../corpus/transfer.puzzle")).unwrap();
+    let solution = parse_solution(include_bytes!("../corpus/collision.solution")).unwrap();
+    assert!(!verify(&puzzle, &solution).is_valid());
+    let config = SimConfig{ skip_collision: true, ..SimConfig::default() };
+    let verdict = verify_with_config(&puzzle, &solution, config);
+    assert!(!verdict.collisions_checked);
+    assert!(verdict.is_valid());
+    assert!(verdict.to_string().ends_with("(collisions not checked)"));
+}
+
+#[test]
+fn part_costs_and_instructions_add_up_like_the_game(){
+    // worked out by hand from the game's prices: every case has one 20g arm with 8 instructions, plus a 10g glyph
+    // for calcify and bond
+    let expected = [("transfer", 20, 8), ("calcify", 30, 8), ("bond", 30, 8)];
+    for (name, cost, instructions) in expected{
+        let case = CORPUS.iter().find(|case| case.name == name).unwrap();
+        let solution = parse_solution(case.solution).unwrap();
+        assert_eq!(solution.parts.iter().map(Part::cost).sum::<i32>(), cost, "{name}");
+        assert_eq!(solution.parts.iter().map(Part::instruction_count).sum::<i32>(), instructions, "{name}");
+        let Expected::Valid(metrics) = case.expected else { panic!("{name} should be valid") };
+        assert_eq!((metrics.cost, metrics.instructions), (cost, instructions), "{name}");
+    }
+
+    // a piston on three hexes of track, whose period override and blank don't count but whose reset and repeat do
+    let track = Part{
+        ty: PartType::Track, pos: HexIndex::new(0, 0), rotation: 0, arm_number: 0, arm_length: 1, index: 0, conduit_index: 0,
+        track_hexes: vec![HexIndex::new(0, 0), HexIndex::new(1, 0), HexIndex::new(2, 0)], conduit_hexes: Vec::new(), instructions: Vec::new()
+    };
+    let instructions = vec![(Instruction::Grab, 0), (Instruction::Extend, 1), (Instruction::PeriodOverride, 2), (Instruction::Reset, 4), (Instruction::Repeat, 5)];
+    let piston = Part{ ty: PartType::PistonArm, track_hexes: Vec::new(), instructions, ..track.clone() };
+    assert_eq!((track.cost(), piston.cost()), (15, 40));
+    assert_eq!((track.instruction_count(), piston.instruction_count()), (0, 4));
+    assert_eq!(PartType::Track.cost(), 5);
+    assert_eq!(PartType::PistonArm.cost(), 40);
+    assert_eq!(PartType::Conduit.cost(), 0);
+}
+
+#[test]
+fn extended_metrics_are_in_the_json_verdict(){
+    let verdict = transfer_with_recorded(Metrics{ cycles: 45, cost: 20, area: 7, instructions: 8 });
+    let asymptotic = AsymptoticMetrics{ period: 8, products: 1, area: 7, cost: 20 };
+    // the input spawns once more after the sixth product is taken
+    let activations = vec![PartActivations{ part: 0, count: 7 }, PartActivations{ part: 1, count: 6 }];
+    let extensions = vec![ArmExtension{ part: 2, number: 1, max_length: 1 }];
+    assert_eq!(verdict.extended, Some(ExtendedMetrics{ last_instruction_cycle: Some(7), tape_length: 8, arms: 1, asymptotic: Some(asymptotic), activations, extensions }));
+    let json = verdict.to_json();
+    assert!(json.starts_with(r#"{"schema_version":1,"valid":true,"metrics":{"cycles":45,"cost":20,"area":7,"instructions":8},"#), "{json}");
+    assert!(json.contains(r#""extended":{"last_instruction_cycle":7,"tape_length":8,"arms":1,"asymptotic":{"period":8,"products":1,"area":7,"rate":8,"cost_per_product":20},"activations":[{"part":0,"count":7},{"part":1,"count":6}],"extensions":[{"part":2,"arm":1,"max_length":1}]}"#), "{json}");
+}
+
+#[test]
+fn asymptotic_metrics_are_opt_in(){
+    let puzzle = parse_puzzle(include_bytes!("../corpus/transfer.puzzle")).unwrap();
+    let solution = parse_solution(include_bytes!("../corpus/transfer.solution")).unwrap();
+    let extended = verify(&puzzle, &solution).extended.unwrap();
+    assert_eq!(extended.asymptotic, None);
+    assert_eq!(extended.tape_length, 8);
+}
+
+#[test]
+fn asymptotic_metrics_are_amortized_per_product(){
+    let asymptotic = AsymptoticMetrics{ period: 14, products: 4, area: 30, cost: 110 };
+    assert_eq!(asymptotic.rate(), Some(3.5));
+    assert_eq!(asymptotic.cost_per_product(), Some(27.5));
+    assert_eq!(AsymptoticMetrics{ products: 0, ..asymptotic }.rate(), None);
+}
+
+#[test]
+fn failed_json_verdicts_escape_their_error(){
+    let puzzle = parse_puzzle(include_bytes!("../corpus/transfer.puzzle")).unwrap();
+    let solution = parse_solution(include_bytes!("../corpus/collision.solution")).unwrap();
+    let json = verify(&puzzle, &solution).to_json();
+    assert!(json.starts_with(r#"{"schema_version":1,"valid":false,"error":"collision at ("#), "{json}");
+    assert!(json.contains(r#""extended":null"#), "{json}");
+}
+
+#[test]
+fn overlapping_parts_are_flagged(){
+    let puzzle = parse_puzzle(include_bytes!("../corpus/transfer.puzzle")).unwrap();
+    let mut solution = parse_solution(include_bytes!("../corpus/transfer.solution")).unwrap();
+    assert!(!verify(&puzzle, &solution).uses_overlap());
+    // an arm on a track is fine, but a track under an output isn't
+    let mut track = solution.parts[2].clone();
+    track.ty = PartType::Track;
+    track.track_hexes = vec![HexIndex::new(0, 0), HexIndex::new(1, 0)];
+    assert_eq!((&solution.parts[1].ty, solution.parts[1].pos), (&PartType::Output, HexIndex::new(2, 0)));
+    track.instructions.clear();
+    solution.parts.push(track);
+    let verdict = verify(&puzzle, &solution);
+    assert_eq!(verdict.overlaps, vec![(1, 3)]);
+    assert!(verdict.to_json().ends_with(r#""uses_overlap":true,"overlaps":[[1,3]]}"#));
+
+    // the bonder sits under both inputs
+    let puzzle = parse_puzzle(include_bytes!("../corpus/bond.puzzle")).unwrap();
+    let solution = parse_solution(include_bytes!("../corpus/bond.solution")).unwrap();
+    assert_eq!(verify(&puzzle, &solution).overlaps, vec![(0, 2), (1, 2)]);
+}
+
+#[test]
+fn layout_flags_are_derived_from_parts(){
+    let puzzle = parse_puzzle(include_bytes!("../corpus/bond.puzzle")).unwrap();
+    let mut solution = parse_solution(include_bytes!("../corpus/bond.solution")).unwrap();
+    let flags = verify(&puzzle, &solution).flags;
+    assert_eq!(flags, SolutionFlags{ trackless: true, single_arm: true, no_pivot: true, glyphs: vec![PartType::Bonding] });
+    assert!(flags.uses_only_glyphs(&[PartType::Bonding, PartType::Unbonding]));
+    assert!(!flags.uses_only_glyphs(&[PartType::Calcification]));
+
+    let mut second = solution.parts[4].clone();
+    second.pos = HexIndex::new(-4, 3);
+    second.instructions = vec![(Instruction::PivotClockwise, 0)];
+    solution.parts.push(second);
+    let flags = SolutionFlags::of(&solution);
+    assert!(!flags.single_arm && !flags.no_pivot);
+    assert!(verify(&puzzle, &solution).to_json().contains(r#""flags":{"trackless":true,"single_arm":false,"no_pivot":false,"glyphs":["bonder"]}"#));
+}
+
+#[test]
+fn modded_parts_parse_but_are_unsupported(){
+    let puzzle = parse_puzzle(include_bytes!("../corpus/transfer.puzzle")).unwrap();
+    let mut solution = parse_solution(include_bytes!("../corpus/transfer.solution")).unwrap();
+    solution.parts.push(Part{ ty: PartType::Unknown("glyph-foo".to_string()), pos: HexIndex::new(5, 5), ..solution.parts[0].clone() });
+    let reparsed = parse_solution(&solution.unparse()).unwrap();
+    assert_eq!(reparsed, solution);
+    assert_eq!(reparsed.parts[3].ty.to_name(), "glyph-foo");
+    let verdict = verify(&puzzle, &reparsed);
+    assert_eq!(verdict.result, Err(SimError::UnsupportedPart("glyph-foo".to_string())));
+    assert_eq!(verdict.to_string(), "invalid: contains unsupported part 'glyph-foo'");
+}
+
+/// Whether `current` still has every field of `golden`, with the same JSON types, so tools written against `golden` can read it.
+fn is_compatible(golden: &serde_json::Value, current: &serde_json::Value) -> bool{
+    use serde_json::Value;
+    match (golden, current){
+        (Value::Object(golden), Value::Object(current)) => golden.iter().all(|(key, value)| current.get(key).is_some_and(|field| is_compatible(value, field))),
+        (Value::Array(golden), Value::Array(current)) => golden.len() == current.len() && golden.iter().zip(current).all(|(golden, current)| is_compatible(golden, current)),
+        (Value::Null, Value::Null) | (Value::Bool(_), Value::Bool(_)) | (Value::Number(_), Value::Number(_)) | (Value::String(_), Value::String(_)) => true,
+        _ => false
+    }
+}
+
+/// The verdicts the golden files were written from, with the golden files.
+fn golden_verdicts() -> [(&'static str, Verdict); 3]{
+    let bond = verify_with_config(&parse_puzzle(include_bytes!("../corpus/bond.puzzle")).unwrap(), &parse_solution(include_bytes!("../corpus/bond.solution")).unwrap(),
+        SimConfig{ asymptotic: true, ..SimConfig::default() });
+    let collision = verify(&parse_puzzle(include_bytes!("../corpus/transfer.puzzle")).unwrap(), &parse_solution(include_bytes!("../corpus/collision.solution")).unwrap());
+    [
+        (include_str!("golden/transfer-verdict.json"), transfer_with_recorded(Metrics{ cycles: 45, cost: 20, area: 7, instructions: 8 })),
+        (include_str!("golden/bond-verdict.json"), bond),
+        (include_str!("golden/collision-verdict.json"), collision)
+    ]
+}
+
+#[test]
+fn json_verdicts_only_gain_fields(){
+    for (golden, verdict) in golden_verdicts(){
+        let golden: serde_json::Value = serde_json::from_str(golden).unwrap();
+        let current: serde_json::Value = serde_json::from_str(&verdict.to_json()).unwrap();
+        // an incompatible change must come with a new schema version, and new golden files for it
+        assert_eq!(golden["schema_version"], VERDICT_SCHEMA_VERSION, "golden files are for an older schema version");
+        assert!(is_compatible(&golden, &current), "verdict JSON changed incompatibly without a new schema version:\n{current:#}");
+    }
+}
+
+#[test]
+fn unused_glyphs_have_no_activations(){
+    let puzzle = parse_puzzle(include_bytes!("../corpus/transfer.puzzle")).unwrap();
+    let mut solution = parse_solution(include_bytes!("../corpus/transfer.solution")).unwrap();
+    let mut bonder = solution.parts[0].clone();
+    bonder.ty = PartType::Bonding;
+    bonder.pos = HexIndex::new(5, 5);
+    solution.parts.push(bonder);
+    let activations = verify(&puzzle, &solution).extended.unwrap().activations;
+    assert_eq!(activations.last(), Some(&PartActivations{ part: 3, count: 0 }));
+
+    let puzzle = parse_puzzle(include_bytes!("../corpus/calcify.puzzle")).unwrap();
+    let solution = parse_solution(include_bytes!("../corpus/calcify.solution")).unwrap();
+    let verdict = verify(&puzzle, &solution);
+    let glyph = solution.parts.iter().position(|part| part.ty == PartType::Calcification).unwrap();
+    let calcified = verdict.extended.unwrap().activations.iter().find(|a| a.part == glyph).unwrap().count;
+    assert!(calcified >= 6, "{calcified}");
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn json_verdicts_match_their_model(){
+    use omsim_rs::verify::VerdictJson;
+    for (golden, verdict) in golden_verdicts(){
+        // verdicts from before fields were added still read
+        let old: VerdictJson = serde_json::from_str(golden).unwrap();
+        assert_eq!(old.schema_version, VERDICT_SCHEMA_VERSION);
+        let model = verdict.to_json_model();
+        assert_eq!(serde_json::from_str::<VerdictJson>(&verdict.to_json()).unwrap(), model);
+        assert_eq!(serde_json::from_str::<VerdictJson>(&serde_json::to_string(&model).unwrap()).unwrap(), model);
+    }
+}